@@ -23,17 +23,17 @@ fn main() {
     // The loop where the game progresses.
     game_loop();
     // When this point is reached, the game has ended.
-    println!("");
+    println!();
     println!("Goodbye!");
 }
 
 fn print_intro() {
     println!("Autosave");
-    println!("");
+    println!();
     println!("Example to demonstrate how to use an event sink to create autosaves with weasel.");
     println!("Create soldiers and exit whenever you want.");
     println!("Next time you launch the game it will resume from the latest progress!");
-    println!("");
+    println!();
     println!("  Controls:");
     println!("    c - Create a new soldier");
     println!("    q - Quit");
@@ -47,6 +47,7 @@ fn game_loop() {
     loop {
         // Read a char from stdin.
         let input: Option<char> = std::io::stdin()
+            .lock()
             .bytes()
             .next()
             .and_then(|result| result.ok())
@@ -32,6 +32,8 @@ impl TeamRules<PiratesRules> for PiratesTeamRules {
     // goal of sinking the enemy ship was achieved.
     type ObjectivesSeed = Self::Id;
     type Objectives = Self::ObjectivesSeed;
+    // We don't need to keep track of partial progress.
+    type ObjectivesProgress = ();
 
     // Generate the objectives for a team. We said the seed and the objective are both
     // the enemy team id.
@@ -50,14 +52,7 @@ impl TeamRules<PiratesRules> for PiratesTeamRules {
         // Get the objective of the team. Which is equal to its enemy id.
         let enemy_id = team.objectives();
         // Now check if the enemy has any creatures left.
-        if state
-            .entities()
-            .team(&enemy_id)
-            .unwrap()
-            .creatures()
-            .count()
-            == 0
-        {
+        if state.entities().team(enemy_id).unwrap().creatures().count() == 0 {
             // We won.
             Some(Conclusion::Victory)
         } else {
@@ -73,6 +68,7 @@ pub struct PiratesCharacterRules {}
 impl CharacterRules<PiratesRules> for PiratesCharacterRules {
     // We want to an integer as creature id.
     type CreatureId = u8;
+    type ObjectId = u8;
     // Use statistics with integer as id and as value.
     type Statistic = SimpleStatistic<u8, i16>;
     // No need for a seed. All ships have the same statistics.
@@ -196,11 +192,13 @@ impl FightRules<PiratesRules> for PiratesFightRules {
         mut event_queue: &mut Option<EventQueue<PiratesRules>>,
         _entropy: &mut Entropy<PiratesRules>,
         _metrics: &mut WriteMetrics<PiratesRules>,
-    ) {
+    ) -> Vec<Self::Impact> {
         let target = &impact.0;
         // We know the target will be always there (we end the battle when the first ship sinks).
         // Thus we can safely create alteration events for the target.
         AlterStatistics::trigger(&mut event_queue, *target, impact.1).fire();
+        // Cannonballs don't cause any further impact.
+        Vec::new()
     }
 }
 
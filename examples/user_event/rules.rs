@@ -36,7 +36,7 @@ impl MakePizza {
     pub(crate) fn trigger<P: EventProcessor<CustomRules>>(
         processor: &mut P,
         name: String,
-    ) -> MakePizzaTrigger<P> {
+    ) -> MakePizzaTrigger<'_, P> {
         MakePizzaTrigger { processor, name }
     }
 }
@@ -110,7 +110,7 @@ impl UserEventPacker<CustomRules> for EventPackage {
     /// In this method we extract an event trait object out of a packaged user event.
     fn boxed(self) -> WeaselResult<Box<dyn Event<CustomRules>>, CustomRules> {
         let event = match self {
-            EventPackage::MakePizza(event) => (Box::new(event) as Box<dyn Event<CustomRules>>),
+            EventPackage::MakePizza(event) => Box::new(event) as Box<dyn Event<CustomRules>>,
         };
         Ok(event)
     }
@@ -68,7 +68,7 @@ impl<R: BattleRules> ActivateAbility<R> {
         processor: &mut P,
         entity_id: EntityId<R>,
         ability_id: AbilityId<R>,
-    ) -> ActivateAbilityTrigger<R, P> {
+    ) -> ActivateAbilityTrigger<'_, R, P> {
         ActivateAbilityTrigger {
             processor,
             entity_id,
@@ -127,18 +127,19 @@ impl<R: BattleRules + 'static> Event<R> for ActivateAbility<R> {
             }
             // Verify if the creature knowns this ability.
             if let Some(ability) = actor.ability(&self.ability_id) {
-                // Verify if this ability can be activated.
-                if !battle.rules.actor_rules().activable(Action::new(
-                    actor,
-                    ability,
-                    &self.activation,
-                )) {
-                    return Err(WeaselError::AbilityNotActivable(
+                // Verify that the ability is not on cooldown.
+                if actor.cooldown(&self.ability_id) > 0 {
+                    return Err(WeaselError::AbilityOnCooldown(
                         self.entity_id.clone(),
                         self.ability_id.clone(),
                     ));
                 }
-                Ok(())
+                // Verify if this ability can be activated.
+                battle.rules.actor_rules().activation_result(Action::new(
+                    actor,
+                    ability,
+                    &self.activation,
+                ))
             } else {
                 Err(WeaselError::AbilityNotKnown(
                     self.entity_id.clone(),
@@ -164,6 +165,7 @@ impl<R: BattleRules + 'static> Event<R> for ActivateAbility<R> {
                 self.ability_id, self.entity_id
             )
         });
+        let cooldown = battle.rules.actor_rules().ability_cooldown(ability);
         battle.rules.actor_rules().activate(
             &battle.state,
             Action::new(actor, ability, &self.activation),
@@ -171,6 +173,17 @@ impl<R: BattleRules + 'static> Event<R> for ActivateAbility<R> {
             &mut battle.entropy,
             &mut battle.metrics.write_handle(),
         );
+        // Put the ability on cooldown.
+        if cooldown > 0 {
+            battle
+                .state
+                .entities
+                .actor_mut(&self.entity_id)
+                .unwrap_or_else(|| {
+                    panic!("constraint violated: entity {:?} not found", self.entity_id)
+                })
+                .set_cooldown(&self.ability_id, cooldown);
+        }
     }
 
     fn kind(&self) -> EventKind {
@@ -7,8 +7,8 @@ use crate::entity::{Entities, EntityId};
 use crate::entropy::Entropy;
 use crate::error::{WeaselError, WeaselResult};
 use crate::event::{Event, EventKind, EventProcessor, EventQueue, EventTrigger};
-use crate::metric::WriteMetrics;
-use crate::team::TeamId;
+use crate::metric::{system::ABILITIES_CLEARED, WriteMetrics};
+use crate::team::{Relation, TeamId};
 use crate::util::Id;
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
@@ -36,6 +36,22 @@ pub trait Actor<R: BattleRules>: Character<R> {
 
     /// Returns the id of the team to which this actor belongs.
     fn team_id(&self) -> &TeamId<R>;
+
+    /// Returns the number of rounds left before the ability with the given id
+    /// can be activated again. Zero means that there's no cooldown.
+    fn cooldown(&self, id: &AbilityId<R>) -> u32;
+
+    /// Sets the number of rounds left before the ability with the given id
+    /// can be activated again.
+    fn set_cooldown(&mut self, id: &AbilityId<R>, rounds: u32);
+
+    /// Returns true if this actor is disabled.
+    ///
+    /// Disabled actors are still part of the battle, but they can't start a round.
+    fn is_disabled(&self) -> bool;
+
+    /// Disables or re-enables this actor.
+    fn set_disabled(&mut self, disabled: bool);
 }
 
 /// Set of rules that handle how abilities are represented and how they can alter
@@ -90,6 +106,41 @@ pub trait ActorRules<R: BattleRules> {
         true
     }
 
+    /// Checks if the actor can activate this ability with the given activation profile.
+    /// The ability is guaranteed to be known by the actor.\
+    /// Unlike `activable`, it returns an error explaining why the ability can't be used.
+    /// `WeaselError::UserError` is a good choice for rules-specific reasons.
+    ///
+    /// The provided implementation calls `activable` and returns
+    /// `WeaselError::AbilityNotActivable` if the latter returns false.
+    fn activation_result(&self, action: Action<R>) -> WeaselResult<(), R> {
+        let actor_id = action.actor.entity_id().clone();
+        let ability_id = action.ability.id().clone();
+        if self.activable(action) {
+            Ok(())
+        } else {
+            Err(WeaselError::AbilityNotActivable(actor_id, ability_id))
+        }
+    }
+
+    /// Returns the cooldown of an ability, that is for how many rounds it can't be activated
+    /// after being used.\
+    /// The cooldown is decreased at the end of each round of the actor, including the round
+    /// in which the ability was activated.
+    ///
+    /// The provided implementation returns zero, meaning that abilities have no cooldown.
+    fn ability_cooldown(&self, _ability: &Self::Ability) -> u32 {
+        0
+    }
+
+    /// Returns the requirements on the targets of an ability.\
+    /// Rules can use `validate_targets` to check a set of targets against them.
+    ///
+    /// The provided implementation returns a `TargetSpec` without any constraint.
+    fn ability_targets(&self, _ability: &Self::Ability) -> TargetSpec {
+        TargetSpec::default()
+    }
+
     /// Activate an ability.
     /// `ability` is guaranteed to be known by `actor`.\
     /// In order to change the state of the world, abilities should insert
@@ -141,6 +192,34 @@ pub trait ActorRules<R: BattleRules> {
         _metrics: &mut WriteMetrics<R>,
     ) {
     }
+
+    /// Invoked after an actor gained a new ability, as result of an event
+    /// changing its set of abilities.
+    ///
+    /// The provided implementation does nothing.
+    fn on_ability_added(
+        &self,
+        _actor: &dyn Actor<R>,
+        _ability_id: &AbilityId<R>,
+        _event_queue: &mut Option<EventQueue<R>>,
+        _entropy: &mut Entropy<R>,
+        _metrics: &mut WriteMetrics<R>,
+    ) {
+    }
+
+    /// Invoked after an actor lost one of its abilities, as result of an event
+    /// changing its set of abilities.
+    ///
+    /// The provided implementation does nothing.
+    fn on_ability_removed(
+        &self,
+        _actor: &dyn Actor<R>,
+        _ability_id: &AbilityId<R>,
+        _event_queue: &mut Option<EventQueue<R>>,
+        _entropy: &mut Entropy<R>,
+        _metrics: &mut WriteMetrics<R>,
+    ) {
+    }
 }
 
 /// An action is comprised by an actor who activates an ability with a given activation profile.
@@ -168,6 +247,76 @@ impl<'a, R: BattleRules> Action<'a, R> {
     }
 }
 
+/// Returns an iterator over the abilities of `actor` that can be activated right now
+/// without an activation profile.
+///
+/// Each ability is checked with `ActorRules::activation_result`, using `None` as activation.
+/// Abilities which require an activation profile are thus skipped, and so are the
+/// abilities on cooldown.
+pub fn activable_abilities<'a, R: BattleRules>(
+    actor: &'a dyn Actor<R>,
+    rules: &'a R::AR,
+) -> impl Iterator<Item = &'a Ability<R>> + 'a {
+    actor.abilities().filter(move |ability| {
+        actor.cooldown(ability.id()) == 0
+            && rules
+                .activation_result(Action::new(actor, ability, &None))
+                .is_ok()
+    })
+}
+
+/// Describes how many targets an ability needs and which ones are allowed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TargetSpec {
+    /// Minimum number of targets.
+    pub min: usize,
+    /// Maximum number of targets. `None` means there's no upper limit.
+    pub max: Option<usize>,
+    /// Relations that the actor must have towards each target.
+    /// An empty list allows targets of any relation.
+    pub relations: Vec<Relation>,
+}
+
+/// Checks whether `targets` satisfy the `TargetSpec` returned by
+/// `ActorRules::ability_targets` for an actor's ability.
+///
+/// The relation towards a target is the directed relation of the actor's team towards
+/// the target's team.
+pub fn validate_targets<R: BattleRules>(
+    rules: &R::AR,
+    state: &BattleState<R>,
+    actor: &dyn Actor<R>,
+    ability: &Ability<R>,
+    targets: &[EntityId<R>],
+) -> WeaselResult<(), R> {
+    let spec = rules.ability_targets(ability);
+    if targets.len() < spec.min || spec.max.is_some_and(|max| targets.len() > max) {
+        return Err(WeaselError::InvalidTargetsCount(
+            ability.id().clone(),
+            targets.len(),
+        ));
+    }
+    if spec.relations.is_empty() {
+        return Ok(());
+    }
+    for target in targets {
+        let target_actor = state
+            .entities()
+            .actor(target)
+            .ok_or_else(|| WeaselError::EntityNotFound(target.clone()))?;
+        let relation = state
+            .entities()
+            .directed_relation(actor.team_id(), target_actor.team_id());
+        if !relation.is_some_and(|relation| spec.relations.contains(&relation)) {
+            return Err(WeaselError::InvalidTarget(
+                ability.id().clone(),
+                target.clone(),
+            ));
+        }
+    }
+    Ok(())
+}
+
 /// An event to alter the abilities of an actor.
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct AlterAbilities<R: BattleRules> {
@@ -239,13 +388,14 @@ impl<R: BattleRules + 'static> Event<R> for AlterAbilities<R> {
         verify_is_actor(battle.entities(), &self.id)
     }
 
-    fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
         // Retrieve the actor.
         let actor = battle
             .state
             .entities
             .actor_mut(&self.id)
             .unwrap_or_else(|| panic!("constraint violated: actor {:?} not found", self.id));
+        let before: Vec<_> = actor.abilities().map(|e| e.id().clone()).collect();
         // Alter the actor.
         battle.rules.actor_rules().alter(
             actor,
@@ -253,6 +403,17 @@ impl<R: BattleRules + 'static> Event<R> for AlterAbilities<R> {
             &mut battle.entropy,
             &mut battle.metrics.write_handle(),
         );
+        // Find out which abilities were added or removed by the alteration.
+        let added: Vec<_> = actor
+            .abilities()
+            .filter(|e| !before.contains(e.id()))
+            .map(|e| e.id().clone())
+            .collect();
+        let removed: Vec<_> = before
+            .into_iter()
+            .filter(|id| actor.ability(id).is_none())
+            .collect();
+        notify_abilities_changed(battle, &self.id, &added, &removed, event_queue);
     }
 
     fn kind(&self) -> EventKind {
@@ -373,7 +534,7 @@ impl<R: BattleRules + 'static> Event<R> for RegenerateAbilities<R> {
         verify_is_actor(battle.entities(), &self.id)
     }
 
-    fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
         // Retrieve the actor.
         let actor = battle
             .state
@@ -397,15 +558,18 @@ impl<R: BattleRules + 'static> Event<R> for RegenerateAbilities<R> {
                 to_remove.push(ability.id().clone());
             }
         }
-        for ability_id in to_remove {
-            actor.remove_ability(&ability_id);
+        for ability_id in &to_remove {
+            actor.remove_ability(ability_id);
         }
         // Add all abilities present in the new set but not in the actor.
+        let mut added = Vec::new();
         for ability in abilities {
             if actor.ability(ability.id()).is_none() {
+                added.push(ability.id().clone());
                 actor.add_ability(ability);
             }
         }
+        notify_abilities_changed(battle, &self.id, &added, &to_remove, event_queue);
     }
 
     fn kind(&self) -> EventKind {
@@ -465,6 +629,116 @@ where
     }
 }
 
+/// An event to remove all abilities from an actor.
+///
+/// The actor's statistics are not affected.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct ClearAbilities<R: BattleRules> {
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "EntityId<R>: Serialize",
+            deserialize = "EntityId<R>: Deserialize<'de>"
+        ))
+    )]
+    id: EntityId<R>,
+}
+
+impl<R: BattleRules> ClearAbilities<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &'_ mut P,
+        id: EntityId<R>,
+    ) -> ClearAbilitiesTrigger<'_, R, P> {
+        ClearAbilitiesTrigger { processor, id }
+    }
+
+    /// Returns the actor's entity id.
+    pub fn id(&self) -> &EntityId<R> {
+        &self.id
+    }
+}
+
+impl<R: BattleRules> Debug for ClearAbilities<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "ClearAbilities {{ id: {:?} }}", self.id)
+    }
+}
+
+impl<R: BattleRules> Clone for ClearAbilities<R> {
+    fn clone(&self) -> Self {
+        ClearAbilities {
+            id: self.id.clone(),
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for ClearAbilities<R> {
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        verify_is_actor(battle.entities(), &self.id)
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
+        // Retrieve the actor.
+        let actor = battle
+            .state
+            .entities
+            .actor_mut(&self.id)
+            .unwrap_or_else(|| panic!("constraint violated: actor {:?} not found", self.id));
+        // Remove all abilities.
+        let to_remove: Vec<_> = actor.abilities().map(|e| e.id().clone()).collect();
+        for ability_id in &to_remove {
+            actor.remove_ability(ability_id);
+        }
+        // Update metrics.
+        battle
+            .metrics
+            .write_handle()
+            .add_system_u64(ABILITIES_CLEARED, to_remove.len() as u64)
+            .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
+        notify_abilities_changed(battle, &self.id, &[], &to_remove, event_queue);
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::ClearAbilities
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire a `ClearAbilities` event.
+pub struct ClearAbilitiesTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    id: EntityId<R>,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for ClearAbilitiesTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns a `ClearAbilities` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(ClearAbilities {
+            id: self.id.clone(),
+        })
+    }
+}
+
 /// Checks if an entity exists and is an actor.
 fn verify_is_actor<R>(entities: &Entities<R>, id: &EntityId<R>) -> WeaselResult<(), R>
 where
@@ -480,3 +754,41 @@ where
         .ok_or_else(|| WeaselError::EntityNotFound(id.clone()))?;
     Ok(())
 }
+
+/// Invokes the `ActorRules` callbacks for abilities that were removed from
+/// or added to the actor with the given id.
+fn notify_abilities_changed<R>(
+    battle: &mut Battle<R>,
+    id: &EntityId<R>,
+    added: &[AbilityId<R>],
+    removed: &[AbilityId<R>],
+    event_queue: &mut Option<EventQueue<R>>,
+) where
+    R: BattleRules,
+{
+    let actor = battle
+        .state
+        .entities
+        .actor(id)
+        .unwrap_or_else(|| panic!("constraint violated: actor {:?} not found", id));
+    let rules = battle.rules.actor_rules();
+    let mut metrics = battle.metrics.write_handle();
+    for ability_id in removed {
+        rules.on_ability_removed(
+            actor,
+            ability_id,
+            event_queue,
+            &mut battle.entropy,
+            &mut metrics,
+        );
+    }
+    for ability_id in added {
+        rules.on_ability_added(
+            actor,
+            ability_id,
+            event_queue,
+            &mut battle.entropy,
+            &mut metrics,
+        );
+    }
+}
@@ -1,8 +1,9 @@
 //! Battle module.
 
+use crate::ability::{Ability, AbilityId};
 use crate::actor::ActorRules;
-use crate::character::CharacterRules;
-use crate::entity::Entities;
+use crate::character::{CharacterRules, Statistic, StatisticId};
+use crate::entity::{Entities, EntityId};
 use crate::entropy::{Entropy, EntropyRules};
 use crate::error::{WeaselError, WeaselResult};
 use crate::event::{
@@ -11,11 +12,15 @@ use crate::event::{
 };
 use crate::fight::FightRules;
 use crate::history::History;
-use crate::metric::{Metrics, ReadMetrics, WriteMetrics};
+use crate::metric::{Metrics, MetricsChanges, MetricsDelta, ReadMetrics, WriteMetrics};
 use crate::player::{Rights, RightsHandle, RightsHandleMut};
-use crate::round::{Rounds, RoundsRules};
-use crate::space::{Space, SpaceRules};
-use crate::team::{ConcludeObjectives, TeamId, TeamRules};
+use crate::round::{Rounds, RoundsModel, RoundsRules};
+#[cfg(feature = "serialization")]
+use crate::serde::BattleSnapshot;
+use crate::space::{MoveEntity, Space, SpaceModel, SpaceRules};
+use crate::team::{
+    ConcludeObjectives, Conclusion, Objectives, ObjectivesProgress, TeamId, TeamRules,
+};
 use crate::user::UserRules;
 use crate::util::Id;
 #[cfg(feature = "serialization")]
@@ -44,7 +49,14 @@ pub struct Battle<R: BattleRules> {
     pub(crate) rules: R,
     pub(crate) event_callback: Option<EventCallback<R>>,
     pub(crate) metrics: Metrics<R>,
-    rights: Rights<R>,
+    pub(crate) rights: Rights<R>,
+    /// Changes done by each event in the history, used to revert them.
+    changes: Vec<EventChanges<R>>,
+    pub(crate) outcome: Option<BattleOutcome<R>>,
+    /// Outcome decided by the rules, waiting for the `EndBattle` event that applies it.
+    pending_outcome: Option<BattleOutcome<R>>,
+    /// Whether the battle was restored from a snapshot.
+    restored: bool,
 }
 
 impl<R: BattleRules + 'static> Battle<R> {
@@ -53,16 +65,19 @@ impl<R: BattleRules + 'static> Battle<R> {
         BattleBuilder {
             rules,
             event_callback: None,
+            #[cfg(feature = "serialization")]
+            snapshot: None,
         }
     }
 
     /// Verifies the consistency of an event.
-    #[allow(clippy::borrowed_box)]
-    pub(crate) fn verify_event(&self, event: &Box<dyn Event<R>>) -> WeaselResult<(), R> {
-        if self.phase() == BattlePhase::Ended {
+    ///
+    /// Once the battle has ended, only `ResetObjectives` is accepted.
+    pub(crate) fn verify_event(&self, event: &dyn Event<R>) -> WeaselResult<(), R> {
+        if self.phase() == BattlePhase::Ended && event.kind() != EventKind::ResetObjectives {
             Err(WeaselError::BattleEnded)
         } else {
-            event.verify(&self)
+            event.verify(self)
         }
     }
 
@@ -75,7 +90,34 @@ impl<R: BattleRules + 'static> Battle<R> {
             }
         }
         // Verify event.
-        self.verify_event(event)
+        self.verify_event(event.as_ref())
+    }
+
+    /// Upgrades a `VersionedEventWrapper` created with an older rules' version,
+    /// if the rules know how to migrate it.
+    pub(crate) fn migrate_wrapper(&self, event: &mut VersionedEventWrapper<R>) {
+        let version = self.rules.version();
+        if event.version != *version {
+            if let Some(migrated) = self
+                .rules
+                .migrate_event(&event.version, &*event.wrapper.event)
+            {
+                event.wrapper.event = migrated;
+                event.version = version.clone();
+            }
+        }
+    }
+
+    /// Upgrades a `ClientEventPrototype` created with an older rules' version,
+    /// if the rules know how to migrate it.
+    pub(crate) fn migrate_client(&self, event: &mut ClientEventPrototype<R>) {
+        let version = self.rules.version();
+        if event.version != *version {
+            if let Some(migrated) = self.rules.migrate_event(&event.version, &*event.event) {
+                event.event = migrated;
+                event.version = version.clone();
+            }
+        }
     }
 
     /// Verifies the consistency of a `VersionedEventWrapper`.
@@ -91,7 +133,7 @@ impl<R: BattleRules + 'static> Battle<R> {
         // Verify timeline consistency.
         self.history.verify_event(event.wrapper())?;
         // Verify event.
-        self.verify_event(event.wrapper())
+        self.verify_event(event.wrapper().as_ref())
     }
 
     pub(crate) fn verify_client(&self, event: &ClientEventPrototype<R>) -> WeaselResult<(), R> {
@@ -104,7 +146,12 @@ impl<R: BattleRules + 'static> Battle<R> {
             ));
         }
         // Verify event.
-        self.verify_event(event)
+        self.verify_event(event.as_ref())?;
+        // Players can only move the active actor during a round.
+        if let Some(event) = event.as_ref().as_any().downcast_ref::<MoveEntity<R>>() {
+            event.verify_turn(self)?;
+        }
+        Ok(())
     }
 
     /// Promotes an `EventPrototype` into an `EventWrapper`.
@@ -112,20 +159,114 @@ impl<R: BattleRules + 'static> Battle<R> {
         event.promote(self.history.next_id())
     }
 
+    /// Returns a new battle, built with `rules`, in the same state of this one.
+    ///
+    /// The copy is created by replaying the whole history. Metrics and rights are copied
+    /// as they are, since they might have been changed outside of events.\
+    /// Returns `None` if this battle was restored from a snapshot, because its history
+    /// doesn't contain the events that led to the snapshot.
+    pub(crate) fn replica(&self, rules: R) -> Option<Battle<R>> {
+        let mut battle = self.replay_history(rules, self.history.events().len())?;
+        battle.metrics = self.metrics.snapshot();
+        battle.rights = self.rights.snapshot();
+        battle.changes = self.changes.clone();
+        Some(battle)
+    }
+
+    /// Returns a new battle, built with `rules`, holding a copy of the state, history,
+    /// entropy, metrics and rights of this one.
+    ///
+    /// Unlike `replica`, nothing is replayed, thus it works for restored battles as well.
+    pub(crate) fn duplicate(&self, rules: R) -> Battle<R>
+    where
+        Ability<R>: Clone,
+        Objectives<R>: Clone,
+        ObjectivesProgress<R>: Clone,
+        SpaceModel<R>: Clone,
+        RoundsModel<R>: Clone,
+    {
+        let mut battle = Battle::builder(rules).build();
+        battle.state.entities = self.state.entities.clone();
+        battle.state.space.model = self.state.space.model.clone();
+        battle
+            .state
+            .rounds
+            .set_state(self.state.rounds.state().clone());
+        battle.state.rounds.model = self.state.rounds.model.clone();
+        battle.state.rounds.order = self.state.rounds.order.clone();
+        battle.state.phase = self.state.phase;
+        battle.entropy.restore_state(self.entropy.state());
+        battle.history = self.history.clone();
+        battle.metrics = self.metrics.snapshot();
+        battle.rights = self.rights.snapshot();
+        battle.changes = self.changes.clone();
+        battle.outcome = self.outcome.clone();
+        battle.pending_outcome = self.pending_outcome.clone();
+        battle.restored = self.restored;
+        battle
+    }
+
+    /// Returns a new battle, built with `rules`, in the state this one had before
+    /// its last event.
+    ///
+    /// Works as `replica`, but the last event is not replayed and its changes to
+    /// metrics and rights are reverted.
+    pub(crate) fn rewind(&self, rules: R) -> Option<Battle<R>> {
+        let len = self.history.events().len().checked_sub(1)?;
+        let mut battle = self.replay_history(rules, len)?;
+        let changes = &self.changes[len];
+        battle.metrics = self.metrics.snapshot();
+        battle.metrics.revert(&changes.metrics);
+        battle.rights = changes.rights.as_ref().unwrap_or(&self.rights).snapshot();
+        battle.changes = self.changes[..len].to_vec();
+        Some(battle)
+    }
+
+    /// Returns a new battle, built with `rules`, on which the first `len` events
+    /// of the history are applied.
+    ///
+    /// Returns `None` if this battle was restored from a snapshot.
+    fn replay_history(&self, rules: R, len: usize) -> Option<Battle<R>> {
+        if self.restored {
+            return None;
+        }
+        let mut battle = Battle::builder(rules).build();
+        for event in &self.history.events()[..len] {
+            battle.apply(event, &mut None);
+        }
+        Some(battle)
+    }
+
     /// Apply an event to the world.
     /// Takes in a optional `EventQueue`, to eventually store new prototypes derived from `event`.
     pub(crate) fn apply(&mut self, event: &EventWrapper<R>, queue: &mut Option<EventQueue<R>>) {
         // Apply the event to the world.
+        self.metrics.track_changes();
+        self.rights.track_changes();
         event.apply(self, queue);
+        self.changes.push(EventChanges {
+            metrics: self.metrics.take_changes(),
+            rights: self.rights.take_changes(),
+        });
         // Save into history.
         self.history.archive(event);
+        // Update teams' objectives progress.
+        Battle::update_objectives_progress(
+            &mut self.state,
+            self.rules.team_rules(),
+            &self.metrics.read_handle(),
+        );
+        // Check if the battle should end. Done before checking the objectives, so that
+        // teams' conclusions are applied ahead of the end of the battle.
+        self.check_battle_end(&mut queue.as_mut().map(|queue| Prioritized::new(queue)));
         // Check teams' objectives.
+        let metrics_changes = &self.changes[self.changes.len() - 1].metrics;
         Battle::check_objectives(
             &self.state,
-            &self.rules.team_rules(),
+            self.rules.team_rules(),
             &self.metrics.read_handle(),
             &mut queue.as_mut().map(|queue| Prioritized::new(queue)),
-            Checkpoint::EventEnd,
+            Checkpoint::EventEnd(&MetricsDelta::new(metrics_changes, &self.metrics)),
         );
         // Invoke user callback.
         if let Some(cb) = &mut self.event_callback {
@@ -133,9 +274,17 @@ impl<R: BattleRules + 'static> Battle<R> {
         }
     }
 
-    /// Ends the battle.
+    /// Ends the battle, with the outcome decided by the rules if there's one.
     pub(crate) fn end(&mut self) {
         self.state.phase = BattlePhase::Ended;
+        self.outcome = self.pending_outcome.take();
+    }
+
+    /// Brings an ended battle back into the `Started` phase, discarding its outcome.
+    pub(crate) fn resume(&mut self) {
+        self.state.phase = BattlePhase::Started;
+        self.outcome = None;
+        self.pending_outcome = None;
     }
 
     /// Returns in which phase is the battle.
@@ -143,6 +292,67 @@ impl<R: BattleRules + 'static> Battle<R> {
         self.state.phase
     }
 
+    /// Returns whether the battle is over.
+    ///
+    /// A battle is over once it reached the `Ended` phase, when `BattleRules::check_battle_end`
+    /// returned an outcome or when all of its teams have a conclusion.
+    /// A battle without teams is never over by itself.
+    pub fn ended(&self) -> bool {
+        if self.phase() == BattlePhase::Ended || self.outcome.is_some() {
+            return true;
+        }
+        let mut teams = self.entities().teams().peekable();
+        teams.peek().is_some() && teams.all(|team| team.conclusion().is_some())
+    }
+
+    /// Returns whether the objectives checks run by the events from `start` onwards
+    /// gave a conclusion to the last teams without one, while the battle is still ongoing.
+    ///
+    /// Battles in which a single team took part are not considered, because a team alone
+    /// has nobody to win against.
+    pub(crate) fn concluded_since(&self, start: usize) -> bool {
+        if self.phase() == BattlePhase::Ended {
+            return false;
+        }
+        let events = &self.history.events()[start..];
+        let checked = events
+            .iter()
+            .any(|event| event.kind() == EventKind::ConcludeObjectives && event.origin().is_some());
+        let removed = events
+            .iter()
+            .filter(|event| event.kind() == EventKind::RemoveTeam)
+            .count();
+        checked
+            && self.entities().teams().count() + removed > 1
+            && self
+                .entities()
+                .teams()
+                .all(|team| team.conclusion().is_some())
+    }
+
+    /// Returns a summary of the teams' conclusions, if the battle is over.
+    ///
+    /// The outcome decided by `BattleRules::check_battle_end`, if any, takes precedence.
+    pub fn outcome(&self) -> Option<BattleOutcome<R>> {
+        if let Some(outcome) = &self.outcome {
+            Some(outcome.clone())
+        } else if self.ended() {
+            Some(BattleOutcome::new(self.entities().teams().filter_map(
+                |team| {
+                    team.conclusion()
+                        .map(|conclusion| (team.id().clone(), conclusion))
+                },
+            )))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the state of this battle.
+    pub fn state(&self) -> &BattleState<R> {
+        &self.state
+    }
+
     /// Returns the entities manager for this battle.
     pub fn entities(&self) -> &Entities<R> {
         &self.state.entities
@@ -174,24 +384,24 @@ impl<R: BattleRules + 'static> Battle<R> {
     }
 
     /// Returns a handle from which metrics can be read.
-    pub fn metrics(&self) -> ReadMetrics<R> {
+    pub fn metrics(&self) -> ReadMetrics<'_, R> {
         self.metrics.read_handle()
     }
 
     /// Returns a handle from which metrics can be modified.
-    pub fn metrics_mut(&mut self) -> WriteMetrics<R> {
+    pub fn metrics_mut(&mut self) -> WriteMetrics<'_, R> {
         self.metrics.write_handle()
     }
 
     /// Returns a handle to access the players' rights to control one or more teams.
-    pub(crate) fn rights(&self) -> RightsHandle<R> {
+    pub(crate) fn rights(&self) -> RightsHandle<'_, R> {
         RightsHandle::new(&self.rights)
     }
 
     /// Returns a mutable handle to manage the players' rights to control one or more teams.
     pub(crate) fn rights_mut<'a>(
         &'a mut self,
-    ) -> RightsHandleMut<R, impl Iterator<Item = &'a TeamId<R>>> {
+    ) -> RightsHandleMut<'a, R, impl Iterator<Item = &'a TeamId<R>>> {
         RightsHandleMut::new(
             &mut self.rights,
             self.state.entities().teams().map(|team| team.id()),
@@ -210,25 +420,68 @@ impl<R: BattleRules + 'static> Battle<R> {
             .map(move |e| e.clone().version(self.rules().version().clone()))
     }
 
+    /// Asks the rules whether the battle should end and, if so, fires an `EndBattle` event.
+    ///
+    /// The outcome is stored only when `EndBattle` is applied, so that the events queued
+    /// before it are still accepted.
+    fn check_battle_end<P>(&mut self, processor: &mut P)
+    where
+        P: EventProcessor<R>,
+    {
+        if self.phase() == BattlePhase::Ended || self.pending_outcome.is_some() {
+            return;
+        }
+        if let Some(outcome) = self
+            .rules
+            .check_battle_end(&self.state, &self.metrics.read_handle())
+        {
+            self.pending_outcome = Some(outcome);
+            EndBattle::trigger(processor).fire();
+        }
+    }
+
+    /// Computes the objectives progress of all teams and stores it in each team.
+    fn update_objectives_progress(
+        state: &mut BattleState<R>,
+        rules: &R::TR,
+        metrics: &ReadMetrics<R>,
+    ) {
+        let progress: Vec<_> = state
+            .entities
+            .teams()
+            .map(|team| {
+                (
+                    team.id().clone(),
+                    rules.objectives_progress(state, team, metrics),
+                )
+            })
+            .collect();
+        for (id, progress) in progress {
+            if let Some(team) = state.entities.team_mut(&id) {
+                team.set_objectives_progress(progress);
+            }
+        }
+    }
+
     /// Checks if one or more teams have completed their objectives and creates events accordingly.
     pub(crate) fn check_objectives<P>(
         state: &BattleState<R>,
         rules: &R::TR,
         metrics: &ReadMetrics<R>,
         processor: &mut P,
-        checkpoint: Checkpoint,
+        checkpoint: Checkpoint<'_, R>,
     ) where
         P: EventProcessor<R>,
     {
         /// Put common login into a macro.
         macro_rules! run_check {
-            ($function: ident) => {{
+            ($function: ident $(, $arg: expr)*) => {{
                 for team in state
                     .entities
                     .teams()
                     .filter(|team| team.conclusion().is_none())
                 {
-                    if let Some(conclusion) = rules.$function(state, team, metrics) {
+                    if let Some(conclusion) = rules.$function(state, team, metrics $(, $arg)*) {
                         // Team has a conclusion, fire an event.
                         ConcludeObjectives::trigger(processor, team.id().clone(), conclusion)
                             .fire();
@@ -242,19 +495,50 @@ impl<R: BattleRules + 'static> Battle<R> {
             Checkpoint::RoundEnd => {
                 run_check!(check_objectives_on_round);
             }
-            Checkpoint::EventEnd => {
-                run_check!(check_objectives_on_event);
+            Checkpoint::EventEnd(delta) => {
+                run_check!(check_objectives_on_event, delta);
             }
         }
     }
 }
 
+/// Changes done by an event to the metrics and to the players' rights.
+struct EventChanges<R: BattleRules> {
+    /// Original value of the changed metrics.
+    metrics: MetricsChanges<R>,
+    /// Players' rights before the event, if it changed them.
+    rights: Option<Rights<R>>,
+}
+
+impl<R: BattleRules> Clone for EventChanges<R> {
+    fn clone(&self) -> Self {
+        EventChanges {
+            metrics: self.metrics.clone(),
+            rights: self.rights.as_ref().map(Rights::snapshot),
+        }
+    }
+}
+
 /// Checkpoint in which a `check_objective` is run.
-pub(crate) enum Checkpoint {
+pub(crate) enum Checkpoint<'a, R: BattleRules> {
     /// At the end of a round.
     RoundEnd,
-    /// At the end of an event.
-    EventEnd,
+    /// At the end of an event, with the changes in metrics caused by the event.
+    EventEnd(&'a MetricsDelta<'a, R>),
+}
+
+/// A trait for objects that hold a battle and give read access to its state.
+///
+/// Use it to write code, such as UI or AI, that works with both clients and servers.
+pub trait HasBattleState<R: BattleRules> {
+    /// Returns the current state of the battle.
+    fn state(&self) -> &BattleState<R>;
+}
+
+impl<R: BattleRules> HasBattleState<R> for Battle<R> {
+    fn state(&self) -> &BattleState<R> {
+        &self.state
+    }
 }
 
 /// Owns he battle submodules that contain the current state of the battle.
@@ -285,10 +569,140 @@ impl<R: BattleRules> BattleState<R> {
     pub fn phase(&self) -> BattlePhase {
         self.phase
     }
+
+    /// Compares this state with `other` and returns all differences between the two.
+    ///
+    /// The comparison covers teams with their conclusions and objectives, entities with
+    /// their statistics, actors with their abilities, cooldowns and disabled state,
+    /// relations between teams, the rounds' state and the battle phase.
+    /// Two states produced by the same history should never have any difference.
+    pub fn diff(&self, other: &BattleState<R>) -> Vec<StateDifference<R>>
+    where
+        Statistic<R>: PartialEq,
+        Ability<R>: PartialEq,
+        Objectives<R>: PartialEq,
+        ObjectivesProgress<R>: PartialEq,
+    {
+        let mut differences = Vec::new();
+        let (this, that) = (&self.entities, &other.entities);
+        // Compare teams and their relations.
+        for team in this.teams().chain(that.teams()) {
+            if this.team(team.id()).is_none() || that.team(team.id()).is_none() {
+                differences.push(StateDifference::Team(team.id().clone()));
+            }
+        }
+        for team in this.teams() {
+            let other_team = match that.team(team.id()) {
+                Some(other_team) => other_team,
+                None => continue,
+            };
+            if team.conclusion() != other_team.conclusion() {
+                differences.push(StateDifference::Conclusion(team.id().clone()));
+            }
+            if team.objectives() != other_team.objectives()
+                || team.objectives_progress() != other_team.objectives_progress()
+            {
+                differences.push(StateDifference::Objectives(team.id().clone()));
+            }
+        }
+        for first in this.teams() {
+            for second in this.teams() {
+                if first.id() == second.id() {
+                    continue;
+                }
+                let relation = that.directed_relation(first.id(), second.id());
+                if relation.is_some() && this.directed_relation(first.id(), second.id()) != relation
+                {
+                    differences.push(StateDifference::Relation(
+                        first.id().clone(),
+                        second.id().clone(),
+                    ));
+                }
+            }
+        }
+        // Compare entities.
+        for entity in this.entities().chain(that.entities()) {
+            let id = entity.entity_id();
+            if this.entity(id).is_none() || that.entity(id).is_none() {
+                differences.push(StateDifference::Entity(id.clone()));
+            }
+        }
+        for entity in this.entities() {
+            let id = entity.entity_id();
+            let other_entity = match that.entity(id) {
+                Some(other_entity) => other_entity,
+                None => continue,
+            };
+            if entity.position() != other_entity.position() {
+                differences.push(StateDifference::Position(id.clone()));
+            }
+            if let (Some(actor), Some(other_actor)) = (this.actor(id), that.actor(id)) {
+                if actor.team_id() != other_actor.team_id() {
+                    differences.push(StateDifference::EntityTeam(id.clone()));
+                }
+                if actor.is_disabled() != other_actor.is_disabled() {
+                    differences.push(StateDifference::Disabled(id.clone()));
+                }
+                for ability in actor.abilities() {
+                    if other_actor.ability(ability.id()) != Some(ability) {
+                        differences
+                            .push(StateDifference::Ability(id.clone(), ability.id().clone()));
+                    }
+                }
+                for ability in other_actor.abilities() {
+                    if actor.ability(ability.id()).is_none() {
+                        differences
+                            .push(StateDifference::Ability(id.clone(), ability.id().clone()));
+                    }
+                }
+                for ability in actor.abilities().chain(
+                    other_actor
+                        .abilities()
+                        .filter(|ability| actor.ability(ability.id()).is_none()),
+                ) {
+                    if actor.cooldown(ability.id()) != other_actor.cooldown(ability.id()) {
+                        differences
+                            .push(StateDifference::Cooldown(id.clone(), ability.id().clone()));
+                    }
+                }
+            }
+            if let (Some(character), Some(other_character)) =
+                (this.character(id), that.character(id))
+            {
+                for statistic in character.statistics() {
+                    if other_character.statistic(statistic.id()) != Some(statistic) {
+                        differences.push(StateDifference::Statistic(
+                            id.clone(),
+                            statistic.id().clone(),
+                        ));
+                    }
+                }
+                for statistic in other_character.statistics() {
+                    if character.statistic(statistic.id()).is_none() {
+                        differences.push(StateDifference::Statistic(
+                            id.clone(),
+                            statistic.id().clone(),
+                        ));
+                    }
+                }
+            }
+        }
+        // Compare rounds and phase.
+        if self.rounds.state() != other.rounds.state()
+            || self.rounds.order() != other.rounds.order()
+        {
+            differences.push(StateDifference::Rounds);
+        }
+        if self.phase != other.phase {
+            differences.push(StateDifference::Phase);
+        }
+        differences
+    }
 }
 
 /// All possible phases in which a battle can be.
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub enum BattlePhase {
     /// The battle has started.
     Started,
@@ -296,6 +710,197 @@ pub enum BattlePhase {
     Ended,
 }
 
+/// The final result of a battle, that is the conclusion reached by each team.
+///
+/// Teams that didn't reach any conclusion are not part of the outcome.
+pub struct BattleOutcome<R: BattleRules> {
+    conclusions: Vec<(TeamId<R>, Conclusion)>,
+}
+
+impl<R: BattleRules> BattleOutcome<R> {
+    /// Creates a new outcome from a list of teams' conclusions.
+    pub fn new<I>(conclusions: I) -> Self
+    where
+        I: IntoIterator<Item = (TeamId<R>, Conclusion)>,
+    {
+        Self {
+            conclusions: conclusions.into_iter().collect(),
+        }
+    }
+
+    /// Returns the conclusion reached by the team with the given id.
+    pub fn conclusion(&self, id: &TeamId<R>) -> Option<Conclusion> {
+        self.conclusions
+            .iter()
+            .find(|(team_id, _)| team_id == id)
+            .map(|(_, conclusion)| *conclusion)
+    }
+
+    /// Returns an iterator over all teams' conclusions.
+    pub fn conclusions(&self) -> impl Iterator<Item = (&TeamId<R>, Conclusion)> {
+        self.conclusions
+            .iter()
+            .map(|(team_id, conclusion)| (team_id, *conclusion))
+    }
+
+    /// Returns an iterator over the teams that won the battle.
+    pub fn winners(&self) -> impl Iterator<Item = &TeamId<R>> {
+        self.teams_with(Conclusion::Victory)
+    }
+
+    /// Returns an iterator over the teams that lost the battle.
+    pub fn losers(&self) -> impl Iterator<Item = &TeamId<R>> {
+        self.teams_with(Conclusion::Defeat)
+    }
+
+    /// Returns an iterator over the teams that ended the battle in a draw.
+    pub fn draws(&self) -> impl Iterator<Item = &TeamId<R>> {
+        self.teams_with(Conclusion::Draw)
+    }
+
+    fn teams_with(&self, conclusion: Conclusion) -> impl Iterator<Item = &TeamId<R>> {
+        self.conclusions
+            .iter()
+            .filter(move |(_, c)| *c == conclusion)
+            .map(|(team_id, _)| team_id)
+    }
+}
+
+impl<R: BattleRules> Debug for BattleOutcome<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BattleOutcome {{ conclusions: {:?} }}", self.conclusions)
+    }
+}
+
+impl<R: BattleRules> Clone for BattleOutcome<R> {
+    fn clone(&self) -> Self {
+        Self {
+            conclusions: self.conclusions.clone(),
+        }
+    }
+}
+
+impl<R: BattleRules> PartialEq for BattleOutcome<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.conclusions == other.conclusions
+    }
+}
+
+/// A divergence between two battle states, as found by `BattleState::diff`.
+pub enum StateDifference<R: BattleRules> {
+    /// A team exists only in one of the two states.
+    Team(TeamId<R>),
+    /// A team reached a different conclusion.
+    Conclusion(TeamId<R>),
+    /// A team has different objectives or a different progress towards them.
+    Objectives(TeamId<R>),
+    /// The relation of the first team towards the second one is different.
+    Relation(TeamId<R>, TeamId<R>),
+    /// An entity exists only in one of the two states.
+    Entity(EntityId<R>),
+    /// An entity is in a different position.
+    Position(EntityId<R>),
+    /// An entity belongs to a different team.
+    EntityTeam(EntityId<R>),
+    /// A statistic of an entity has a different value or exists only in one of the two states.
+    Statistic(EntityId<R>, StatisticId<R>),
+    /// An ability of an actor has a different value or exists only in one of the two states.
+    Ability(EntityId<R>, AbilityId<R>),
+    /// An ability of an actor has a different cooldown.
+    Cooldown(EntityId<R>, AbilityId<R>),
+    /// An actor is disabled in only one of the two states.
+    Disabled(EntityId<R>),
+    /// The rounds' state or the order of actors is different.
+    Rounds,
+    /// The battle is in a different phase.
+    Phase,
+}
+
+impl<R: BattleRules> Debug for StateDifference<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateDifference::Team(id) => write!(f, "Team {{ {:?} }}", id),
+            StateDifference::Conclusion(id) => write!(f, "Conclusion {{ {:?} }}", id),
+            StateDifference::Objectives(id) => write!(f, "Objectives {{ {:?} }}", id),
+            StateDifference::Relation(first, second) => {
+                write!(f, "Relation {{ {:?}, {:?} }}", first, second)
+            }
+            StateDifference::Entity(id) => write!(f, "Entity {{ {:?} }}", id),
+            StateDifference::Position(id) => write!(f, "Position {{ {:?} }}", id),
+            StateDifference::EntityTeam(id) => write!(f, "EntityTeam {{ {:?} }}", id),
+            StateDifference::Statistic(id, statistic_id) => {
+                write!(f, "Statistic {{ {:?}, {:?} }}", id, statistic_id)
+            }
+            StateDifference::Ability(id, ability_id) => {
+                write!(f, "Ability {{ {:?}, {:?} }}", id, ability_id)
+            }
+            StateDifference::Cooldown(id, ability_id) => {
+                write!(f, "Cooldown {{ {:?}, {:?} }}", id, ability_id)
+            }
+            StateDifference::Disabled(id) => write!(f, "Disabled {{ {:?} }}", id),
+            StateDifference::Rounds => write!(f, "Rounds"),
+            StateDifference::Phase => write!(f, "Phase"),
+        }
+    }
+}
+
+impl<R: BattleRules> Clone for StateDifference<R> {
+    fn clone(&self) -> Self {
+        match self {
+            StateDifference::Team(id) => StateDifference::Team(id.clone()),
+            StateDifference::Conclusion(id) => StateDifference::Conclusion(id.clone()),
+            StateDifference::Objectives(id) => StateDifference::Objectives(id.clone()),
+            StateDifference::Relation(first, second) => {
+                StateDifference::Relation(first.clone(), second.clone())
+            }
+            StateDifference::Entity(id) => StateDifference::Entity(id.clone()),
+            StateDifference::Position(id) => StateDifference::Position(id.clone()),
+            StateDifference::EntityTeam(id) => StateDifference::EntityTeam(id.clone()),
+            StateDifference::Statistic(id, statistic_id) => {
+                StateDifference::Statistic(id.clone(), statistic_id.clone())
+            }
+            StateDifference::Ability(id, ability_id) => {
+                StateDifference::Ability(id.clone(), ability_id.clone())
+            }
+            StateDifference::Cooldown(id, ability_id) => {
+                StateDifference::Cooldown(id.clone(), ability_id.clone())
+            }
+            StateDifference::Disabled(id) => StateDifference::Disabled(id.clone()),
+            StateDifference::Rounds => StateDifference::Rounds,
+            StateDifference::Phase => StateDifference::Phase,
+        }
+    }
+}
+
+impl<R: BattleRules> PartialEq for StateDifference<R> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (StateDifference::Team(a), StateDifference::Team(b)) => a == b,
+            (StateDifference::Conclusion(a), StateDifference::Conclusion(b)) => a == b,
+            (StateDifference::Objectives(a), StateDifference::Objectives(b)) => a == b,
+            (StateDifference::Relation(a1, a2), StateDifference::Relation(b1, b2)) => {
+                a1 == b1 && a2 == b2
+            }
+            (StateDifference::Entity(a), StateDifference::Entity(b)) => a == b,
+            (StateDifference::Position(a), StateDifference::Position(b)) => a == b,
+            (StateDifference::EntityTeam(a), StateDifference::EntityTeam(b)) => a == b,
+            (StateDifference::Statistic(a1, a2), StateDifference::Statistic(b1, b2)) => {
+                a1 == b1 && a2 == b2
+            }
+            (StateDifference::Ability(a1, a2), StateDifference::Ability(b1, b2)) => {
+                a1 == b1 && a2 == b2
+            }
+            (StateDifference::Cooldown(a1, a2), StateDifference::Cooldown(b1, b2)) => {
+                a1 == b1 && a2 == b2
+            }
+            (StateDifference::Disabled(a), StateDifference::Disabled(b)) => a == b,
+            (StateDifference::Rounds, StateDifference::Rounds) => true,
+            (StateDifference::Phase, StateDifference::Phase) => true,
+            _ => false,
+        }
+    }
+}
+
 /// Contains the set of rules for this battle.
 /// It's a trait that uses composition to gather all other subsystem rules in a single place.
 ///
@@ -351,6 +956,47 @@ pub trait BattleRules: std::marker::Sized {
 
     /// Returns the version of this battle rules.
     fn version(&self) -> &Self::Version;
+
+    /// Invoked when an event created with a different rules' version is received.\
+    /// Returns an equivalent event compatible with the current version, or `None` if
+    /// `event` can't be upgraded and must be rejected.
+    ///
+    /// The provided implementation returns `None`.
+    fn migrate_event(
+        &self,
+        _from_version: &Self::Version,
+        _event: &dyn Event<Self>,
+    ) -> Option<Box<dyn Event<Self>>> {
+        None
+    }
+
+    /// Invoked after each event, including those ending a round, to decide whether
+    /// the battle is over.\
+    /// Returning an outcome fires an `EndBattle` event, after which no other event
+    /// is accepted. The outcome is then available through `Battle::outcome`.
+    ///
+    /// The provided implementation returns `None`, so battles never end by themselves.
+    fn check_battle_end(
+        &self,
+        _state: &BattleState<Self>,
+        _metrics: &ReadMetrics<Self>,
+    ) -> Option<BattleOutcome<Self>> {
+        None
+    }
+
+    /// Invoked once every time a round ends, after the callbacks of actor, team and
+    /// rounds rules.\
+    /// Use it for battlefield-wide effects that don't belong to any team or actor.
+    ///
+    /// The provided implementation does nothing.
+    fn on_round_end(
+        &self,
+        _state: &BattleState<Self>,
+        _event_queue: &mut Option<EventQueue<Self>>,
+        _entropy: &mut Entropy<Self>,
+        _metrics: &mut WriteMetrics<Self>,
+    ) {
+    }
 }
 
 /// Type to represent the version of this battle rules.
@@ -361,6 +1007,8 @@ pub type Version<R> = <R as BattleRules>::Version;
 pub struct BattleBuilder<R: BattleRules> {
     rules: R,
     event_callback: Option<EventCallback<R>>,
+    #[cfg(feature = "serialization")]
+    snapshot: Option<BattleSnapshot<R>>,
 }
 
 impl<R: BattleRules> BattleBuilder<R> {
@@ -370,9 +1018,17 @@ impl<R: BattleRules> BattleBuilder<R> {
         self
     }
 
+    /// Restores the state of the battle from a snapshot.
+    #[cfg(feature = "serialization")]
+    pub fn snapshot(mut self, snapshot: BattleSnapshot<R>) -> BattleBuilder<R> {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
     /// Creates a new battle.
     pub fn build(mut self) -> Battle<R> {
-        Battle {
+        #[allow(unused_mut)]
+        let mut battle = Battle {
             state: BattleState {
                 entities: Entities::new(),
                 space: Space::new(None, self.rules.space_rules()),
@@ -385,7 +1041,19 @@ impl<R: BattleRules> BattleBuilder<R> {
             event_callback: self.event_callback,
             metrics: Metrics::new(),
             rights: Rights::new(),
+            changes: Vec::new(),
+            outcome: None,
+            pending_outcome: None,
+            restored: false,
+        };
+        #[cfg(feature = "serialization")]
+        {
+            if let Some(snapshot) = self.snapshot {
+                snapshot.restore(&mut battle);
+                battle.restored = true;
+            }
         }
+        battle
     }
 }
 
@@ -398,7 +1066,7 @@ pub struct EndBattle<R> {
 
 impl<R: BattleRules> EndBattle<R> {
     /// Returns a trigger for this event.
-    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> EndBattleTrigger<R, P> {
+    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> EndBattleTrigger<'_, R, P> {
         EndBattleTrigger {
             processor,
             _phantom: PhantomData,
@@ -486,18 +1154,15 @@ mod tests {
         _: &BattleState<CustomRules>,
         event_queue: &mut Option<EventQueue<CustomRules>>,
     ) {
-        match event.kind() {
-            // Each time a team is created, check the team id and fire a dummy event.
-            EventKind::CreateTeam => {
-                let create_team: &CreateTeam<CustomRules> =
-                    match event.as_any().downcast_ref::<CreateTeam<CustomRules>>() {
-                        Some(b) => b,
-                        None => panic!("incorrect cast!"),
-                    };
-                assert_eq!(*create_team.id(), 1);
-                dummy(event_queue);
-            }
-            _ => {} // Do nothing.
+        // Each time a team is created, check the team id and fire a dummy event.
+        if let EventKind::CreateTeam = event.kind() {
+            let create_team: &CreateTeam<CustomRules> =
+                match event.as_any().downcast_ref::<CreateTeam<CustomRules>>() {
+                    Some(b) => b,
+                    None => panic!("incorrect cast!"),
+                };
+            assert_eq!(*create_team.id(), 1);
+            dummy(event_queue);
         }
     }
 
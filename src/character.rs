@@ -1,6 +1,7 @@
 //! Character rules.
 
 use crate::battle::{Battle, BattleRules};
+use crate::creature::RemovalReason;
 use crate::entity::{transmute_entity, Entities, Entity, EntityId, Transmutation};
 use crate::entropy::Entropy;
 use crate::error::{WeaselError, WeaselResult};
@@ -22,8 +23,19 @@ pub trait CharacterRules<R: BattleRules> {
     /// See [CreatureId](../creature/type.CreatureId.html).
     type CreatureId: Hash + Eq + Clone + Debug + Serialize + for<'a> Deserialize<'a>;
 
+    #[cfg(not(feature = "serialization"))]
+    /// See [ObjectId](../object/type.ObjectId.html).
+    type ObjectId: Hash + Eq + Clone + Debug;
+    #[cfg(feature = "serialization")]
+    /// See [ObjectId](../object/type.ObjectId.html).
+    type ObjectId: Hash + Eq + Clone + Debug + Serialize + for<'a> Deserialize<'a>;
+
+    #[cfg(not(feature = "serialization"))]
+    /// See [Statistic](type.Statistic.html).
+    type Statistic: Id + Clone + Debug + 'static;
+    #[cfg(feature = "serialization")]
     /// See [Statistic](type.Statistic.html).
-    type Statistic: Id + 'static;
+    type Statistic: Id + Clone + Debug + 'static + Serialize + for<'a> Deserialize<'a>;
 
     #[cfg(not(feature = "serialization"))]
     /// See [StatisticsSeed](type.StatisticsSeed.html).
@@ -66,6 +78,50 @@ pub trait CharacterRules<R: BattleRules> {
     ) -> Option<Transmutation> {
         None
     }
+
+    /// Invoked after a character gained a new statistic, as result of an event
+    /// changing its set of statistics.
+    ///
+    /// The provided implementation does nothing.
+    fn on_statistic_added(
+        &self,
+        _character: &dyn Character<R>,
+        _statistic: &Statistic<R>,
+        _event_queue: &mut Option<EventQueue<R>>,
+        _entropy: &mut Entropy<R>,
+        _metrics: &mut WriteMetrics<R>,
+    ) {
+    }
+
+    /// Invoked after a character lost one of its statistics, as result of an event
+    /// changing its set of statistics.
+    ///
+    /// The provided implementation does nothing.
+    fn on_statistic_removed(
+        &self,
+        _character: &dyn Character<R>,
+        _statistic: &Statistic<R>,
+        _event_queue: &mut Option<EventQueue<R>>,
+        _entropy: &mut Entropy<R>,
+        _metrics: &mut WriteMetrics<R>,
+    ) {
+    }
+
+    /// Invoked after a character has been removed from the battle.
+    ///
+    /// `reason` tells why the character was removed, for instance to distinguish
+    /// a defeat from a manual cleanup.
+    ///
+    /// The provided implementation does nothing.
+    fn on_remove(
+        &self,
+        _character: &dyn Character<R>,
+        _reason: RemovalReason,
+        _event_queue: &mut Option<EventQueue<R>>,
+        _entropy: &mut Entropy<R>,
+        _metrics: &mut WriteMetrics<R>,
+    ) {
+    }
 }
 
 /// Type to represent an individual statistic.
@@ -92,6 +148,19 @@ pub trait Character<R: BattleRules>: Entity<R> {
     /// Returns the statistic with the given id.
     fn statistic(&self, id: &StatisticId<R>) -> Option<&Statistic<R>>;
 
+    /// Returns an iterator over the statistics that satisfy `predicate`.
+    ///
+    /// The provided implementation filters the output of `statistics`.
+    fn statistics_where<'a>(
+        &'a self,
+        predicate: &'a dyn Fn(&Statistic<R>) -> bool,
+    ) -> Box<dyn Iterator<Item = &'a Statistic<R>> + 'a> {
+        Box::new(
+            self.statistics()
+                .filter(move |statistic| predicate(statistic)),
+        )
+    }
+
     /// Returns a mutable reference to the statistic with the given id.
     fn statistic_mut(&mut self, id: &StatisticId<R>) -> Option<&mut Statistic<R>>;
 
@@ -241,6 +310,135 @@ where
     }
 }
 
+/// An event to alter the statistics of several characters at once.
+///
+/// Alterations are applied in order. The event is verified as a whole: if any of the
+/// characters doesn't exist, none of the alterations will take place.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct AlterStatisticsBatch<R: BattleRules> {
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "Vec<(EntityId<R>, StatisticsAlteration<R>)>: Serialize",
+            deserialize = "Vec<(EntityId<R>, StatisticsAlteration<R>)>: Deserialize<'de>"
+        ))
+    )]
+    alterations: Vec<(EntityId<R>, StatisticsAlteration<R>)>,
+}
+
+impl<R: BattleRules> AlterStatisticsBatch<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &mut P,
+        alterations: Vec<(EntityId<R>, StatisticsAlteration<R>)>,
+    ) -> AlterStatisticsBatchTrigger<'_, R, P> {
+        AlterStatisticsBatchTrigger {
+            processor,
+            alterations,
+        }
+    }
+
+    /// Returns all pairs of character's entity id and alteration, in order of application.
+    pub fn alterations(&self) -> &Vec<(EntityId<R>, StatisticsAlteration<R>)> {
+        &self.alterations
+    }
+}
+
+impl<R: BattleRules> Debug for AlterStatisticsBatch<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "AlterStatisticsBatch {{ alterations: {:?} }}",
+            self.alterations
+        )
+    }
+}
+
+impl<R: BattleRules> Clone for AlterStatisticsBatch<R> {
+    fn clone(&self) -> Self {
+        AlterStatisticsBatch {
+            alterations: self.alterations.clone(),
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for AlterStatisticsBatch<R> {
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        for (index, (id, _)) in self.alterations.iter().enumerate() {
+            verify_is_character(battle.entities(), id)
+                .map_err(|err| WeaselError::BatchItemFailed(index, Box::new(err)))?;
+        }
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
+        // Collect the transmutations, to keep them in the same order as the alterations.
+        let mut transmutations = EventQueue::new();
+        for (id, alteration) in &self.alterations {
+            // Retrieve the character.
+            let character = battle
+                .state
+                .entities
+                .character_mut(id)
+                .unwrap_or_else(|| panic!("constraint violated: character {:?} not found", id));
+            // Alter the character.
+            let transmutation = battle.rules.character_rules().alter(
+                character,
+                alteration,
+                &mut battle.entropy,
+                &mut battle.metrics.write_handle(),
+            );
+            // Change the character's existence if needed.
+            if let Some(transmutation) = transmutation {
+                transmute_entity(id, transmutation, &mut transmutations);
+            }
+        }
+        // Put all transmutations in front of the queue.
+        if let Some(queue) = event_queue {
+            queue.splice(0..0, transmutations);
+        }
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::AlterStatisticsBatch
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire an `AlterStatisticsBatch` event.
+pub struct AlterStatisticsBatchTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    alterations: Vec<(EntityId<R>, StatisticsAlteration<R>)>,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for AlterStatisticsBatchTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns an `AlterStatisticsBatch` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(AlterStatisticsBatch {
+            alterations: self.alterations.clone(),
+        })
+    }
+}
+
 /// An event to regenerate the statistics of a character.
 ///
 /// A new set of statistics is created from a seed.\
@@ -317,7 +515,7 @@ impl<R: BattleRules + 'static> Event<R> for RegenerateStatistics<R> {
         verify_is_character(battle.entities(), &self.id)
     }
 
-    fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
         // Retrieve the character.
         let character = battle
             .state
@@ -345,15 +543,19 @@ impl<R: BattleRules + 'static> Event<R> for RegenerateStatistics<R> {
                 to_remove.push(statistic.id().clone());
             }
         }
-        for statistic_id in to_remove {
-            character.remove_statistic(&statistic_id);
-        }
+        let removed: Vec<_> = to_remove
+            .iter()
+            .filter_map(|statistic_id| character.remove_statistic(statistic_id))
+            .collect();
         // Add all statistics present in the new set but not in the character.
+        let mut added = Vec::new();
         for statistic in statistics {
             if character.statistic(statistic.id()).is_none() {
+                added.push(statistic.clone());
                 character.add_statistic(statistic);
             }
         }
+        notify_statistics_changed(battle, &self.id, &added, &removed, event_queue);
     }
 
     fn kind(&self) -> EventKind {
@@ -413,8 +615,330 @@ where
     }
 }
 
+/// An event to remove a single statistic from a character.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct RemoveStatistic<R: BattleRules> {
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "EntityId<R>: Serialize",
+            deserialize = "EntityId<R>: Deserialize<'de>"
+        ))
+    )]
+    id: EntityId<R>,
+
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "StatisticId<R>: Serialize",
+            deserialize = "StatisticId<R>: Deserialize<'de>"
+        ))
+    )]
+    statistic_id: StatisticId<R>,
+}
+
+impl<R: BattleRules> RemoveStatistic<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &'_ mut P,
+        id: EntityId<R>,
+        statistic_id: StatisticId<R>,
+    ) -> RemoveStatisticTrigger<'_, R, P> {
+        RemoveStatisticTrigger {
+            processor,
+            id,
+            statistic_id,
+        }
+    }
+
+    /// Returns the character's entity id.
+    pub fn id(&self) -> &EntityId<R> {
+        &self.id
+    }
+
+    /// Returns the id of the statistic to be removed.
+    pub fn statistic_id(&self) -> &StatisticId<R> {
+        &self.statistic_id
+    }
+}
+
+impl<R: BattleRules> Debug for RemoveStatistic<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "RemoveStatistic {{ id: {:?}, statistic_id: {:?} }}",
+            self.id, self.statistic_id
+        )
+    }
+}
+
+impl<R: BattleRules> Clone for RemoveStatistic<R> {
+    fn clone(&self) -> Self {
+        RemoveStatistic {
+            id: self.id.clone(),
+            statistic_id: self.statistic_id.clone(),
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for RemoveStatistic<R> {
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        verify_is_character(battle.entities(), &self.id)?;
+        // Check if the character has the statistic.
+        let character = battle
+            .entities()
+            .character(&self.id)
+            .ok_or_else(|| WeaselError::NotACharacter(self.id.clone()))?;
+        if character.statistic(&self.statistic_id).is_none() {
+            return Err(WeaselError::StatisticNotFound(
+                self.id.clone(),
+                self.statistic_id.clone(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
+        // Retrieve the character.
+        let character = battle
+            .state
+            .entities
+            .character_mut(&self.id)
+            .unwrap_or_else(|| panic!("constraint violated: character {:?} not found", self.id));
+        // Remove the statistic.
+        let statistic = character
+            .remove_statistic(&self.statistic_id)
+            .unwrap_or_else(|| {
+                panic!(
+                    "constraint violated: statistic {:?} not found in {:?}",
+                    self.statistic_id, self.id
+                )
+            });
+        notify_statistics_changed(battle, &self.id, &[], &[statistic], event_queue);
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::RemoveStatistic
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire a `RemoveStatistic` event.
+pub struct RemoveStatisticTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    id: EntityId<R>,
+    statistic_id: StatisticId<R>,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for RemoveStatisticTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns a `RemoveStatistic` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(RemoveStatistic {
+            id: self.id.clone(),
+            statistic_id: self.statistic_id.clone(),
+        })
+    }
+}
+
+/// An event to add a single statistic to a character.
+///
+/// By default the event is rejected if the character already has a statistic with
+/// the same id. Use `AddStatisticTrigger::replace` to overwrite it instead.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct AddStatistic<R: BattleRules> {
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "EntityId<R>: Serialize",
+            deserialize = "EntityId<R>: Deserialize<'de>"
+        ))
+    )]
+    id: EntityId<R>,
+
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "Statistic<R>: Serialize",
+            deserialize = "Statistic<R>: Deserialize<'de>"
+        ))
+    )]
+    statistic: Statistic<R>,
+
+    replace: bool,
+}
+
+impl<R: BattleRules> AddStatistic<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &'_ mut P,
+        id: EntityId<R>,
+        statistic: Statistic<R>,
+    ) -> AddStatisticTrigger<'_, R, P> {
+        AddStatisticTrigger {
+            processor,
+            id,
+            statistic,
+            replace: false,
+        }
+    }
+
+    /// Returns the character's entity id.
+    pub fn id(&self) -> &EntityId<R> {
+        &self.id
+    }
+
+    /// Returns the statistic to be added.
+    pub fn statistic(&self) -> &Statistic<R> {
+        &self.statistic
+    }
+
+    /// Returns whether an existing statistic with the same id will be replaced.
+    pub fn replace(&self) -> bool {
+        self.replace
+    }
+}
+
+impl<R: BattleRules> Debug for AddStatistic<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "AddStatistic {{ id: {:?}, statistic: {:?}, replace: {:?} }}",
+            self.id, self.statistic, self.replace
+        )
+    }
+}
+
+impl<R: BattleRules> Clone for AddStatistic<R> {
+    fn clone(&self) -> Self {
+        AddStatistic {
+            id: self.id.clone(),
+            statistic: self.statistic.clone(),
+            replace: self.replace,
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for AddStatistic<R> {
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        verify_is_character(battle.entities(), &self.id)?;
+        // Check if the character already has the statistic.
+        if !self.replace {
+            let character = battle
+                .entities()
+                .character(&self.id)
+                .ok_or_else(|| WeaselError::NotACharacter(self.id.clone()))?;
+            if character.statistic(self.statistic.id()).is_some() {
+                return Err(WeaselError::DuplicatedStatistic(
+                    self.id.clone(),
+                    self.statistic.id().clone(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
+        // Retrieve the character.
+        let character = battle
+            .state
+            .entities
+            .character_mut(&self.id)
+            .unwrap_or_else(|| panic!("constraint violated: character {:?} not found", self.id));
+        // Add the statistic.
+        let removed: Vec<_> = character
+            .add_statistic(self.statistic.clone())
+            .into_iter()
+            .collect();
+        notify_statistics_changed(
+            battle,
+            &self.id,
+            std::slice::from_ref(&self.statistic),
+            &removed,
+            event_queue,
+        );
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::AddStatistic
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire an `AddStatistic` event.
+pub struct AddStatisticTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    id: EntityId<R>,
+    statistic: Statistic<R>,
+    replace: bool,
+}
+
+impl<'a, R, P> AddStatisticTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    /// Replaces the character's statistic with the same id, if it exists,
+    /// instead of rejecting the event.
+    pub fn replace(&'a mut self) -> &'a mut AddStatisticTrigger<'a, R, P> {
+        self.replace = true;
+        self
+    }
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for AddStatisticTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns an `AddStatistic` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(AddStatistic {
+            id: self.id.clone(),
+            statistic: self.statistic.clone(),
+            replace: self.replace,
+        })
+    }
+}
+
 /// Checks if an entity exists and is a character.
-fn verify_is_character<R>(entities: &Entities<R>, id: &EntityId<R>) -> WeaselResult<(), R>
+pub(crate) fn verify_is_character<R>(
+    entities: &Entities<R>,
+    id: &EntityId<R>,
+) -> WeaselResult<(), R>
 where
     R: BattleRules,
 {
@@ -428,3 +952,41 @@ where
         .ok_or_else(|| WeaselError::EntityNotFound(id.clone()))?;
     Ok(())
 }
+
+/// Invokes the `CharacterRules` callbacks for statistics that were removed from
+/// or added to the character with the given id.
+fn notify_statistics_changed<R>(
+    battle: &mut Battle<R>,
+    id: &EntityId<R>,
+    added: &[Statistic<R>],
+    removed: &[Statistic<R>],
+    event_queue: &mut Option<EventQueue<R>>,
+) where
+    R: BattleRules,
+{
+    let character = battle
+        .state
+        .entities
+        .character(id)
+        .unwrap_or_else(|| panic!("constraint violated: character {:?} not found", id));
+    let rules = battle.rules.character_rules();
+    let mut metrics = battle.metrics.write_handle();
+    for statistic in removed {
+        rules.on_statistic_removed(
+            character,
+            statistic,
+            event_queue,
+            &mut battle.entropy,
+            &mut metrics,
+        );
+    }
+    for statistic in added {
+        rules.on_statistic_added(
+            character,
+            statistic,
+            event_queue,
+            &mut battle.entropy,
+            &mut metrics,
+        );
+    }
+}
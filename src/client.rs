@@ -1,12 +1,21 @@
 //! A battle client.
 
-use crate::battle::{Battle, BattleRules, EventCallback};
-use crate::error::WeaselResult;
+use crate::battle::{Battle, BattleRules, BattleState, EventCallback, HasBattleState};
+use crate::error::{WeaselError, WeaselResult};
 use crate::event::{
-    EventProcessor, EventPrototype, EventReceiver, MultiClientSink, MultiClientSinkHandle,
-    MultiClientSinkHandleMut, ServerSink, VersionedEventWrapper,
+    Event, EventId, EventProcessor, EventPrototype, EventQueue, EventReceiver, MultiClientSink,
+    MultiClientSinkHandle, MultiClientSinkHandleMut, ServerSink, VersionedEventWrapper,
 };
 use crate::player::PlayerId;
+use std::collections::{BTreeMap, VecDeque};
+use std::rc::Rc;
+
+/// Maximum number of out-of-order events that a client keeps by default.
+const DEFAULT_PENDING_EVENTS_LIMIT: usize = 64;
+
+/// Function returning a new instance of the battle rules.
+/// Clients and servers use it to build copies of their battle.
+pub type RulesFactory<R> = Rc<dyn Fn() -> R>;
 
 /// A client event processor.
 ///
@@ -20,6 +29,11 @@ pub struct Client<R: BattleRules> {
     server_sink: Box<dyn ServerSink<R>>,
     client_sinks: MultiClientSink<R>,
     player: Option<PlayerId>,
+    pending_events: BTreeMap<EventId, VersionedEventWrapper<R>>,
+    pending_events_limit: usize,
+    prediction_rules: Option<RulesFactory<R>>,
+    predictions: VecDeque<Box<dyn Event<R>>>,
+    predicted: Option<Battle<R>>,
 }
 
 impl<R: BattleRules + 'static> Client<R> {
@@ -29,6 +43,8 @@ impl<R: BattleRules + 'static> Client<R> {
             battle,
             server_sink,
             player: None,
+            pending_events_limit: DEFAULT_PENDING_EVENTS_LIMIT,
+            prediction_rules: None,
         }
     }
 
@@ -80,6 +96,174 @@ impl<R: BattleRules + 'static> Client<R> {
     pub fn set_event_callback(&mut self, callback: Option<EventCallback<R>>) {
         self.battle.event_callback = callback;
     }
+
+    /// Receives an event, tolerating gaps in the timeline.
+    ///
+    /// Events coming ahead of the next expected one are kept aside and applied
+    /// as soon as all missing events have been received. If the number of pending
+    /// events would exceed the client's limit, the event is rejected with a
+    /// `NonContiguousEventId` error, as `receive` would do.
+    pub fn receive_buffered(&mut self, event: VersionedEventWrapper<R>) -> WeaselResult<(), R> {
+        let next_id = self.battle.history().len();
+        if event.wrapper().id() > next_id {
+            if self.pending_events.len() >= self.pending_events_limit {
+                return Err(WeaselError::NonContiguousEventId(
+                    event.wrapper().id(),
+                    next_id,
+                ));
+            }
+            self.pending_events.insert(event.wrapper().id(), event);
+            return Ok(());
+        }
+        self.receive(event)?;
+        // Apply all pending events that are now contiguous.
+        while let Some(event) = self.pending_events.remove(&self.battle.history().len()) {
+            self.receive(event)?;
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over the events received out of order and not yet applied,
+    /// sorted by id.
+    pub fn pending_events(&self) -> impl Iterator<Item = &VersionedEventWrapper<R>> {
+        self.pending_events.values()
+    }
+
+    /// Sends an event to the server and, if prediction is enabled, immediately applies it
+    /// to a local copy of the battle, without waiting for the server's confirmation.
+    ///
+    /// The predicted state is available through `predicted_battle`. Events received from
+    /// the server are applied to the predicted battle as well. If they diverge from the
+    /// predictions, the predicted state is rebuilt from the authoritative battle by applying
+    /// again all unconfirmed predictions. Thus, the server's outcome always overrides
+    /// the prediction.\
+    /// Predictions rejected by the server are discarded as soon as a more recent one
+    /// is confirmed.\
+    /// If the server rejects the event right away, an error is returned and nothing is
+    /// predicted. If prediction is not enabled, or the battle was restored from a snapshot,
+    /// this method is equivalent to `process`.
+    pub fn predict(&mut self, event: EventPrototype<R>) -> WeaselResult<(), R> {
+        self.process(event.clone())?;
+        if self.predicted.is_none() {
+            self.predicted = self.replica();
+        }
+        if let Some(predicted) = &mut self.predicted {
+            Client::apply_prediction(predicted, event.event().clone());
+            self.predictions.push_back(event.event().clone());
+        }
+        Ok(())
+    }
+
+    /// Returns the battle with all predicted events applied on top of it.
+    /// If there are no pending predictions, it is the same as `battle`.
+    pub fn predicted_battle(&self) -> &Battle<R> {
+        match &self.predicted {
+            Some(predicted) if !self.predictions.is_empty() => predicted,
+            _ => &self.battle,
+        }
+    }
+
+    /// Returns an iterator over the predicted events not yet confirmed by the server,
+    /// in the order in which they were predicted.
+    pub fn predictions(&self) -> impl Iterator<Item = &Box<dyn Event<R>>> {
+        self.predictions.iter()
+    }
+
+    /// Discards all predictions not yet confirmed by the server.
+    ///
+    /// Use this method if you know that the server won't confirm them, for instance
+    /// because it rejected the events asynchronously.
+    pub fn clear_predictions(&mut self) {
+        self.predictions.clear();
+        self.predicted = None;
+    }
+
+    /// Updates the predictions after an event has been received from the server.
+    fn reconcile_predictions(&mut self, event: &VersionedEventWrapper<R>) {
+        // A prediction is confirmed if the server sent back an equal event, fired by
+        // this client. The server processes the events of a client in order, therefore
+        // all older predictions were rejected.
+        let player = event.wrapper().player();
+        if event.origin().is_none() && (player.is_none() || player == self.player) {
+            if let Some(index) = self.predictions.iter().position(|prediction| {
+                Client::same_event(prediction.as_ref(), event.event().as_ref())
+            }) {
+                self.predictions.drain(..=index);
+            }
+        }
+        let predicted = match &mut self.predicted {
+            Some(predicted) => predicted,
+            None => return,
+        };
+        // The predicted battle contains all events received so far, followed by
+        // the predicted ones.
+        let index = event.id() as usize;
+        match predicted.history().events().get(index) {
+            // The event was predicted correctly.
+            Some(expected)
+                if expected.origin() == event.origin()
+                    && Client::same_event(expected.event().as_ref(), event.event().as_ref()) =>
+            {
+                return
+            }
+            // There are no predicted events left, thus the event can be applied right away.
+            None if index == predicted.history().len() as usize => {
+                predicted.apply(event.wrapper(), &mut None);
+                return;
+            }
+            // The prediction diverged.
+            _ => {}
+        }
+        // Rebuild the predicted state on top of the authoritative state.
+        self.predicted = self.replica();
+        if let Some(predicted) = &mut self.predicted {
+            for prediction in &self.predictions {
+                Client::apply_prediction(predicted, prediction.clone());
+            }
+        }
+    }
+
+    /// Returns a new battle in the same state of this client's battle.
+    ///
+    /// Returns `None` if prediction is not enabled or if the battle can't be copied.
+    fn replica(&self) -> Option<Battle<R>> {
+        let rules = (self.prediction_rules.as_ref()?)();
+        self.battle.replica(rules)
+    }
+
+    /// Applies a predicted event to `battle`, together with all events derived from it.
+    /// Events that are not valid are skipped.
+    fn apply_prediction(battle: &mut Battle<R>, event: Box<dyn Event<R>>) {
+        let mut prototypes = vec![EventPrototype::new(event)];
+        while !prototypes.is_empty() {
+            let prototype = prototypes.remove(0);
+            if battle.verify_prototype(&prototype).is_ok() {
+                let event = battle.promote(prototype);
+                let mut queue = Some(EventQueue::new());
+                battle.apply(&event, &mut queue);
+                // Derived events come before the remaining ones, as in the server.
+                for (i, mut derived) in queue.unwrap().into_iter().enumerate() {
+                    derived.origin = Some(event.id());
+                    prototypes.insert(i, derived);
+                }
+            }
+        }
+    }
+
+    /// Returns whether two events are equal.
+    ///
+    /// Events implement `PartialEq` by comparing only their kind, so the full
+    /// debug representations are compared instead.
+    fn same_event(first: &dyn Event<R>, second: &dyn Event<R>) -> bool {
+        first.kind() == second.kind() && format!("{:?}", first) == format!("{:?}", second)
+    }
+}
+
+impl<R: BattleRules + 'static> HasBattleState<R> for Client<R> {
+    /// Returns the state of the battle, without the predicted events.
+    fn state(&self) -> &BattleState<R> {
+        self.battle.state()
+    }
 }
 
 impl<R: BattleRules + 'static> EventProcessor<R> for Client<R> {
@@ -92,16 +276,49 @@ impl<R: BattleRules + 'static> EventProcessor<R> for Client<R> {
         // Send the event to the server.
         self.server_sink.send(&event)
     }
+
+    /// Sends a sequence of events to the server, in order.
+    ///
+    /// If prediction is enabled, the events are first applied on a copy of the battle,
+    /// without the events derived from them.
+    /// Thus, each event is verified against the state left by the previous ones.
+    /// Otherwise, or if the battle was restored from a snapshot, all events are verified
+    /// against the current state.\
+    /// Nothing is sent unless all events pass the verification. Keep in mind that the
+    /// server verifies again each event on its own, when it's received.
+    fn fire_all(&mut self, events: Vec<Box<dyn Event<R>>>) -> Self::ProcessOutput {
+        if let Some(mut battle) = self.replica() {
+            // Try all events on the copy.
+            for event in &events {
+                battle.verify_event(event.as_ref())?;
+                let event = battle.promote(EventPrototype::new(event.clone()));
+                battle.apply(&event, &mut None);
+            }
+        } else {
+            // Verify all events before sending any of them.
+            for event in &events {
+                self.battle.verify_event(event.as_ref())?;
+            }
+        }
+        for event in events {
+            self.process(EventPrototype::new(event))?;
+        }
+        Ok(())
+    }
 }
 
 impl<R: BattleRules + 'static> EventReceiver<R> for Client<R> {
-    fn receive(&mut self, event: VersionedEventWrapper<R>) -> WeaselResult<(), R> {
+    fn receive(&mut self, mut event: VersionedEventWrapper<R>) -> WeaselResult<(), R> {
+        // Upgrade the event if it comes from older rules.
+        self.battle.migrate_wrapper(&mut event);
         // Verify the event.
         self.battle.verify_wrapper(&event)?;
         // Apply the event on the battle.
-        self.battle.apply(&event.wrapper(), &mut None);
+        self.battle.apply(event.wrapper(), &mut None);
         // Send the event to all client sinks.
         self.client_sinks.send_all(&event);
+        // Update the predicted state.
+        self.reconcile_predictions(&event);
         Ok(())
     }
 }
@@ -111,6 +328,8 @@ pub struct ClientBuilder<R: BattleRules> {
     battle: Battle<R>,
     server_sink: Box<dyn ServerSink<R>>,
     player: Option<PlayerId>,
+    pending_events_limit: usize,
+    prediction_rules: Option<RulesFactory<R>>,
 }
 
 impl<R: BattleRules> ClientBuilder<R> {
@@ -121,6 +340,21 @@ impl<R: BattleRules> ClientBuilder<R> {
         self
     }
 
+    /// Sets the maximum number of out-of-order events kept by `receive_buffered`.
+    /// The default is 64.
+    pub fn pending_events_limit(mut self, limit: usize) -> ClientBuilder<R> {
+        self.pending_events_limit = limit;
+        self
+    }
+
+    /// Enables the prediction of events on the new client. See `Client::predict`.
+    ///
+    /// `rules` must return battle rules identical to the ones of the client's battle.
+    pub fn enable_prediction(mut self, rules: RulesFactory<R>) -> ClientBuilder<R> {
+        self.prediction_rules = Some(rules);
+        self
+    }
+
     /// Creates a new client.
     pub fn build(self) -> Client<R> {
         Client {
@@ -128,6 +362,11 @@ impl<R: BattleRules> ClientBuilder<R> {
             server_sink: self.server_sink,
             client_sinks: MultiClientSink::new(),
             player: self.player,
+            pending_events: BTreeMap::new(),
+            pending_events_limit: self.pending_events_limit,
+            prediction_rules: self.prediction_rules,
+            predictions: VecDeque::new(),
+            predicted: None,
         }
     }
 }
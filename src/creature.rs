@@ -2,13 +2,13 @@
 
 use crate::ability::{AbilitiesSeed, Ability, AbilityId};
 use crate::actor::{Actor, ActorRules};
-use crate::battle::{Battle, BattleRules, Checkpoint};
+use crate::battle::{Battle, BattleRules};
 use crate::character::{Character, CharacterRules, Statistic, StatisticId, StatisticsSeed};
 use crate::entity::{Entity, EntityId};
 use crate::error::{WeaselError, WeaselResult};
 use crate::event::{Event, EventKind, EventProcessor, EventQueue, EventTrigger};
 use crate::metric::system::*;
-use crate::round::RoundState;
+use crate::round::end_round;
 use crate::space::Position;
 use crate::team::{EntityAddition, TeamId, TeamRules};
 use crate::util::Id;
@@ -16,18 +16,18 @@ use crate::util::Id;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::collections::hash_map::Values;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter, Result};
 
 /// Type to represent the id of creatures.
 pub type CreatureId<R> = <<R as BattleRules>::CR as CharacterRules<R>>::CreatureId;
 
-type Statistics<R> = HashMap<
+pub(crate) type Statistics<R> = HashMap<
     <<<R as BattleRules>::CR as CharacterRules<R>>::Statistic as Id>::Id,
     <<R as BattleRules>::CR as CharacterRules<R>>::Statistic,
 >;
 
-type Abilities<R> = HashMap<
+pub(crate) type Abilities<R> = HashMap<
     <<<R as BattleRules>::AR as ActorRules<R>>::Ability as Id>::Id,
     <<R as BattleRules>::AR as ActorRules<R>>::Ability,
 >;
@@ -37,16 +37,18 @@ type Abilities<R> = HashMap<
 /// Creatures can activate abilities during their round, occupy a spatial position and
 /// are characterized by their statistics.
 pub struct Creature<R: BattleRules> {
-    id: EntityId<R>,
-    team_id: TeamId<R>,
-    position: Position<R>,
-    statistics: Statistics<R>,
-    abilities: Abilities<R>,
+    pub(crate) id: EntityId<R>,
+    pub(crate) team_id: TeamId<R>,
+    pub(crate) position: Position<R>,
+    pub(crate) statistics: Statistics<R>,
+    pub(crate) abilities: Abilities<R>,
+    pub(crate) cooldowns: HashMap<AbilityId<R>, u32>,
+    pub(crate) disabled: bool,
 }
 
 impl<R: BattleRules> Creature<R> {
     /// Returns an iterator over abilities.
-    pub fn abilities(&self) -> Values<AbilityId<R>, Ability<R>> {
+    pub fn abilities(&self) -> Values<'_, AbilityId<R>, Ability<R>> {
         self.abilities.values()
     }
 
@@ -55,17 +57,42 @@ impl<R: BattleRules> Creature<R> {
         self.abilities.get(id)
     }
 
+    /// Returns the id of the team to which this creature belongs.
+    pub fn team_id(&self) -> &TeamId<R> {
+        &self.team_id
+    }
+
     pub(crate) fn set_team_id(&mut self, id: TeamId<R>) {
         self.team_id = id;
     }
 }
 
+impl<R: BattleRules> Clone for Creature<R>
+where
+    Ability<R>: Clone,
+{
+    fn clone(&self) -> Self {
+        Creature {
+            id: self.id.clone(),
+            team_id: self.team_id.clone(),
+            position: self.position.clone(),
+            statistics: self.statistics.clone(),
+            abilities: self.abilities.clone(),
+            cooldowns: self.cooldowns.clone(),
+            disabled: self.disabled,
+        }
+    }
+}
+
 impl<R: BattleRules> Id for Creature<R> {
     type Id = CreatureId<R>;
 
     fn id(&self) -> &CreatureId<R> {
-        let EntityId::Creature(id) = &self.id;
-        id
+        if let EntityId::Creature(id) = &self.id {
+            id
+        } else {
+            panic!("constraint violated: creature's id has a wrong type")
+        }
     }
 }
 
@@ -129,9 +156,32 @@ impl<R: BattleRules> Actor<R> for Creature<R> {
     fn team_id(&self) -> &TeamId<R> {
         &self.team_id
     }
+
+    fn cooldown(&self, id: &AbilityId<R>) -> u32 {
+        self.cooldowns.get(id).copied().unwrap_or_default()
+    }
+
+    fn set_cooldown(&mut self, id: &AbilityId<R>, rounds: u32) {
+        if rounds > 0 {
+            self.cooldowns.insert(id.clone(), rounds);
+        } else {
+            self.cooldowns.remove(id);
+        }
+    }
+
+    fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
+    fn set_disabled(&mut self, disabled: bool) {
+        self.disabled = disabled;
+    }
 }
 
 /// Event to create a new creature.
+///
+/// The creature's starting position is validated with `SpaceRules::check_move` and
+/// then taken through `SpaceRules::move_entity`.
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct CreateCreature<R: BattleRules> {
     #[cfg_attr(
@@ -206,7 +256,7 @@ impl<R: BattleRules> Clone for CreateCreature<R> {
 impl<R: BattleRules> CreateCreature<R> {
     /// Collects an iterator into an hashmap.
     /// Subsequent values with same key are ignored.
-    fn collect_from_iter<I>(
+    pub(crate) fn collect_from_iter<I>(
         it: I,
     ) -> HashMap<<<I as Iterator>::Item as Id>::Id, <I as Iterator>::Item>
     where
@@ -222,6 +272,26 @@ impl<R: BattleRules> CreateCreature<R> {
         map
     }
 
+    /// Creates a new `CreateCreature` event.
+    ///
+    /// Use this method to build the creatures of a `CreateCreatures` batch. To fire a single
+    /// `CreateCreature` event use `trigger` instead.
+    pub fn new(
+        id: CreatureId<R>,
+        team_id: TeamId<R>,
+        position: Position<R>,
+        statistics_seed: Option<StatisticsSeed<R>>,
+        abilities_seed: Option<AbilitiesSeed<R>>,
+    ) -> CreateCreature<R> {
+        CreateCreature {
+            id,
+            team_id,
+            position,
+            statistics_seed,
+            abilities_seed,
+        }
+    }
+
     /// Returns a trigger for this event.
     pub fn trigger<'a, P: EventProcessor<R>>(
         processor: &'a mut P,
@@ -263,6 +333,64 @@ impl<R: BattleRules> CreateCreature<R> {
     pub fn abilities_seed(&self) -> &Option<AbilitiesSeed<R>> {
         &self.abilities_seed
     }
+
+    /// Returns a creature without statistics nor abilities, standing for the one
+    /// that this event will create.
+    fn placeholder(&self) -> Creature<R> {
+        Creature {
+            id: EntityId::Creature(self.id.clone()),
+            team_id: self.team_id.clone(),
+            position: self.position.clone(),
+            statistics: HashMap::new(),
+            abilities: HashMap::new(),
+            cooldowns: HashMap::new(),
+            disabled: false,
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> CreateCreature<R> {
+    /// Verifies this event against the creatures that precede it in a batch.
+    /// `pending` contains the placeholders of such creatures.
+    fn verify_in_batch(&self, battle: &Battle<R>, pending: &[Creature<R>]) -> WeaselResult<(), R> {
+        // Check if the team accepts a new creature on top of those of the batch.
+        let count = pending
+            .iter()
+            .filter(|creature| creature.team_id == self.team_id)
+            .count();
+        if count > 0 {
+            let team = battle
+                .entities()
+                .team(&self.team_id)
+                .ok_or_else(|| WeaselError::TeamNotFound(self.team_id.clone()))?;
+            if !battle.rules().team_rules().allow_new_entity(
+                &battle.state,
+                team,
+                EntityAddition::CreatureBatchSpawn(count),
+            ) {
+                return Err(WeaselError::NewCreatureUnaccepted(self.team_id.clone()));
+            }
+        }
+        // Check creatures of the batch taking the same position.
+        let entity = self.placeholder();
+        for occupant in pending
+            .iter()
+            .filter(|creature| creature.position == self.position)
+        {
+            let relation = battle
+                .entities()
+                .directed_relation(&self.team_id, &occupant.team_id);
+            if let Some(relation) = relation {
+                if !battle.space().check_overlap(&entity, occupant, relation) {
+                    return Err(WeaselError::PositionOccupied(
+                        self.position.clone(),
+                        occupant.id.clone(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<R: BattleRules + 'static> Event<R> for CreateCreature<R> {
@@ -274,7 +402,7 @@ impl<R: BattleRules + 'static> Event<R> for CreateCreature<R> {
         // Check if the team accepts a new creature.
         if !battle.rules().team_rules().allow_new_entity(
             &battle.state,
-            &team,
+            team,
             EntityAddition::CreatureSpawn,
         ) {
             return Err(WeaselError::NewCreatureUnaccepted(self.team_id.clone()));
@@ -312,6 +440,8 @@ impl<R: BattleRules + 'static> Event<R> for CreateCreature<R> {
             position: self.position.clone(),
             statistics,
             abilities,
+            cooldowns: HashMap::new(),
+            disabled: false,
         };
         // Take the position.
         battle
@@ -331,11 +461,13 @@ impl<R: BattleRules + 'static> Event<R> for CreateCreature<R> {
             .add_creature(creature)
             .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
         // Update metrics.
-        battle
-            .metrics
-            .write_handle()
+        let mut metrics = battle.metrics.write_handle();
+        metrics
             .add_system_u64(CREATURES_CREATED, 1)
             .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
+        metrics
+            .add_team_u64(&self.team_id, TEAM_CREATURES, 1)
+            .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
     }
 
     fn kind(&self) -> EventKind {
@@ -410,6 +542,123 @@ where
     }
 }
 
+/// Event to create many creatures at once.
+///
+/// All creatures are verified before any of them is created. If a single creature
+/// is invalid the whole batch is rejected and the battle is left untouched.\
+/// Each creature is verified against the state of the battle before the batch and
+/// against the creatures preceding it in the batch. `TeamRules::allow_new_entity` is
+/// asked to accept them with `EntityAddition::CreatureBatchSpawn`, while creatures
+/// taking the same position must be accepted by `SpaceRules::check_overlap`.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct CreateCreatures<R: BattleRules> {
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "Vec<CreateCreature<R>>: Serialize",
+            deserialize = "Vec<CreateCreature<R>>: Deserialize<'de>"
+        ))
+    )]
+    creatures: Vec<CreateCreature<R>>,
+}
+
+impl<R: BattleRules> CreateCreatures<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &mut P,
+        creatures: Vec<CreateCreature<R>>,
+    ) -> CreateCreaturesTrigger<'_, R, P> {
+        CreateCreaturesTrigger {
+            processor,
+            creatures,
+        }
+    }
+
+    /// Returns the creatures to be created.
+    pub fn creatures(&self) -> &Vec<CreateCreature<R>> {
+        &self.creatures
+    }
+}
+
+impl<R: BattleRules> Debug for CreateCreatures<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "CreateCreatures {{ creatures: {:?} }}", self.creatures)
+    }
+}
+
+impl<R: BattleRules> Clone for CreateCreatures<R> {
+    fn clone(&self) -> Self {
+        CreateCreatures {
+            creatures: self.creatures.clone(),
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for CreateCreatures<R> {
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        let mut ids = HashSet::new();
+        let mut pending = Vec::with_capacity(self.creatures.len());
+        for (index, creature) in self.creatures.iter().enumerate() {
+            // Check id duplication inside the batch.
+            let result = if !ids.insert(creature.id()) {
+                Err(WeaselError::DuplicatedCreature(creature.id().clone()))
+            } else {
+                creature
+                    .verify(battle)
+                    .and_then(|_| creature.verify_in_batch(battle, &pending))
+            };
+            result.map_err(|err| WeaselError::BatchItemFailed(index, Box::new(err)))?;
+            pending.push(creature.placeholder());
+        }
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
+        for creature in &self.creatures {
+            creature.apply(battle, event_queue);
+        }
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::CreateCreatures
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire a `CreateCreatures` event.
+pub struct CreateCreaturesTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    creatures: Vec<CreateCreature<R>>,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for CreateCreaturesTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns a `CreateCreatures` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(CreateCreatures {
+            creatures: self.creatures.clone(),
+        })
+    }
+}
+
 /// Event to switch a creature from its current team to another.
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct ConvertCreature<R: BattleRules> {
@@ -438,7 +687,7 @@ impl<R: BattleRules> ConvertCreature<R> {
         processor: &mut P,
         creature_id: CreatureId<R>,
         team_id: TeamId<R>,
-    ) -> ConvertCreatureTrigger<R, P> {
+    ) -> ConvertCreatureTrigger<'_, R, P> {
         ConvertCreatureTrigger {
             processor,
             creature_id,
@@ -496,8 +745,8 @@ impl<R: BattleRules + 'static> Event<R> for ConvertCreature<R> {
         }
         if !battle.rules().team_rules().allow_new_entity(
             &battle.state,
-            &team,
-            EntityAddition::CreatureConversion(&creature),
+            team,
+            EntityAddition::CreatureConversion(creature),
         ) {
             return Err(WeaselError::ConvertedCreatureUnaccepted(
                 self.team_id.clone(),
@@ -508,11 +757,31 @@ impl<R: BattleRules + 'static> Event<R> for ConvertCreature<R> {
     }
 
     fn apply(&self, battle: &mut Battle<R>, _event_queue: &mut Option<EventQueue<R>>) {
+        let current_team_id = battle
+            .state
+            .entities
+            .creature(&self.creature_id)
+            .unwrap_or_else(|| {
+                panic!(
+                    "constraint violated: creature {:?} not found",
+                    self.creature_id
+                )
+            })
+            .team_id()
+            .clone();
         battle
             .state
             .entities
             .convert_creature(&self.creature_id, &self.team_id)
             .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
+        // Update metrics.
+        let mut metrics = battle.metrics.write_handle();
+        metrics
+            .sub_team_u64(&current_team_id, TEAM_CREATURES, 1)
+            .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
+        metrics
+            .add_team_u64(&self.team_id, TEAM_CREATURES, 1)
+            .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
     }
 
     fn kind(&self) -> EventKind {
@@ -557,6 +826,20 @@ where
     }
 }
 
+/// The reason why a creature or an object was removed from the battle.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum RemovalReason {
+    /// The entity was removed explicitly, without any particular reason.
+    #[default]
+    Manual,
+    /// The entity was defeated, as result of a `Transmutation::REMOVAL`
+    /// returned by `CharacterRules::alter`.
+    Defeated,
+    /// The creature was removed together with its team.
+    TeamRemoved,
+}
+
 /// Event to remove a creature from the battle.
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct RemoveCreature<R: BattleRules> {
@@ -568,6 +851,9 @@ pub struct RemoveCreature<R: BattleRules> {
         ))
     )]
     id: CreatureId<R>,
+
+    #[cfg_attr(feature = "serialization", serde(default))]
+    reason: RemovalReason,
 }
 
 impl<R: BattleRules> RemoveCreature<R> {
@@ -575,19 +861,32 @@ impl<R: BattleRules> RemoveCreature<R> {
     pub fn trigger<P: EventProcessor<R>>(
         processor: &mut P,
         id: CreatureId<R>,
-    ) -> RemoveCreatureTrigger<R, P> {
-        RemoveCreatureTrigger { processor, id }
+    ) -> RemoveCreatureTrigger<'_, R, P> {
+        RemoveCreatureTrigger {
+            processor,
+            id,
+            reason: RemovalReason::default(),
+        }
     }
 
     /// Returns the id of the creature to be removed.
     pub fn id(&self) -> &CreatureId<R> {
         &self.id
     }
+
+    /// Returns the reason why the creature is removed.
+    pub fn reason(&self) -> RemovalReason {
+        self.reason
+    }
 }
 
 impl<R: BattleRules> Debug for RemoveCreature<R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "RemoveCreature {{ id: {:?} }}", self.id)
+        write!(
+            f,
+            "RemoveCreature {{ id: {:?}, reason: {:?} }}",
+            self.id, self.reason
+        )
     }
 }
 
@@ -595,6 +894,7 @@ impl<R: BattleRules> Clone for RemoveCreature<R> {
     fn clone(&self) -> Self {
         RemoveCreature {
             id: self.id.clone(),
+            reason: self.reason,
         }
     }
 }
@@ -609,38 +909,28 @@ impl<R: BattleRules + 'static> Event<R> for RemoveCreature<R> {
     }
 
     fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
-        let creature = battle
-            .state
-            .entities
-            .creature(&self.id)
-            .unwrap_or_else(|| panic!("constraint violated: creature {:?} not found", self.id));
         // End the current round, if this creature was the actor.
-        if let RoundState::Started(current_actor_id) = battle.state.rounds.state() {
-            if current_actor_id == creature.entity_id() {
-                // Invoke `RoundRules` callback.
-                battle.state.rounds.on_end(
-                    creature as &dyn Actor<_>,
-                    &mut battle.entropy,
-                    &mut battle.metrics.write_handle(),
-                );
-                // Check teams' objectives.
-                Battle::check_objectives(
-                    &battle.state,
-                    &battle.rules.team_rules(),
-                    &battle.metrics.read_handle(),
-                    event_queue,
-                    Checkpoint::RoundEnd,
-                );
-                // Set the round state.
-                battle.state.rounds.set_state(RoundState::Ready);
-            }
-        }
+        end_round_if_acting(battle, &self.id, event_queue);
         // Remove the creature.
-        battle
+        let creature = battle
             .state
             .entities
             .remove_creature(&self.id)
             .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
+        // Update metrics.
+        battle
+            .metrics
+            .write_handle()
+            .sub_team_u64(creature.team_id(), TEAM_CREATURES, 1)
+            .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
+        // Notify the rules.
+        battle.rules.character_rules().on_remove(
+            &creature,
+            self.reason,
+            event_queue,
+            &mut battle.entropy,
+            &mut battle.metrics.write_handle(),
+        );
     }
 
     fn kind(&self) -> EventKind {
@@ -664,6 +954,19 @@ where
 {
     processor: &'a mut P,
     id: CreatureId<R>,
+    reason: RemovalReason,
+}
+
+impl<'a, R, P> RemoveCreatureTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    /// Sets the reason of the removal. The default is `RemovalReason::Manual`.
+    pub fn reason(&'a mut self, reason: RemovalReason) -> &'a mut RemoveCreatureTrigger<'a, R, P> {
+        self.reason = reason;
+        self
+    }
 }
 
 impl<'a, R, P> EventTrigger<'a, R, P> for RemoveCreatureTrigger<'a, R, P>
@@ -679,10 +982,149 @@ where
     fn event(&self) -> Box<dyn Event<R>> {
         Box::new(RemoveCreature {
             id: self.id.clone(),
+            reason: self.reason,
+        })
+    }
+}
+
+/// Event to disable a creature or to bring it back into action.
+///
+/// Disabled creatures remain in the battle and in their team, but they are not
+/// eligible to start a round. Disabling the creature currently acting ends its round.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct SetCreatureDisabled<R: BattleRules> {
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "CreatureId<R>: Serialize",
+            deserialize = "CreatureId<R>: Deserialize<'de>"
+        ))
+    )]
+    id: CreatureId<R>,
+
+    disabled: bool,
+}
+
+impl<R: BattleRules> SetCreatureDisabled<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &mut P,
+        id: CreatureId<R>,
+        disabled: bool,
+    ) -> SetCreatureDisabledTrigger<'_, R, P> {
+        SetCreatureDisabledTrigger {
+            processor,
+            id,
+            disabled,
+        }
+    }
+
+    /// Returns the id of the creature.
+    pub fn id(&self) -> &CreatureId<R> {
+        &self.id
+    }
+
+    /// Returns whether the creature will be disabled or enabled.
+    pub fn disabled(&self) -> bool {
+        self.disabled
+    }
+}
+
+impl<R: BattleRules> Debug for SetCreatureDisabled<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "SetCreatureDisabled {{ id: {:?}, disabled: {:?} }}",
+            self.id, self.disabled
+        )
+    }
+}
+
+impl<R: BattleRules> Clone for SetCreatureDisabled<R> {
+    fn clone(&self) -> Self {
+        SetCreatureDisabled {
+            id: self.id.clone(),
+            disabled: self.disabled,
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for SetCreatureDisabled<R> {
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        // Verify if the creature exists.
+        if battle.entities().creature(&self.id).is_none() {
+            return Err(WeaselError::CreatureNotFound(self.id.clone()));
+        }
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
+        if self.disabled {
+            // End the current round, if this creature was the actor.
+            end_round_if_acting(battle, &self.id, event_queue);
+        }
+        battle
+            .state
+            .entities
+            .creature_mut(&self.id)
+            .unwrap_or_else(|| panic!("constraint violated: creature {:?} not found", self.id))
+            .set_disabled(self.disabled);
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::SetCreatureDisabled
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire a `SetCreatureDisabled` event.
+pub struct SetCreatureDisabledTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    id: CreatureId<R>,
+    disabled: bool,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for SetCreatureDisabledTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns a `SetCreatureDisabled` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(SetCreatureDisabled {
+            id: self.id.clone(),
+            disabled: self.disabled,
         })
     }
 }
 
+/// Ends the current round if the creature with the given id is the actor.
+fn end_round_if_acting<R: BattleRules + 'static>(
+    battle: &mut Battle<R>,
+    id: &CreatureId<R>,
+    event_queue: &mut Option<EventQueue<R>>,
+) {
+    let entity_id = EntityId::Creature(id.clone());
+    if battle.state.rounds.active_actor() == Some(&entity_id) {
+        end_round(battle, event_queue);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -698,6 +1140,7 @@ mod tests {
 
     impl<R: BattleRules> CharacterRules<R> for CustomCharacterRules {
         type CreatureId = u32;
+        type ObjectId = u32;
         type Statistic = SimpleStatistic<u32, u32>;
         type StatisticsSeed = ();
         type StatisticsAlteration = ();
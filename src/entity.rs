@@ -1,13 +1,17 @@
 //! Module for entities and their storage.
 
+use crate::ability::Ability;
 use crate::actor::Actor;
 use crate::battle::BattleRules;
 use crate::character::Character;
-use crate::creature::{Creature, CreatureId, RemoveCreature};
+use crate::creature::{Creature, CreatureId, RemovalReason, RemoveCreature, SetCreatureDisabled};
 use crate::error::{WeaselError, WeaselResult};
 use crate::event::{EventProcessor, EventTrigger};
+use crate::object::{Object, ObjectId, RemoveObject};
 use crate::space::Position;
-use crate::team::{Conclusion, Relation, RelationshipPair, Team, TeamId};
+use crate::team::{
+    Conclusion, Objectives, ObjectivesProgress, Relation, RelationshipPair, Team, TeamId,
+};
 use crate::util::Id;
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
@@ -40,20 +44,47 @@ pub enum EntityId<R: BattleRules> {
         ))
     )]
     Creature(CreatureId<R>),
+    /// Inanimate object.
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "ObjectId<R>: Serialize",
+            deserialize = "ObjectId<R>: Deserialize<'de>"
+        ))
+    )]
+    Object(ObjectId<R>),
 }
 
 impl<R: BattleRules> EntityId<R> {
-    /// Returns this entity id refers to an object that satisfies the `Character` trait.
-    pub(crate) fn is_character(&self) -> bool {
+    /// Returns whether this entity id refers to an object that satisfies the `Character` trait.
+    pub fn is_character(&self) -> bool {
+        match self {
+            EntityId::Creature(_) => true,
+            EntityId::Object(_) => true,
+        }
+    }
+
+    /// Returns whether this entity id refers to an object that satisfies the `Actor` trait.
+    pub fn is_actor(&self) -> bool {
         match self {
             EntityId::Creature(_) => true,
+            EntityId::Object(_) => false,
         }
     }
 
-    /// Returns this entity id refers to an object that satisfies the `Actor` trait.
-    pub(crate) fn is_actor(&self) -> bool {
+    /// Returns whether this entity id refers to a creature.
+    pub fn is_creature(&self) -> bool {
         match self {
             EntityId::Creature(_) => true,
+            EntityId::Object(_) => false,
+        }
+    }
+
+    /// Returns whether this entity id refers to an object.
+    pub fn is_object(&self) -> bool {
+        match self {
+            EntityId::Creature(_) => false,
+            EntityId::Object(_) => true,
         }
     }
 
@@ -63,6 +94,17 @@ impl<R: BattleRules> EntityId<R> {
     pub fn creature(&self) -> WeaselResult<CreatureId<R>, R> {
         match self {
             EntityId::Creature(id) => Ok(id.clone()),
+            EntityId::Object(_) => Err(WeaselError::NotACreature(self.clone())),
+        }
+    }
+
+    /// Extracts an object id out of this entity id.
+    ///
+    /// Returns an error if the entity id's type is not object.
+    pub fn object(&self) -> WeaselResult<ObjectId<R>, R> {
+        match self {
+            EntityId::Creature(_) => Err(WeaselError::NotAnObject(self.clone())),
+            EntityId::Object(id) => Ok(id.clone()),
         }
     }
 }
@@ -71,19 +113,27 @@ impl<R: BattleRules> Debug for EntityId<R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
             EntityId::Creature(id) => write!(f, "EntityId::Creature {{ {:?} }}", id),
+            EntityId::Object(id) => write!(f, "EntityId::Object {{ {:?} }}", id),
         }
     }
 }
 
-impl<R: BattleRules> Copy for EntityId<R> where CreatureId<R>: Copy {}
+impl<R: BattleRules> Copy for EntityId<R>
+where
+    CreatureId<R>: Copy,
+    ObjectId<R>: Copy,
+{
+}
 
 impl<R: BattleRules> Display for EntityId<R>
 where
     CreatureId<R>: Display,
+    ObjectId<R>: Display,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
             EntityId::Creature(id) => write!(f, "Creature ({})", id),
+            EntityId::Object(id) => write!(f, "Object ({})", id),
         }
     }
 }
@@ -92,6 +142,7 @@ impl<R: BattleRules> Clone for EntityId<R> {
     fn clone(&self) -> Self {
         match self {
             EntityId::Creature(id) => EntityId::Creature(id.clone()),
+            EntityId::Object(id) => EntityId::Object(id.clone()),
         }
     }
 }
@@ -101,6 +152,11 @@ impl<R: BattleRules> PartialEq<EntityId<R>> for EntityId<R> {
         match self {
             EntityId::Creature(id) => match other {
                 EntityId::Creature(other_id) => id == other_id,
+                _ => false,
+            },
+            EntityId::Object(id) => match other {
+                EntityId::Object(other_id) => id == other_id,
+                _ => false,
             },
         }
     }
@@ -110,6 +166,11 @@ impl<R: BattleRules> PartialEq<EntityId<R>> for EntityId<R> {
 pub enum Transmutation {
     /// Entity entirely removed from the battle.
     REMOVAL,
+    /// Entity disabled, but still present in the battle.
+    /// Disabled actors can't start a round. Objects can't be disabled.
+    DISABLE,
+    /// A disabled entity is brought back into action.
+    RESPAWN,
 }
 
 /// Triggers an event to transmute an entity.
@@ -124,8 +185,27 @@ pub(crate) fn transmute_entity<R, P>(
     match transmutation {
         Transmutation::REMOVAL => match id {
             EntityId::Creature(id) => {
-                RemoveCreature::trigger(processor, id.clone()).fire();
+                RemoveCreature::trigger(processor, id.clone())
+                    .reason(RemovalReason::Defeated)
+                    .fire();
+            }
+            EntityId::Object(id) => {
+                RemoveObject::trigger(processor, id.clone())
+                    .reason(RemovalReason::Defeated)
+                    .fire();
+            }
+        },
+        Transmutation::DISABLE => match id {
+            EntityId::Creature(id) => {
+                SetCreatureDisabled::trigger(processor, id.clone(), true).fire();
             }
+            EntityId::Object(_) => {}
+        },
+        Transmutation::RESPAWN => match id {
+            EntityId::Creature(id) => {
+                SetCreatureDisabled::trigger(processor, id.clone(), false).fire();
+            }
+            EntityId::Object(_) => {}
         },
     }
 }
@@ -133,16 +213,41 @@ pub(crate) fn transmute_entity<R, P>(
 /// Data structure to manage ownership of teams and entities.
 pub struct Entities<R: BattleRules> {
     teams: HashMap<TeamId<R>, Team<R>>,
-    creatures: HashMap<CreatureId<R>, Creature<R>>,
+    /// Ids of all teams, in order of creation.
+    team_order: Vec<TeamId<R>>,
+    pub(crate) creatures: HashMap<CreatureId<R>, Creature<R>>,
+    pub(crate) objects: HashMap<ObjectId<R>, Object<R>>,
     relations: HashMap<RelationshipPair<R>, Relation>,
+    pub(crate) directed_relations: HashMap<(TeamId<R>, TeamId<R>), Relation>,
+}
+
+impl<R: BattleRules> Clone for Entities<R>
+where
+    Ability<R>: Clone,
+    Objectives<R>: Clone,
+    ObjectivesProgress<R>: Clone,
+{
+    fn clone(&self) -> Self {
+        Entities {
+            teams: self.teams.clone(),
+            team_order: self.team_order.clone(),
+            creatures: self.creatures.clone(),
+            objects: self.objects.clone(),
+            relations: self.relations.clone(),
+            directed_relations: self.directed_relations.clone(),
+        }
+    }
 }
 
 impl<R: BattleRules> Entities<R> {
     pub(crate) fn new() -> Entities<R> {
         Entities {
             teams: HashMap::new(),
+            team_order: Vec::new(),
             creatures: HashMap::new(),
+            objects: HashMap::new(),
             relations: HashMap::new(),
+            directed_relations: HashMap::new(),
         }
     }
 
@@ -156,11 +261,38 @@ impl<R: BattleRules> Entities<R> {
         self.creatures.get(id)
     }
 
+    /// Returns an iterator over the creatures belonging to the team with the given id.
+    /// The iterator is empty if the team doesn't exist.
+    pub fn creatures_of_team<'a>(
+        &'a self,
+        id: &TeamId<R>,
+    ) -> impl Iterator<Item = &'a Creature<R>> + 'a {
+        self.team(id)
+            .into_iter()
+            .flat_map(|team| team.creatures())
+            .filter_map(move |id| self.creature(id))
+    }
+
     /// Returns a mutable reference to the creature with the given id.
     pub(crate) fn creature_mut(&mut self, id: &CreatureId<R>) -> Option<&mut Creature<R>> {
         self.creatures.get_mut(id)
     }
 
+    /// Returns an iterator over objects.
+    pub fn objects(&self) -> impl Iterator<Item = &Object<R>> {
+        self.objects.values()
+    }
+
+    /// Returns the object with the given id.
+    pub fn object(&self, id: &ObjectId<R>) -> Option<&Object<R>> {
+        self.objects.get(id)
+    }
+
+    /// Returns a mutable reference to the object with the given id.
+    pub(crate) fn object_mut(&mut self, id: &ObjectId<R>) -> Option<&mut Object<R>> {
+        self.objects.get_mut(id)
+    }
+
     /// Returns the team with the given id.
     pub fn team(&self, id: &TeamId<R>) -> Option<&Team<R>> {
         self.teams.get(id)
@@ -171,12 +303,17 @@ impl<R: BattleRules> Entities<R> {
         self.teams.get_mut(id)
     }
 
-    /// Returns an iterator over teams.
+    /// Returns an iterator over teams, in order of creation.
     pub fn teams(&self) -> impl Iterator<Item = &Team<R>> {
-        self.teams.values()
+        self.team_order
+            .iter()
+            .filter_map(move |id| self.teams.get(id))
     }
 
     pub(crate) fn add_team(&mut self, team: Team<R>) {
+        if !self.teams.contains_key(team.id()) {
+            self.team_order.push(team.id().clone());
+        }
         self.teams.insert(team.id().clone(), team);
     }
 
@@ -184,28 +321,99 @@ impl<R: BattleRules> Entities<R> {
         // Update team's creature list.
         let team = self
             .teams
-            .get_mut(&creature.team_id())
+            .get_mut(creature.team_id())
             .ok_or_else(|| WeaselError::TeamNotFound(creature.team_id().clone()))?;
         team.creatures_mut().push(creature.id().clone());
         // Insert the creature.
-        self.creatures.insert(creature.id().clone(), creature);
+        let id = creature.id().clone();
+        self.creatures.insert(id.clone(), creature);
+        debug_assert!(
+            self.is_membership_consistent(&id),
+            "team membership of creature {:?} is inconsistent",
+            id
+        );
         Ok(())
     }
 
+    /// Returns true if the creature with the given id is listed exactly once, and only
+    /// by the team stored in the creature itself.
+    fn is_membership_consistent(&self, id: &CreatureId<R>) -> bool {
+        match self.creature(id) {
+            Some(creature) => self.teams().all(|team| {
+                let count = team.creatures().filter(|other| *other == id).count();
+                if team.id() == creature.team_id() {
+                    count == 1
+                } else {
+                    count == 0
+                }
+            }),
+            None => false,
+        }
+    }
+
+    pub(crate) fn add_object(&mut self, object: Object<R>) {
+        self.objects.insert(object.id().clone(), object);
+    }
+
+    /// Removes an object from the battle. The object must exist.
+    ///
+    /// Returns the removed object.
+    pub(crate) fn remove_object(&mut self, id: &ObjectId<R>) -> WeaselResult<Object<R>, R> {
+        self.objects
+            .remove(id)
+            .ok_or_else(|| WeaselError::ObjectNotFound(id.clone()))
+    }
+
     /// Returns an iterator over entities.
     pub fn entities(&self) -> impl Iterator<Item = &dyn Entity<R>> {
-        self.creatures.values().map(|e| e as &dyn Entity<R>)
+        self.creatures
+            .values()
+            .map(|e| e as &dyn Entity<R>)
+            .chain(self.objects.values().map(|e| e as &dyn Entity<R>))
     }
 
     /// Returns a mutable iterator over entities.
     pub fn entities_mut(&mut self) -> impl Iterator<Item = &mut dyn Entity<R>> {
-        self.creatures.values_mut().map(|e| e as &mut dyn Entity<R>)
+        self.creatures
+            .values_mut()
+            .map(|e| e as &mut dyn Entity<R>)
+            .chain(self.objects.values_mut().map(|e| e as &mut dyn Entity<R>))
+    }
+
+    /// Returns an iterator over characters.
+    pub fn characters(&self) -> impl Iterator<Item = &dyn Character<R>> {
+        self.creatures
+            .values()
+            .map(|e| e as &dyn Character<R>)
+            .chain(self.objects.values().map(|e| e as &dyn Character<R>))
+    }
+
+    /// Returns an iterator over actors.
+    pub fn actors(&self) -> impl Iterator<Item = &dyn Actor<R>> {
+        self.creatures.values().map(|e| e as &dyn Actor<R>)
+    }
+
+    /// Returns an iterator over the ids in `ids` that refer to existing entities
+    /// for which `predicate` returns true.
+    pub fn filter_ids<'a, I, P>(
+        &'a self,
+        ids: I,
+        predicate: P,
+    ) -> impl Iterator<Item = &'a EntityId<R>> + 'a
+    where
+        I: IntoIterator<Item = &'a EntityId<R>>,
+        I::IntoIter: 'a,
+        P: Fn(&dyn Entity<R>) -> bool + 'a,
+    {
+        ids.into_iter()
+            .filter(move |id| self.entity(id).is_some_and(&predicate))
     }
 
     /// Returns the entity with the given id.
     pub fn entity(&self, id: &EntityId<R>) -> Option<&dyn Entity<R>> {
         match id {
             EntityId::Creature(id) => self.creature(id).map(|e| e as &dyn Entity<R>),
+            EntityId::Object(id) => self.object(id).map(|e| e as &dyn Entity<R>),
         }
     }
 
@@ -213,6 +421,7 @@ impl<R: BattleRules> Entities<R> {
     pub(crate) fn entity_mut(&mut self, id: &EntityId<R>) -> Option<&mut dyn Entity<R>> {
         match id {
             EntityId::Creature(id) => self.creature_mut(id).map(|e| e as &mut dyn Entity<R>),
+            EntityId::Object(id) => self.object_mut(id).map(|e| e as &mut dyn Entity<R>),
         }
     }
 
@@ -220,6 +429,7 @@ impl<R: BattleRules> Entities<R> {
     pub fn character(&self, id: &EntityId<R>) -> Option<&dyn Character<R>> {
         match id {
             EntityId::Creature(id) => self.creature(id).map(|e| e as &dyn Character<R>),
+            EntityId::Object(id) => self.object(id).map(|e| e as &dyn Character<R>),
         }
     }
 
@@ -227,6 +437,7 @@ impl<R: BattleRules> Entities<R> {
     pub(crate) fn character_mut(&mut self, id: &EntityId<R>) -> Option<&mut dyn Character<R>> {
         match id {
             EntityId::Creature(id) => self.creature_mut(id).map(|e| e as &mut dyn Character<R>),
+            EntityId::Object(id) => self.object_mut(id).map(|e| e as &mut dyn Character<R>),
         }
     }
 
@@ -234,6 +445,7 @@ impl<R: BattleRules> Entities<R> {
     pub fn actor(&self, id: &EntityId<R>) -> Option<&dyn Actor<R>> {
         match id {
             EntityId::Creature(id) => self.creature(id).map(|e| e as &dyn Actor<R>),
+            EntityId::Object(_) => None,
         }
     }
 
@@ -241,6 +453,7 @@ impl<R: BattleRules> Entities<R> {
     pub(crate) fn actor_mut(&mut self, id: &EntityId<R>) -> Option<&mut dyn Actor<R>> {
         match id {
             EntityId::Creature(id) => self.creature_mut(id).map(|e| e as &mut dyn Actor<R>),
+            EntityId::Object(_) => None,
         }
     }
 
@@ -248,15 +461,30 @@ impl<R: BattleRules> Entities<R> {
     /// Existing relations are overridden.
     pub(crate) fn update_relations(&mut self, relations: Vec<(RelationshipPair<R>, Relation)>) {
         for (pair, relation) in relations {
+            debug_assert!(
+                pair.first != pair.second && relation != Relation::Kin,
+                "relation {:?} for {:?} breaks kinship",
+                relation,
+                pair
+            );
             self.relations.insert(pair, relation);
         }
     }
 
+    /// Removes all relations between teams, including directed ones.
+    pub(crate) fn clear_relations(&mut self) {
+        self.relations.clear();
+        self.directed_relations.clear();
+    }
+
     /// Returns the `Relation` between two teams. Relations are symmetric.
     ///
-    /// The relation of a team towards itself is `Kin`.
+    /// The relation of a team towards itself is `Kin`.\
+    /// Returns `None` if any of the two teams doesn't exist.
     pub fn relation(&self, first: &TeamId<R>, second: &TeamId<R>) -> Option<Relation> {
-        if first == second {
+        if !self.teams.contains_key(first) || !self.teams.contains_key(second) {
+            None
+        } else if first == second {
             Some(Relation::Kin)
         } else {
             self.relations
@@ -265,42 +493,80 @@ impl<R: BattleRules> Entities<R> {
         }
     }
 
-    /// Returns all allied teams' id of a team.
-    pub fn allies_id<'a>(&'a self, id: &'a TeamId<R>) -> impl Iterator<Item = TeamId<R>> + 'a {
+    /// Returns an iterator over all stored relations between teams.
+    ///
+    /// Each unordered pair of teams appears only once, in no particular order.
+    /// Kinship of a team towards itself is implicit and thus never returned.
+    pub fn relations(&self) -> impl Iterator<Item = (&TeamId<R>, &TeamId<R>, Relation)> {
         self.relations
             .iter()
-            .filter(move |&(k, _)| k.first == *id || k.second == *id)
-            .filter(|&(_, v)| *v == Relation::Ally)
-            .map(|(k, _)| k.values())
-            .flatten()
-            .filter(move |v| v != id)
+            .map(|(pair, relation)| (&pair.first, &pair.second, *relation))
+    }
+
+    /// Updates current directed relations by merging them with `relations`.
+    /// Existing directed relations are overridden.
+    pub(crate) fn update_directed_relations(
+        &mut self,
+        relations: Vec<(TeamId<R>, TeamId<R>, Relation)>,
+    ) {
+        for (first, second, relation) in relations {
+            debug_assert!(
+                first != second && relation != Relation::Kin,
+                "directed relation {:?} from {:?} to {:?} breaks kinship",
+                relation,
+                first,
+                second
+            );
+            self.directed_relations.insert((first, second), relation);
+        }
+    }
+
+    /// Returns the `Relation` of team `first` towards team `second`.
+    ///
+    /// Relations set with `SetDirectedRelations` take precedence. If there's none,
+    /// the symmetric relation returned by `relation` is used instead.\
+    /// Returns `None` if any of the two teams doesn't exist.
+    pub fn directed_relation(&self, first: &TeamId<R>, second: &TeamId<R>) -> Option<Relation> {
+        if first != second {
+            if let Some(relation) = self
+                .directed_relations
+                .get(&(first.clone(), second.clone()))
+            {
+                return Some(*relation);
+            }
+        }
+        self.relation(first, second)
+    }
+
+    /// Returns all allied teams' id of a team, in order of creation.
+    pub fn allies_id<'a>(&'a self, id: &'a TeamId<R>) -> impl Iterator<Item = TeamId<R>> + 'a {
+        self.teams()
+            .map(|team| team.id())
+            .filter(move |other| self.relation(id, other) == Some(Relation::Ally))
+            .cloned()
     }
 
     /// Returns all allied teams of a team.
-    pub fn allies<'a>(&'a self, id: &'a TeamId<R>) -> impl Iterator<Item = &Team<R>> + 'a {
+    pub fn allies<'a>(&'a self, id: &'a TeamId<R>) -> impl Iterator<Item = &'a Team<R>> + 'a {
         self.allies_id(id).map(move |id| self.team(&id).unwrap())
     }
 
-    /// Returns all enemy teams' id of a team.
+    /// Returns all enemy teams' id of a team, in order of creation.
     pub fn enemies_id<'a>(&'a self, id: &'a TeamId<R>) -> impl Iterator<Item = TeamId<R>> + 'a {
-        self.relations
-            .iter()
-            .filter(move |&(k, _)| k.first == *id || k.second == *id)
-            .filter(|&(_, v)| *v == Relation::Enemy)
-            .map(|(k, _)| k.values())
-            .flatten()
-            .filter(move |v| v != id)
+        self.teams()
+            .map(|team| team.id())
+            .filter(move |other| self.relation(id, other) == Some(Relation::Enemy))
+            .cloned()
     }
 
     /// Returns all enemy teams of a team.
-    pub fn enemies<'a>(&'a self, id: &'a TeamId<R>) -> impl Iterator<Item = &Team<R>> + 'a {
+    pub fn enemies<'a>(&'a self, id: &'a TeamId<R>) -> impl Iterator<Item = &'a Team<R>> + 'a {
         self.enemies_id(id).map(move |id| self.team(&id).unwrap())
     }
 
     /// Returns all victorious teams.
     pub fn victorious(&self) -> impl Iterator<Item = &Team<R>> {
-        self.teams
-            .values()
+        self.teams()
             .filter(|&team| team.conclusion() == Some(Conclusion::Victory))
     }
 
@@ -311,8 +577,7 @@ impl<R: BattleRules> Entities<R> {
 
     /// Returns all defeated teams.
     pub fn defeated(&self) -> impl Iterator<Item = &Team<R>> {
-        self.teams
-            .values()
+        self.teams()
             .filter(|&team| team.conclusion() == Some(Conclusion::Defeat))
     }
 
@@ -321,10 +586,26 @@ impl<R: BattleRules> Entities<R> {
         self.defeated().map(|team| team.id().clone())
     }
 
+    /// Returns all teams which concluded their objectives with a draw.
+    pub fn drawn(&self) -> impl Iterator<Item = &Team<R>> {
+        self.teams()
+            .filter(|&team| team.conclusion() == Some(Conclusion::Draw))
+    }
+
+    /// Returns the id of all teams which concluded their objectives with a draw.
+    pub fn drawn_id(&self) -> impl Iterator<Item = TeamId<R>> + '_ {
+        self.drawn().map(|team| team.id().clone())
+    }
+
     /// Removes a creature from the battle. The creature must exist.
     ///
     /// Returns the removed creature.
     pub(crate) fn remove_creature(&mut self, id: &CreatureId<R>) -> WeaselResult<Creature<R>, R> {
+        debug_assert!(
+            self.creature(id).is_none() || self.is_membership_consistent(id),
+            "team membership of creature {:?} is inconsistent",
+            id
+        );
         // Extract the creature.
         let creature = self
             .creatures
@@ -347,7 +628,7 @@ impl<R: BattleRules> Entities<R> {
     ) -> WeaselResult<(), R> {
         let creature = self
             .creatures
-            .get_mut(&creature_id)
+            .get_mut(creature_id)
             .ok_or_else(|| WeaselError::CreatureNotFound(creature_id.clone()))?;
         let current_team_id = creature.team_id().clone();
         // Change the original team's creature lists.
@@ -359,15 +640,21 @@ impl<R: BattleRules> Entities<R> {
         // Change the new team's creature lists.
         let new_team = self
             .teams
-            .get_mut(&team_id)
+            .get_mut(team_id)
             .ok_or_else(|| WeaselError::TeamNotFound(team_id.clone()))?;
         new_team.creatures_mut().push(creature_id.clone());
         // Change the creature's team.
         creature.set_team_id(team_id.clone());
+        debug_assert!(
+            self.is_membership_consistent(creature_id),
+            "team membership of creature {:?} is inconsistent",
+            creature_id
+        );
         Ok(())
     }
 
     /// Removes a team from the battle. The team must exist and be empty.
+    /// All relations involving the team are removed as well.
     ///
     /// Returns the removed team.
     pub(crate) fn remove_team(&mut self, id: &TeamId<R>) -> WeaselResult<Team<R>, R> {
@@ -384,8 +671,68 @@ impl<R: BattleRules> Entities<R> {
             .teams
             .remove(id)
             .ok_or_else(|| WeaselError::TeamNotFound(id.clone()))?;
+        self.team_order.retain(|e| e != id);
+        // Forget the team's relations, so that they can't be inherited by another team
+        // taking the same id later on.
+        self.relations
+            .retain(|pair, _| pair.first != *id && pair.second != *id);
+        self.directed_relations
+            .retain(|(first, second), _| first != id && second != id);
         Ok(team)
     }
+
+    /// Changes the id of a team, updating all references to it.
+    /// The team must exist and `new_id` must not belong to any other team.
+    pub(crate) fn rename_team(
+        &mut self,
+        id: &TeamId<R>,
+        new_id: &TeamId<R>,
+    ) -> WeaselResult<(), R> {
+        // Check preconditions.
+        if self.teams.contains_key(new_id) {
+            return Err(WeaselError::DuplicatedTeam(new_id.clone()));
+        }
+        let mut team = self
+            .teams
+            .remove(id)
+            .ok_or_else(|| WeaselError::TeamNotFound(id.clone()))?;
+        // Update the team and its creatures.
+        team.set_id(new_id.clone());
+        for creature_id in team.creatures() {
+            if let Some(creature) = self.creatures.get_mut(creature_id) {
+                creature.set_team_id(new_id.clone());
+            }
+        }
+        self.teams.insert(new_id.clone(), team);
+        // Keep the team in the same position.
+        for e in self.team_order.iter_mut().filter(|e| *e == id) {
+            *e = new_id.clone();
+        }
+        // Update all relations.
+        let rename = |team_id: TeamId<R>| {
+            if team_id == *id {
+                new_id.clone()
+            } else {
+                team_id
+            }
+        };
+        self.relations = self
+            .relations
+            .drain()
+            .map(|(pair, relation)| {
+                (
+                    RelationshipPair::new(rename(pair.first), rename(pair.second)),
+                    relation,
+                )
+            })
+            .collect();
+        self.directed_relations = self
+            .directed_relations
+            .drain()
+            .map(|((first, second), relation)| ((rename(first), rename(second)), relation))
+            .collect();
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -3,7 +3,7 @@
 use crate::battle::{Battle, BattleRules};
 use crate::error::WeaselResult;
 use crate::event::{Event, EventKind, EventProcessor, EventQueue, EventTrigger};
-use num_traits::Num;
+use num_traits::{Num, NumCast, Zero};
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
 use std::any::Any;
@@ -41,6 +41,19 @@ impl<R: BattleRules> Entropy<R> {
         &self.model
     }
 
+    /// Returns a copy of the current state of the entropy model.
+    ///
+    /// The state can be restored later on with a `RestoreEntropy` event, in order to
+    /// resume the same sequence of random values.
+    pub fn state(&self) -> EntropyModel<R> {
+        self.model.clone()
+    }
+
+    /// Replaces the state of the entropy model.
+    pub(crate) fn restore_state(&mut self, state: EntropyModel<R>) {
+        self.model = state;
+    }
+
     /// Returns the `EntropyRules` in use.
     pub fn rules(&self) -> &R::ER {
         &self.rules
@@ -52,6 +65,93 @@ impl<R: BattleRules> Entropy<R> {
     }
 }
 
+impl<R: BattleRules> Entropy<R>
+where
+    EntropyOutput<R>: NumCast,
+{
+    /// Picks a random element of `items`, or `None` if `items` is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `items` can't be represented as an `EntropyOutput`.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.index(items.len())])
+        }
+    }
+
+    /// Shuffles `items` in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `items` can't be represented as an `EntropyOutput`.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.index(i + 1);
+            items.swap(i, j);
+        }
+    }
+
+    /// Returns a random index of `weights`, with a probability proportional to the
+    /// weight at that index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weights` is empty or all its weights are zero.\
+    /// Panics if the sum of all weights can't be represented as an `EntropyOutput`.
+    pub fn roll_weighted(&mut self, weights: &[u32]) -> usize {
+        let total: usize = weights.iter().map(|weight| *weight as usize).sum();
+        if total == 0 {
+            panic!("weights must contain at least one non zero element");
+        }
+        let mut roll = self.index(total);
+        for (i, weight) in weights.iter().enumerate() {
+            let weight = *weight as usize;
+            if roll < weight {
+                return i;
+            }
+            roll -= weight;
+        }
+        unreachable!()
+    }
+
+    /// Returns `true` with the given `probability`, which should be within [0, 1].
+    ///
+    /// The probability is approximated to four decimal digits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is within (0, 1) and `EntropyOutput` can't represent 10000,
+    /// as is the case for `u8` and `i8`.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        if probability <= 0.0 {
+            false
+        } else if probability >= 1.0 {
+            true
+        } else {
+            let threshold = (probability * CHANCE_RESOLUTION as f64).round() as usize;
+            self.index(CHANCE_RESOLUTION) < threshold
+        }
+    }
+
+    /// Generates a random index within [0, `len`).
+    ///
+    /// Panics if `len` can't be represented as an `EntropyOutput`.
+    fn index(&mut self, len: usize) -> usize {
+        let high = NumCast::from(len).unwrap_or_else(|| {
+            panic!("{} can't be represented as entropy output", len);
+        });
+        let value = self.generate(EntropyOutput::<R>::zero(), high);
+        let index: usize = NumCast::from(value).unwrap_or(0);
+        index.min(len - 1)
+    }
+}
+
+/// Number of equally likely outcomes used by `Entropy::chance`.
+const CHANCE_RESOLUTION: usize = 10_000;
+
 /// Defines how casuality works inside the battle system.
 ///
 /// Entropy must be deterministic. If you use a generator, make sure that by starting
@@ -64,8 +164,12 @@ pub trait EntropyRules {
     /// See [EntropySeed](type.EntropySeed.html).
     type EntropySeed: Clone + Debug + Serialize + for<'a> Deserialize<'a>;
 
+    #[cfg(not(feature = "serialization"))]
+    /// See [EntropyModel](type.EntropyModel.html).
+    type EntropyModel: Clone + Debug;
+    #[cfg(feature = "serialization")]
     /// See [EntropyModel](type.EntropyModel.html).
-    type EntropyModel;
+    type EntropyModel: Clone + Debug + Serialize + for<'a> Deserialize<'a>;
     /// See [EntropyOutput](type.EntropyOutput.html).
     type EntropyOutput: PartialOrd + Copy + Num + Debug;
 
@@ -111,7 +215,7 @@ pub struct ResetEntropy<R: BattleRules> {
 
 impl<R: BattleRules> ResetEntropy<R> {
     /// Returns a trigger for this event.
-    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> ResetEntropyTrigger<R, P> {
+    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> ResetEntropyTrigger<'_, R, P> {
         ResetEntropyTrigger {
             processor,
             seed: None,
@@ -199,6 +303,99 @@ where
     }
 }
 
+/// Event to restore the entropy model to a previously saved state.
+///
+/// Use `Entropy::state` to save the state.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct RestoreEntropy<R: BattleRules> {
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "EntropyModel<R>: Serialize",
+            deserialize = "EntropyModel<R>: Deserialize<'de>"
+        ))
+    )]
+    state: EntropyModel<R>,
+}
+
+impl<R: BattleRules> RestoreEntropy<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &mut P,
+        state: EntropyModel<R>,
+    ) -> RestoreEntropyTrigger<'_, R, P> {
+        RestoreEntropyTrigger { processor, state }
+    }
+
+    /// Returns the state to be restored.
+    pub fn state(&self) -> &EntropyModel<R> {
+        &self.state
+    }
+}
+
+impl<R: BattleRules> std::fmt::Debug for RestoreEntropy<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RestoreEntropy {{ state: {:?} }}", self.state)
+    }
+}
+
+impl<R: BattleRules> Clone for RestoreEntropy<R> {
+    fn clone(&self) -> Self {
+        RestoreEntropy {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for RestoreEntropy<R> {
+    fn verify(&self, _battle: &Battle<R>) -> WeaselResult<(), R> {
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
+        battle.entropy.restore_state(self.state.clone());
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::RestoreEntropy
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire a `RestoreEntropy` event.
+pub struct RestoreEntropyTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    state: EntropyModel<R>,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for RestoreEntropyTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns a `RestoreEntropy` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(RestoreEntropy {
+            state: self.state.clone(),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,9 +425,7 @@ mod tests {
             _low: Self::EntropyOutput,
             _high: Self::EntropyOutput,
         ) -> Self::EntropyOutput {
-            let model = *model;
-            let result: Self::EntropyOutput = model.into();
-            result
+            *model
         }
     }
 
@@ -249,6 +444,19 @@ mod tests {
         assert_eq!(server.battle.entropy.generate(1, 5), 5);
     }
 
+    #[test]
+    fn restore_state() {
+        let battle = Battle::builder(CustomRules::new()).build();
+        let mut server = Server::builder(battle).build();
+        // Save the current state.
+        let state = server.battle().entropy().state();
+        assert_eq!(state, DEFAULT_SEED);
+        // Change the model and then restore the previous state.
+        assert!(ResetEntropy::trigger(&mut server).seed(5).fire().is_ok());
+        assert!(RestoreEntropy::trigger(&mut server, state).fire().is_ok());
+        assert_eq!(server.battle.entropy.generate(1, 5), DEFAULT_SEED);
+    }
+
     #[test]
     fn low_high_guarantee() {
         battle_rules! {}
@@ -256,6 +464,79 @@ mod tests {
         assert_eq!(server.battle.entropy.generate(5, 1), 3);
     }
 
+    #[test]
+    fn choose_and_shuffle() {
+        let battle = Battle::builder(CustomRules::new()).build();
+        let mut server = Server::builder(battle).build();
+        let entropy = &mut server.battle.entropy;
+        // Entropy always generates the seed, clamped within the range.
+        assert_eq!(entropy.choose(&[0, 1, 2, 3, 4]), Some(&3));
+        assert_eq!(entropy.choose(&[0, 1]), Some(&1));
+        assert_eq!(entropy.choose::<u32>(&[]), None);
+        let mut items = [0, 1, 2, 3, 4];
+        entropy.shuffle(&mut items);
+        assert_eq!(items, [0, 1, 2, 4, 3]);
+        let mut items: [u32; 0] = [];
+        entropy.shuffle(&mut items);
+    }
+
+    #[test]
+    fn roll_weighted_and_chance() {
+        let battle = Battle::builder(CustomRules::new()).build();
+        let mut server = Server::builder(battle).build();
+        let entropy = &mut server.battle.entropy;
+        // Entropy always generates the seed (3).
+        assert_eq!(entropy.roll_weighted(&[1, 1, 1, 1]), 3);
+        assert_eq!(entropy.roll_weighted(&[2, 0, 5]), 2);
+        assert_eq!(entropy.roll_weighted(&[4, 1]), 0);
+        assert!(entropy.chance(0.0004));
+        assert!(!entropy.chance(0.0003));
+        assert!(entropy.chance(1.0));
+        assert!(!entropy.chance(0.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn roll_weighted_all_zero() {
+        let battle = Battle::builder(CustomRules::new()).build();
+        let mut server = Server::builder(battle).build();
+        server.battle.entropy.roll_weighted(&[0, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn chance_output_too_small() {
+        battle_rules_with_entropy! { crate::rules::entropy::FixedLow<u8> }
+        let mut server = server(CustomRules::new());
+        server.battle.entropy.chance(0.5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn roll_weighted_output_too_small() {
+        battle_rules_with_entropy! { crate::rules::entropy::FixedLow<u8> }
+        let mut server = server(CustomRules::new());
+        server.battle.entropy.roll_weighted(&[200, 200]);
+    }
+
+    #[cfg(feature = "random")]
+    #[test]
+    fn shuffle_is_deterministic() {
+        battle_rules_with_entropy! { crate::rules::entropy::UniformDistribution<u32> }
+        let shuffled = || {
+            let mut server = server(CustomRules::new());
+            assert!(ResetEntropy::trigger(&mut server).seed(7).fire().is_ok());
+            let mut items: Vec<_> = (0..10).collect();
+            server.battle.entropy.shuffle(&mut items);
+            items
+        };
+        let items = shuffled();
+        assert_eq!(items, shuffled());
+        let mut sorted = items.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+    }
+
     #[cfg(feature = "random")]
     #[test]
     fn empty_range() {
@@ -2,10 +2,12 @@
 
 use crate::ability::AbilityId;
 use crate::battle::{BattleRules, Version};
+use crate::character::StatisticId;
 use crate::creature::CreatureId;
 use crate::entity::EntityId;
 use crate::event::{DefaultOutput, Event, EventId, EventSinkId};
 use crate::metric::MetricIdType;
+use crate::object::ObjectId;
 use crate::player::PlayerId;
 use crate::space::Position;
 use crate::team::TeamId;
@@ -20,8 +22,10 @@ pub type WeaselErrorType<R> = WeaselError<
     TeamId<R>,
     EntityId<R>,
     CreatureId<R>,
+    ObjectId<R>,
     Position<R>,
     AbilityId<R>,
+    StatisticId<R>,
     MetricIdType<R>,
     Box<dyn Event<R>>,
 >;
@@ -31,7 +35,7 @@ pub type WeaselResult<T, R> = Result<T, WeaselErrorType<R>>;
 
 /// Error type for all kind of errors generated by weasel.
 #[derive(Debug, Clone, PartialEq)]
-pub enum WeaselError<V, TI, EI, CI, PI, AI, MI, E> {
+pub enum WeaselError<V, TI, EI, CI, OI, PI, AI, SI, MI, E> {
     /// Duplicated creature id.
     DuplicatedCreature(CI),
     /// Duplicated team id.
@@ -40,6 +44,10 @@ pub enum WeaselError<V, TI, EI, CI, PI, AI, MI, E> {
     TeamNotFound(TI),
     /// The creature doesn't exist.
     CreatureNotFound(CI),
+    /// Duplicated object id.
+    DuplicatedObject(OI),
+    /// The object doesn't exist.
+    ObjectNotFound(OI),
     /// Creation of creatures is disabled.
     NewCreatureUnaccepted(TI),
     /// The creature can't be transferred to the team.
@@ -50,8 +58,14 @@ pub enum WeaselError<V, TI, EI, CI, PI, AI, MI, E> {
     TeamNotEmpty(TI),
     /// Position is invalid.
     PositionError(Option<PI>, PI),
+    /// The position is occupied by an entity that can't be overlapped.
+    PositionOccupied(PI, EI),
     /// The entity doesn't exist.
     EntityNotFound(EI),
+    /// The entity is not a creature.
+    NotACreature(EI),
+    /// The entity is not an object.
+    NotAnObject(EI),
     /// The event id is not contiguous.
     NonContiguousEventId(EventId, EventId),
     /// A round is already in progress.
@@ -60,22 +74,36 @@ pub enum WeaselError<V, TI, EI, CI, PI, AI, MI, E> {
     NoRoundInProgress,
     /// The actor can't start a new round.
     ActorNotEligible(EI),
+    /// The rules prevent the actor from starting a new round.
+    RoundCannotStart(EI),
     /// The actor can't act at the moment.
     ActorNotReady(EI),
     /// Actor does not know such ability.
     AbilityNotKnown(EI, AI),
     /// The ability can't be activated.
     AbilityNotActivable(EI, AI),
+    /// The ability is on cooldown.
+    AbilityOnCooldown(EI, AI),
+    /// The ability can't be activated on this number of targets.
+    InvalidTargetsCount(AI, usize),
+    /// The entity is not a valid target for the ability.
+    InvalidTarget(AI, EI),
     /// The event processor is not valid.
     EmptyEventProcessor,
     /// The entity is not a character.
     NotACharacter(EI),
     /// The entity is not an actor.
     NotAnActor(EI),
+    /// The character doesn't have such statistic.
+    StatisticNotFound(EI, SI),
+    /// The character already has a statistic with the same id.
+    DuplicatedStatistic(EI, SI),
     /// Attempt to set `Relation::Kin`.
     KinshipRelation,
     /// Attempt to set relation towards oneself.
     SelfRelation,
+    /// The same pair of teams appears more than once in a list of relations.
+    DuplicatedRelation(TI, TI),
     /// Two versions of the battle rules are incompatible.
     IncompatibleVersions(V, V),
     /// The battle has already ended.
@@ -102,24 +130,42 @@ pub enum WeaselError<V, TI, EI, CI, PI, AI, MI, E> {
     UserEventUnpackingError(String),
     /// The event is invalid.
     #[allow(clippy::type_complexity)]
-    InvalidEvent(E, Box<WeaselError<V, TI, EI, CI, PI, AI, MI, E>>),
+    InvalidEvent(E, Box<WeaselError<V, TI, EI, CI, OI, PI, AI, SI, MI, E>>),
     /// An error containing multiple inner errors.
     #[allow(clippy::type_complexity)]
-    MultiError(Vec<WeaselError<V, TI, EI, CI, PI, AI, MI, E>>),
+    MultiError(Vec<WeaselError<V, TI, EI, CI, OI, PI, AI, SI, MI, E>>),
+    /// The item at the given index of a batched operation is invalid.
+    #[allow(clippy::type_complexity)]
+    BatchItemFailed(
+        usize,
+        Box<WeaselError<V, TI, EI, CI, OI, PI, AI, SI, MI, E>>,
+    ),
     /// An user defined error.
+    /// Rules can return it to reject an event for their own reasons, for instance
+    /// from `FightRules::verify_impact`.
     UserError(String),
     /// A generic event sink error.
     EventSinkError(String),
+    /// The history doesn't contain any event.
+    EmptyHistory,
+    /// The history doesn't contain all the events of the battle,
+    /// because the battle was restored from a snapshot.
+    IncompleteHistory,
+    /// Failure while serializing or deserializing data.
+    SerializationError(String),
 }
 
-impl<V, TI, EI, CI, PI, AI, MI, E> fmt::Display for WeaselError<V, TI, EI, CI, PI, AI, MI, E>
+impl<V, TI, EI, CI, OI, PI, AI, SI, MI, E> fmt::Display
+    for WeaselError<V, TI, EI, CI, OI, PI, AI, SI, MI, E>
 where
     V: Debug,
     TI: Debug,
     EI: Debug,
     CI: Debug,
+    OI: Debug,
     PI: Debug,
     AI: Debug,
+    SI: Debug,
     MI: Debug,
     E: Debug,
 {
@@ -131,6 +177,8 @@ where
             WeaselError::DuplicatedTeam(id) => write!(f, "duplicated team with id {:?}", id),
             WeaselError::TeamNotFound(id) => write!(f, "team {:?} not found", id),
             WeaselError::CreatureNotFound(id) => write!(f, "creature {:?} not found", id),
+            WeaselError::DuplicatedObject(id) => write!(f, "duplicated object with id {:?}", id),
+            WeaselError::ObjectNotFound(id) => write!(f, "object {:?} not found", id),
             WeaselError::NewCreatureUnaccepted(id) => {
                 write!(f, "team {:?} does not accept new creatures", id)
             }
@@ -150,7 +198,14 @@ where
                 "can't move entity from position {:?} to position {:?}",
                 source, destination
             ),
+            WeaselError::PositionOccupied(position, occupant) => write!(
+                f,
+                "position {:?} is occupied by entity {:?}",
+                position, occupant
+            ),
             WeaselError::EntityNotFound(id) => write!(f, "entity {:?} not found", id),
+            WeaselError::NotACreature(id) => write!(f, "entity {:?} is not a creature", id),
+            WeaselError::NotAnObject(id) => write!(f, "entity {:?} is not an object", id),
             WeaselError::NonContiguousEventId(id, expected) => {
                 write!(f, "event has id {:?}, expected {:?}", id, expected)
             }
@@ -159,6 +214,9 @@ where
             WeaselError::ActorNotEligible(id) => {
                 write!(f, "actor {:?} is not eligible to start a new round", id)
             }
+            WeaselError::RoundCannotStart(id) => {
+                write!(f, "actor {:?} is not allowed to start a new round", id)
+            }
             WeaselError::ActorNotReady(id) => {
                 write!(f, "actor {:?} can't act outside of his round", id)
             }
@@ -172,8 +230,33 @@ where
                 "actor {:?} can't activate ability {:?}",
                 actor_id, ability_id
             ),
+            WeaselError::AbilityOnCooldown(actor_id, ability_id) => write!(
+                f,
+                "ability {:?} of actor {:?} is on cooldown",
+                ability_id, actor_id
+            ),
+            WeaselError::InvalidTargetsCount(ability_id, count) => write!(
+                f,
+                "ability {:?} can't be activated on {} targets",
+                ability_id, count
+            ),
+            WeaselError::InvalidTarget(ability_id, target) => write!(
+                f,
+                "entity {:?} is not a valid target for ability {:?}",
+                target, ability_id
+            ),
             WeaselError::NotACharacter(id) => write!(f, "entity {:?} is not a character", id),
             WeaselError::NotAnActor(id) => write!(f, "entity {:?} is not an actor", id),
+            WeaselError::StatisticNotFound(id, statistic_id) => write!(
+                f,
+                "character {:?} doesn't have statistic {:?}",
+                id, statistic_id
+            ),
+            WeaselError::DuplicatedStatistic(id, statistic_id) => write!(
+                f,
+                "character {:?} already has statistic {:?}",
+                id, statistic_id
+            ),
             WeaselError::EmptyEventProcessor => {
                 write!(f, "() is not a valid event processor to process events")
             }
@@ -181,6 +264,11 @@ where
             WeaselError::SelfRelation => {
                 write!(f, "a team can't explicitly set a relation towards itself")
             }
+            WeaselError::DuplicatedRelation(first, second) => write!(
+                f,
+                "relation between teams {:?} and {:?} is set more than once",
+                first, second
+            ),
             WeaselError::IncompatibleVersions(client, server) => write!(
                 f,
                 "client version {:?} is different from server version {:?}",
@@ -228,20 +316,31 @@ where
                 }
                 write!(f, "]")
             }
+            WeaselError::BatchItemFailed(index, error) => {
+                write!(f, "batch item {} failed due to {:?}", index, error)
+            }
             WeaselError::UserError(msg) => write!(f, "user error: {}", msg),
             WeaselError::EventSinkError(msg) => write!(f, "sink error: {}", msg),
+            WeaselError::EmptyHistory => write!(f, "the history is empty"),
+            WeaselError::IncompleteHistory => {
+                write!(f, "the history doesn't contain all events of the battle")
+            }
+            WeaselError::SerializationError(msg) => write!(f, "serialization error: {}", msg),
         }
     }
 }
 
-impl<V, TI, EI, CI, PI, AI, MI, E> error::Error for WeaselError<V, TI, EI, CI, PI, AI, MI, E>
+impl<V, TI, EI, CI, OI, PI, AI, SI, MI, E> error::Error
+    for WeaselError<V, TI, EI, CI, OI, PI, AI, SI, MI, E>
 where
     V: Debug,
     TI: Debug,
     EI: Debug,
     CI: Debug,
+    OI: Debug,
     PI: Debug,
     AI: Debug,
+    SI: Debug,
     MI: Debug,
     E: Debug,
 {
@@ -250,10 +349,11 @@ where
     }
 }
 
-impl<V, TI, EI, CI, PI, AI, MI, E> WeaselError<V, TI, EI, CI, PI, AI, MI, E> {
+impl<V, TI, EI, CI, OI, PI, AI, SI, MI, E> WeaselError<V, TI, EI, CI, OI, PI, AI, SI, MI, E> {
     /// Unfolds an error, return the inner one in case the original is an `InvalidEvent`.
     /// If not, it returns the original.\
     /// In the case of `MultiError`, unfolds all contained errors.
+    /// In the case of `BatchItemFailed`, unfolds the inner error but keeps the item's index.
     ///
     /// # Examples
     /// ```
@@ -275,6 +375,9 @@ impl<V, TI, EI, CI, PI, AI, MI, E> WeaselError<V, TI, EI, CI, PI, AI, MI, E> {
             WeaselError::MultiError(v) => {
                 WeaselError::MultiError(v.into_iter().map(|err| err.unfold()).collect())
             }
+            WeaselError::BatchItemFailed(index, inner) => {
+                WeaselError::BatchItemFailed(index, Box::new(inner.unfold()))
+            }
             _ => self,
         }
     }
@@ -282,7 +385,7 @@ impl<V, TI, EI, CI, PI, AI, MI, E> WeaselError<V, TI, EI, CI, PI, AI, MI, E> {
     /// Consumes this error and filters it with the given `filter` function.
     ///
     /// `filter` is applied to this error, to the error inside `InvalidEvent`
-    /// and to all errors contained by `MultiError`.\
+    /// or `BatchItemFailed` and to all errors contained by `MultiError`.\
     /// Only the errors for which `filter` returns true are kept.
     ///
     /// # Examples
@@ -329,6 +432,17 @@ impl<V, TI, EI, CI, PI, AI, MI, E> WeaselError<V, TI, EI, CI, PI, AI, MI, E> {
                         Ok(())
                     }
                 }
+                WeaselError::BatchItemFailed(index, error) => {
+                    let new_error = error.filter(op);
+                    if new_error.is_err() {
+                        Err(WeaselError::BatchItemFailed(
+                            index,
+                            Box::new(new_error.err().unwrap()),
+                        ))
+                    } else {
+                        Ok(())
+                    }
+                }
                 WeaselError::MultiError(v) => {
                     let mut new_errors = Vec::new();
                     for error in v {
@@ -380,6 +494,13 @@ mod tests {
         let error: WeaselErrorType<CustomRules> =
             WeaselError::InvalidEvent(trigger.event(), Box::new(WeaselError::EmptyEventProcessor));
         assert_eq!(error.clone().unfold(), WeaselError::EmptyEventProcessor);
+        // Test unfolding of batch errors.
+        let batch_error: WeaselErrorType<CustomRules> =
+            WeaselError::BatchItemFailed(1, Box::new(error.clone()));
+        assert_eq!(
+            batch_error.unfold(),
+            WeaselError::BatchItemFailed(1, Box::new(WeaselError::EmptyEventProcessor))
+        );
         // Test unfolding of multierrors.
         let error: WeaselErrorType<CustomRules> = WeaselError::MultiError(vec![error]);
         assert_eq!(
@@ -399,13 +520,7 @@ mod tests {
         assert_eq!(
             error
                 .clone()
-                .filter(|err| {
-                    if let WeaselError::EmptyEventProcessor = err {
-                        false
-                    } else {
-                        true
-                    }
-                })
+                .filter(|err| !matches!(err, WeaselError::EmptyEventProcessor))
                 .err(),
             None
         );
@@ -413,15 +528,17 @@ mod tests {
         let error: WeaselErrorType<CustomRules> = WeaselError::MultiError(vec![error]);
         assert_eq!(
             error
-                .filter(|err| {
-                    if let WeaselError::EmptyEventProcessor = err {
-                        false
-                    } else {
-                        true
-                    }
-                })
+                .filter(|err| !matches!(err, WeaselError::EmptyEventProcessor))
                 .err(),
             None
         );
     }
+
+    #[test]
+    fn std_error() {
+        battle_rules! {}
+        let error: WeaselErrorType<CustomRules> = WeaselError::TeamNotFound(1);
+        let error: Box<dyn error::Error> = Box::new(error);
+        assert_eq!(error.to_string(), "team 1 not found");
+    }
 }
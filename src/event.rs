@@ -9,6 +9,7 @@ use log::error;
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
 use std::any::Any;
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::ops::{Deref, Range};
 
@@ -64,6 +65,38 @@ pub enum EventKind {
     RegenerateStatistics,
     /// Regenerate the abilities of an actor.
     RegenerateAbilities,
+    /// Set new one-directional relations between teams.
+    SetDirectedRelations,
+    /// Change the id of a team.
+    RenameTeam,
+    /// Modify the statistics of several characters.
+    AlterStatisticsBatch,
+    /// End the current round of a specific actor.
+    EndRoundFor,
+    /// Begin a new turn cycle.
+    StartTurnCycle,
+    /// Restore the entropy model to a saved state.
+    RestoreEntropy,
+    /// Remove all abilities from an actor.
+    ClearAbilities,
+    /// Disable or re-enable a creature.
+    SetCreatureDisabled,
+    /// Create many creatures at once.
+    CreateCreatures,
+    /// Give a player the rights to control a team.
+    GrantRights,
+    /// Take away from a player the rights to control a team.
+    RevokeRights,
+    /// Remove a single statistic from a character.
+    RemoveStatistic,
+    /// Add a single statistic to a character.
+    AddStatistic,
+    /// Create a new object.
+    CreateObject,
+    /// Remove an object from the battle.
+    RemoveObject,
+    /// Replace all relations between teams.
+    OverwriteRelations,
     /// A user defined event with an unique id.
     UserEvent(UserEventId),
 }
@@ -129,11 +162,18 @@ pub struct EventWrapper<R: BattleRules> {
     pub(crate) origin: Option<EventId>,
     /// The actual event wrapped inside this struct.
     pub(crate) event: Box<dyn Event<R>>,
+    /// Optional user note attached to the event.
+    pub(crate) note: Option<String>,
+    /// Id of the authenticated player who fired the event.
+    pub(crate) player: Option<PlayerId>,
 }
 
 impl<R: BattleRules> Clone for EventWrapper<R> {
     fn clone(&self) -> EventWrapper<R> {
-        EventWrapper::new(self.id, self.origin, self.event.clone())
+        let mut wrapper = EventWrapper::new(self.id, self.origin, self.event.clone());
+        wrapper.note = self.note.clone();
+        wrapper.player = self.player;
+        wrapper
     }
 }
 
@@ -144,7 +184,13 @@ impl<R: BattleRules> EventWrapper<R> {
         origin: Option<EventId>,
         event: Box<dyn Event<R>>,
     ) -> EventWrapper<R> {
-        EventWrapper { id, origin, event }
+        EventWrapper {
+            id,
+            origin,
+            event,
+            note: None,
+            player: None,
+        }
     }
 
     /// Returns this event's id.
@@ -163,6 +209,19 @@ impl<R: BattleRules> EventWrapper<R> {
         &self.event
     }
 
+    /// Returns the note attached to this event, if any.
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    /// Returns the id of the authenticated player who fired this event.
+    ///
+    /// Events generated by the server, or coming from clients when authentication
+    /// is not enforced, don't have a player.
+    pub fn player(&self) -> Option<PlayerId> {
+        self.player
+    }
+
     /// Consume this event wrapper and returns a versioned instance of it.
     pub fn version(self, version: Version<R>) -> VersionedEventWrapper<R> {
         VersionedEventWrapper::new(self, version)
@@ -226,6 +285,10 @@ pub struct EventPrototype<R: BattleRules> {
     pub(crate) event: Box<dyn Event<R>>,
     /// Condition that must be satisfied for this prototype to be valid.
     pub(crate) condition: Option<Condition<R>>,
+    /// Optional user note attached to the event.
+    pub(crate) note: Option<String>,
+    /// Id of the authenticated player who fired the event.
+    pub(crate) player: Option<PlayerId>,
 }
 
 impl<R: BattleRules> EventPrototype<R> {
@@ -235,11 +298,16 @@ impl<R: BattleRules> EventPrototype<R> {
             origin: None,
             event,
             condition: None,
+            note: None,
+            player: None,
         }
     }
 
     pub(crate) fn promote(self, id: EventId) -> EventWrapper<R> {
-        EventWrapper::new(id, self.origin, self.event)
+        let mut wrapper = EventWrapper::new(id, self.origin, self.event);
+        wrapper.note = self.note;
+        wrapper.player = self.player;
+        wrapper
     }
 
     /// Returns the id of the event that caused this one.
@@ -258,13 +326,20 @@ impl<R: BattleRules> EventPrototype<R> {
         &self.condition
     }
 
+    /// Returns the note attached to this prototype, if any.
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
     /// Consume this event prototype and returns a `ClientEventPrototype` instance of it.
     pub fn client_prototype(
         self,
         version: Version<R>,
         player: Option<PlayerId>,
     ) -> ClientEventPrototype<R> {
-        ClientEventPrototype::new(self.origin, self.event, version, player)
+        let mut prototype = ClientEventPrototype::new(self.origin, self.event, version, player);
+        prototype.note = self.note;
+        prototype
     }
 }
 
@@ -282,6 +357,8 @@ impl<R: BattleRules> Clone for EventPrototype<R> {
             origin: self.origin,
             event: self.event.clone(),
             condition: self.condition.clone(),
+            note: self.note.clone(),
+            player: self.player,
         }
     }
 }
@@ -297,6 +374,8 @@ pub struct ClientEventPrototype<R: BattleRules> {
     pub(crate) version: Version<R>,
     /// Id of the player who fired this event.
     player: Option<PlayerId>,
+    /// Optional user note attached to the event.
+    pub(crate) note: Option<String>,
 }
 
 impl<R: BattleRules> ClientEventPrototype<R> {
@@ -312,6 +391,7 @@ impl<R: BattleRules> ClientEventPrototype<R> {
             event,
             version,
             player,
+            note: None,
         }
     }
 
@@ -337,6 +417,8 @@ impl<R: BattleRules> ClientEventPrototype<R> {
             origin: self.origin,
             event: self.event,
             condition: None,
+            note: self.note,
+            player: self.player,
         }
     }
 
@@ -352,6 +434,11 @@ impl<R: BattleRules> ClientEventPrototype<R> {
     pub fn player(&self) -> Option<PlayerId> {
         self.player
     }
+
+    /// Returns the note attached to this prototype, if any.
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
 }
 
 impl<R: BattleRules> Deref for ClientEventPrototype<R> {
@@ -369,6 +456,7 @@ impl<R: BattleRules> Clone for ClientEventPrototype<R> {
             event: self.event.clone(),
             version: self.version.clone(),
             player: self.player,
+            note: self.note.clone(),
         }
     }
 }
@@ -391,6 +479,37 @@ pub trait EventProcessor<R: BattleRules> {
 
     /// Processes a local event prototype.
     fn process(&mut self, event: EventPrototype<R>) -> Self::ProcessOutput;
+
+    /// Processes a sequence of events, in order.
+    ///
+    /// Processors owning a battle verify all events before processing the first one.
+    /// Whether events depending on the previous ones are accepted, and whether a failure
+    /// can leave part of the sequence applied, depends on the processor. See the
+    /// implementations of `Server` and `Client` for details.
+    ///
+    /// The provided implementation processes the events one after the other and
+    /// returns the output of the last one.
+    fn fire_all(&mut self, events: Vec<Box<dyn Event<R>>>) -> Self::ProcessOutput {
+        let mut output = Self::ProcessOutput::ok();
+        for event in events {
+            output = self.process(EventPrototype::new(event));
+        }
+        output
+    }
+}
+
+/// A trait for processors that can tell which events were applied as a result
+/// of processing a prototype.
+pub trait OutputProcessor<R: BattleRules>: EventProcessor<R> {
+    /// Processes a local event prototype and returns all events appended to the history
+    /// because of it, in order. The first one is the event itself, followed by
+    /// the events derived from it.
+    ///
+    /// If an error is returned, some events might have been applied nonetheless.
+    fn process_with_output(
+        &mut self,
+        event: EventPrototype<R>,
+    ) -> WeaselResult<Vec<EventWrapper<R>>, R>;
 }
 
 /// A trait for objects that can verify and process new client events.
@@ -419,13 +538,39 @@ pub trait EventTrigger<'a, R: BattleRules, P: 'a + EventProcessor<R>> {
         self.processor().process(prototype)
     }
 
+    /// Fires the event constructed by this builder and returns all events that
+    /// were appended to the history as a result, including the derived ones.
+    fn fire_with_output(&'a mut self) -> WeaselResult<Vec<EventWrapper<R>>, R>
+    where
+        P: OutputProcessor<R>,
+    {
+        let prototype = self.prototype();
+        self.processor().process_with_output(prototype)
+    }
+
     /// Returns the event constructed by this builder, wrapped in a prototype.
     fn prototype(&self) -> EventPrototype<R> {
         EventPrototype::new(self.event())
     }
+
+    /// Attaches a note to the event constructed by this builder.
+    ///
+    /// Notes are stored in the battle's history, but they have no effect on the event itself.
+    fn note<S: Into<String>>(self, note: S) -> Annotated<'a, R, Self, P>
+    where
+        Self: Sized,
+    {
+        Annotated::new(self, note.into())
+    }
 }
 
 /// Collection to queue events prototypes, in order of insertion.
+///
+/// Queued events are processed in FIFO order, once the event that generated them
+/// has been applied. Processing is depth first: the events derived from a queued event
+/// are processed before the next one in the queue.\
+/// Being a `Vec`, methods such as `len()` and `first()` can be used to inspect
+/// the pending events.
 pub type EventQueue<R> = Vec<EventPrototype<R>>;
 
 // Implement `EventProcessor` for event queues, so that it can be possible to
@@ -447,7 +592,7 @@ pub struct DummyEvent<R> {
 
 impl<R: BattleRules> DummyEvent<R> {
     /// Returns a trigger for this event.
-    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> DummyEventTrigger<R, P> {
+    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> DummyEventTrigger<'_, R, P> {
         DummyEventTrigger {
             processor,
             _phantom: PhantomData,
@@ -520,6 +665,151 @@ where
     }
 }
 
+/// Function to verify a `UserEvent` with the given payload.
+pub type UserEventVerify<R, T> = fn(&T, &Battle<R>) -> WeaselResult<(), R>;
+
+/// Function to apply a `UserEvent` with the given payload.
+pub type UserEventApply<R, T> = fn(&T, &mut Battle<R>, &mut Option<EventQueue<R>>);
+
+/// A generic user event, made of a payload and of the functions to verify and apply it.
+///
+/// It spares the need to write a new type implementing `Event` for simple user defined logic.
+/// The event's kind is `EventKind::UserEvent`, with the id given at construction.\
+/// Since functions can't be serialized, you'll have to pack the payload yourself in
+/// `UserEventPacker` if you wish to save these events.
+pub struct UserEvent<R: BattleRules, T> {
+    id: UserEventId,
+    payload: T,
+    verify: UserEventVerify<R, T>,
+    apply: UserEventApply<R, T>,
+}
+
+impl<R: BattleRules, T: Clone> UserEvent<R, T> {
+    /// Returns a trigger for this event.
+    ///
+    /// By default, the event is always valid.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &mut P,
+        id: UserEventId,
+        payload: T,
+        apply: UserEventApply<R, T>,
+    ) -> UserEventTrigger<'_, R, P, T> {
+        UserEventTrigger {
+            processor,
+            id,
+            payload,
+            verify: |_, _| Ok(()),
+            apply,
+        }
+    }
+
+    /// Returns the id of this user event.
+    pub fn id(&self) -> UserEventId {
+        self.id
+    }
+
+    /// Returns the payload of this event.
+    pub fn payload(&self) -> &T {
+        &self.payload
+    }
+}
+
+impl<R: BattleRules, T: std::fmt::Debug> std::fmt::Debug for UserEvent<R, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "UserEvent {{ id: {:?}, payload: {:?} }}",
+            self.id, self.payload
+        )
+    }
+}
+
+impl<R: BattleRules, T: Clone> Clone for UserEvent<R, T> {
+    fn clone(&self) -> Self {
+        UserEvent {
+            id: self.id,
+            payload: self.payload.clone(),
+            verify: self.verify,
+            apply: self.apply,
+        }
+    }
+}
+
+impl<R, T> Event<R> for UserEvent<R, T>
+where
+    R: BattleRules + 'static,
+    T: Clone + std::fmt::Debug + 'static,
+{
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        (self.verify)(&self.payload, battle)
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
+        (self.apply)(&self.payload, battle, event_queue)
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::UserEvent(self.id)
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire a `UserEvent` event.
+pub struct UserEventTrigger<'a, R, P, T>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    id: UserEventId,
+    payload: T,
+    verify: UserEventVerify<R, T>,
+    apply: UserEventApply<R, T>,
+}
+
+impl<'a, R, P, T> UserEventTrigger<'a, R, P, T>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    /// Adds a function to verify the event's payload.
+    pub fn verify(
+        &'a mut self,
+        verify: UserEventVerify<R, T>,
+    ) -> &'a mut UserEventTrigger<'a, R, P, T> {
+        self.verify = verify;
+        self
+    }
+}
+
+impl<'a, R, P, T> EventTrigger<'a, R, P> for UserEventTrigger<'a, R, P, T>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+    T: Clone + std::fmt::Debug + 'static,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns a `UserEvent` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(UserEvent {
+            id: self.id,
+            payload: self.payload.clone(),
+            verify: self.verify,
+            apply: self.apply,
+        })
+    }
+}
+
 // Implement `EventProcessor` for option, so that it would be possible to pass
 // None or a real processor to event triggers.
 impl<R, T> EventProcessor<R> for &mut Option<T>
@@ -536,6 +826,14 @@ where
             Self::ProcessOutput::ok()
         }
     }
+
+    fn fire_all(&mut self, events: Vec<Box<dyn Event<R>>>) -> Self::ProcessOutput {
+        if let Some(processor) = self {
+            processor.fire_all(events)
+        } else {
+            Self::ProcessOutput::ok()
+        }
+    }
 }
 
 impl<R, T> EventProcessor<R> for Option<T>
@@ -552,6 +850,14 @@ where
             Self::ProcessOutput::ok()
         }
     }
+
+    fn fire_all(&mut self, events: Vec<Box<dyn Event<R>>>) -> Self::ProcessOutput {
+        if let Some(processor) = self {
+            processor.fire_all(events)
+        } else {
+            Self::ProcessOutput::ok()
+        }
+    }
 }
 
 // Implement `EventProcessor` for (), doing nothing.
@@ -582,7 +888,7 @@ pub struct Prioritized<'a, R: BattleRules> {
 
 impl<'a, R: BattleRules> Prioritized<'a, R> {
     /// Creates a new Prioritized decorator for the given `event_queue`.
-    pub fn new(event_queue: &'a mut EventQueue<R>) -> Prioritized<R> {
+    pub fn new(event_queue: &'a mut EventQueue<R>) -> Prioritized<'a, R> {
         Prioritized { event_queue }
     }
 }
@@ -596,6 +902,13 @@ where
     fn process(&mut self, event: EventPrototype<R>) -> Self::ProcessOutput {
         self.event_queue.insert(0, event);
     }
+
+    fn fire_all(&mut self, events: Vec<Box<dyn Event<R>>>) -> Self::ProcessOutput {
+        // Keep the events in the same order between themselves.
+        for (i, event) in events.into_iter().enumerate() {
+            self.event_queue.insert(i, EventPrototype::new(event));
+        }
+    }
 }
 
 /// Decorator for event triggers to add a condition on the generated event prototype.
@@ -649,6 +962,55 @@ where
     }
 }
 
+/// Decorator for event triggers to attach a note to the generated event prototype.
+pub struct Annotated<'a, R, T, P>
+where
+    R: BattleRules,
+    T: EventTrigger<'a, R, P>,
+    P: 'a + EventProcessor<R>,
+{
+    trigger: T,
+    note: String,
+    _phantom: PhantomData<&'a (R, P)>,
+}
+
+impl<'a, R, T, P> Annotated<'a, R, T, P>
+where
+    R: BattleRules,
+    T: EventTrigger<'a, R, P>,
+    P: 'a + EventProcessor<R>,
+{
+    /// Creates a new `Annotated` decorator for an `EventTrigger`.
+    pub fn new(trigger: T, note: String) -> Annotated<'a, R, T, P> {
+        Annotated {
+            trigger,
+            note,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, R, T, P> EventTrigger<'a, R, P> for Annotated<'a, R, T, P>
+where
+    R: BattleRules,
+    T: EventTrigger<'a, R, P>,
+    P: 'a + EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.trigger.processor()
+    }
+
+    fn event(&self) -> Box<dyn Event<R>> {
+        self.trigger.event()
+    }
+
+    fn prototype(&self) -> EventPrototype<R> {
+        let mut prototype = self.trigger.prototype();
+        prototype.note = Some(self.note.clone());
+        prototype
+    }
+}
+
 /// Id of an event sink.
 pub type EventSinkId = u16;
 
@@ -667,6 +1029,14 @@ pub trait EventSink {
 pub trait ClientSink<R: BattleRules>: EventSink {
     /// Sends an already accepted event to a remote or local client.
     fn send(&mut self, event: &VersionedEventWrapper<R>) -> WeaselResult<(), R>;
+
+    /// Flushes the events sent so far.
+    /// It's invoked after each chunk of events sent with `send_range_chunked`.
+    ///
+    /// The provided implementation does nothing.
+    fn flush(&mut self) -> WeaselResult<(), R> {
+        Ok(())
+    }
 }
 
 /// An output sink to dump tentative events to a server.
@@ -675,29 +1045,98 @@ pub trait ServerSink<R: BattleRules>: EventSink {
     fn send(&mut self, event: &ClientEventPrototype<R>) -> WeaselResult<(), R>;
 }
 
+/// Function that tells whether an event should be forwarded to the client sink with
+/// the given id.
+pub type ClientSinkFilter<R> = Box<dyn Fn(&VersionedEventWrapper<R>, EventSinkId) -> bool>;
+
 /// A data structure to contain multiple client sinks.
 pub(crate) struct MultiClientSink<R: BattleRules> {
     sinks: Vec<Box<dyn ClientSink<R>>>,
+    filters: HashMap<EventSinkId, ClientSinkFilter<R>>,
+    /// Id of the next event expected by each sink.
+    cursors: HashMap<EventSinkId, EventId>,
 }
 
 impl<R: BattleRules> MultiClientSink<R> {
     pub(crate) fn new() -> MultiClientSink<R> {
-        MultiClientSink { sinks: Vec::new() }
+        MultiClientSink {
+            sinks: Vec::new(),
+            filters: HashMap::new(),
+            cursors: HashMap::new(),
+        }
     }
+}
 
+impl<R: BattleRules + 'static> MultiClientSink<R> {
     /// Adds a new sink.
     /// Returns an error if another sink with the same id already exists.
     fn add(&mut self, sink: Box<dyn ClientSink<R>>) -> WeaselResult<(), R> {
         if self.sinks.iter().any(|e| e.id() == sink.id()) {
             Err(WeaselError::DuplicatedEventSink(sink.id()))
         } else {
+            self.cursors.insert(sink.id(), 0);
             self.sinks.push(sink);
             Ok(())
         }
     }
 
+    /// Sets the id of the next event expected by the sink with the given id.
+    fn set_cursor(&mut self, id: EventSinkId, cursor: EventId) {
+        if let Some(current) = self.cursors.get_mut(&id) {
+            *current = cursor;
+        }
+    }
+
+    /// Adds a new sink which will receive only the events accepted by `filter`.
+    /// Returns an error if another sink with the same id already exists.
+    fn add_filtered(
+        &mut self,
+        sink: Box<dyn ClientSink<R>>,
+        filter: ClientSinkFilter<R>,
+    ) -> WeaselResult<(), R> {
+        let id = sink.id();
+        self.add(sink)?;
+        self.filters.insert(id, filter);
+        Ok(())
+    }
+
+    /// Replaces the sink having the same id of `sink`, keeping its filter.
+    /// Returns an error if no such sink exists.
+    fn replace(&mut self, sink: Box<dyn ClientSink<R>>) -> WeaselResult<(), R> {
+        let id = sink.id();
+        if let Some(old) = self.sinks.iter_mut().find(|e| e.id() == id) {
+            *old = sink;
+            Ok(())
+        } else {
+            Err(WeaselError::EventSinkNotFound(id))
+        }
+    }
+
+    /// Returns the event to be sent to the sink with the given id.
+    /// Events discarded by the sink's filter are replaced by a `DummyEvent`.
+    fn filter(
+        &self,
+        id: EventSinkId,
+        event: &VersionedEventWrapper<R>,
+    ) -> VersionedEventWrapper<R> {
+        match self.filters.get(&id) {
+            Some(filter) if !filter(event, id) => EventWrapper::new(
+                event.id(),
+                event.origin(),
+                Box::new(DummyEvent {
+                    _phantom: PhantomData,
+                }),
+            )
+            .version(event.version().clone()),
+            _ => event.clone(),
+        }
+    }
+
     /// Sends all `events` to an existing sink.
     /// Returns an error if sending the events failed or the sink doesn't exist.
+    ///
+    /// Sending stops at the first event refused by the sink. The sink is then disconnected,
+    /// while all events before the failed one are considered delivered.
     fn send<I>(&mut self, id: EventSinkId, events: I) -> WeaselResult<(), R>
     where
         I: Iterator<Item = VersionedEventWrapper<R>>,
@@ -706,13 +1145,17 @@ impl<R: BattleRules> MultiClientSink<R> {
         if let Some(index) = index {
             // Send events.
             for event in events {
+                let event = self.filter(id, &event);
                 let sink = &mut self.sinks[index];
                 let result = sink.send(&event);
                 if result.is_err() {
                     sink.on_disconnect();
                     self.sinks.remove(index);
+                    self.filters.remove(&id);
+                    self.cursors.remove(&id);
                 }
                 result?;
+                self.set_cursor(id, event.id() + 1);
             }
             Ok(())
         } else {
@@ -720,11 +1163,30 @@ impl<R: BattleRules> MultiClientSink<R> {
         }
     }
 
+    /// Flushes an existing sink.
+    /// If flushing fails, the sink is disconnected.
+    fn flush(&mut self, id: EventSinkId) -> WeaselResult<(), R> {
+        let index = self.sinks.iter().position(|e| e.id() == id);
+        if let Some(index) = index {
+            let sink = &mut self.sinks[index];
+            let result = sink.flush();
+            if result.is_err() {
+                sink.on_disconnect();
+                self.remove(id);
+            }
+            result
+        } else {
+            Err(WeaselError::EventSinkNotFound(id))
+        }
+    }
+
     /// Removes the sink with the given `id`, if it exists.
     fn remove(&mut self, id: EventSinkId) {
         let index = self.sinks.iter().position(|e| e.id() == id);
         if let Some(index) = index {
             self.sinks.remove(index);
+            self.filters.remove(&id);
+            self.cursors.remove(&id);
         }
     }
 
@@ -733,21 +1195,39 @@ impl<R: BattleRules> MultiClientSink<R> {
     /// and the sink is disconnected from the server.
     pub(crate) fn send_all(&mut self, event: &VersionedEventWrapper<R>) {
         let mut failed_sinks_index = Vec::new();
-        for (i, sink) in self.sinks.iter_mut().enumerate() {
-            sink.send(event).unwrap_or_else(|err| {
-                error!("{:?}", err);
-                failed_sinks_index.push(i)
-            });
+        for i in 0..self.sinks.len() {
+            let id = self.sinks[i].id();
+            let event = self.filter(id, event);
+            match self.sinks[i].send(&event) {
+                Ok(()) => self.set_cursor(id, event.id() + 1),
+                Err(err) => {
+                    error!("{:?}", err);
+                    failed_sinks_index.push(i)
+                }
+            }
         }
-        for i in failed_sinks_index {
-            self.sinks[i].on_disconnect();
-            self.sinks.remove(i);
+        for i in failed_sinks_index.into_iter().rev() {
+            let mut sink = self.sinks.remove(i);
+            sink.on_disconnect();
+            self.filters.remove(&sink.id());
+            self.cursors.remove(&sink.id());
         }
     }
 
     fn sinks(&self) -> impl Iterator<Item = &Box<dyn ClientSink<R>>> {
         self.sinks.iter()
     }
+
+    /// Returns the lowest id not used by any sink, or `None` if all ids are taken.
+    fn next_id(&self) -> Option<EventSinkId> {
+        (0..=EventSinkId::MAX).find(|id| self.sinks.iter().all(|sink| sink.id() != *id))
+    }
+
+    fn status(&self) -> impl Iterator<Item = (EventSinkId, EventId)> + '_ {
+        self.sinks
+            .iter()
+            .map(move |sink| (sink.id(), self.cursors[&sink.id()]))
+    }
 }
 
 /// A structure to access client sinks.
@@ -770,6 +1250,17 @@ where
     pub fn sinks(&self) -> impl Iterator<Item = &Box<dyn ClientSink<R>>> {
         self.sinks.sinks()
     }
+
+    /// Returns an id not used by any sink, or `None` if all ids are taken.
+    pub fn next_id(&self) -> Option<EventSinkId> {
+        self.sinks.next_id()
+    }
+
+    /// Returns an iterator over the ids of all sinks, each paired with
+    /// the id of the next event the sink expects to receive.
+    pub fn status(&self) -> impl Iterator<Item = (EventSinkId, EventId)> + '_ {
+        self.sinks.status()
+    }
 }
 
 /// A structure to access and manipulate client sinks.
@@ -796,7 +1287,26 @@ where
     ///
     /// Sinks must have unique ids.
     pub fn add_sink(&mut self, sink: Box<dyn ClientSink<R>>) -> WeaselResult<(), R> {
-        self.sinks.add(sink)
+        let sink_id = sink.id();
+        self.sinks.add(sink)?;
+        self.sinks.set_cursor(sink_id, self.battle.history().len());
+        Ok(())
+    }
+
+    /// Adds a new sink which will receive only the events for which `filter` returns true.
+    ///
+    /// Events rejected by the filter are replaced by a `DummyEvent` having the same id,
+    /// so that the sink still receives a contiguous timeline.\
+    /// Sinks must have unique ids.
+    pub fn add_sink_filtered(
+        &mut self,
+        sink: Box<dyn ClientSink<R>>,
+        filter: ClientSinkFilter<R>,
+    ) -> WeaselResult<(), R> {
+        let sink_id = sink.id();
+        self.sinks.add_filtered(sink, filter)?;
+        self.sinks.set_cursor(sink_id, self.battle.history().len());
+        Ok(())
     }
 
     /// Adds a new sink and shares the battle history with it,
@@ -830,18 +1340,84 @@ where
         // Add the new sink.
         let sink_id = sink.id();
         self.sinks.add(sink)?;
+        self.sinks.set_cursor(sink_id, range.start as EventId);
         // Get all versioned events from history and send them.
         self.sinks
             .send(sink_id, self.battle.versioned_events(range))
     }
 
+    /// Replaces an existing sink with another one having the same id, then shares
+    /// the battle history with it starting from the event having `event_id`
+    /// up to the most recent event.
+    ///
+    /// Use this method when a client reconnects. Any filter set on the previous sink
+    /// remains in place.
+    pub fn reconnect_sink(
+        &mut self,
+        sink: Box<dyn ClientSink<R>>,
+        event_id: EventId,
+    ) -> WeaselResult<(), R> {
+        let range = normalize_range(
+            Range {
+                start: event_id,
+                end: self.battle.history().len(),
+            },
+            self.battle.history().len(),
+        )?;
+        let sink_id = sink.id();
+        self.sinks.replace(sink)?;
+        self.sinks.set_cursor(sink_id, event_id);
+        self.sinks
+            .send(sink_id, self.battle.versioned_events(range))
+    }
+
     /// Sends a range of events from the battle history to the sink with the given id.
+    ///
+    /// If the sink fails to receive an event, it is disconnected and the error is returned.
+    /// The events before the failed one have been delivered, thus the client can
+    /// resume from where it stopped by adding the sink again with `add_sink_from`.
     pub fn send_range(&mut self, id: EventSinkId, range: Range<EventId>) -> WeaselResult<(), R> {
         let range = normalize_range(range, self.battle.history().len())?;
         // Get all versioned events from history and send them.
         self.sinks.send(id, self.battle.versioned_events(range))
     }
 
+    /// Sends a range of events from the battle history to the sink with the given id,
+    /// in chunks of at most `chunk_size` events. The sink is flushed after each chunk.
+    ///
+    /// Sending stops at the first chunk that fails, and the sink is disconnected.\
+    /// Returns the number of events delivered in the chunks that were successfully flushed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn send_range_chunked(
+        &mut self,
+        id: EventSinkId,
+        range: Range<EventId>,
+        chunk_size: usize,
+    ) -> WeaselResult<usize, R> {
+        assert!(chunk_size > 0, "chunk_size must be greater than zero");
+        let range = normalize_range(range, self.battle.history().len())?;
+        if self.sinks.sinks().all(|sink| sink.id() != id) {
+            return Err(WeaselError::EventSinkNotFound(id));
+        }
+        let mut delivered = 0;
+        for start in range.clone().step_by(chunk_size) {
+            let end = std::cmp::min(start + chunk_size, range.end);
+            let result = self
+                .sinks
+                .send(id, self.battle.versioned_events(start..end))
+                .and_then(|_| self.sinks.flush(id));
+            if let Err(err) = result {
+                error!("{:?}", err);
+                break;
+            }
+            delivered += end - start;
+        }
+        Ok(delivered)
+    }
+
     /// Removes the sink with the given id.
     pub fn remove_sink(&mut self, id: EventSinkId) {
         self.sinks.remove(id);
@@ -851,6 +1427,17 @@ where
     pub fn sinks(&self) -> impl Iterator<Item = &Box<dyn ClientSink<R>>> {
         self.sinks.sinks()
     }
+
+    /// Returns an id not used by any sink, or `None` if all ids are taken.
+    pub fn next_id(&self) -> Option<EventSinkId> {
+        self.sinks.next_id()
+    }
+
+    /// Returns an iterator over the ids of all sinks, each paired with
+    /// the id of the next event the sink expects to receive.
+    pub fn status(&self) -> impl Iterator<Item = (EventSinkId, EventId)> + '_ {
+        self.sinks.status()
+    }
 }
 
 /// Converts a range of `EventId` into a range of `usize`.
@@ -930,6 +1517,14 @@ mod tests {
             Some(WeaselError::DuplicatedEventSink(0))
         );
         assert_eq!(multi.sinks.len(), 1);
+        // Check next_id.
+        assert_eq!(multi.next_id(), Some(1));
+        assert_eq!(multi.add(Box::new(Sink { id: 2, ok: true })).err(), None);
+        assert_eq!(multi.next_id(), Some(1));
+        assert_eq!(multi.add(Box::new(Sink { id: 1, ok: true })).err(), None);
+        assert_eq!(multi.next_id(), Some(3));
+        multi.remove(1);
+        multi.remove(2);
         // Check remove.
         multi.remove(2);
         assert_eq!(multi.sinks.len(), 1);
@@ -20,9 +20,23 @@ pub trait FightRules<R: BattleRules> {
     /// See [Impact](type.Impact.html).
     type Impact: Clone + Debug + Serialize + for<'a> Deserialize<'a>;
 
+    /// Checks if an impact is valid before applying it.
+    ///
+    /// The provided implementation accepts any impact.
+    fn verify_impact(
+        &self,
+        _state: &BattleState<R>,
+        _impact: &Self::Impact,
+    ) -> WeaselResult<(), R> {
+        Ok(())
+    }
+
     /// Takes an impact and generates one or more events to change the state of creatures or
     /// other objects.
     ///
+    /// Returns a list of follow-up impacts. Each of them is wrapped into an `ApplyImpact` event
+    /// and queued, in order, after the events inserted into `event_queue`.
+    ///
     /// The provided implementation does nothing.
     fn apply_impact(
         &self,
@@ -31,7 +45,8 @@ pub trait FightRules<R: BattleRules> {
         _event_queue: &mut Option<EventQueue<R>>,
         _entropy: &mut Entropy<R>,
         _metrics: &mut WriteMetrics<R>,
-    ) {
+    ) -> Vec<Self::Impact> {
+        Vec::new()
     }
 }
 
@@ -89,21 +104,28 @@ impl<R: BattleRules> Clone for ApplyImpact<R> {
 }
 
 impl<R: BattleRules + 'static> Event<R> for ApplyImpact<R> {
-    fn verify(&self, _: &Battle<R>) -> WeaselResult<(), R> {
-        // For simplicity, don't verify an impact.
-        // Trust the server to generate *processable* impacts.
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        // Impact verification is delegated to the rules, which by default
+        // trust the server to generate *processable* impacts.
         // `apply` should take care of generating correct events in all cases.
-        Ok(())
+        battle
+            .rules()
+            .fight_rules()
+            .verify_impact(&battle.state, &self.impact)
     }
 
     fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
-        battle.rules.fight_rules().apply_impact(
+        let impacts = battle.rules.fight_rules().apply_impact(
             &battle.state,
             &self.impact,
             event_queue,
             &mut battle.entropy,
             &mut battle.metrics.write_handle(),
         );
+        // Chain the follow-up impacts.
+        for impact in impacts {
+            ApplyImpact::trigger(event_queue, impact).fire();
+        }
     }
 
     fn kind(&self) -> EventKind {
@@ -1,10 +1,11 @@
 //! History of events.
 
-use crate::battle::BattleRules;
+use crate::battle::{BattleRules, Version};
 use crate::error::{WeaselError, WeaselResult};
-use crate::event::EventId;
-use crate::event::EventWrapper;
+use crate::event::{EventId, EventKind};
+use crate::event::{EventWrapper, VersionedEventWrapper};
 use std::convert::TryInto;
+use std::ops::Range;
 
 /// History is the place where all events are kept, in a way such that they
 /// construct a single, consistent timeline.
@@ -12,6 +13,14 @@ pub struct History<R: BattleRules> {
     events: Vec<EventWrapper<R>>,
 }
 
+impl<R: BattleRules> Clone for History<R> {
+    fn clone(&self) -> Self {
+        History {
+            events: self.events.clone(),
+        }
+    }
+}
+
 impl<R: BattleRules> History<R> {
     /// Creates a new History.
     pub(crate) fn new() -> History<R> {
@@ -23,6 +32,31 @@ impl<R: BattleRules> History<R> {
         &self.events
     }
 
+    /// Returns the events whose ids are inside `range`.\
+    /// Since event ids start from zero and have no gaps, the event at index `i`
+    /// in the history has id `i`.
+    ///
+    /// Returns an error if `range` is decreasing or goes past the end of the history.
+    pub fn range(&self, range: Range<EventId>) -> WeaselResult<&[EventWrapper<R>], R> {
+        if range.start > range.end || range.end > self.len() {
+            return Err(WeaselError::InvalidEventRange(range, self.len()));
+        }
+        Ok(&self.events[range.start as usize..range.end as usize])
+    }
+
+    /// Returns a copy of all events inside this timeline, decorated with the given version.
+    pub fn to_vec(&self, version: &Version<R>) -> Vec<VersionedEventWrapper<R>> {
+        self.events
+            .iter()
+            .map(|e| e.clone().version(version.clone()))
+            .collect()
+    }
+
+    /// Returns the number of events of the given kind inside this timeline.
+    pub fn count_by_kind(&self, kind: EventKind) -> usize {
+        self.events.iter().filter(|e| e.kind() == kind).count()
+    }
+
     /// Stores a new event in the history logs.
     pub(crate) fn archive(&mut self, event: &EventWrapper<R>) {
         assert_eq!(event.id as usize, self.events.len());
@@ -61,6 +95,7 @@ impl<R: BattleRules> History<R> {
 mod tests {
     use super::*;
     use crate::event::{DummyEvent, EventTrigger};
+    use crate::round::EndRound;
     use crate::{battle_rules, rules::empty::*};
 
     #[test]
@@ -71,6 +106,8 @@ mod tests {
             let event = EventWrapper {
                 id,
                 origin: None,
+                note: None,
+                player: None,
                 event: DummyEvent::trigger(&mut ()).event(),
             };
             history.verify_event(&event)?;
@@ -84,4 +121,70 @@ mod tests {
         assert!(try_archive(1).is_err());
         assert!(try_archive(0).is_err());
     }
+
+    #[test]
+    fn range() {
+        battle_rules! {}
+        let mut history = History::<CustomRules>::new();
+        for id in 0..4 {
+            history.archive(&EventWrapper {
+                id,
+                origin: None,
+                note: None,
+                player: None,
+                event: DummyEvent::trigger(&mut ()).event(),
+            });
+        }
+        // Ids should match indices.
+        let ids: Vec<_> = history
+            .range(1..3)
+            .unwrap()
+            .iter()
+            .map(|e| e.id())
+            .collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert!(history.range(4..4).unwrap().is_empty());
+        // Invalid ranges should be rejected.
+        assert_eq!(
+            history.range(2..5).err(),
+            Some(WeaselError::InvalidEventRange(2..5, 4))
+        );
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 3..1;
+        assert_eq!(
+            history.range(reversed.clone()).err(),
+            Some(WeaselError::InvalidEventRange(reversed, 4))
+        );
+        // Export all events.
+        let events = history.to_vec(&0);
+        assert_eq!(events.len(), 4);
+        assert!(events
+            .iter()
+            .enumerate()
+            .all(|(i, e)| e.id() as usize == i && *e.version() == 0));
+    }
+
+    #[test]
+    fn count_by_kind() {
+        battle_rules! {}
+        let mut history = History::<CustomRules>::new();
+        assert_eq!(history.count_by_kind(EventKind::DummyEvent), 0);
+        for id in 0..5 {
+            let event = if id % 2 == 0 {
+                DummyEvent::trigger(&mut ()).event()
+            } else {
+                EndRound::trigger(&mut ()).event()
+            };
+            history.archive(&EventWrapper {
+                id,
+                origin: None,
+                event,
+                note: None,
+                player: None,
+            });
+        }
+        assert_eq!(history.count_by_kind(EventKind::DummyEvent), 3);
+        assert_eq!(history.count_by_kind(EventKind::EndRound), 2);
+        assert_eq!(history.count_by_kind(EventKind::StartRound), 0);
+    }
 }
@@ -72,7 +72,9 @@
 //!
 //! It's possible to create your own events, by implementing the `Event` trait and using the
 //! reserved `EventKind::UserEvent`. Remember to also write a `UserEventPacker` in the case
-//! you wish to enable serialization.
+//! you wish to enable serialization.\
+//! For simple cases, `UserEvent` lets you define a new event out of a payload and
+//! an apply function.
 //!
 //! ## Client - server architecture
 //!
@@ -98,6 +100,7 @@
 //!
 //! - `random`: enables built-in entropy rules that use a pseudorandom number generator.
 //! - `serialization`: enables serialization and deserialization of events.
+//! - `compression`: enables compression of serialized events. Implies `serialization`.
 
 pub use crate::client::Client;
 pub use crate::error::{WeaselError, WeaselResult};
@@ -116,6 +119,7 @@ pub mod event;
 pub mod fight;
 pub mod history;
 pub mod metric;
+pub mod object;
 pub mod player;
 pub mod round;
 pub mod rules;
@@ -2,50 +2,149 @@
 
 use crate::battle::BattleRules;
 use crate::error::{WeaselError, WeaselResult};
+use crate::team::TeamId;
 use crate::user::{UserMetricId, UserRules};
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::hash::Hash;
 
 /// Manages all metrics in a battle.
 pub(crate) struct Metrics<R: BattleRules> {
     map: HashMap<MetricIdType<R>, Metric>,
+    /// Original value of the metrics changed while tracking was enabled.
+    changes: Option<MetricsChanges<R>>,
 }
 
+/// Original value of a set of metrics. `None` means that the metric didn't exist.
+pub(crate) type MetricsChanges<R> = HashMap<MetricIdType<R>, Option<Metric>>;
+
 impl<R: BattleRules> Metrics<R> {
     pub(crate) fn new() -> Metrics<R> {
         Metrics {
             map: HashMap::new(),
+            changes: None,
         }
     }
 
     /// Returns a handle to read metrics.
-    pub(crate) fn read_handle(&self) -> ReadMetrics<R> {
+    pub(crate) fn read_handle(&self) -> ReadMetrics<'_, R> {
         ReadMetrics { metrics: self }
     }
 
     /// Returns a handle to write metrics.
-    pub(crate) fn write_handle(&mut self) -> WriteMetrics<R> {
+    pub(crate) fn write_handle(&mut self) -> WriteMetrics<'_, R> {
         WriteMetrics { metrics: self }
     }
+
+    /// Returns a copy of the current value of all metrics.
+    pub(crate) fn snapshot(&self) -> Metrics<R> {
+        Metrics {
+            map: self.map.clone(),
+            changes: None,
+        }
+    }
+
+    /// Creates a new set of metrics with the given values.
+    #[cfg(feature = "serialization")]
+    pub(crate) fn with_entries<I>(entries: I) -> Metrics<R>
+    where
+        I: IntoIterator<Item = (MetricIdType<R>, Metric)>,
+    {
+        Metrics {
+            map: entries.into_iter().collect(),
+            changes: None,
+        }
+    }
+
+    /// Returns an iterator over all metrics.
+    #[cfg(feature = "serialization")]
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (&MetricIdType<R>, &Metric)> {
+        self.map.iter()
+    }
+
+    /// Starts recording the original value of every metric modified from now on.
+    pub(crate) fn track_changes(&mut self) {
+        self.changes = Some(HashMap::new());
+    }
+
+    /// Stops recording and returns the original value of the metrics modified
+    /// since `track_changes` was called.
+    pub(crate) fn take_changes(&mut self) -> MetricsChanges<R> {
+        self.changes.take().unwrap_or_default()
+    }
+
+    /// Restores the original value of the metrics in `changes`.
+    pub(crate) fn revert(&mut self, changes: &MetricsChanges<R>) {
+        for (id, metric) in changes {
+            match metric {
+                Some(metric) => self.map.insert(id.clone(), *metric),
+                None => self.map.remove(id),
+            };
+        }
+    }
+
+    /// Records the current value of a metric about to be modified, if tracking is enabled.
+    fn record(&mut self, id: &MetricIdType<R>) {
+        if let Some(changes) = &mut self.changes {
+            if !changes.contains_key(id) {
+                changes.insert(id.clone(), self.map.get(id).copied());
+            }
+        }
+    }
+
+    fn get_mut(&mut self, id: &MetricIdType<R>) -> Option<&mut Metric> {
+        if self.map.contains_key(id) {
+            self.record(id);
+        }
+        self.map.get_mut(id)
+    }
+
+    fn insert(&mut self, id: MetricIdType<R>, metric: Metric) {
+        self.record(&id);
+        self.map.insert(id, metric);
+    }
+
+    fn remove(&mut self, id: &MetricIdType<R>) -> Option<Metric> {
+        if self.map.contains_key(id) {
+            self.record(id);
+        }
+        self.map.remove(id)
+    }
+
+    fn clear(&mut self) {
+        if let Some(changes) = &mut self.changes {
+            for (id, metric) in self.map.drain() {
+                changes.entry(id).or_insert(Some(metric));
+            }
+        } else {
+            self.map.clear();
+        }
+    }
 }
 
 /// Alias for system metrics id.
 pub type SystemMetricId = u16;
 
 /// Alias for `MetricId` parameterized on the `BattleRules` R.
-pub type MetricIdType<R> = MetricId<<<R as BattleRules>::UR as UserRules<R>>::UserMetricId>;
+pub type MetricIdType<R> =
+    MetricId<<<R as BattleRules>::UR as UserRules<R>>::UserMetricId, TeamId<R>>;
 
 /// An id to uniquely identify metrics.
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
-pub enum MetricId<T> {
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum MetricId<T, TI> {
     /// System metric.
     System(SystemMetricId),
     /// User defined metric.
     User(T),
+    /// System metric tracked separately for each team.
+    Team(TI, SystemMetricId),
 }
 
 /// A metric is a compact measurement of some quantity.
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub enum Metric {
     /// A 64 bit unsigned counter.
     CounterU64(u64),
@@ -61,8 +160,8 @@ pub struct ReadMetrics<'a, R: BattleRules> {
 }
 
 macro_rules! get_metric {
-    ($map: expr, $id: expr, $class: ident, $field: ident) => {{
-        $map.get(&MetricIdType::<R>::$class($id))
+    ($map: expr, $($id: expr),+; $class: ident, $field: ident) => {{
+        $map.get(&MetricIdType::<R>::$class($($id),+))
             .and_then(|metric| match metric {
                 Metric::$field(v) => Some(*v),
                 _ => None,
@@ -71,9 +170,9 @@ macro_rules! get_metric {
 }
 
 macro_rules! add_metric {
-    ($map: expr, $id: expr, $value: expr, $class: ident, $field: ident) => {{
-        let full_id = MetricIdType::<R>::$class($id);
-        if let Some(metric) = $map.get_mut(&full_id) {
+    ($metrics: expr, $($id: expr),+; $value: expr, $class: ident, $field: ident) => {{
+        let full_id = MetricIdType::<R>::$class($($id),+);
+        if let Some(metric) = $metrics.get_mut(&full_id) {
             match metric {
                 Metric::$field(v) => {
                     *v += $value;
@@ -82,7 +181,7 @@ macro_rules! add_metric {
                 _ => Err(WeaselError::WrongMetricType(full_id)),
             }
         } else {
-            $map.insert(full_id, Metric::$field($value));
+            $metrics.insert(full_id, Metric::$field($value));
             Ok(())
         }
     }};
@@ -93,46 +192,175 @@ impl<'a, R: BattleRules> ReadMetrics<'a, R> {
     ///
     /// Returns `None` if there's no such system counter or if it has another type.
     pub fn system_u64(&self, id: SystemMetricId) -> Option<u64> {
-        get_metric!(self.metrics.map, id, System, CounterU64)
+        get_metric!(self.metrics.map, id; System, CounterU64)
     }
 
     /// Returns the value of a `i64` system counter.
     ///
     /// Returns `None` if there's no such system counter or if it has another type.
     pub fn system_i64(&self, id: SystemMetricId) -> Option<i64> {
-        get_metric!(self.metrics.map, id, System, CounterI64)
+        get_metric!(self.metrics.map, id; System, CounterI64)
     }
 
     /// Returns the value of a `f64` system counter.
     ///
     /// Returns `None` if there's no such system counter or if it has another type.
     pub fn system_f64(&self, id: SystemMetricId) -> Option<f64> {
-        get_metric!(self.metrics.map, id, System, CounterF64)
+        get_metric!(self.metrics.map, id; System, CounterF64)
     }
 
     /// Returns the value of a `u64` user counter.
     ///
     /// Returns `None` if there's no such user counter or if it has another type.
     pub fn user_u64(&self, id: UserMetricId<R>) -> Option<u64> {
-        get_metric!(self.metrics.map, id, User, CounterU64)
+        get_metric!(self.metrics.map, id; User, CounterU64)
+    }
+
+    /// Returns the sum of all `u64` user counters whose id satisfies `predicate`.
+    ///
+    /// User counters having another type are ignored.
+    pub fn user_u64_where<F>(&self, predicate: F) -> u64
+    where
+        F: Fn(&UserMetricId<R>) -> bool,
+    {
+        self.metrics
+            .map
+            .iter()
+            .filter_map(|(id, metric)| match (id, metric) {
+                (MetricId::User(id), Metric::CounterU64(v)) if predicate(id) => Some(*v),
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// Returns an iterator over all `u64` system counters, in no particular order.
+    ///
+    /// Each item contains the id of the counter, one of those defined in
+    /// [system](system/index.html), and its value.\
+    /// Counters having another type and counters tracked separately for each team
+    /// are not included.
+    pub fn system_metrics(&self) -> impl Iterator<Item = (SystemMetricId, u64)> + 'a {
+        self.metrics
+            .map
+            .iter()
+            .filter_map(|(id, metric)| match (id, metric) {
+                (MetricId::System(id), Metric::CounterU64(v)) => Some((*id, *v)),
+                _ => None,
+            })
+    }
+
+    /// Returns an iterator over all `u64` user counters, in no particular order.
+    ///
+    /// User counters having another type are not included.
+    pub fn user_metrics(&self) -> impl Iterator<Item = (&'a UserMetricId<R>, u64)> + 'a {
+        self.metrics
+            .map
+            .iter()
+            .filter_map(|(id, metric)| match (id, metric) {
+                (MetricId::User(id), Metric::CounterU64(v)) => Some((id, *v)),
+                _ => None,
+            })
     }
 
     /// Returns the value of a `i64` user counter.
     ///
     /// Returns `None` if there's no such user counter or if it has another type.
     pub fn user_i64(&self, id: UserMetricId<R>) -> Option<i64> {
-        get_metric!(self.metrics.map, id, User, CounterI64)
+        get_metric!(self.metrics.map, id; User, CounterI64)
     }
 
     /// Returns the value of a `f64` user counter.
     ///
     /// Returns `None` if there's no such user counter or if it has another type.
     pub fn user_f64(&self, id: UserMetricId<R>) -> Option<f64> {
-        get_metric!(self.metrics.map, id, User, CounterF64)
+        get_metric!(self.metrics.map, id; User, CounterF64)
+    }
+
+    /// Returns the value of a `u64` system counter tracked for the team `team`.
+    ///
+    /// Returns `None` if there's no such counter or if it has another type.
+    pub fn team_u64(&self, team: &TeamId<R>, id: SystemMetricId) -> Option<u64> {
+        get_metric!(self.metrics.map, team.clone(), id; Team, CounterU64)
+    }
+}
+
+/// Handle to read how metrics changed while applying an event.
+///
+/// A metric missing either before or after the event is considered to be zero.
+/// All methods return `None` if the metric didn't exist at all, or if it has another type.
+pub struct MetricsDelta<'a, R: BattleRules> {
+    changes: &'a MetricsChanges<R>,
+    after: &'a Metrics<R>,
+}
+
+macro_rules! delta_metric {
+    ($delta: expr, $($id: expr),+; $class: ident, $field: ident) => {{
+        let after = get_metric!($delta.after.map, $($id),+; $class, $field);
+        let before = match $delta.changes.get(&MetricIdType::<R>::$class($($id),+)) {
+            Some(Some(Metric::$field(v))) => Some(*v),
+            Some(_) => None,
+            None => after,
+        };
+        match (before, after) {
+            (None, None) => None,
+            (before, after) => Some((before.unwrap_or_default(), after.unwrap_or_default())),
+        }
+    }};
+}
+
+impl<'a, R: BattleRules> MetricsDelta<'a, R> {
+    /// Creates a new delta from the original value of the changed metrics
+    /// and the current metrics.
+    pub(crate) fn new(
+        changes: &'a MetricsChanges<R>,
+        after: &'a Metrics<R>,
+    ) -> MetricsDelta<'a, R> {
+        MetricsDelta { changes, after }
+    }
+
+    /// Returns the change of a `u64` system counter.
+    pub fn system_u64(&self, id: SystemMetricId) -> Option<i64> {
+        delta_metric!(self, id; System, CounterU64).map(|(b, a)| a.wrapping_sub(b) as i64)
+    }
+
+    /// Returns the change of a `i64` system counter.
+    pub fn system_i64(&self, id: SystemMetricId) -> Option<i64> {
+        delta_metric!(self, id; System, CounterI64).map(|(b, a)| a.wrapping_sub(b))
+    }
+
+    /// Returns the change of a `f64` system counter.
+    pub fn system_f64(&self, id: SystemMetricId) -> Option<f64> {
+        delta_metric!(self, id; System, CounterF64).map(|(b, a)| a - b)
+    }
+
+    /// Returns the change of a `u64` user counter.
+    pub fn user_u64(&self, id: UserMetricId<R>) -> Option<i64> {
+        delta_metric!(self, id.clone(); User, CounterU64).map(|(b, a)| a.wrapping_sub(b) as i64)
+    }
+
+    /// Returns the change of a `i64` user counter.
+    pub fn user_i64(&self, id: UserMetricId<R>) -> Option<i64> {
+        delta_metric!(self, id.clone(); User, CounterI64).map(|(b, a)| a.wrapping_sub(b))
+    }
+
+    /// Returns the change of a `f64` user counter.
+    pub fn user_f64(&self, id: UserMetricId<R>) -> Option<f64> {
+        delta_metric!(self, id.clone(); User, CounterF64).map(|(b, a)| a - b)
+    }
+
+    /// Returns the change of a `u64` system counter tracked for the team `team`.
+    pub fn team_u64(&self, team: &TeamId<R>, id: SystemMetricId) -> Option<i64> {
+        delta_metric!(self, team.clone(), id; Team, CounterU64)
+            .map(|(b, a)| a.wrapping_sub(b) as i64)
     }
 }
 
 /// Handle to write metrics.
+///
+/// All metrics of the same kind share a single namespace, regardless of their type.
+/// For instance, an `UserMetricId` used for a `u64` counter can't also identify a `f64`
+/// counter: writing a value of a different type returns a `WrongMetricType` error.
+/// Remove the metric first if you want to change its type.
 pub struct WriteMetrics<'a, R: BattleRules> {
     metrics: &'a mut Metrics<R>,
 }
@@ -141,12 +369,25 @@ impl<'a, R: BattleRules> WriteMetrics<'a, R> {
     /// Removes a system metric.
     #[allow(dead_code)]
     pub(crate) fn remove_system(&mut self, id: SystemMetricId) {
-        self.metrics.map.remove(&MetricIdType::<R>::System(id));
+        self.metrics.remove(&MetricIdType::<R>::System(id));
     }
 
     /// Removes an user metric.
     pub fn remove_user(&mut self, id: UserMetricId<R>) {
-        self.metrics.map.remove(&MetricIdType::<R>::User(id));
+        self.metrics.remove(&MetricIdType::<R>::User(id));
+    }
+
+    /// Removes all metrics: system, user and team ones.
+    ///
+    /// Metrics are meant for analytics and they are not part of the battle's authoritative
+    /// state, which is the one defined by its history. After clearing, counters restart
+    /// from zero and they no longer describe the current state of the battle. For instance,
+    /// `TEAMS_CREATED` and `TEAM_CREATURES` won't count teams and creatures created
+    /// before this call. `Server::reset_metrics` instead rebuilds `TEAM_CREATURES`.\
+    /// Use this method to discard the measurements of previous runs, for instance when
+    /// benchmarking the same rules many times.
+    pub fn clear_all(&mut self) {
+        self.metrics.clear();
     }
 
     /// Adds `value` to the system metric with the given `id`.\
@@ -155,7 +396,7 @@ impl<'a, R: BattleRules> WriteMetrics<'a, R> {
     /// Returns an error if the metric exists, but its type is different.
     #[allow(dead_code)]
     pub(crate) fn add_system_u64(&mut self, id: SystemMetricId, value: u64) -> WeaselResult<(), R> {
-        add_metric!(self.metrics.map, id, value, System, CounterU64)
+        add_metric!(self.metrics, id; value, System, CounterU64)
     }
 
     /// Adds `value` to the system metric with the given `id`.\
@@ -164,7 +405,7 @@ impl<'a, R: BattleRules> WriteMetrics<'a, R> {
     /// Returns an error if the metric exists, but its type is different.
     #[allow(dead_code)]
     pub(crate) fn add_system_i64(&mut self, id: SystemMetricId, value: i64) -> WeaselResult<(), R> {
-        add_metric!(self.metrics.map, id, value, System, CounterI64)
+        add_metric!(self.metrics, id; value, System, CounterI64)
     }
 
     /// Adds `value` to the system metric with the given `id`.\
@@ -173,7 +414,7 @@ impl<'a, R: BattleRules> WriteMetrics<'a, R> {
     /// Returns an error if the metric exists, but its type is different.
     #[allow(dead_code)]
     pub(crate) fn add_system_f64(&mut self, id: SystemMetricId, value: f64) -> WeaselResult<(), R> {
-        add_metric!(self.metrics.map, id, value, System, CounterF64)
+        add_metric!(self.metrics, id; value, System, CounterF64)
     }
 
     /// Adds `value` to the user metric with the given `id`.\
@@ -181,7 +422,7 @@ impl<'a, R: BattleRules> WriteMetrics<'a, R> {
     /// Creates the metric (initialized with `value`) if it doesn't exist.
     /// Returns an error if the metric exists, but its type is different.
     pub fn add_user_u64(&mut self, id: UserMetricId<R>, value: u64) -> WeaselResult<(), R> {
-        add_metric!(self.metrics.map, id, value, User, CounterU64)
+        add_metric!(self.metrics, id; value, User, CounterU64)
     }
 
     /// Adds `value` to the user metric with the given `id`.\
@@ -189,7 +430,7 @@ impl<'a, R: BattleRules> WriteMetrics<'a, R> {
     /// Creates the metric (initialized with `value`) if it doesn't exist.
     /// Returns an error if the metric exists, but its type is different.
     pub fn add_user_i64(&mut self, id: UserMetricId<R>, value: i64) -> WeaselResult<(), R> {
-        add_metric!(self.metrics.map, id, value, User, CounterI64)
+        add_metric!(self.metrics, id; value, User, CounterI64)
     }
 
     /// Adds `value` to the user metric with the given `id`.\
@@ -197,7 +438,129 @@ impl<'a, R: BattleRules> WriteMetrics<'a, R> {
     /// Creates the metric (initialized with `value`) if it doesn't exist.
     /// Returns an error if the metric exists, but its type is different.
     pub fn add_user_f64(&mut self, id: UserMetricId<R>, value: f64) -> WeaselResult<(), R> {
-        add_metric!(self.metrics.map, id, value, User, CounterF64)
+        add_metric!(self.metrics, id; value, User, CounterF64)
+    }
+
+    /// Sets the user metric with the given `id` to `value`.\
+    ///
+    /// Creates the metric if it doesn't exist.
+    /// Returns an error if the metric exists, but its type is different.
+    pub fn set_user_u64(&mut self, id: UserMetricId<R>, value: u64) -> WeaselResult<(), R> {
+        let full_id = MetricIdType::<R>::User(id);
+        match self.metrics.get_mut(&full_id) {
+            Some(Metric::CounterU64(v)) => {
+                *v = value;
+                Ok(())
+            }
+            Some(_) => Err(WeaselError::WrongMetricType(full_id)),
+            None => {
+                self.metrics.insert(full_id, Metric::CounterU64(value));
+                Ok(())
+            }
+        }
+    }
+
+    /// Subtracts `value` from the user metric with the given `id`.
+    /// The counter never goes below zero.
+    ///
+    /// Creates the metric (initialized with zero) if it doesn't exist.
+    /// Returns an error if the metric exists, but its type is different.
+    pub fn sub_user_u64(&mut self, id: UserMetricId<R>, value: u64) -> WeaselResult<(), R> {
+        let full_id = MetricIdType::<R>::User(id);
+        match self.metrics.get_mut(&full_id) {
+            Some(Metric::CounterU64(v)) => {
+                *v = v.saturating_sub(value);
+                Ok(())
+            }
+            Some(_) => Err(WeaselError::WrongMetricType(full_id)),
+            None => {
+                self.metrics.insert(full_id, Metric::CounterU64(0));
+                Ok(())
+            }
+        }
+    }
+
+    /// Resets the user metric with the given `id` to zero, preserving its type.
+    ///
+    /// Does nothing if the metric doesn't exist.
+    pub fn reset_user(&mut self, id: UserMetricId<R>) {
+        if let Some(metric) = self.metrics.get_mut(&MetricIdType::<R>::User(id)) {
+            *metric = match metric {
+                Metric::CounterU64(_) => Metric::CounterU64(0),
+                Metric::CounterI64(_) => Metric::CounterI64(0),
+                Metric::CounterF64(_) => Metric::CounterF64(0.0),
+            };
+        }
+    }
+
+    /// Adds `value` to the system metric with the given `id` tracked for the team `team`.\
+    ///
+    /// Creates the metric (initialized with `value`) if it doesn't exist.
+    /// Returns an error if the metric exists, but its type is different.
+    pub(crate) fn add_team_u64(
+        &mut self,
+        team: &TeamId<R>,
+        id: SystemMetricId,
+        value: u64,
+    ) -> WeaselResult<(), R> {
+        add_metric!(self.metrics, team.clone(), id; value, Team, CounterU64)
+    }
+
+    /// Subtracts `value` from the system metric with the given `id` tracked for the team `team`.
+    /// The counter never goes below zero.
+    ///
+    /// Returns an error if the metric exists, but its type is different.
+    pub(crate) fn sub_team_u64(
+        &mut self,
+        team: &TeamId<R>,
+        id: SystemMetricId,
+        value: u64,
+    ) -> WeaselResult<(), R> {
+        let full_id = MetricIdType::<R>::Team(team.clone(), id);
+        match self.metrics.get_mut(&full_id) {
+            Some(Metric::CounterU64(v)) => {
+                *v = v.saturating_sub(value);
+                Ok(())
+            }
+            Some(_) => Err(WeaselError::WrongMetricType(full_id)),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes all metrics tracked for the team `team`.
+    pub(crate) fn remove_team(&mut self, team: &TeamId<R>) {
+        let ids: Vec<_> = self
+            .metrics
+            .map
+            .keys()
+            .filter(|id| matches!(id, MetricId::Team(team_id, _) if team_id == team))
+            .cloned()
+            .collect();
+        for id in ids {
+            self.metrics.remove(&id);
+        }
+    }
+
+    /// Moves all metrics tracked for the team `team` to the team `new_team`.
+    pub(crate) fn rename_team(&mut self, team: &TeamId<R>, new_team: &TeamId<R>) {
+        let ids: Vec<_> = self
+            .metrics
+            .map
+            .keys()
+            .filter_map(|id| match id {
+                MetricId::Team(team_id, id) if team_id == team => Some(*id),
+                _ => None,
+            })
+            .collect();
+        for id in ids {
+            if let Some(metric) = self
+                .metrics
+                .remove(&MetricIdType::<R>::Team(team.clone(), id))
+            {
+                self.metrics
+                    .insert(MetricIdType::<R>::Team(new_team.clone(), id), metric);
+            }
+        }
     }
 }
 
@@ -211,10 +574,19 @@ pub mod system {
     pub static CREATURES_CREATED: SystemMetricId = 1;
     /// Number of teams created.
     pub static TEAMS_CREATED: SystemMetricId = 2;
+    /// Number of creatures currently part of a team. There's one counter for each team.
+    pub static TEAM_CREATURES: SystemMetricId = 3;
+    /// Number of times an entity changed position.
+    pub static ENTITIES_MOVED: SystemMetricId = 4;
+    /// Number of abilities removed from actors by `ClearAbilities`.
+    pub static ABILITIES_CLEARED: SystemMetricId = 5;
+    /// Number of objects created.
+    pub static OBJECTS_CREATED: SystemMetricId = 6;
 }
 
 #[cfg(test)]
 mod tests {
+    use super::system::*;
     use super::*;
     use crate::util::tests::server;
     use crate::{battle_rules, rules::empty::*};
@@ -250,6 +622,158 @@ mod tests {
         assert_eq!(reader.system_f64(2), None);
     }
 
+    #[test]
+    fn team_metrics() {
+        let mut server = server(CustomRules::new());
+        let mut writer = server.battle.metrics.write_handle();
+        assert_eq!(writer.add_team_u64(&1, 0, 2).err(), None);
+        assert_eq!(writer.sub_team_u64(&1, 0, 1).err(), None);
+        assert_eq!(writer.add_team_u64(&2, 0, 5).err(), None);
+        // Counters can't go below zero.
+        assert_eq!(writer.sub_team_u64(&2, 0, 10).err(), None);
+        let reader = server.battle.metrics.read_handle();
+        assert_eq!(reader.team_u64(&1, 0), Some(1));
+        assert_eq!(reader.team_u64(&2, 0), Some(0));
+        assert_eq!(reader.system_u64(0), None);
+        // Try rename and remove.
+        let mut writer = server.battle.metrics.write_handle();
+        writer.rename_team(&1, &3);
+        writer.remove_team(&2);
+        let reader = server.battle.metrics.read_handle();
+        assert_eq!(reader.team_u64(&1, 0), None);
+        assert_eq!(reader.team_u64(&2, 0), None);
+        assert_eq!(reader.team_u64(&3, 0), Some(1));
+    }
+
+    #[test]
+    fn user_gauges() {
+        let mut server = server(CustomRules::new());
+        let mut writer = server.battle.metrics.write_handle();
+        assert_eq!(writer.add_user_u64(0, 4).err(), None);
+        assert_eq!(writer.sub_user_u64(0, 1).err(), None);
+        assert_eq!(writer.sub_user_u64(1, 1).err(), None);
+        assert_eq!(writer.set_user_u64(2, 8).err(), None);
+        assert_eq!(writer.add_user_i64(3, -3).err(), None);
+        assert_eq!(writer.add_user_f64(4, 1.5).err(), None);
+        let reader = server.battle.metrics.read_handle();
+        assert_eq!(reader.user_u64(0), Some(3));
+        assert_eq!(reader.user_u64(1), Some(0));
+        assert_eq!(reader.user_u64(2), Some(8));
+        // Counters can't go below zero.
+        let mut writer = server.battle.metrics.write_handle();
+        assert_eq!(writer.sub_user_u64(0, 10).err(), None);
+        assert_eq!(writer.set_user_u64(2, 1).err(), None);
+        let reader = server.battle.metrics.read_handle();
+        assert_eq!(reader.user_u64(0), Some(0));
+        assert_eq!(reader.user_u64(2), Some(1));
+        // Reset counters of all types.
+        let mut writer = server.battle.metrics.write_handle();
+        writer.reset_user(2);
+        writer.reset_user(3);
+        writer.reset_user(4);
+        writer.reset_user(5);
+        let reader = server.battle.metrics.read_handle();
+        assert_eq!(reader.user_u64(2), Some(0));
+        assert_eq!(reader.user_i64(3), Some(0));
+        assert_eq!(reader.user_f64(4), Some(0.0));
+        assert_eq!(reader.user_u64(5), None);
+        // Check for wrong metric type.
+        let mut writer = server.battle.metrics.write_handle();
+        assert_eq!(
+            writer.set_user_u64(3, 1).err(),
+            Some(WeaselError::WrongMetricType(MetricId::User(3)))
+        );
+        assert_eq!(
+            writer.sub_user_u64(4, 1).err(),
+            Some(WeaselError::WrongMetricType(MetricId::User(4)))
+        );
+    }
+
+    #[test]
+    fn user_aggregation() {
+        let mut server = server(CustomRules::new());
+        let mut writer = server.battle.metrics.write_handle();
+        assert_eq!(writer.add_user_u64(10, 4).err(), None);
+        assert_eq!(writer.add_user_u64(11, 3).err(), None);
+        assert_eq!(writer.add_user_u64(20, 5).err(), None);
+        assert_eq!(writer.add_user_f64(12, 2.0).err(), None);
+        assert_eq!(writer.add_system_u64(13, 1).err(), None);
+        let reader = server.battle.metrics.read_handle();
+        assert_eq!(reader.user_u64_where(|id| *id / 10 == 1), 7);
+        assert_eq!(reader.user_u64_where(|_| true), 12);
+        assert_eq!(reader.user_u64_where(|_| false), 0);
+    }
+
+    #[test]
+    fn iterate_metrics() {
+        let mut server = server(CustomRules::new());
+        let mut writer = server.battle.metrics.write_handle();
+        assert_eq!(writer.add_user_u64(0, 4).err(), None);
+        assert_eq!(writer.add_user_u64(1, 2).err(), None);
+        assert_eq!(writer.add_user_f64(2, 1.5).err(), None);
+        assert_eq!(writer.add_system_u64(CREATURES_CREATED, 3).err(), None);
+        assert_eq!(writer.add_system_i64(ROUNDS_STARTED, 1).err(), None);
+        assert_eq!(writer.add_team_u64(&1, TEAM_CREATURES, 5).err(), None);
+        let reader = server.battle.metrics.read_handle();
+        let mut system: Vec<_> = reader.system_metrics().collect();
+        system.sort_unstable();
+        assert_eq!(system, vec![(CREATURES_CREATED, 3)]);
+        let mut user: Vec<_> = reader.user_metrics().collect();
+        user.sort_unstable();
+        assert_eq!(user, vec![(&0, 4), (&1, 2)]);
+    }
+
+    #[test]
+    fn delta() {
+        let mut server = server(CustomRules::new());
+        let metrics = &mut server.battle.metrics;
+        let mut writer = metrics.write_handle();
+        assert_eq!(writer.add_user_u64(0, 4).err(), None);
+        assert_eq!(writer.add_user_u64(1, 2).err(), None);
+        assert_eq!(writer.add_user_i64(2, 3).err(), None);
+        // Only the metrics modified while tracking are recorded.
+        metrics.track_changes();
+        let mut writer = metrics.write_handle();
+        assert_eq!(writer.add_user_u64(0, 3).err(), None);
+        assert_eq!(writer.add_user_u64(0, 1).err(), None);
+        assert_eq!(writer.add_user_f64(3, 1.5).err(), None);
+        writer.remove_user(2);
+        let changes = metrics.take_changes();
+        assert_eq!(changes.len(), 3);
+        let delta = MetricsDelta::new(&changes, metrics);
+        assert_eq!(delta.user_u64(0), Some(4));
+        assert_eq!(delta.user_u64(1), Some(0));
+        assert_eq!(delta.user_i64(2), Some(-3));
+        assert_eq!(delta.user_f64(3), Some(1.5));
+        assert_eq!(delta.user_u64(4), None);
+        // Clearing while tracking records all metrics.
+        metrics.track_changes();
+        metrics.write_handle().clear_all();
+        let changes = metrics.take_changes();
+        let delta = MetricsDelta::new(&changes, metrics);
+        assert_eq!(delta.user_u64(0), Some(-8));
+        assert_eq!(delta.user_u64(1), Some(-2));
+    }
+
+    #[test]
+    fn clear_all() {
+        let mut server = server(CustomRules::new());
+        let mut writer = server.battle.metrics.write_handle();
+        assert_eq!(writer.add_user_u64(0, 4).err(), None);
+        assert_eq!(writer.add_system_u64(CREATURES_CREATED, 3).err(), None);
+        assert_eq!(writer.add_team_u64(&1, TEAM_CREATURES, 5).err(), None);
+        writer.clear_all();
+        let reader = server.battle.metrics.read_handle();
+        assert_eq!(reader.user_u64(0), None);
+        assert_eq!(reader.system_u64(CREATURES_CREATED), None);
+        assert_eq!(reader.team_u64(&1, TEAM_CREATURES), None);
+        // Metrics can be written again after clearing.
+        let mut writer = server.battle.metrics.write_handle();
+        assert_eq!(writer.add_user_f64(0, 1.5).err(), None);
+        let reader = server.battle.metrics.read_handle();
+        assert_eq!(reader.user_f64(0), Some(1.5));
+    }
+
     #[test]
     fn error_conditions() {
         let mut server = server(CustomRules::new());
@@ -267,5 +791,11 @@ mod tests {
             writer.add_user_f64(0, 4.4).err(),
             Some(WeaselError::WrongMetricType(MetricId::User(0)))
         );
+        // The type of a metric can be changed only after removing it.
+        writer.remove_user(0);
+        assert_eq!(writer.add_user_f64(0, 4.4).err(), None);
+        let reader = server.battle.metrics.read_handle();
+        assert_eq!(reader.user_u64(0), None);
+        assert_eq!(reader.user_f64(0), Some(4.4));
     }
 }
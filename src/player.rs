@@ -1,8 +1,13 @@
 //! Player-oriented features.
 
-use crate::battle::BattleRules;
+use crate::battle::{Battle, BattleRules};
 use crate::error::{WeaselError, WeaselResult};
+use crate::event::{Event, EventKind, EventProcessor, EventQueue, EventTrigger};
 use crate::team::TeamId;
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::fmt::{Debug, Formatter, Result};
 
 /// Type to uniquely identify players.
 ///
@@ -16,11 +21,53 @@ pub type PlayerId = u64;
 /// Manages players' rights to initiate events on behalf of a given team.
 pub(crate) struct Rights<R: BattleRules> {
     data: Vec<(PlayerId, Vec<TeamId<R>>)>,
+    tracking: bool,
+    /// Rights as they were before the first change done while tracking was enabled.
+    original: Option<Vec<(PlayerId, Vec<TeamId<R>>)>>,
 }
 
 impl<R: BattleRules> Rights<R> {
     pub(crate) fn new() -> Rights<R> {
-        Rights { data: Vec::new() }
+        Rights::with_data(Vec::new())
+    }
+
+    fn with_data(data: Vec<(PlayerId, Vec<TeamId<R>>)>) -> Rights<R> {
+        Rights {
+            data,
+            tracking: false,
+            original: None,
+        }
+    }
+
+    /// Returns a copy of all players' rights.
+    pub(crate) fn snapshot(&self) -> Rights<R> {
+        Rights::with_data(self.data.clone())
+    }
+
+    /// Starts recording the rights as they are before the next change.
+    pub(crate) fn track_changes(&mut self) {
+        self.tracking = true;
+        self.original = None;
+    }
+
+    /// Stops recording and returns the rights as they were before changing,
+    /// or `None` if they didn't change since `track_changes` was called.
+    pub(crate) fn take_changes(&mut self) -> Option<Rights<R>> {
+        self.tracking = false;
+        self.original.take().map(Rights::with_data)
+    }
+
+    /// Saves the current rights before they are modified, if tracking is enabled.
+    /// Must be called only right before an actual change.
+    fn record(&mut self) {
+        if self.tracking && self.original.is_none() {
+            self.original = Some(self.data.clone());
+        }
+    }
+
+    /// Returns `true` if any player has rights for `team`.
+    fn has_team(&self, team: &TeamId<R>) -> bool {
+        self.data.iter().any(|(_, rights)| rights.contains(team))
     }
 
     /// Removes all players without any rights.
@@ -29,11 +76,13 @@ impl<R: BattleRules> Rights<R> {
     }
 
     /// Add rights for `team` to `player`.
-    fn add(&mut self, player: PlayerId, team: &TeamId<R>) {
+    pub(crate) fn add(&mut self, player: PlayerId, team: &TeamId<R>) {
+        if self.check(player, team) {
+            return;
+        }
+        self.record();
         if let Some((_, rights)) = self.data.iter_mut().find(|(e, _)| *e == player) {
-            if rights.iter_mut().find(|e| *e == team).is_none() {
-                rights.push(team.clone());
-            }
+            rights.push(team.clone());
         } else {
             self.data.push((player, vec![team.clone()]));
         }
@@ -41,6 +90,10 @@ impl<R: BattleRules> Rights<R> {
 
     /// Remove rights for `team` to `player`.
     fn remove(&mut self, player: PlayerId, team: &TeamId<R>) {
+        if !self.check(player, team) {
+            return;
+        }
+        self.record();
         if let Some((_, rights)) = self.data.iter_mut().find(|(e, _)| *e == player) {
             let index = rights.iter().position(|e| e == team);
             if let Some(index) = index {
@@ -52,6 +105,10 @@ impl<R: BattleRules> Rights<R> {
 
     /// Removes all stored rights.
     fn clear(&mut self) {
+        if self.data.is_empty() {
+            return;
+        }
+        self.record();
         self.data.clear();
     }
 
@@ -68,8 +125,28 @@ impl<R: BattleRules> Rights<R> {
         false
     }
 
+    /// Returns an iterator over the teams that `player` can control.
+    fn teams_of(&self, player: PlayerId) -> impl Iterator<Item = &TeamId<R>> {
+        self.data
+            .iter()
+            .filter(move |(e, _)| *e == player)
+            .flat_map(|(_, rights)| rights.iter())
+    }
+
+    /// Returns an iterator over the players that can control `team`.
+    fn players_of<'a>(&'a self, team: &'a TeamId<R>) -> impl Iterator<Item = PlayerId> + 'a {
+        self.data
+            .iter()
+            .filter(move |(_, rights)| rights.contains(team))
+            .map(|(player, _)| *player)
+    }
+
     /// Remove all occurrences of a team from all players' rights.
     fn remove_team(&mut self, team: &TeamId<R>) {
+        if !self.has_team(team) {
+            return;
+        }
+        self.record();
         for (_, rights) in &mut self.data {
             let index = rights.iter().position(|e| e == team);
             if let Some(index) = index {
@@ -79,13 +156,42 @@ impl<R: BattleRules> Rights<R> {
         self.cleanup_players();
     }
 
+    /// Replace all occurrences of a team in all players' rights with `new_team`.
+    fn rename_team(&mut self, team: &TeamId<R>, new_team: &TeamId<R>) {
+        if team == new_team || !self.has_team(team) {
+            return;
+        }
+        self.record();
+        for (_, rights) in &mut self.data {
+            for e in rights.iter_mut().filter(|e| *e == team) {
+                *e = new_team.clone();
+            }
+        }
+    }
+
     /// Remove all rights of a player.
     fn remove_player(&mut self, player: PlayerId) {
         let index = self.data.iter().position(|(e, _)| *e == player);
         if let Some(index) = index {
+            self.record();
             self.data.remove(index);
         }
     }
+
+    /// Move all rights of `from` to `to`.
+    fn transfer(&mut self, from: PlayerId, to: PlayerId) {
+        if from == to {
+            return;
+        }
+        let index = self.data.iter().position(|(e, _)| *e == from);
+        if let Some(index) = index {
+            self.record();
+            let (_, teams) = self.data.remove(index);
+            for team in &teams {
+                self.add(to, team);
+            }
+        }
+    }
 }
 
 /// A structure to access player's rights.
@@ -114,6 +220,16 @@ where
     pub fn check(&self, player: PlayerId, team: &TeamId<R>) -> bool {
         self.rights.check(player, team)
     }
+
+    /// Returns an iterator over the teams that `player` can control.
+    pub fn teams_of(&self, player: PlayerId) -> impl Iterator<Item = &TeamId<R>> {
+        self.rights.teams_of(player)
+    }
+
+    /// Returns an iterator over the players that can control `team`.
+    pub fn players_of<'b>(&'b self, team: &'b TeamId<R>) -> impl Iterator<Item = PlayerId> + 'b {
+        self.rights.players_of(team)
+    }
 }
 
 /// A structure to access and manipulate player's rights.
@@ -160,11 +276,22 @@ where
         self.rights.remove_team(team);
     }
 
+    /// Transfer all rights on `team` to `new_team`.
+    pub(crate) fn rename_team(&mut self, team: &TeamId<R>, new_team: &TeamId<R>) {
+        self.rights.rename_team(team, new_team);
+    }
+
     /// Remove all rights of a player.
     pub fn remove_player(&mut self, player: PlayerId) {
         self.rights.remove_player(player);
     }
 
+    /// Transfer all rights of the player `from` to the player `to`.
+    /// Rights already owned by `to` are kept.
+    pub fn transfer(&mut self, from: PlayerId, to: PlayerId) {
+        self.rights.transfer(from, to);
+    }
+
     /// Returns an iterator over all players' rights.
     pub fn get(&self) -> impl Iterator<Item = (PlayerId, &[TeamId<R>])> {
         self.rights.get()
@@ -174,6 +301,247 @@ where
     pub fn check(&self, player: PlayerId, team: &TeamId<R>) -> bool {
         self.rights.check(player, team)
     }
+
+    /// Returns an iterator over the teams that `player` can control.
+    pub fn teams_of(&self, player: PlayerId) -> impl Iterator<Item = &TeamId<R>> {
+        self.rights.teams_of(player)
+    }
+
+    /// Returns an iterator over the players that can control `team`.
+    pub fn players_of<'b>(&'b self, team: &'b TeamId<R>) -> impl Iterator<Item = PlayerId> + 'b {
+        self.rights.players_of(team)
+    }
+}
+
+/// Event to give a player the rights to control a team.
+///
+/// Unlike `RightsHandleMut::add`, this change is recorded in the battle's history.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct GrantRights<R: BattleRules> {
+    player: PlayerId,
+
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "TeamId<R>: Serialize",
+            deserialize = "TeamId<R>: Deserialize<'de>"
+        ))
+    )]
+    team_id: TeamId<R>,
+}
+
+impl<R: BattleRules> GrantRights<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &mut P,
+        player: PlayerId,
+        team_id: TeamId<R>,
+    ) -> GrantRightsTrigger<'_, R, P> {
+        GrantRightsTrigger {
+            processor,
+            player,
+            team_id,
+        }
+    }
+
+    /// Returns the id of the player receiving the rights.
+    pub fn player(&self) -> PlayerId {
+        self.player
+    }
+
+    /// Returns the id of the team to be controlled.
+    pub fn team_id(&self) -> &TeamId<R> {
+        &self.team_id
+    }
+}
+
+impl<R: BattleRules> Debug for GrantRights<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "GrantRights {{ player: {:?}, team_id: {:?} }}",
+            self.player, self.team_id
+        )
+    }
+}
+
+impl<R: BattleRules> Clone for GrantRights<R> {
+    fn clone(&self) -> Self {
+        GrantRights {
+            player: self.player,
+            team_id: self.team_id.clone(),
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for GrantRights<R> {
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        // Team must exist.
+        if battle.entities().team(&self.team_id).is_none() {
+            return Err(WeaselError::TeamNotFound(self.team_id.clone()));
+        }
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
+        battle
+            .rights_mut()
+            .add(self.player, &self.team_id)
+            .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::GrantRights
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire a `GrantRights` event.
+pub struct GrantRightsTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    player: PlayerId,
+    team_id: TeamId<R>,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for GrantRightsTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns a `GrantRights` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(GrantRights {
+            player: self.player,
+            team_id: self.team_id.clone(),
+        })
+    }
+}
+
+/// Event to take away from a player the rights to control a team.
+///
+/// Revoking rights that the player doesn't have is not an error.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct RevokeRights<R: BattleRules> {
+    player: PlayerId,
+
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "TeamId<R>: Serialize",
+            deserialize = "TeamId<R>: Deserialize<'de>"
+        ))
+    )]
+    team_id: TeamId<R>,
+}
+
+impl<R: BattleRules> RevokeRights<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &mut P,
+        player: PlayerId,
+        team_id: TeamId<R>,
+    ) -> RevokeRightsTrigger<'_, R, P> {
+        RevokeRightsTrigger {
+            processor,
+            player,
+            team_id,
+        }
+    }
+
+    /// Returns the id of the player losing the rights.
+    pub fn player(&self) -> PlayerId {
+        self.player
+    }
+
+    /// Returns the id of the team no longer controlled by the player.
+    pub fn team_id(&self) -> &TeamId<R> {
+        &self.team_id
+    }
+}
+
+impl<R: BattleRules> Debug for RevokeRights<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "RevokeRights {{ player: {:?}, team_id: {:?} }}",
+            self.player, self.team_id
+        )
+    }
+}
+
+impl<R: BattleRules> Clone for RevokeRights<R> {
+    fn clone(&self) -> Self {
+        RevokeRights {
+            player: self.player,
+            team_id: self.team_id.clone(),
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for RevokeRights<R> {
+    fn verify(&self, _: &Battle<R>) -> WeaselResult<(), R> {
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
+        battle.rights_mut().remove(self.player, &self.team_id);
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::RevokeRights
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire a `RevokeRights` event.
+pub struct RevokeRightsTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    player: PlayerId,
+    team_id: TeamId<R>,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for RevokeRightsTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns a `RevokeRights` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(RevokeRights {
+            player: self.player,
+            team_id: self.team_id.clone(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -201,22 +569,22 @@ mod tests {
         // Add rights for team 1 to player 2.
         rights.add(PLAYER_2_ID, &TEAM_1_ID);
         assert_eq!(rights.data.len(), 2);
-        assert_eq!(rights.check(PLAYER_1_ID, &TEAM_1_ID), true);
-        assert_eq!(rights.check(PLAYER_1_ID, &TEAM_2_ID), true);
-        assert_eq!(rights.check(PLAYER_2_ID, &TEAM_1_ID), true);
-        assert_eq!(rights.check(PLAYER_2_ID, &TEAM_2_ID), false);
+        assert!(rights.check(PLAYER_1_ID, &TEAM_1_ID));
+        assert!(rights.check(PLAYER_1_ID, &TEAM_2_ID));
+        assert!(rights.check(PLAYER_2_ID, &TEAM_1_ID));
+        assert!(!rights.check(PLAYER_2_ID, &TEAM_2_ID));
         // Remove rights for team 2 to player 1.
         rights.remove(PLAYER_1_ID, &TEAM_2_ID);
-        assert_eq!(rights.check(PLAYER_1_ID, &TEAM_2_ID), false);
+        assert!(!rights.check(PLAYER_1_ID, &TEAM_2_ID));
         // Remove rights for team 1 to player 2.
         rights.remove(PLAYER_2_ID, &TEAM_1_ID);
-        assert_eq!(rights.check(PLAYER_2_ID, &TEAM_1_ID), false);
+        assert!(!rights.check(PLAYER_2_ID, &TEAM_1_ID));
         assert_eq!(rights.data.len(), 1);
         // Clear.
-        assert_eq!(rights.check(PLAYER_1_ID, &TEAM_1_ID), true);
+        assert!(rights.check(PLAYER_1_ID, &TEAM_1_ID));
         rights.clear();
         assert_eq!(rights.data.len(), 0);
-        assert_eq!(rights.check(PLAYER_1_ID, &TEAM_1_ID), false);
+        assert!(!rights.check(PLAYER_1_ID, &TEAM_1_ID));
     }
 
     #[test]
@@ -225,34 +593,85 @@ mod tests {
         // Add rights for team 1 to player 1 and player 2.
         rights.add(PLAYER_1_ID, &TEAM_1_ID);
         rights.add(PLAYER_2_ID, &TEAM_1_ID);
-        assert_eq!(rights.check(PLAYER_1_ID, &TEAM_1_ID), true);
-        assert_eq!(rights.check(PLAYER_2_ID, &TEAM_1_ID), true);
+        assert!(rights.check(PLAYER_1_ID, &TEAM_1_ID));
+        assert!(rights.check(PLAYER_2_ID, &TEAM_1_ID));
         // Add rights for team 2 to player 1.
         rights.add(PLAYER_1_ID, &TEAM_2_ID);
-        assert_eq!(rights.check(PLAYER_1_ID, &TEAM_2_ID), true);
+        assert!(rights.check(PLAYER_1_ID, &TEAM_2_ID));
         assert_eq!(rights.data.len(), 2);
         // Remove team 1.
         rights.remove_team(&TEAM_1_ID);
-        assert_eq!(rights.check(PLAYER_1_ID, &TEAM_1_ID), false);
-        assert_eq!(rights.check(PLAYER_2_ID, &TEAM_1_ID), false);
+        assert!(!rights.check(PLAYER_1_ID, &TEAM_1_ID));
+        assert!(!rights.check(PLAYER_2_ID, &TEAM_1_ID));
         assert_eq!(rights.data.len(), 1);
     }
 
+    #[test]
+    fn query_rights() {
+        let mut rights: Rights<CustomRules> = Rights::new();
+        // Add rights for team 1 and 2 to player 1 and for team 1 to player 2.
+        rights.add(PLAYER_1_ID, &TEAM_1_ID);
+        rights.add(PLAYER_1_ID, &TEAM_2_ID);
+        rights.add(PLAYER_2_ID, &TEAM_1_ID);
+        // Check teams of each player.
+        assert_eq!(
+            rights.teams_of(PLAYER_1_ID).collect::<Vec<_>>(),
+            vec![&TEAM_1_ID, &TEAM_2_ID]
+        );
+        assert_eq!(
+            rights.teams_of(PLAYER_2_ID).collect::<Vec<_>>(),
+            vec![&TEAM_1_ID]
+        );
+        assert_eq!(rights.teams_of(3).count(), 0);
+        // Check players of each team.
+        assert_eq!(
+            rights.players_of(&TEAM_1_ID).collect::<Vec<_>>(),
+            vec![PLAYER_1_ID, PLAYER_2_ID]
+        );
+        assert_eq!(
+            rights.players_of(&TEAM_2_ID).collect::<Vec<_>>(),
+            vec![PLAYER_1_ID]
+        );
+    }
+
     #[test]
     fn remove_player() {
         let mut rights: Rights<CustomRules> = Rights::new();
         // Add rights for team 1 to player 1 and player 2.
         rights.add(PLAYER_1_ID, &TEAM_1_ID);
         rights.add(PLAYER_2_ID, &TEAM_1_ID);
-        assert_eq!(rights.check(PLAYER_1_ID, &TEAM_1_ID), true);
-        assert_eq!(rights.check(PLAYER_2_ID, &TEAM_1_ID), true);
+        assert!(rights.check(PLAYER_1_ID, &TEAM_1_ID));
+        assert!(rights.check(PLAYER_2_ID, &TEAM_1_ID));
         assert_eq!(rights.data.len(), 2);
         // Remove player 1.
         rights.remove_player(PLAYER_1_ID);
-        assert_eq!(rights.check(PLAYER_1_ID, &TEAM_1_ID), false);
+        assert!(!rights.check(PLAYER_1_ID, &TEAM_1_ID));
         assert_eq!(rights.data.len(), 1);
     }
 
+    #[test]
+    fn transfer() {
+        let mut rights: Rights<CustomRules> = Rights::new();
+        // Add rights for team 1 and 2 to player 1 and for team 1 to player 2.
+        rights.add(PLAYER_1_ID, &TEAM_1_ID);
+        rights.add(PLAYER_1_ID, &TEAM_2_ID);
+        rights.add(PLAYER_2_ID, &TEAM_1_ID);
+        // Transfer rights from player 1 to player 2.
+        rights.transfer(PLAYER_1_ID, PLAYER_2_ID);
+        assert_eq!(rights.data.len(), 1);
+        assert_eq!(rights.teams_of(PLAYER_1_ID).count(), 0);
+        assert_eq!(
+            rights.teams_of(PLAYER_2_ID).collect::<Vec<_>>(),
+            vec![&TEAM_1_ID, &TEAM_2_ID]
+        );
+        // Transferring from a player without rights does nothing.
+        rights.transfer(PLAYER_1_ID, PLAYER_2_ID);
+        assert_eq!(rights.teams_of(PLAYER_2_ID).count(), 2);
+        // Transferring to the same player does nothing.
+        rights.transfer(PLAYER_2_ID, PLAYER_2_ID);
+        assert_eq!(rights.teams_of(PLAYER_2_ID).count(), 2);
+    }
+
     #[test]
     fn handle() {
         let mut battle = Battle::builder(CustomRules::new()).build();
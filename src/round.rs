@@ -1,12 +1,14 @@
 //! Everything related to the battle's rounds.
 
 use crate::actor::{Actor, ActorRules};
-use crate::battle::{Battle, BattleRules, Checkpoint};
-use crate::entity::EntityId;
+use crate::battle::{Battle, BattleRules, BattleState, Checkpoint};
+use crate::entity::{Entities, EntityId};
 use crate::entropy::Entropy;
 use crate::error::{WeaselError, WeaselResult};
 use crate::event::{Event, EventKind, EventProcessor, EventQueue, EventRights, EventTrigger};
 use crate::metric::{system::*, WriteMetrics};
+use crate::team::TeamRules;
+use crate::util::Id;
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
 use std::any::Any;
@@ -16,7 +18,8 @@ use std::marker::PhantomData;
 /// Manages the battle's rounds. The main purpose is to tell which actor will act next.
 pub struct Rounds<R: BattleRules> {
     state: RoundStateType<R>,
-    model: RoundsModel<R>,
+    pub(crate) model: RoundsModel<R>,
+    pub(crate) order: Vec<EntityId<R>>,
     rules: R::RR,
 }
 
@@ -25,6 +28,7 @@ impl<R: BattleRules> Rounds<R> {
         Rounds {
             state: RoundState::Ready,
             model: rules.generate_model(&seed),
+            order: Vec::new(),
             rules,
         }
     }
@@ -38,16 +42,13 @@ impl<R: BattleRules> Rounds<R> {
     /// Returns true if the entity with the given id is the current actor.
     /// Entity existence is not verified.
     pub(crate) fn is_acting(&self, entity_id: &EntityId<R>) -> bool {
-        if let RoundState::Started(id) = &self.state {
-            entity_id == id
-        } else {
-            false
-        }
+        self.active_actor() == Some(entity_id)
     }
 
     /// See [eligible](trait.RoundsRules.html#method.eligible).
+    /// Disabled actors are never eligible.
     fn eligible(&self, actor: &dyn Actor<R>) -> bool {
-        self.rules.eligible(&self.model, actor)
+        !actor.is_disabled() && self.rules.eligible(&self.model, actor)
     }
 
     /// Returns the state of the current round.
@@ -55,11 +56,46 @@ impl<R: BattleRules> Rounds<R> {
         &self.state
     }
 
+    /// Returns the id of the entity currently acting, if a round is in progress.
+    pub fn active_actor(&self) -> Option<&EntityId<R>> {
+        if let RoundState::Started(id) = &self.state {
+            Some(id)
+        } else {
+            None
+        }
+    }
+
     /// Sets the state of the current round.
     pub(crate) fn set_state(&mut self, state: RoundStateType<R>) {
         self.state = state;
     }
 
+    /// Returns the actors that will act next in the current turn cycle, in order.
+    ///
+    /// The order is empty if no turn cycle was started or if it's already over.
+    pub fn order(&self) -> &[EntityId<R>] {
+        &self.order
+    }
+
+    /// Computes a new turn order and replaces the current one.
+    pub(crate) fn compute_order(&mut self, actors: &mut dyn Iterator<Item = &dyn Actor<R>>) {
+        self.order = self.rules.turn_order(&self.model, actors);
+    }
+
+    /// Removes from the turn order all actors up to the first one that exists and is
+    /// eligible to start a round. Returns the id of such actor.
+    pub(crate) fn next_in_order(&mut self, entities: &Entities<R>) -> Option<EntityId<R>> {
+        while !self.order.is_empty() {
+            let id = self.order.remove(0);
+            if let Some(actor) = entities.actor(&id) {
+                if self.eligible(actor) {
+                    return Some(id);
+                }
+            }
+        }
+        None
+    }
+
     /// Returns the `RoundRules` in use.
     pub fn rules(&self) -> &R::RR {
         &self.rules
@@ -87,8 +123,10 @@ impl<R: BattleRules> Rounds<R> {
     }
 
     /// Regenerates this rounds' model starting from the given seed.
+    /// Any pending turn order is discarded.
     pub(crate) fn regenerate_model(&mut self, seed: &Option<RoundsSeed<R>>) {
-        self.model = self.rules.generate_model(seed)
+        self.model = self.rules.generate_model(seed);
+        self.order.clear();
     }
 }
 
@@ -97,6 +135,7 @@ pub type RoundStateType<R> = RoundState<EntityId<R>>;
 
 /// State machine to manage the rounds' state.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub enum RoundState<EI>
 where
     EI: Debug,
@@ -133,6 +172,14 @@ pub trait RoundsRules<R: BattleRules> {
         true
     }
 
+    /// Checks if the given actor is allowed to start a new round, in the current
+    /// state of the battle.
+    ///
+    /// The provided implementation allows any actor to start a round.
+    fn allow_start(&self, _state: &BattleState<R>, _actor: &dyn Actor<R>) -> bool {
+        true
+    }
+
     /// Invoked when a new round begins.
     ///
     /// The provided implementation does nothing.
@@ -168,6 +215,23 @@ pub trait RoundsRules<R: BattleRules> {
         _metrics: &mut WriteMetrics<R>,
     ) {
     }
+
+    /// Computes the order in which actors will take their rounds during a turn cycle.\
+    /// `actors` iterates over all actors in the battle, in no particular order.
+    ///
+    /// Rounds in the cycle are started automatically, one after the other, following
+    /// the returned order. Actors that are not eligible or not allowed to start a round
+    /// when their turn comes are skipped.
+    ///
+    /// The provided implementation returns an empty order, meaning that rounds must be
+    /// started manually.
+    fn turn_order(
+        &self,
+        _model: &Self::RoundsModel,
+        _actors: &mut dyn Iterator<Item = &dyn Actor<R>>,
+    ) -> Vec<EntityId<R>> {
+        Vec::new()
+    }
 }
 
 /// Type to represent a rounds seed.
@@ -199,7 +263,7 @@ impl<R: BattleRules> StartRound<R> {
     pub fn trigger<P: EventProcessor<R>>(
         processor: &mut P,
         id: EntityId<R>,
-    ) -> StartRoundTrigger<R, P> {
+    ) -> StartRoundTrigger<'_, R, P> {
         StartRoundTrigger { processor, id }
     }
 
@@ -239,6 +303,10 @@ impl<R: BattleRules + 'static> Event<R> for StartRound<R> {
             if !battle.rounds().eligible(actor) {
                 return Err(WeaselError::ActorNotEligible(self.id.clone()));
             }
+            // Verify if rules allow the actor to start a round.
+            if !battle.rounds().rules().allow_start(&battle.state, actor) {
+                return Err(WeaselError::RoundCannotStart(self.id.clone()));
+            }
             Ok(())
         } else {
             Err(WeaselError::EntityNotFound(self.id.clone()))
@@ -272,6 +340,17 @@ impl<R: BattleRules + 'static> Event<R> for StartRound<R> {
             .rules
             .actor_rules()
             .on_round_start(actor, event_queue, &mut battle.entropy, metrics);
+        // Invoke `TeamRules` callback for each team.
+        for team in battle.state.entities.teams() {
+            battle.rules.team_rules().on_round_start(
+                &battle.state,
+                team,
+                actor,
+                event_queue,
+                &mut battle.entropy,
+                metrics,
+            );
+        }
     }
 
     fn kind(&self) -> EventKind {
@@ -332,7 +411,7 @@ pub struct EndRound<R> {
 
 impl<R: BattleRules> EndRound<R> {
     /// Returns a trigger for this event.
-    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> EndRoundTrigger<R, P> {
+    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> EndRoundTrigger<'_, R, P> {
         EndRoundTrigger {
             processor,
             _phantom: PhantomData,
@@ -364,37 +443,7 @@ impl<R: BattleRules + 'static> Event<R> for EndRound<R> {
     }
 
     fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
-        let id = if let RoundState::Started(id) = battle.state.rounds.state() {
-            Some(id.clone())
-        } else {
-            panic!("constraint violated: end round called when state is not started");
-        };
-        let actor = battle
-            .state
-            .entities
-            .actor(&id.clone().unwrap())
-            .unwrap_or_else(|| panic!("constraint violated: actor {:?} not found", id));
-        let metrics = &mut battle.metrics.write_handle();
-        // Invoke `CharacterRules` callback.
-        battle
-            .rules
-            .actor_rules()
-            .on_round_end(actor, event_queue, &mut battle.entropy, metrics);
-        // Invoke `RoundRules` callback.
-        battle
-            .state
-            .rounds
-            .on_end(actor, &mut battle.entropy, metrics);
-        // Check teams' objectives.
-        Battle::check_objectives(
-            &battle.state,
-            &battle.rules.team_rules(),
-            &battle.metrics.read_handle(),
-            event_queue,
-            Checkpoint::RoundEnd,
-        );
-        // Set the round state.
-        battle.state.rounds.set_state(RoundState::Ready);
+        end_round(battle, event_queue);
     }
 
     fn kind(&self) -> EventKind {
@@ -451,6 +500,214 @@ where
     }
 }
 
+/// Event to end the current round, only if it belongs to the given actor.
+///
+/// Prefer this event over `EndRound` when there's a chance that the round was
+/// already passed to another actor, e.g. in case of events fired by remote clients.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct EndRoundFor<R: BattleRules> {
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "EntityId<R>: Serialize",
+            deserialize = "EntityId<R>: Deserialize<'de>"
+        ))
+    )]
+    id: EntityId<R>,
+}
+
+impl<R: BattleRules> EndRoundFor<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &mut P,
+        id: EntityId<R>,
+    ) -> EndRoundForTrigger<'_, R, P> {
+        EndRoundForTrigger { processor, id }
+    }
+
+    /// Returns the id of the entity whose round should end.
+    pub fn id(&self) -> &EntityId<R> {
+        &self.id
+    }
+}
+
+impl<R: BattleRules> Debug for EndRoundFor<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "EndRoundFor {{ id: {:?} }}", self.id)
+    }
+}
+
+impl<R: BattleRules> Clone for EndRoundFor<R> {
+    fn clone(&self) -> Self {
+        EndRoundFor {
+            id: self.id.clone(),
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for EndRoundFor<R> {
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        // Verify if the round can end.
+        if let RoundState::Ready = battle.rounds().state() {
+            return Err(WeaselError::NoRoundInProgress);
+        }
+        // Verify that the round belongs to the given actor.
+        if !battle.rounds().is_acting(&self.id) {
+            return Err(WeaselError::ActorNotReady(self.id.clone()));
+        }
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
+        end_round(battle, event_queue);
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::EndRoundFor
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn rights<'a>(&'a self, battle: &'a Battle<R>) -> EventRights<'a, R> {
+        let actor = battle
+            .state
+            .entities
+            .actor(&self.id)
+            .unwrap_or_else(|| panic!("constraint violated: actor {:?} not found", self.id));
+        EventRights::Team(actor.team_id())
+    }
+}
+
+/// Trigger to build and fire an `EndRoundFor` event.
+pub struct EndRoundForTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    id: EntityId<R>,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for EndRoundForTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns an `EndRoundFor` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(EndRoundFor {
+            id: self.id.clone(),
+        })
+    }
+}
+
+/// Event to begin a new turn cycle.
+///
+/// The turn order is computed by `RoundsRules::turn_order`. The first actor in the order
+/// starts its round right away, while each subsequent actor starts its round as soon
+/// as the previous one ends.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct StartTurnCycle<R> {
+    #[cfg_attr(feature = "serialization", serde(skip))]
+    _phantom: PhantomData<R>,
+}
+
+impl<R: BattleRules> StartTurnCycle<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> StartTurnCycleTrigger<'_, R, P> {
+        StartTurnCycleTrigger {
+            processor,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<R> Debug for StartTurnCycle<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "StartTurnCycle {{ }}")
+    }
+}
+
+impl<R> Clone for StartTurnCycle<R> {
+    fn clone(&self) -> Self {
+        StartTurnCycle {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for StartTurnCycle<R> {
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        // A new cycle can't begin while a round is in progress.
+        if let RoundState::Started(_) = battle.rounds().state() {
+            return Err(WeaselError::RoundInProgress);
+        }
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
+        // Compute the new turn order.
+        let state = &mut battle.state;
+        state.rounds.compute_order(
+            &mut state
+                .entities
+                .creatures()
+                .map(|creature| creature as &dyn Actor<R>),
+        );
+        // Start the round of the first actor.
+        start_next_in_order(battle, event_queue);
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::StartTurnCycle
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire a `StartTurnCycle` event.
+pub struct StartTurnCycleTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    _phantom: PhantomData<R>,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for StartTurnCycleTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns a `StartTurnCycle` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(StartTurnCycle {
+            _phantom: self._phantom,
+        })
+    }
+}
+
 /// Event to reset the rounds model.
 ///
 /// This event can be fired only if no round is in progress.
@@ -468,7 +725,7 @@ pub struct ResetRounds<R: BattleRules> {
 
 impl<R: BattleRules> ResetRounds<R> {
     /// Returns a trigger for this event.
-    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> ResetRoundsTrigger<R, P> {
+    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> ResetRoundsTrigger<'_, R, P> {
         ResetRoundsTrigger {
             processor,
             seed: None,
@@ -559,3 +816,92 @@ where
         })
     }
 }
+
+/// Ends the current round, invoking all callbacks related to the end of a round.
+pub(crate) fn end_round<R>(battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>)
+where
+    R: BattleRules + 'static,
+{
+    let id = if let RoundState::Started(id) = battle.state.rounds.state() {
+        Some(id.clone())
+    } else {
+        panic!("constraint violated: end round called when state is not started");
+    };
+    // Decrease the cooldown of the actor's abilities.
+    let actor = battle
+        .state
+        .entities
+        .actor_mut(&id.clone().unwrap())
+        .unwrap_or_else(|| panic!("constraint violated: actor {:?} not found", id));
+    let abilities: Vec<_> = actor
+        .abilities()
+        .map(|ability| ability.id().clone())
+        .collect();
+    for ability_id in abilities {
+        let cooldown = actor.cooldown(&ability_id);
+        if cooldown > 0 {
+            actor.set_cooldown(&ability_id, cooldown - 1);
+        }
+    }
+    let actor = battle
+        .state
+        .entities
+        .actor(&id.clone().unwrap())
+        .unwrap_or_else(|| panic!("constraint violated: actor {:?} not found", id));
+    let metrics = &mut battle.metrics.write_handle();
+    // Invoke `CharacterRules` callback.
+    battle
+        .rules
+        .actor_rules()
+        .on_round_end(actor, event_queue, &mut battle.entropy, metrics);
+    // Invoke `TeamRules` callback for each team.
+    for team in battle.state.entities.teams() {
+        battle.rules.team_rules().on_round_end(
+            &battle.state,
+            team,
+            actor,
+            event_queue,
+            &mut battle.entropy,
+            metrics,
+        );
+    }
+    // Invoke `RoundRules` callback.
+    battle
+        .state
+        .rounds
+        .on_end(actor, &mut battle.entropy, metrics);
+    // Invoke `BattleRules` callback.
+    battle
+        .rules
+        .on_round_end(&battle.state, event_queue, &mut battle.entropy, metrics);
+    // Check teams' objectives.
+    Battle::check_objectives(
+        &battle.state,
+        battle.rules.team_rules(),
+        &battle.metrics.read_handle(),
+        event_queue,
+        Checkpoint::RoundEnd,
+    );
+    // Set the round state.
+    battle.state.rounds.set_state(RoundState::Ready);
+    // Continue the turn cycle, if any.
+    start_next_in_order(battle, event_queue);
+}
+
+/// Fires a `StartRound` event for the next eligible actor in the turn order.
+fn start_next_in_order<R>(battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>)
+where
+    R: BattleRules + 'static,
+{
+    while let Some(id) = battle.state.rounds.next_in_order(&battle.state.entities) {
+        let actor = battle
+            .state
+            .entities
+            .actor(&id)
+            .unwrap_or_else(|| panic!("constraint violated: actor {:?} not found", id));
+        if battle.state.rounds.rules.allow_start(&battle.state, actor) {
+            StartRound::trigger(event_queue, id).fire();
+            break;
+        }
+    }
+}
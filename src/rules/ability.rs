@@ -1,23 +1,47 @@
 //! Generic implementations for all purpose abilities.
 
+use crate::actor::{Action, ActorRules};
+use crate::battle::BattleRules;
+use crate::character::CharacterRules;
+use crate::event::{EventProcessor, EventTrigger};
+use crate::rules::statistic::{SetStatisticValue, SimpleStatistic};
 use crate::util::Id;
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::ops::{Add, Sub};
 
 /// A simple generic ability.
+///
+/// An ability can optionally have a cost, that is an amount of a statistic
+/// (identified by `S`) consumed each time the ability is activated.
 #[derive(PartialEq, Clone, Debug)]
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
-pub struct SimpleAbility<I, V> {
+pub struct SimpleAbility<I, V, S = I> {
     id: I,
     power: V,
+    cost: Option<(S, V)>,
 }
 
-impl<I, V: Copy> SimpleAbility<I, V> {
-    /// Creates a new `SimpleAbility`.
-    pub fn new(id: I, power: V) -> SimpleAbility<I, V> {
-        SimpleAbility { id, power }
+impl<I, V: Copy, S> SimpleAbility<I, V, S> {
+    /// Creates a new `SimpleAbility` without any cost.
+    pub fn new(id: I, power: V) -> SimpleAbility<I, V, S> {
+        SimpleAbility {
+            id,
+            power,
+            cost: None,
+        }
+    }
+
+    /// Creates a new `SimpleAbility` whose activation consumes `cost` points
+    /// of the statistic with id `statistic_id`.
+    pub fn with_cost(id: I, power: V, statistic_id: S, cost: V) -> SimpleAbility<I, V, S> {
+        SimpleAbility {
+            id,
+            power,
+            cost: Some((statistic_id, cost)),
+        }
     }
 
     /// Returns this ability's power.
@@ -29,10 +53,16 @@ impl<I, V: Copy> SimpleAbility<I, V> {
     pub fn set_power(&mut self, power: V) {
         self.power = power;
     }
+
+    /// Returns the id of the statistic consumed by this ability and the amount consumed,
+    /// if the ability has a cost.
+    pub fn cost(&self) -> Option<(&S, V)> {
+        self.cost.as_ref().map(|(id, cost)| (id, *cost))
+    }
 }
 
 #[cfg(not(feature = "serialization"))]
-impl<I, V> Id for SimpleAbility<I, V>
+impl<I, V, S> Id for SimpleAbility<I, V, S>
 where
     I: Debug + Hash + Eq + Clone,
 {
@@ -43,7 +73,7 @@ where
 }
 
 #[cfg(feature = "serialization")]
-impl<I, V> Id for SimpleAbility<I, V>
+impl<I, V, S> Id for SimpleAbility<I, V, S>
 where
     I: Debug + Hash + Eq + Clone + Serialize + for<'a> Deserialize<'a>,
 {
@@ -52,3 +82,52 @@ where
         &self.id
     }
 }
+
+/// Returns true if the actor in `action` has enough of the statistic consumed
+/// by the ability. Abilities without a cost are always activable.
+///
+/// Use this function to implement `ActorRules::activable` when abilities are
+/// `SimpleAbility` and statistics are `SimpleStatistic`.
+pub fn activable_with_cost<R, I, V, S>(action: Action<R>) -> bool
+where
+    R: BattleRules,
+    R::AR: ActorRules<R, Ability = SimpleAbility<I, V, S>>,
+    R::CR: CharacterRules<R, Statistic = SimpleStatistic<S, V>>,
+    SimpleStatistic<S, V>: Id<Id = S>,
+    V: Copy + PartialOrd + Add<Output = V>,
+{
+    match action.ability.cost() {
+        Some((statistic_id, cost)) => action
+            .actor
+            .statistic(statistic_id)
+            .is_some_and(|statistic| statistic.value() >= cost),
+        None => true,
+    }
+}
+
+/// Consumes the cost of the ability in `action`, by firing a `SetStatisticValue` event
+/// that decreases the actor's statistic.
+///
+/// Nothing happens if the ability has no cost or the actor doesn't have the statistic.
+pub fn consume_cost<R, P, I, V, S>(action: Action<R>, processor: &mut P)
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+    R::AR: ActorRules<R, Ability = SimpleAbility<I, V, S>>,
+    R::CR: CharacterRules<R, Statistic = SimpleStatistic<S, V>>,
+    SimpleStatistic<S, V>: Id<Id = S>,
+    S: Clone,
+    V: Copy + PartialOrd + Add<Output = V> + Sub<Output = V> + Debug + 'static,
+{
+    if let Some((statistic_id, cost)) = action.ability.cost() {
+        if let Some(statistic) = action.actor.statistic(statistic_id) {
+            SetStatisticValue::trigger(
+                processor,
+                action.actor.entity_id().clone(),
+                statistic_id.clone(),
+                statistic.value() - cost,
+            )
+            .fire();
+        }
+    }
+}
@@ -15,7 +15,7 @@ use crate::util::Id;
 use serde::{Deserialize, Serialize};
 
 /// An empty statistic.
-#[derive(Hash, Eq, PartialEq, Debug)]
+#[derive(Hash, Eq, PartialEq, Clone, Debug)]
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct EmptyStat {
     /// The id of this statistic.
@@ -40,6 +40,7 @@ impl<R: BattleRules> TeamRules<R> for EmptyTeamRules {
     type Id = u32;
     type ObjectivesSeed = ();
     type Objectives = ();
+    type ObjectivesProgress = ();
 }
 
 /// Minimalistic implementation of character rules, doing no-op for everything.
@@ -48,6 +49,7 @@ pub struct EmptyCharacterRules {}
 
 impl<R: BattleRules> CharacterRules<R> for EmptyCharacterRules {
     type CreatureId = u32;
+    type ObjectId = u32;
     type Statistic = EmptyStat;
     type StatisticsSeed = ();
     type StatisticsAlteration = ();
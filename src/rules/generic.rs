@@ -1,6 +1,9 @@
 //! This module contains generic structs that you can use to compose rules.
 
 /// Macro to quickly generate battle rules.
+///
+/// Components can be given either all in order, or by name as in `battle_rules_with!`.
+/// Without any argument, all components are empty rules.
 #[macro_export]
 macro_rules! battle_rules {
     () => {
@@ -15,6 +18,9 @@ macro_rules! battle_rules {
             EmptyEntropyRules
         }
     };
+    ($($name: ident : $ty: ty),+ $(,)?) => {
+        $crate::battle_rules_with! { $($name : $ty),+ }
+    };
     ($ty: ty, $cy: ty, $ay: ty, $fy: ty, $uy: ty, $sy: ty, $ry: ty, $ey: ty) => {
         pub(crate) struct CustomRules {
             pub(crate) team_rules: $ty,
@@ -222,3 +228,67 @@ macro_rules! battle_rules_with_user {
         }
     };
 }
+
+/// Battle rules with any number of user defined components, given by name.
+///
+/// The names of the components are `team`, `character`, `actor`, `fight`, `user`,
+/// `space`, `rounds` and `entropy`. Unspecified components are empty rules.
+///
+/// ```ignore
+/// battle_rules_with! {
+///     character: MyCharacterRules,
+///     actor: MyActorRules
+/// }
+/// ```
+#[macro_export]
+macro_rules! battle_rules_with {
+    ($($name: ident : $ty: ty),* $(,)?) => {
+        $crate::battle_rules_with! {
+            @munch
+            [EmptyTeamRules]
+            [EmptyCharacterRules]
+            [EmptyActorRules]
+            [EmptyFightRules]
+            [EmptyUserRules]
+            [EmptySpaceRules]
+            [EmptyRoundsRules]
+            [EmptyEntropyRules]
+            $($name : $ty,)*
+        }
+    };
+    (@munch [$ty: ty] [$cy: ty] [$ay: ty] [$fy: ty] [$uy: ty] [$sy: ty] [$ry: ty] [$ey: ty]) => {
+        $crate::battle_rules! { $ty, $cy, $ay, $fy, $uy, $sy, $ry, $ey }
+    };
+    (@munch [$ty: ty] [$cy: ty] [$ay: ty] [$fy: ty] [$uy: ty] [$sy: ty] [$ry: ty] [$ey: ty]
+        team : $x: ty, $($rest: tt)*) => {
+        $crate::battle_rules_with! { @munch [$x] [$cy] [$ay] [$fy] [$uy] [$sy] [$ry] [$ey] $($rest)* }
+    };
+    (@munch [$ty: ty] [$cy: ty] [$ay: ty] [$fy: ty] [$uy: ty] [$sy: ty] [$ry: ty] [$ey: ty]
+        character : $x: ty, $($rest: tt)*) => {
+        $crate::battle_rules_with! { @munch [$ty] [$x] [$ay] [$fy] [$uy] [$sy] [$ry] [$ey] $($rest)* }
+    };
+    (@munch [$ty: ty] [$cy: ty] [$ay: ty] [$fy: ty] [$uy: ty] [$sy: ty] [$ry: ty] [$ey: ty]
+        actor : $x: ty, $($rest: tt)*) => {
+        $crate::battle_rules_with! { @munch [$ty] [$cy] [$x] [$fy] [$uy] [$sy] [$ry] [$ey] $($rest)* }
+    };
+    (@munch [$ty: ty] [$cy: ty] [$ay: ty] [$fy: ty] [$uy: ty] [$sy: ty] [$ry: ty] [$ey: ty]
+        fight : $x: ty, $($rest: tt)*) => {
+        $crate::battle_rules_with! { @munch [$ty] [$cy] [$ay] [$x] [$uy] [$sy] [$ry] [$ey] $($rest)* }
+    };
+    (@munch [$ty: ty] [$cy: ty] [$ay: ty] [$fy: ty] [$uy: ty] [$sy: ty] [$ry: ty] [$ey: ty]
+        user : $x: ty, $($rest: tt)*) => {
+        $crate::battle_rules_with! { @munch [$ty] [$cy] [$ay] [$fy] [$x] [$sy] [$ry] [$ey] $($rest)* }
+    };
+    (@munch [$ty: ty] [$cy: ty] [$ay: ty] [$fy: ty] [$uy: ty] [$sy: ty] [$ry: ty] [$ey: ty]
+        space : $x: ty, $($rest: tt)*) => {
+        $crate::battle_rules_with! { @munch [$ty] [$cy] [$ay] [$fy] [$uy] [$x] [$ry] [$ey] $($rest)* }
+    };
+    (@munch [$ty: ty] [$cy: ty] [$ay: ty] [$fy: ty] [$uy: ty] [$sy: ty] [$ry: ty] [$ey: ty]
+        rounds : $x: ty, $($rest: tt)*) => {
+        $crate::battle_rules_with! { @munch [$ty] [$cy] [$ay] [$fy] [$uy] [$sy] [$x] [$ey] $($rest)* }
+    };
+    (@munch [$ty: ty] [$cy: ty] [$ay: ty] [$fy: ty] [$uy: ty] [$sy: ty] [$ry: ty] [$ey: ty]
+        entropy : $x: ty, $($rest: tt)*) => {
+        $crate::battle_rules_with! { @munch [$ty] [$cy] [$ay] [$fy] [$uy] [$sy] [$ry] [$x] $($rest)* }
+    };
+}
@@ -1,10 +1,17 @@
 //! Generic implementations for different types of statistic.
 
+use crate::battle::{Battle, BattleRules};
+use crate::character::{verify_is_character, CharacterRules, StatisticId};
+use crate::entity::EntityId;
+use crate::error::{WeaselError, WeaselResult};
+use crate::event::{Event, EventKind, EventProcessor, EventQueue, EventTrigger};
+use crate::user::UserEventId;
 use crate::util::Id;
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
+use std::any::Any;
 use std::cmp::PartialOrd;
-use std::fmt::Debug;
+use std::fmt::{Debug, Formatter, Result};
 use std::hash::Hash;
 use std::ops::Add;
 
@@ -26,6 +33,9 @@ impl<I, V: Copy + Default> SimpleStatistic<I, V> {
     }
 
     /// Creates a new `SimpleStatistic` with the given value.
+    ///
+    /// `value` is stored as it is, even if it's outside of the bounds.
+    /// Use `with_bounds` to clamp it.
     pub fn with_value(id: I, min: V, max: V, value: V) -> SimpleStatistic<I, V> {
         SimpleStatistic {
             id,
@@ -40,6 +50,20 @@ impl<I, V> SimpleStatistic<I, V>
 where
     V: Copy + PartialOrd + Add<Output = V>,
 {
+    /// Creates a new `SimpleStatistic` bounded by `min` and `max`.
+    ///
+    /// `value` is clamped between the bounds.
+    pub fn with_bounds(id: I, value: V, min: V, max: V) -> SimpleStatistic<I, V> {
+        let mut statistic = SimpleStatistic {
+            id,
+            min,
+            max,
+            value,
+        };
+        statistic.set_value(value);
+        statistic
+    }
+
     /// Returns the current value of this statistic.
     pub fn value(&self) -> V {
         self.value
@@ -93,6 +117,182 @@ where
     }
 }
 
+/// User event id of `SetStatisticValue`.
+///
+/// Don't use this id for other user events, if you register `SetStatisticValue`
+/// in your `UserEventPackage`.
+pub const SET_STATISTIC_VALUE_ID: UserEventId = UserEventId::MAX;
+
+/// Event to set the value of a `SimpleStatistic` of a character.
+///
+/// The new value is clamped within the statistic's bounds.\
+/// This event is available to all battle rules whose statistics are `SimpleStatistic`.
+/// It's not part of the built-in events, therefore its kind is
+/// `EventKind::UserEvent(SET_STATISTIC_VALUE_ID)`.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct SetStatisticValue<R: BattleRules, V> {
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "EntityId<R>: Serialize",
+            deserialize = "EntityId<R>: Deserialize<'de>"
+        ))
+    )]
+    id: EntityId<R>,
+
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "StatisticId<R>: Serialize",
+            deserialize = "StatisticId<R>: Deserialize<'de>"
+        ))
+    )]
+    statistic_id: StatisticId<R>,
+
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(serialize = "V: Serialize", deserialize = "V: Deserialize<'de>"))
+    )]
+    value: V,
+}
+
+impl<R: BattleRules, V> SetStatisticValue<R, V> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &mut P,
+        id: EntityId<R>,
+        statistic_id: StatisticId<R>,
+        value: V,
+    ) -> SetStatisticValueTrigger<'_, R, P, V> {
+        SetStatisticValueTrigger {
+            processor,
+            id,
+            statistic_id,
+            value,
+        }
+    }
+
+    /// Returns the id of the character whose statistic will be changed.
+    pub fn id(&self) -> &EntityId<R> {
+        &self.id
+    }
+
+    /// Returns the id of the statistic to change.
+    pub fn statistic_id(&self) -> &StatisticId<R> {
+        &self.statistic_id
+    }
+
+    /// Returns the new value of the statistic.
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+}
+
+impl<R: BattleRules, V: Debug> Debug for SetStatisticValue<R, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "SetStatisticValue {{ id: {:?}, statistic_id: {:?}, value: {:?} }}",
+            self.id, self.statistic_id, self.value
+        )
+    }
+}
+
+impl<R: BattleRules, V: Clone> Clone for SetStatisticValue<R, V> {
+    fn clone(&self) -> Self {
+        SetStatisticValue {
+            id: self.id.clone(),
+            statistic_id: self.statistic_id.clone(),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<R, I, V> Event<R> for SetStatisticValue<R, V>
+where
+    R: BattleRules + 'static,
+    R::CR: CharacterRules<R, Statistic = SimpleStatistic<I, V>>,
+    SimpleStatistic<I, V>: Id,
+    V: Copy + PartialOrd + Add<Output = V> + Debug + 'static,
+{
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        verify_is_character(battle.entities(), &self.id)?;
+        // Check if the character has the statistic.
+        let character = battle
+            .entities()
+            .character(&self.id)
+            .ok_or_else(|| WeaselError::NotACharacter(self.id.clone()))?;
+        if character.statistic(&self.statistic_id).is_none() {
+            return Err(WeaselError::StatisticNotFound(
+                self.id.clone(),
+                self.statistic_id.clone(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
+        battle
+            .state
+            .entities
+            .character_mut(&self.id)
+            .and_then(|character| character.statistic_mut(&self.statistic_id))
+            .unwrap_or_else(|| {
+                panic!(
+                    "constraint violated: statistic {:?} of {:?} not found",
+                    self.statistic_id, self.id
+                )
+            })
+            .set_value(self.value);
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::UserEvent(SET_STATISTIC_VALUE_ID)
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire a `SetStatisticValue` event.
+pub struct SetStatisticValueTrigger<'a, R, P, V>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    id: EntityId<R>,
+    statistic_id: StatisticId<R>,
+    value: V,
+}
+
+impl<'a, R, P, I, V> EventTrigger<'a, R, P> for SetStatisticValueTrigger<'a, R, P, V>
+where
+    R: BattleRules + 'static,
+    R::CR: CharacterRules<R, Statistic = SimpleStatistic<I, V>>,
+    SimpleStatistic<I, V>: Id,
+    P: EventProcessor<R>,
+    V: Copy + PartialOrd + Add<Output = V> + Debug + 'static,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns a `SetStatisticValue` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(SetStatisticValue {
+            id: self.id.clone(),
+            statistic_id: self.statistic_id.clone(),
+            value: self.value,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,4 +305,13 @@ mod tests {
         stat.add(-100);
         assert_eq!(stat.value(), stat.min());
     }
+
+    #[test]
+    fn simple_statistic_initial_value() {
+        assert_eq!(SimpleStatistic::with_bounds(1, 50, 0, 10).value(), 10);
+        assert_eq!(SimpleStatistic::with_bounds(1, -5, 0, 10).value(), 0);
+        assert_eq!(SimpleStatistic::with_bounds(1, 5, 0, 10).value(), 5);
+        // `with_value` doesn't clamp.
+        assert_eq!(SimpleStatistic::with_value(1, 0, 10, 50).value(), 50);
+    }
 }
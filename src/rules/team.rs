@@ -0,0 +1,215 @@
+//! Generic implementations of team rules.
+
+use crate::actor::Actor;
+use crate::battle::{BattleRules, BattleState};
+use crate::metric::{MetricsDelta, ReadMetrics};
+use crate::team::{Conclusion, Team, TeamRules};
+use crate::util::Id;
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// Team rules for the common "last team standing" scenario.
+///
+/// Only creatures that are not disabled can fight. Teams without any such creature
+/// are defeated. If only one team still has creatures able to fight while all
+/// the others have none, it achieves victory.\
+/// Objectives are checked when a round ends and after every event changing the number
+/// of creatures able to fight of a team. In the latter case, teams that never had
+/// any creature able to fight, such as teams just created, are not taken into account
+/// until they reach a conclusion.
+///
+/// `I` is the type of the teams' id.
+/// Use it as any other component, for example with `battle_rules_with_team!`.
+pub struct LastTeamStandingRules<I = u32> {
+    _phantom: PhantomData<I>,
+}
+
+impl<I> Default for LastTeamStandingRules<I> {
+    fn default() -> Self {
+        LastTeamStandingRules {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Progress of a team in a battle with `LastTeamStandingRules`.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct Standing {
+    creatures: usize,
+    deployed: bool,
+    changed: bool,
+}
+
+impl Standing {
+    /// Returns the number of creatures of the team able to fight.
+    pub fn creatures(&self) -> usize {
+        self.creatures
+    }
+
+    /// Returns whether the team ever had any creature able to fight.
+    pub fn deployed(&self) -> bool {
+        self.deployed
+    }
+}
+
+impl<I> LastTeamStandingRules<I> {
+    /// Returns the number of creatures of `team` that are not disabled.
+    fn fighters<R: BattleRules>(state: &BattleState<R>, team: &Team<R>) -> usize {
+        team.creatures()
+            .filter_map(|id| state.entities().creature(id))
+            .filter(|creature| !creature.is_disabled())
+            .count()
+    }
+
+    /// Returns the conclusion reached by `team`, if any.
+    ///
+    /// `counts` tells whether another team is taken into account to decide the victory.
+    fn conclusion<R, F>(state: &BattleState<R>, team: &Team<R>, counts: F) -> Option<Conclusion>
+    where
+        R: BattleRules,
+        F: Fn(&Team<R>) -> bool,
+    {
+        if Self::fighters(state, team) == 0 {
+            return Some(Conclusion::Defeat);
+        }
+        let mut others = state
+            .entities()
+            .teams()
+            .filter(|other| other.id() != team.id())
+            .peekable();
+        // A team alone in the battle has nobody to win against.
+        others.peek()?;
+        if others.any(|other| !counts(other) || Self::fighters(state, other) > 0) {
+            None
+        } else {
+            Some(Conclusion::Victory)
+        }
+    }
+
+    /// Returns the conclusion reached by `team` at the end of a round, if any.
+    fn conclusion_on_round<R: BattleRules>(
+        state: &BattleState<R>,
+        team: &Team<R>,
+    ) -> Option<Conclusion> {
+        Self::conclusion(state, team, |_| true)
+    }
+
+    /// Returns the conclusion reached by `team` after an event, if any.
+    fn conclusion_on_event<R>(state: &BattleState<R>, team: &Team<R>) -> Option<Conclusion>
+    where
+        R: BattleRules<TR = Self>,
+        Self: TeamRules<R, ObjectivesProgress = Standing>,
+    {
+        // Conclusions depend only on the creatures of each team.
+        let changed = state
+            .entities()
+            .teams()
+            .any(|team| team.objectives_progress().changed);
+        // Teams are created empty, so wait until they are ready to fight.
+        if !changed || !team.objectives_progress().deployed {
+            return None;
+        }
+        Self::conclusion(state, team, |other| {
+            other.objectives_progress().deployed || other.conclusion().is_some()
+        })
+    }
+
+    /// Computes the standing of `team` after an event.
+    fn standing<R>(state: &BattleState<R>, team: &Team<R>) -> Standing
+    where
+        R: BattleRules<TR = Self>,
+        Self: TeamRules<R, ObjectivesProgress = Standing>,
+    {
+        let previous = team.objectives_progress();
+        let creatures = Self::fighters(state, team);
+        Standing {
+            creatures,
+            deployed: previous.deployed || creatures > 0,
+            changed: creatures != previous.creatures,
+        }
+    }
+}
+
+#[cfg(not(feature = "serialization"))]
+impl<R, I> TeamRules<R> for LastTeamStandingRules<I>
+where
+    R: BattleRules<TR = Self>,
+    I: Hash + Eq + PartialOrd + Clone + Debug,
+{
+    type Id = I;
+    type ObjectivesSeed = ();
+    type Objectives = ();
+    type ObjectivesProgress = Standing;
+
+    fn check_objectives_on_event(
+        &self,
+        state: &BattleState<R>,
+        team: &Team<R>,
+        _metrics: &ReadMetrics<R>,
+        _delta: &MetricsDelta<R>,
+    ) -> Option<Conclusion> {
+        Self::conclusion_on_event(state, team)
+    }
+
+    fn check_objectives_on_round(
+        &self,
+        state: &BattleState<R>,
+        team: &Team<R>,
+        _metrics: &ReadMetrics<R>,
+    ) -> Option<Conclusion> {
+        Self::conclusion_on_round(state, team)
+    }
+
+    fn objectives_progress(
+        &self,
+        state: &BattleState<R>,
+        team: &Team<R>,
+        _metrics: &ReadMetrics<R>,
+    ) -> Self::ObjectivesProgress {
+        Self::standing(state, team)
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl<R, I> TeamRules<R> for LastTeamStandingRules<I>
+where
+    R: BattleRules<TR = Self>,
+    I: Hash + Eq + PartialOrd + Clone + Debug + Serialize + for<'a> Deserialize<'a>,
+{
+    type Id = I;
+    type ObjectivesSeed = ();
+    type Objectives = ();
+    type ObjectivesProgress = Standing;
+
+    fn check_objectives_on_event(
+        &self,
+        state: &BattleState<R>,
+        team: &Team<R>,
+        _metrics: &ReadMetrics<R>,
+        _delta: &MetricsDelta<R>,
+    ) -> Option<Conclusion> {
+        Self::conclusion_on_event(state, team)
+    }
+
+    fn check_objectives_on_round(
+        &self,
+        state: &BattleState<R>,
+        team: &Team<R>,
+        _metrics: &ReadMetrics<R>,
+    ) -> Option<Conclusion> {
+        Self::conclusion_on_round(state, team)
+    }
+
+    fn objectives_progress(
+        &self,
+        state: &BattleState<R>,
+        team: &Team<R>,
+        _metrics: &ReadMetrics<R>,
+    ) -> Self::ObjectivesProgress {
+        Self::standing(state, team)
+    }
+}
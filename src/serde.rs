@@ -1,21 +1,46 @@
 //! Module to handle serialization and deserialization.
+//!
+//! # Binary format
+//!
+//! Events and histories can be converted to and from a compact binary format,
+//! currently produced with [bincode](https://docs.rs/bincode).\
+//! The format is not self-describing and it offers no stability guarantees: data can
+//! only be read back with the same battle rules types and the same version of weasel that
+//! wrote it. Use JSON or any other self-describing format for long lived saves.
 
-use crate::ability::ActivateAbility;
-use crate::actor::{AlterAbilities, RegenerateAbilities};
-use crate::battle::{BattleRules, EndBattle, Version};
-use crate::character::{AlterStatistics, RegenerateStatistics};
-use crate::creature::{ConvertCreature, CreateCreature, RemoveCreature};
-use crate::entropy::ResetEntropy;
+use crate::ability::{Ability, AbilityId, ActivateAbility};
+use crate::actor::{AlterAbilities, ClearAbilities, RegenerateAbilities};
+use crate::battle::{Battle, BattleOutcome, BattlePhase, BattleRules, EndBattle, Version};
+use crate::character::{
+    AddStatistic, AlterStatistics, AlterStatisticsBatch, RegenerateStatistics, RemoveStatistic,
+    Statistic,
+};
+use crate::creature::{
+    ConvertCreature, CreateCreature, CreateCreatures, Creature, CreatureId, RemoveCreature,
+    SetCreatureDisabled,
+};
+use crate::entity::EntityId;
+use crate::entropy::{EntropyModel, ResetEntropy, RestoreEntropy};
+use crate::error::{WeaselError, WeaselErrorType, WeaselResult};
 use crate::event::{
-    ClientEventPrototype, DummyEvent, Event, EventId, EventKind, EventWrapper,
+    ClientEventPrototype, DummyEvent, Event, EventId, EventKind, EventReceiver, EventWrapper,
     VersionedEventWrapper,
 };
 use crate::fight::ApplyImpact;
-use crate::player::PlayerId;
-use crate::round::{EndRound, ResetRounds, StartRound};
-use crate::space::{MoveEntity, ResetSpace};
-use crate::team::{ConcludeObjectives, CreateTeam, RemoveTeam, ResetObjectives, SetRelations};
+use crate::metric::{Metric, MetricIdType, Metrics};
+use crate::object::{CreateObject, Object, ObjectId, RemoveObject};
+use crate::player::{GrantRights, PlayerId, RevokeRights};
+use crate::round::{
+    EndRound, EndRoundFor, ResetRounds, RoundStateType, RoundsModel, StartRound, StartTurnCycle,
+};
+use crate::space::{MoveEntity, Position, ResetSpace, SpaceModel};
+use crate::team::{
+    ConcludeObjectives, Conclusion, CreateTeam, Objectives, ObjectivesProgress, OverwriteRelations,
+    Relation, RelationshipPair, RemoveTeam, RenameTeam, ResetObjectives, SetDirectedRelations,
+    SetRelations, Team, TeamId,
+};
 use crate::user::{UserEventPackage, UserEventPacker};
+use crate::util::Id;
 use serde::{Deserialize, Serialize};
 
 /// Macro to panic on incorrect cast.
@@ -122,6 +147,22 @@ flat_event! {
     RemoveTeam, "RemoveTeam<R>: Serialize", "RemoveTeam<R>: Deserialize<'de>",
     RegenerateStatistics, "RegenerateStatistics<R>: Serialize", "RegenerateStatistics<R>: Deserialize<'de>",
     RegenerateAbilities, "RegenerateAbilities<R>: Serialize", "RegenerateAbilities<R>: Deserialize<'de>",
+    SetDirectedRelations, "SetDirectedRelations<R>: Serialize", "SetDirectedRelations<R>: Deserialize<'de>",
+    RenameTeam, "RenameTeam<R>: Serialize", "RenameTeam<R>: Deserialize<'de>",
+    AlterStatisticsBatch, "AlterStatisticsBatch<R>: Serialize", "AlterStatisticsBatch<R>: Deserialize<'de>",
+    EndRoundFor, "EndRoundFor<R>: Serialize", "EndRoundFor<R>: Deserialize<'de>",
+    StartTurnCycle, "StartTurnCycle<R>: Serialize", "StartTurnCycle<R>: Deserialize<'de>",
+    RestoreEntropy, "RestoreEntropy<R>: Serialize", "RestoreEntropy<R>: Deserialize<'de>",
+    ClearAbilities, "ClearAbilities<R>: Serialize", "ClearAbilities<R>: Deserialize<'de>",
+    SetCreatureDisabled, "SetCreatureDisabled<R>: Serialize", "SetCreatureDisabled<R>: Deserialize<'de>",
+    CreateCreatures, "CreateCreatures<R>: Serialize", "CreateCreatures<R>: Deserialize<'de>",
+    GrantRights, "GrantRights<R>: Serialize", "GrantRights<R>: Deserialize<'de>",
+    RevokeRights, "RevokeRights<R>: Serialize", "RevokeRights<R>: Deserialize<'de>",
+    RemoveStatistic, "RemoveStatistic<R>: Serialize", "RemoveStatistic<R>: Deserialize<'de>",
+    AddStatistic, "AddStatistic<R>: Serialize", "AddStatistic<R>: Deserialize<'de>",
+    CreateObject, "CreateObject<R>: Serialize", "CreateObject<R>: Deserialize<'de>",
+    RemoveObject, "RemoveObject<R>: Serialize", "RemoveObject<R>: Deserialize<'de>",
+    OverwriteRelations, "OverwriteRelations<R>: Serialize", "OverwriteRelations<R>: Deserialize<'de>",
 }
 
 /// A versioned event wrapper containing a flattened event.
@@ -142,6 +183,10 @@ pub struct FlatVersionedEvent<R: BattleRules> {
         deserialize = "Version<R>: Deserialize<'de>"
     ))]
     version: Version<R>,
+
+    note: Option<String>,
+
+    player: Option<PlayerId>,
 }
 
 impl<R: BattleRules + 'static> From<VersionedEventWrapper<R>> for FlatVersionedEvent<R> {
@@ -149,6 +194,8 @@ impl<R: BattleRules + 'static> From<VersionedEventWrapper<R>> for FlatVersionedE
         FlatVersionedEvent {
             id: event.wrapper().id,
             origin: event.wrapper().origin,
+            note: event.wrapper.note,
+            player: event.wrapper.player,
             event: FlatEvent::flattened(event.wrapper.event),
             version: event.version,
         }
@@ -157,11 +204,91 @@ impl<R: BattleRules + 'static> From<VersionedEventWrapper<R>> for FlatVersionedE
 
 impl<R: BattleRules + 'static> From<FlatVersionedEvent<R>> for VersionedEventWrapper<R> {
     fn from(event: FlatVersionedEvent<R>) -> Self {
-        VersionedEventWrapper::new(
-            EventWrapper::new(event.id, event.origin, event.event.boxed()),
-            event.version,
-        )
+        let mut wrapper = EventWrapper::new(event.id, event.origin, event.event.boxed());
+        wrapper.note = event.note;
+        wrapper.player = event.player;
+        VersionedEventWrapper::new(wrapper, event.version)
+    }
+}
+
+/// Converts any serialization error into a `WeaselError`.
+fn serialization_error<R: BattleRules, T: ToString>(err: T) -> WeaselErrorType<R> {
+    WeaselError::SerializationError(err.to_string())
+}
+
+impl<R: BattleRules + 'static> VersionedEventWrapper<R> {
+    /// Serializes this event into the binary format described in the
+    /// [module documentation](index.html#binary-format).
+    pub fn to_bytes(&self) -> WeaselResult<Vec<u8>, R>
+    where
+        FlatVersionedEvent<R>: Serialize,
+    {
+        let flat: FlatVersionedEvent<R> = self.clone().into();
+        bincode::serialize(&flat).map_err(serialization_error)
+    }
+
+    /// Creates an event from bytes produced by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> WeaselResult<VersionedEventWrapper<R>, R>
+    where
+        FlatVersionedEvent<R>: for<'de> Deserialize<'de>,
+    {
+        let flat: FlatVersionedEvent<R> =
+            bincode::deserialize(bytes).map_err(serialization_error)?;
+        Ok(flat.into())
+    }
+
+    /// Serializes this event into a compact binary format, then compresses it with LZ4.
+    #[cfg(feature = "compression")]
+    pub fn to_compressed_bytes(&self) -> WeaselResult<Vec<u8>, R>
+    where
+        FlatVersionedEvent<R>: Serialize,
+    {
+        Ok(lz4_flex::compress_prepend_size(&self.to_bytes()?))
+    }
+
+    /// Creates an event from bytes produced by `to_compressed_bytes`.
+    #[cfg(feature = "compression")]
+    pub fn from_compressed_bytes(bytes: &[u8]) -> WeaselResult<VersionedEventWrapper<R>, R>
+    where
+        FlatVersionedEvent<R>: for<'de> Deserialize<'de>,
+    {
+        let bytes = lz4_flex::decompress_size_prepended(bytes).map_err(serialization_error)?;
+        VersionedEventWrapper::from_bytes(&bytes)
+    }
+}
+
+impl<R: BattleRules + 'static> Battle<R> {
+    /// Serializes the whole history of this battle into the binary format described in the
+    /// [module documentation](index.html#binary-format).
+    ///
+    /// Use `load_history_bytes` to restore the history.
+    pub fn history_to_bytes(&self) -> WeaselResult<Vec<u8>, R>
+    where
+        FlatVersionedEvent<R>: Serialize,
+    {
+        let events: Vec<FlatVersionedEvent<R>> = self
+            .versioned_events(0..self.history().len() as usize)
+            .map(|e| e.into())
+            .collect();
+        bincode::serialize(&events).map_err(serialization_error)
+    }
+}
+
+/// Loads a history produced by `Battle::history_to_bytes` into an event receiver.
+///
+/// Events are received one by one. Loading stops at the first error.
+pub fn load_history_bytes<R, T>(receiver: &mut T, bytes: &[u8]) -> WeaselResult<(), R>
+where
+    R: BattleRules + 'static,
+    T: EventReceiver<R>,
+    FlatVersionedEvent<R>: for<'de> Deserialize<'de>,
+{
+    let events: Vec<FlatVersionedEvent<R>> =
+        bincode::deserialize(bytes).map_err(serialization_error)?;
+    for event in events {
+        receiver.receive(event.into())?;
     }
+    Ok(())
 }
 
 /// A versioned client event containing a flattened event.
@@ -183,6 +310,8 @@ pub struct FlatClientEvent<R: BattleRules> {
     version: Version<R>,
 
     player: Option<PlayerId>,
+
+    note: Option<String>,
 }
 
 impl<R: BattleRules + 'static> From<ClientEventPrototype<R>> for FlatClientEvent<R> {
@@ -190,6 +319,7 @@ impl<R: BattleRules + 'static> From<ClientEventPrototype<R>> for FlatClientEvent
         let player = event.player();
         FlatClientEvent {
             origin: event.origin(),
+            note: event.note,
             event: FlatEvent::flattened(event.event),
             version: event.version,
             player,
@@ -199,11 +329,365 @@ impl<R: BattleRules + 'static> From<ClientEventPrototype<R>> for FlatClientEvent
 
 impl<R: BattleRules + 'static> From<FlatClientEvent<R>> for ClientEventPrototype<R> {
     fn from(event: FlatClientEvent<R>) -> Self {
-        ClientEventPrototype::new(
+        let mut prototype = ClientEventPrototype::new(
             event.origin,
             event.event.boxed(),
             event.version,
             event.player,
-        )
+        );
+        prototype.note = event.note;
+        prototype
+    }
+}
+
+/// A creature, as stored inside a `BattleSnapshot`.
+#[derive(Serialize, Deserialize)]
+struct CreatureSnapshot<R: BattleRules> {
+    #[serde(bound(
+        serialize = "CreatureId<R>: Serialize",
+        deserialize = "CreatureId<R>: Deserialize<'de>"
+    ))]
+    id: CreatureId<R>,
+
+    #[serde(bound(
+        serialize = "TeamId<R>: Serialize",
+        deserialize = "TeamId<R>: Deserialize<'de>"
+    ))]
+    team_id: TeamId<R>,
+
+    #[serde(bound(
+        serialize = "Position<R>: Serialize",
+        deserialize = "Position<R>: Deserialize<'de>"
+    ))]
+    position: Position<R>,
+
+    #[serde(bound(
+        serialize = "Statistic<R>: Serialize",
+        deserialize = "Statistic<R>: Deserialize<'de>"
+    ))]
+    statistics: Vec<Statistic<R>>,
+
+    #[serde(bound(
+        serialize = "Ability<R>: Serialize",
+        deserialize = "Ability<R>: Deserialize<'de>"
+    ))]
+    abilities: Vec<Ability<R>>,
+
+    #[serde(bound(
+        serialize = "AbilityId<R>: Serialize",
+        deserialize = "AbilityId<R>: Deserialize<'de>"
+    ))]
+    cooldowns: Vec<(AbilityId<R>, u32)>,
+
+    disabled: bool,
+}
+
+/// An object, as stored inside a `BattleSnapshot`.
+#[derive(Serialize, Deserialize)]
+struct ObjectSnapshot<R: BattleRules> {
+    #[serde(bound(
+        serialize = "ObjectId<R>: Serialize",
+        deserialize = "ObjectId<R>: Deserialize<'de>"
+    ))]
+    id: ObjectId<R>,
+
+    #[serde(bound(
+        serialize = "Position<R>: Serialize",
+        deserialize = "Position<R>: Deserialize<'de>"
+    ))]
+    position: Position<R>,
+
+    #[serde(bound(
+        serialize = "Statistic<R>: Serialize",
+        deserialize = "Statistic<R>: Deserialize<'de>"
+    ))]
+    statistics: Vec<Statistic<R>>,
+}
+
+/// A team, as stored inside a `BattleSnapshot`.
+#[derive(Serialize, Deserialize)]
+struct TeamSnapshot<R: BattleRules> {
+    #[serde(bound(
+        serialize = "TeamId<R>: Serialize",
+        deserialize = "TeamId<R>: Deserialize<'de>"
+    ))]
+    id: TeamId<R>,
+
+    #[serde(bound(
+        serialize = "CreatureId<R>: Serialize",
+        deserialize = "CreatureId<R>: Deserialize<'de>"
+    ))]
+    creatures: Vec<CreatureId<R>>,
+
+    conclusion: Option<Conclusion>,
+
+    #[serde(bound(
+        serialize = "Objectives<R>: Serialize",
+        deserialize = "Objectives<R>: Deserialize<'de>"
+    ))]
+    objectives: Objectives<R>,
+
+    #[serde(bound(
+        serialize = "ObjectivesProgress<R>: Serialize",
+        deserialize = "ObjectivesProgress<R>: Deserialize<'de>"
+    ))]
+    objectives_progress: ObjectivesProgress<R>,
+}
+
+/// A snapshot of the current state of a battle.
+/// Use this struct to save a battle and restore it later, without replaying its history.
+///
+/// A snapshot contains:
+/// - teams, with their objectives and conclusions
+/// - creatures, with their position, statistics, abilities and cooldowns
+/// - objects, with their position and statistics
+/// - relations and directed relations between teams
+/// - the space model
+/// - the rounds state, model and turn order
+/// - the entropy model
+/// - the battle phase and the outcome decided by the rules, if any
+/// - metrics
+/// - players' rights
+///
+/// The history is not part of a snapshot. A battle restored
+/// from a snapshot starts with an empty history, thus its clients must be restored
+/// from the same snapshot, through `BattleBuilder::snapshot`.
+///
+/// Snapshots can be serialized only if all the types stored inside them,
+/// such as `Statistic`, `SpaceModel`, `RoundsModel` and `UserMetricId`, are serializable as well.
+#[derive(Serialize, Deserialize)]
+pub struct BattleSnapshot<R: BattleRules> {
+    #[serde(bound(
+        serialize = "TeamSnapshot<R>: Serialize",
+        deserialize = "TeamSnapshot<R>: Deserialize<'de>"
+    ))]
+    teams: Vec<TeamSnapshot<R>>,
+
+    #[serde(bound(
+        serialize = "CreatureSnapshot<R>: Serialize",
+        deserialize = "CreatureSnapshot<R>: Deserialize<'de>"
+    ))]
+    creatures: Vec<CreatureSnapshot<R>>,
+
+    #[serde(bound(
+        serialize = "ObjectSnapshot<R>: Serialize",
+        deserialize = "ObjectSnapshot<R>: Deserialize<'de>"
+    ))]
+    objects: Vec<ObjectSnapshot<R>>,
+
+    #[serde(bound(
+        serialize = "TeamId<R>: Serialize",
+        deserialize = "TeamId<R>: Deserialize<'de>"
+    ))]
+    relations: Vec<(TeamId<R>, TeamId<R>, Relation)>,
+
+    #[serde(bound(
+        serialize = "TeamId<R>: Serialize",
+        deserialize = "TeamId<R>: Deserialize<'de>"
+    ))]
+    directed_relations: Vec<(TeamId<R>, TeamId<R>, Relation)>,
+
+    #[serde(bound(
+        serialize = "SpaceModel<R>: Serialize",
+        deserialize = "SpaceModel<R>: Deserialize<'de>"
+    ))]
+    space: SpaceModel<R>,
+
+    #[serde(bound(
+        serialize = "RoundStateType<R>: Serialize",
+        deserialize = "RoundStateType<R>: Deserialize<'de>"
+    ))]
+    round_state: RoundStateType<R>,
+
+    #[serde(bound(
+        serialize = "RoundsModel<R>: Serialize",
+        deserialize = "RoundsModel<R>: Deserialize<'de>"
+    ))]
+    rounds: RoundsModel<R>,
+
+    #[serde(bound(
+        serialize = "EntityId<R>: Serialize",
+        deserialize = "EntityId<R>: Deserialize<'de>"
+    ))]
+    order: Vec<EntityId<R>>,
+
+    #[serde(bound(
+        serialize = "EntropyModel<R>: Serialize",
+        deserialize = "EntropyModel<R>: Deserialize<'de>"
+    ))]
+    entropy: EntropyModel<R>,
+
+    phase: BattlePhase,
+
+    #[serde(bound(
+        serialize = "TeamId<R>: Serialize",
+        deserialize = "TeamId<R>: Deserialize<'de>"
+    ))]
+    outcome: Option<Vec<(TeamId<R>, Conclusion)>>,
+
+    #[serde(bound(
+        serialize = "MetricIdType<R>: Serialize",
+        deserialize = "MetricIdType<R>: Deserialize<'de>"
+    ))]
+    metrics: Vec<(MetricIdType<R>, Metric)>,
+
+    #[serde(bound(
+        serialize = "TeamId<R>: Serialize",
+        deserialize = "TeamId<R>: Deserialize<'de>"
+    ))]
+    rights: Vec<(PlayerId, Vec<TeamId<R>>)>,
+}
+
+impl<R: BattleRules + 'static> BattleSnapshot<R> {
+    /// Takes a snapshot of the current state of `battle`.
+    pub fn new(battle: &Battle<R>) -> BattleSnapshot<R>
+    where
+        Ability<R>: Clone,
+        Objectives<R>: Clone,
+        ObjectivesProgress<R>: Clone,
+        SpaceModel<R>: Clone,
+        RoundsModel<R>: Clone,
+    {
+        let entities = battle.entities();
+        let teams = entities
+            .teams()
+            .map(|team| TeamSnapshot {
+                id: team.id.clone(),
+                creatures: team.creatures.clone(),
+                conclusion: team.conclusion,
+                objectives: team.objectives.clone(),
+                objectives_progress: team.objectives_progress.clone(),
+            })
+            .collect();
+        let creatures = entities
+            .creatures()
+            .map(|creature| CreatureSnapshot {
+                id: creature.id().clone(),
+                team_id: creature.team_id.clone(),
+                position: creature.position.clone(),
+                statistics: creature.statistics.values().cloned().collect(),
+                abilities: creature.abilities.values().cloned().collect(),
+                cooldowns: creature
+                    .cooldowns
+                    .iter()
+                    .map(|(id, cooldown)| (id.clone(), *cooldown))
+                    .collect(),
+                disabled: creature.disabled,
+            })
+            .collect();
+        let objects = entities
+            .objects()
+            .map(|object| ObjectSnapshot {
+                id: object.id().clone(),
+                position: object.position.clone(),
+                statistics: object.statistics.values().cloned().collect(),
+            })
+            .collect();
+        BattleSnapshot {
+            teams,
+            creatures,
+            objects,
+            relations: entities
+                .relations()
+                .map(|(first, second, relation)| (first.clone(), second.clone(), relation))
+                .collect(),
+            directed_relations: entities
+                .directed_relations
+                .iter()
+                .map(|((first, second), relation)| (first.clone(), second.clone(), *relation))
+                .collect(),
+            space: battle.space().model().clone(),
+            round_state: battle.rounds().state().clone(),
+            rounds: battle.rounds().model().clone(),
+            order: battle.rounds().order().to_vec(),
+            entropy: battle.entropy().state(),
+            phase: battle.phase(),
+            outcome: battle.outcome.as_ref().map(|outcome| {
+                outcome
+                    .conclusions()
+                    .map(|(team, conclusion)| (team.clone(), conclusion))
+                    .collect()
+            }),
+            metrics: battle
+                .metrics
+                .entries()
+                .map(|(id, metric)| (id.clone(), *metric))
+                .collect(),
+            rights: battle
+                .rights()
+                .get()
+                .map(|(player, teams)| (player, teams.to_vec()))
+                .collect(),
+        }
+    }
+}
+
+impl<R: BattleRules> BattleSnapshot<R> {
+    /// Replaces the state of a newly created `battle` with the content of this snapshot.
+    pub(crate) fn restore(self, battle: &mut Battle<R>) {
+        let entities = &mut battle.state.entities;
+        for team in self.teams {
+            entities.add_team(Team {
+                id: team.id,
+                creatures: team.creatures,
+                conclusion: team.conclusion,
+                objectives: team.objectives,
+                objectives_progress: team.objectives_progress,
+            });
+        }
+        for creature in self.creatures {
+            let id = creature.id;
+            entities.creatures.insert(
+                id.clone(),
+                Creature {
+                    id: EntityId::Creature(id),
+                    team_id: creature.team_id,
+                    position: creature.position,
+                    statistics: creature
+                        .statistics
+                        .into_iter()
+                        .map(|statistic| (statistic.id().clone(), statistic))
+                        .collect(),
+                    abilities: creature
+                        .abilities
+                        .into_iter()
+                        .map(|ability| (ability.id().clone(), ability))
+                        .collect(),
+                    cooldowns: creature.cooldowns.into_iter().collect(),
+                    disabled: creature.disabled,
+                },
+            );
+        }
+        for object in self.objects {
+            entities.add_object(Object {
+                id: EntityId::Object(object.id),
+                position: object.position,
+                statistics: object
+                    .statistics
+                    .into_iter()
+                    .map(|statistic| (statistic.id().clone(), statistic))
+                    .collect(),
+            });
+        }
+        entities.update_relations(
+            self.relations
+                .into_iter()
+                .map(|(first, second, relation)| (RelationshipPair::new(first, second), relation))
+                .collect(),
+        );
+        entities.update_directed_relations(self.directed_relations);
+        battle.state.space.model = self.space;
+        battle.state.rounds.set_state(self.round_state);
+        battle.state.rounds.model = self.rounds;
+        battle.state.rounds.order = self.order;
+        battle.entropy.restore_state(self.entropy);
+        battle.state.phase = self.phase;
+        battle.outcome = self.outcome.map(BattleOutcome::new);
+        battle.metrics = Metrics::with_entries(self.metrics);
+        for (player, teams) in self.rights {
+            for team in teams {
+                battle.rights.add(player, &team);
+            }
+        }
     }
 }
@@ -1,14 +1,28 @@
 //! A battle server.
 
-use crate::battle::{Battle, BattleRules, EventCallback};
+use crate::ability::Ability;
+use crate::battle::{Battle, BattleRules, BattleState, EndBattle, EventCallback, HasBattleState};
+use crate::client::RulesFactory;
 use crate::error::{WeaselError, WeaselResult};
 use crate::event::{
-    ClientEventPrototype, EventProcessor, EventPrototype, EventQueue, EventReceiver, EventRights,
-    EventServer, EventWrapper, MultiClientSink, MultiClientSinkHandle, MultiClientSinkHandleMut,
-    VersionedEventWrapper,
+    ClientEventPrototype, Event, EventKind, EventProcessor, EventPrototype, EventQueue,
+    EventReceiver, EventRights, EventServer, EventTrigger, EventWrapper, MultiClientSink,
+    MultiClientSinkHandle, MultiClientSinkHandleMut, OutputProcessor, VersionedEventWrapper,
 };
+use crate::metric::system::TEAM_CREATURES;
 use crate::player::{RightsHandle, RightsHandleMut};
-use crate::team::TeamId;
+use crate::round::{EndRound, RoundState, RoundsModel};
+#[cfg(feature = "serialization")]
+use crate::serde::BattleSnapshot;
+use crate::space::SpaceModel;
+use crate::team::{Objectives, ObjectivesProgress, TeamId};
+use crate::util::Id;
+
+/// Type to define an observer of the events applied by a server.
+///
+/// Observers have read only access to the event and to the state of the battle,
+/// taken just after the event has been applied.
+pub type EventObserver<R> = Box<dyn Fn(&dyn Event<R>, &BattleState<R>)>;
 
 /// The server is the main object used to orchestrate a battle.
 ///
@@ -21,6 +35,10 @@ pub struct Server<R: BattleRules> {
     pub(crate) battle: Battle<R>,
     client_sinks: MultiClientSink<R>,
     authentication: bool,
+    auto_end_round: Option<u32>,
+    actions: u32,
+    observers: Vec<EventObserver<R>>,
+    rules_factory: Option<RulesFactory<R>>,
 }
 
 impl<R: BattleRules + 'static> Server<R> {
@@ -29,7 +47,83 @@ impl<R: BattleRules + 'static> Server<R> {
         ServerBuilder {
             battle,
             authentication: false,
+            auto_end_round: None,
+            rules_factory: None,
+        }
+    }
+
+    /// Creates a new server and applies to its battle all `events`, in order.
+    ///
+    /// Events go through the same verification as received events.
+    /// Returns an error as soon as one event is inconsistent with the battle.
+    pub fn replay<I>(rules: R, events: I) -> WeaselResult<Server<R>, R>
+    where
+        I: Iterator<Item = VersionedEventWrapper<R>>,
+    {
+        let battle = Battle::builder(rules).build();
+        let mut server = Server::builder(battle).build();
+        for event in events {
+            server.receive(event)?;
+        }
+        Ok(server)
+    }
+
+    /// Creates a new server whose battle is restored from `snapshot`.
+    ///
+    /// The history of the battle will be empty.
+    #[cfg(feature = "serialization")]
+    pub fn from_snapshot(rules: R, snapshot: BattleSnapshot<R>) -> Server<R> {
+        let battle = Battle::builder(rules).snapshot(snapshot).build();
+        Server::builder(battle).build()
+    }
+
+    /// Reverts the most recent event in the history.
+    ///
+    /// Events can't be inverted, so the battle is rebuilt by replaying the whole history
+    /// but the last event, starting from a new battle created with `rules`.
+    /// The cost of this operation is thus linear in the length of the history.\
+    /// Metrics and players' rights are kept, except for the changes done by the reverted event.
+    /// The server's settings, client sinks, observers and the event callback are kept as well.
+    /// Client sinks aren't notified and must be resynchronized, if needed.
+    ///
+    /// Returns an error if the history is empty or if the battle was restored from a snapshot,
+    /// since in this case the history doesn't contain all the events to replay.
+    pub fn undo_last(&mut self, rules: R) -> WeaselResult<(), R> {
+        if self.battle.history().is_empty() {
+            return Err(WeaselError::EmptyHistory);
         }
+        let mut battle = self
+            .battle
+            .rewind(rules)
+            .ok_or(WeaselError::IncompleteHistory)?;
+        battle.event_callback = self.battle.event_callback.take();
+        self.battle = battle;
+        self.actions = self.actions_in_round();
+        Ok(())
+    }
+
+    /// Creates an independent copy of this server, which can be evolved separately
+    /// to explore an alternative timeline.
+    ///
+    /// `BattleRules` aren't required to implement `Clone`, hence the new instance of the
+    /// rules taken as `rules`, which must be identical to the ones of this server.
+    /// The battle's state, history, entropy, metrics and players' rights are copied
+    /// as they are, together with the server's settings, rules factory included.
+    /// Client sinks, observers and the event callback are not.
+    pub fn fork(&self, rules: R) -> Server<R>
+    where
+        Ability<R>: Clone,
+        Objectives<R>: Clone,
+        ObjectivesProgress<R>: Clone,
+        SpaceModel<R>: Clone,
+        RoundsModel<R>: Clone,
+    {
+        let mut server = Server::builder(self.battle.duplicate(rules)).build();
+        server.authentication = self.authentication;
+        server.auto_end_round = self.auto_end_round;
+        server.actions = self.actions;
+        server.rules_factory = self.rules_factory.clone();
+        server
     }
 
     /// Returns a reference to the battle.
@@ -37,18 +131,42 @@ impl<R: BattleRules + 'static> Server<R> {
         &self.battle
     }
 
+    /// Removes all metrics of this server's battle.
+    ///
+    /// Metrics are not part of the history, thus they aren't reset on clients.\
+    /// See [clear_all](../metric/struct.WriteMetrics.html#method.clear_all) to know how
+    /// clearing affects the counters. The only exception is `TEAM_CREATURES`, which is
+    /// rebuilt from the current teams since it describes the state of the battle.
+    pub fn reset_metrics(&mut self) {
+        let mut metrics = self.battle.metrics.write_handle();
+        metrics.clear_all();
+        for team in self.battle.state.entities.teams() {
+            metrics
+                .add_team_u64(team.id(), TEAM_CREATURES, team.creatures().count() as u64)
+                .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
+        }
+    }
+
     /// Returns true if the client events authentication is enforced.
     pub fn authentication(&self) -> bool {
         self.authentication
     }
 
+    /// Returns the number of abilities activations after which the server ends
+    /// the current round, if set.
+    pub fn auto_end_round(&self) -> Option<u32> {
+        self.auto_end_round
+    }
+
     /// Returns a handle to access the players' rights to control one or more teams.
-    pub fn rights(&self) -> RightsHandle<R> {
+    pub fn rights(&self) -> RightsHandle<'_, R> {
         self.battle.rights()
     }
 
     /// Returns a mutable handle to manage the players' rights to control one or more teams.
-    pub fn rights_mut<'a>(&'a mut self) -> RightsHandleMut<R, impl Iterator<Item = &'a TeamId<R>>> {
+    pub fn rights_mut<'a>(
+        &'a mut self,
+    ) -> RightsHandleMut<'a, R, impl Iterator<Item = &'a TeamId<R>>> {
         self.battle.rights_mut()
     }
 
@@ -73,11 +191,98 @@ impl<R: BattleRules + 'static> Server<R> {
         self.battle.event_callback = callback;
     }
 
+    /// Registers a new observer, invoked each time an event is applied.
+    ///
+    /// Observers are invoked in the same order in which they were added.
+    pub fn add_observer(&mut self, observer: EventObserver<R>) {
+        self.observers.push(observer);
+    }
+
+    /// Invokes all observers for an applied event.
+    fn notify_observers(&self, event: &dyn Event<R>) {
+        for observer in &self.observers {
+            observer(event, self.battle.state());
+        }
+    }
+
+    /// Checks whether an event would be accepted by this server, without applying it.
+    ///
+    /// The event goes through the same verification performed when it's fired,
+    /// but neither the battle nor its history are modified.
+    pub fn check_event(&self, event: &dyn Event<R>) -> WeaselResult<(), R> {
+        self.battle
+            .verify_event(event)
+            .map_err(|e| WeaselError::InvalidEvent(event.box_clone(), e.into()))
+    }
+
+    /// Returns a copy of this server, without sinks and observers, on which events can be
+    /// processed without altering the real battle.
+    ///
+    /// Returns `None` if there's no rules factory or if the battle can't be copied.
+    fn scratch(&self) -> Option<Server<R>> {
+        let rules = (self.rules_factory.as_ref()?)();
+        let mut server = Server::builder(self.battle.replica(rules)?).build();
+        server.auto_end_round = self.auto_end_round;
+        server.actions = self.actions;
+        Some(server)
+    }
+
+    /// Returns the number of abilities activated in the current round, according to the history.
+    fn actions_in_round(&self) -> u32 {
+        if *self.battle.rounds().state() == RoundState::Ready {
+            return 0;
+        }
+        self.battle
+            .history()
+            .events()
+            .iter()
+            .rev()
+            .take_while(|event| event.kind() != EventKind::StartRound)
+            .filter(|event| event.kind() == EventKind::ActivateAbility)
+            .count() as u32
+    }
+
+    /// Verifies and applies an event, together with the events derived from it.
+    fn process_event(&mut self, event: EventPrototype<R>) -> WeaselResult<(), R> {
+        // Verify this event.
+        self.battle
+            .verify_prototype(&event)
+            .map_err(|e| WeaselError::InvalidEvent(event.event().clone(), e.into()))?;
+        // Promote verified event.
+        let event = self.battle.promote(event);
+        // Apply it.
+        self.apply_event(event)
+    }
+
+    /// Ends the battle if the objectives checks run by the events processed from `start`
+    /// onwards gave a conclusion to all teams.\
+    /// Returns `result`, or the error of `EndBattle` if it failed.
+    fn end_concluded_battle(
+        &mut self,
+        start: usize,
+        result: WeaselResult<(), R>,
+    ) -> WeaselResult<(), R> {
+        if self.battle.concluded_since(start) {
+            result.and(EndBattle::trigger(self).fire())
+        } else {
+            result
+        }
+    }
+
     /// Applies an event. The event must be valid.
     fn apply_event(&mut self, event: EventWrapper<R>) -> WeaselResult<(), R> {
         let mut event_queue = Some(EventQueue::<R>::new());
         // Apply the event on the battle.
         self.battle.apply(&event, &mut event_queue);
+        self.notify_observers(event.as_ref());
+        // Count the actions done in the current round.
+        let activation = event.kind() == EventKind::ActivateAbility;
+        if activation {
+            self.actions += 1;
+        }
+        if *self.battle.rounds().state() == RoundState::Ready {
+            self.actions = 0;
+        }
         // Send the event to all client sinks.
         self.client_sinks
             .send_all(&event.clone().version(self.battle.rules().version().clone()));
@@ -87,12 +292,21 @@ impl<R: BattleRules + 'static> Server<R> {
             for mut prototype in event_queue {
                 // Set origin id in derived event.
                 prototype.origin = Some(event.id);
-                let result = self.process(prototype);
+                let result = self.process_event(prototype);
                 if let Err(error) = result {
                     errors.push(error);
                 }
             }
         }
+        // End the round if the actor has done enough actions.
+        if activation
+            && self.auto_end_round.is_some_and(|max| self.actions >= max)
+            && *self.battle.rounds().state() != RoundState::Ready
+        {
+            if let Err(error) = EndRound::trigger(self).fire() {
+                errors.push(error);
+            }
+        }
         // If there is an error, return it.
         // In the case of multiple errors, wrap them into a multi error.
         match errors.len() {
@@ -106,20 +320,68 @@ impl<R: BattleRules + 'static> Server<R> {
 impl<R: BattleRules + 'static> EventProcessor<R> for Server<R> {
     type ProcessOutput = WeaselResult<(), R>;
 
+    /// Processes an event and all events derived from it.
+    ///
+    /// Once they are done, the battle is ended with an `EndBattle` event if the objectives
+    /// checks gave a conclusion to every team.
     fn process(&mut self, event: EventPrototype<R>) -> Self::ProcessOutput {
-        // Verify this event.
-        self.battle
-            .verify_prototype(&event)
-            .map_err(|e| WeaselError::InvalidEvent(event.event().clone(), e.into()))?;
-        // Promote verified event.
-        let event = self.battle.promote(event);
-        // Apply it.
-        self.apply_event(event)
+        let start = self.battle.history().len() as usize;
+        let result = self.process_event(event);
+        self.end_concluded_battle(start, result)
+    }
+
+    /// Processes a sequence of events, in order.
+    ///
+    /// If the server has a rules factory, the events are first processed on a copy of the
+    /// battle. Thus, each event is verified against the state left by the previous ones and
+    /// nothing is applied unless all events, including the derived ones, succeed.
+    /// Building the copy has a cost linear in the length of the history.
+    ///
+    /// Otherwise, or if the battle was restored from a snapshot, all events are only verified
+    /// against the current state before processing the first one. Sequences in which an event
+    /// depends on the previous ones, such as creating a team and then a creature in it,
+    /// are rejected. Moreover, the sequence is not atomic: if an event or one of its derived
+    /// events fails once the previous ones are applied, these are not reverted.
+    fn fire_all(&mut self, events: Vec<Box<dyn Event<R>>>) -> Self::ProcessOutput {
+        if let Some(mut scratch) = self.scratch() {
+            // Try all events on the copy.
+            for event in &events {
+                scratch.process(EventPrototype::new(event.clone()))?;
+            }
+        } else {
+            // Verify all events before applying any of them.
+            for event in &events {
+                self.check_event(event.as_ref())?;
+            }
+        }
+        for event in events {
+            self.process(EventPrototype::new(event))?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: BattleRules + 'static> HasBattleState<R> for Server<R> {
+    fn state(&self) -> &BattleState<R> {
+        self.battle.state()
+    }
+}
+
+impl<R: BattleRules + 'static> OutputProcessor<R> for Server<R> {
+    fn process_with_output(
+        &mut self,
+        event: EventPrototype<R>,
+    ) -> WeaselResult<Vec<EventWrapper<R>>, R> {
+        let start = self.battle.history().len() as usize;
+        self.process(event)?;
+        Ok(self.battle.history().events()[start..].to_vec())
     }
 }
 
 impl<R: BattleRules + 'static> EventServer<R> for Server<R> {
-    fn process_client(&mut self, event: ClientEventPrototype<R>) -> WeaselResult<(), R> {
+    fn process_client(&mut self, mut event: ClientEventPrototype<R>) -> WeaselResult<(), R> {
+        // Upgrade the event if it comes from older rules.
+        self.battle.migrate_client(&mut event);
         // Verify this event.
         self.battle.verify_client(&event)?;
         // Verify event's rights.
@@ -145,19 +407,29 @@ impl<R: BattleRules + 'static> EventServer<R> for Server<R> {
             }
             EventRights::None => {}
         }
+        // Keep track of the player only if its identity has been verified.
+        let mut prototype = event.prototype();
+        if !self.authentication {
+            prototype.player = None;
+        }
         // Promote verified event.
-        let event = self.battle.promote(event.prototype());
+        let event = self.battle.promote(prototype);
         // Apply it.
-        self.apply_event(event)
+        let start = self.battle.history().len() as usize;
+        let result = self.apply_event(event);
+        self.end_concluded_battle(start, result)
     }
 }
 
 impl<R: BattleRules + 'static> EventReceiver<R> for Server<R> {
-    fn receive(&mut self, event: VersionedEventWrapper<R>) -> WeaselResult<(), R> {
+    fn receive(&mut self, mut event: VersionedEventWrapper<R>) -> WeaselResult<(), R> {
+        // Upgrade the event if it comes from older rules.
+        self.battle.migrate_wrapper(&mut event);
         // Verify the event.
         self.battle.verify_wrapper(&event)?;
         // Apply the event on the battle.
-        self.battle.apply(&event.wrapper(), &mut None);
+        self.battle.apply(event.wrapper(), &mut None);
+        self.notify_observers(event.wrapper().as_ref());
         // Send the event to all client sinks.
         self.client_sinks.send_all(&event);
         Ok(())
@@ -168,6 +440,8 @@ impl<R: BattleRules + 'static> EventReceiver<R> for Server<R> {
 pub struct ServerBuilder<R: BattleRules> {
     battle: Battle<R>,
     authentication: bool,
+    auto_end_round: Option<u32>,
+    rules_factory: Option<RulesFactory<R>>,
 }
 
 impl<R: BattleRules> ServerBuilder<R> {
@@ -178,12 +452,33 @@ impl<R: BattleRules> ServerBuilder<R> {
         self
     }
 
+    /// Automatically fire an `EndRound` event once the acting actor activated
+    /// `max_actions` abilities in the current round.\
+    /// The count restarts whenever a round ends, either automatically or not.
+    pub fn auto_end_round(mut self, max_actions: u32) -> ServerBuilder<R> {
+        self.auto_end_round = Some(max_actions);
+        self
+    }
+
+    /// Sets a function to create new instances of the battle rules. The server uses it
+    /// to build copies of the battle, on which `fire_all` tries events before applying them.
+    ///
+    /// `rules` must return battle rules identical to the ones of the server's battle.
+    pub fn rules_factory(mut self, rules: RulesFactory<R>) -> ServerBuilder<R> {
+        self.rules_factory = Some(rules);
+        self
+    }
+
     /// Creates a new server.
     pub fn build(self) -> Server<R> {
         Server {
             battle: self.battle,
             client_sinks: MultiClientSink::new(),
             authentication: self.authentication,
+            auto_end_round: self.auto_end_round,
+            actions: 0,
+            observers: Vec::new(),
+            rules_factory: self.rules_factory,
         }
     }
 }
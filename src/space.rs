@@ -4,8 +4,9 @@ use crate::battle::Battle;
 use crate::battle::BattleRules;
 use crate::entity::{Entity, EntityId};
 use crate::error::{WeaselError, WeaselResult};
-use crate::event::{Event, EventKind, EventProcessor, EventQueue, EventTrigger};
-use crate::metric::WriteMetrics;
+use crate::event::{Event, EventKind, EventProcessor, EventQueue, EventRights, EventTrigger};
+use crate::metric::{system::ENTITIES_MOVED, WriteMetrics};
+use crate::team::Relation;
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
 use std::any::Any;
@@ -14,7 +15,7 @@ use std::fmt::{Debug, Formatter, Result};
 /// This object takes care of everything related to space and movement in the battle.\
 /// It verifies the consistency of every entity's position.
 pub struct Space<R: BattleRules> {
-    model: SpaceModel<R>,
+    pub(crate) model: SpaceModel<R>,
     rules: R::SR,
 }
 
@@ -36,6 +37,17 @@ impl<R: BattleRules> Space<R> {
         self.rules.check_move(&self.model, entity, position)
     }
 
+    /// See [check_overlap](SpaceRules::check_overlap).
+    pub(crate) fn check_overlap(
+        &self,
+        entity: &dyn Entity<R>,
+        occupant: &dyn Entity<R>,
+        relation: Relation,
+    ) -> bool {
+        self.rules
+            .check_overlap(&self.model, entity, occupant, relation)
+    }
+
     /// See [move_entity](SpaceRules::move_entity).
     pub(crate) fn move_entity(
         &mut self,
@@ -99,6 +111,25 @@ pub trait SpaceRules<R: BattleRules> {
         true
     }
 
+    /// Checks if an entity can move into the position of another entity, `occupant`.
+    ///
+    /// `relation` is the relation of the moving entity's team towards the occupant's team,
+    /// as returned by `Entities::directed_relation`. For instance, rules may let creatures
+    /// pass through their `Kin` while being blocked by an `Enemy`.\
+    /// This check is performed only after `check_move` succeeded, and only between
+    /// actors. Overlaps involving objects must be handled in `check_move`.
+    ///
+    /// The provided implementation accepts every overlap.
+    fn check_overlap(
+        &self,
+        _model: &Self::SpaceModel,
+        _entity: &dyn Entity<R>,
+        _occupant: &dyn Entity<R>,
+        _relation: Relation,
+    ) -> bool {
+        true
+    }
+
     /// Moves an entity into a new position.
     ///
     /// Position's correctness will be validated beforehand with `check_move`.\
@@ -152,6 +183,17 @@ pub type SpaceSeed<R> = <<R as BattleRules>::SR as SpaceRules<R>>::SpaceSeed;
 pub type SpaceModel<R> = <<R as BattleRules>::SR as SpaceRules<R>>::SpaceModel;
 
 /// An event to move an entity from its position to a new one.
+///
+/// While a round is in progress, players can only move the acting entity. Moves fired
+/// by the server or derived from other events, such as a knockback, are not restricted.\
+/// The destination must be accepted by `SpaceRules::check_move`. Moreover, if other entities
+/// occupy the same position, `SpaceRules::check_overlap` decides whether the move is allowed
+/// given the directed relation of the mover's team towards the occupant's team. Directed
+/// relations are used because the question is how the mover regards the occupant.\
+/// Objects don't belong to any team, so `check_overlap` is never invoked when either the
+/// mover or the occupant is an object. This is intended: space rules that want objects to
+/// block other entities should keep track of their positions in the `SpaceModel` and
+/// reject such moves in `check_move`.
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct MoveEntity<R: BattleRules> {
     #[cfg_attr(
@@ -198,6 +240,18 @@ impl<R: BattleRules> MoveEntity<R> {
     }
 }
 
+impl<R: BattleRules + 'static> MoveEntity<R> {
+    /// Verifies that, during a round, the entity being moved is the active actor.
+    pub(crate) fn verify_turn(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        if let Some(actor) = battle.rounds().active_actor() {
+            if *actor != self.id {
+                return Err(WeaselError::ActorNotReady(self.id.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<R: BattleRules> Debug for MoveEntity<R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(
@@ -231,6 +285,27 @@ impl<R: BattleRules + 'static> Event<R> for MoveEntity<R> {
                 self.position.clone(),
             ));
         }
+        // Check entities already occupying the destination.
+        if let Some(team_id) = battle.entities().actor(&self.id).map(|a| a.team_id()) {
+            for occupant in battle
+                .entities()
+                .entities()
+                .filter(|e| *e.entity_id() != self.id && *e.position() == self.position)
+            {
+                let relation = battle
+                    .entities()
+                    .actor(occupant.entity_id())
+                    .and_then(|a| battle.entities().directed_relation(team_id, a.team_id()));
+                if let Some(relation) = relation {
+                    if !battle.space().check_overlap(entity, occupant, relation) {
+                        return Err(WeaselError::PositionOccupied(
+                            self.position.clone(),
+                            occupant.entity_id().clone(),
+                        ));
+                    }
+                }
+            }
+        }
         Ok(())
     }
 
@@ -249,6 +324,12 @@ impl<R: BattleRules + 'static> Event<R> for MoveEntity<R> {
         );
         // Update the entity.
         entity.set_position(self.position.clone());
+        // Update metrics.
+        battle
+            .metrics
+            .write_handle()
+            .add_system_u64(ENTITIES_MOVED, 1)
+            .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
     }
 
     fn kind(&self) -> EventKind {
@@ -262,6 +343,14 @@ impl<R: BattleRules + 'static> Event<R> for MoveEntity<R> {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn rights<'a>(&'a self, battle: &'a Battle<R>) -> EventRights<'a, R> {
+        // Objects don't belong to any team, thus only the server can move them.
+        match battle.state.entities.actor(&self.id) {
+            Some(actor) => EventRights::Team(actor.team_id()),
+            None => EventRights::Server,
+        }
+    }
 }
 
 /// Trigger to build and fire a `DummyEvent` event.
@@ -308,7 +397,7 @@ pub struct ResetSpace<R: BattleRules> {
 
 impl<R: BattleRules> ResetSpace<R> {
     /// Returns a trigger for this event.
-    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> ResetSpaceTrigger<R, P> {
+    pub fn trigger<P: EventProcessor<R>>(processor: &mut P) -> ResetSpaceTrigger<'_, R, P> {
         ResetSpaceTrigger {
             processor,
             seed: None,
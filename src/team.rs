@@ -1,17 +1,20 @@
 //! Teams of entities.
 
-use crate::battle::{Battle, BattleRules, BattleState};
-use crate::creature::{Creature, CreatureId};
+use crate::actor::Actor;
+use crate::battle::{Battle, BattlePhase, BattleRules, BattleState};
+use crate::creature::{Creature, CreatureId, RemovalReason, RemoveCreature};
+use crate::entropy::Entropy;
 use crate::error::{WeaselError, WeaselResult};
 use crate::event::{Event, EventKind, EventProcessor, EventQueue, EventTrigger};
 use crate::metric::system::*;
-use crate::metric::ReadMetrics;
+use crate::metric::{MetricsDelta, ReadMetrics, WriteMetrics};
 use crate::util::Id;
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::HashSet;
 use std::fmt::{Debug, Formatter, Result};
 use std::hash::{Hash, Hasher};
-use std::{any::Any, iter};
 
 /// A team is an alliance of entities.
 ///
@@ -19,13 +22,15 @@ use std::{any::Any, iter};
 /// order to win the battle.
 pub struct Team<R: BattleRules> {
     /// The id of this team.
-    id: TeamId<R>,
+    pub(crate) id: TeamId<R>,
     /// Ids of all creatures which are currently part of this team.
-    creatures: Vec<CreatureId<R>>,
+    pub(crate) creatures: Vec<CreatureId<R>>,
     /// `Conclusion`, if any, reached by this team.
-    conclusion: Option<Conclusion>,
+    pub(crate) conclusion: Option<Conclusion>,
     /// Team objectives.
-    objectives: Objectives<R>,
+    pub(crate) objectives: Objectives<R>,
+    /// Latest progress made by the team towards its objectives.
+    pub(crate) objectives_progress: ObjectivesProgress<R>,
 }
 
 impl<R: BattleRules> Team<R> {
@@ -38,6 +43,10 @@ impl<R: BattleRules> Team<R> {
         &mut self.creatures
     }
 
+    pub(crate) fn set_id(&mut self, id: TeamId<R>) {
+        self.id = id;
+    }
+
     /// Returns the conclusion reached by this team, if any.
     pub fn conclusion(&self) -> Option<Conclusion> {
         self.conclusion
@@ -48,6 +57,15 @@ impl<R: BattleRules> Team<R> {
         &self.objectives
     }
 
+    /// Returns the latest progress made by the team towards its objectives.
+    pub fn objectives_progress(&self) -> &ObjectivesProgress<R> {
+        &self.objectives_progress
+    }
+
+    pub(crate) fn set_objectives_progress(&mut self, progress: ObjectivesProgress<R>) {
+        self.objectives_progress = progress;
+    }
+
     /// Removes a creature id from this team.
     ///
     /// # Panics
@@ -63,6 +81,22 @@ impl<R: BattleRules> Team<R> {
     }
 }
 
+impl<R: BattleRules> Clone for Team<R>
+where
+    Objectives<R>: Clone,
+    ObjectivesProgress<R>: Clone,
+{
+    fn clone(&self) -> Self {
+        Team {
+            id: self.id.clone(),
+            creatures: self.creatures.clone(),
+            conclusion: self.conclusion,
+            objectives: self.objectives.clone(),
+            objectives_progress: self.objectives_progress.clone(),
+        }
+    }
+}
+
 impl<R: BattleRules> Id for Team<R> {
     type Id = TeamId<R>;
 
@@ -90,6 +124,9 @@ pub trait TeamRules<R: BattleRules> {
     /// See [Objectives](type.Objectives.html).
     type Objectives: Default;
 
+    /// See [ObjectivesProgress](type.ObjectivesProgress.html).
+    type ObjectivesProgress: Default;
+
     /// Checks if the addition of a new entity in the given team is allowed.
     ///
     /// The provided implementation accepts any new entity.
@@ -102,6 +139,18 @@ pub trait TeamRules<R: BattleRules> {
         true
     }
 
+    /// Returns the relation given to a newly created team towards all existing teams
+    /// for which no explicit relation was provided.
+    ///
+    /// `Relation::Kin` is not a valid default, because kinship is implicit and holds only
+    /// between members of the same team. `CreateTeam` events are rejected if this method
+    /// returns it.
+    ///
+    /// The provided implementation returns `Relation::Enemy`.
+    fn default_relation(&self) -> Relation {
+        Relation::Enemy
+    }
+
     /// Generate the objectives for a team.
     ///
     /// The provided implementation returns `Objectives::default()`.\
@@ -110,29 +159,60 @@ pub trait TeamRules<R: BattleRules> {
         Self::Objectives::default()
     }
 
+    /// Checks if `seed` can be used to generate a team's objectives.
+    /// This check is done while verifying `CreateTeam` and `ResetObjectives` events.
+    ///
+    /// The provided implementation accepts any seed.
+    fn validate_objectives_seed(
+        &self,
+        _state: &BattleState<R>,
+        _seed: &Option<Self::ObjectivesSeed>,
+    ) -> WeaselResult<(), R> {
+        Ok(())
+    }
+
     /// Checks if the team has completed its objectives.
-    /// This check is called after every event.
+    /// This check is called after every event.\
+    /// `delta` contains the changes in metrics caused by the event.
     ///
     /// The provided implementation does not return any conclusion.\
     /// If you set team `Conclusion` manually, you may avoid implementing this method.
     ///
-    /// Returns the `Conclusion` for this team, or none if it did not reach any.
+    /// Returns the `Conclusion` for this team, or none if it did not reach any.\
+    /// Return `Conclusion::Draw` to end the team's objectives in a stalemate.\
+    /// A conclusion is applied through a `ConcludeObjectives` event, thus it's recorded
+    /// in the history like any other change.
     fn check_objectives_on_event(
         &self,
         _state: &BattleState<R>,
         _team: &Team<R>,
         _metrics: &ReadMetrics<R>,
+        _delta: &MetricsDelta<R>,
     ) -> Option<Conclusion> {
         None
     }
 
+    /// Computes how much progress the team has made towards its objectives.
+    /// This method is called after every event and its result is stored in the team.
+    ///
+    /// The provided implementation returns `ObjectivesProgress::default()`.
+    fn objectives_progress(
+        &self,
+        _state: &BattleState<R>,
+        _team: &Team<R>,
+        _metrics: &ReadMetrics<R>,
+    ) -> Self::ObjectivesProgress {
+        Self::ObjectivesProgress::default()
+    }
+
     /// Checks if the team has completed its objectives.
     /// This check is called every time a round ends.
     ///
     /// The provided implementation does not return any conclusion.\
     /// If you set team `Conclusion` manually, you may avoid implementing this method.
     ///
-    /// Returns the `Conclusion` for this team, or none if it did not reach any.
+    /// Returns the `Conclusion` for this team, or none if it did not reach any.\
+    /// Return `Conclusion::Draw` to end the team's objectives in a stalemate.
     fn check_objectives_on_round(
         &self,
         _state: &BattleState<R>,
@@ -141,6 +221,36 @@ pub trait TeamRules<R: BattleRules> {
     ) -> Option<Conclusion> {
         None
     }
+
+    /// Invoked for each team when a new round begins.\
+    /// `actor` is the actor who started the round.
+    ///
+    /// The provided implementation does nothing.
+    fn on_round_start(
+        &self,
+        _state: &BattleState<R>,
+        _team: &Team<R>,
+        _actor: &dyn Actor<R>,
+        _event_queue: &mut Option<EventQueue<R>>,
+        _entropy: &mut Entropy<R>,
+        _metrics: &mut WriteMetrics<R>,
+    ) {
+    }
+
+    /// Invoked for each team when the current round ends.\
+    /// `actor` is the actor whose round is ending.
+    ///
+    /// The provided implementation does nothing.
+    fn on_round_end(
+        &self,
+        _state: &BattleState<R>,
+        _team: &Team<R>,
+        _actor: &dyn Actor<R>,
+        _event_queue: &mut Option<EventQueue<R>>,
+        _entropy: &mut Entropy<R>,
+        _metrics: &mut WriteMetrics<R>,
+    ) {
+    }
 }
 
 /// Type to drive the generation of the objectives for a given team.
@@ -153,10 +263,21 @@ pub type ObjectivesSeed<R> = <<R as BattleRules>::TR as TeamRules<R>>::Objective
 /// The objectives can be checked during the battle to know whether or not a team is victorious.
 pub type Objectives<R> = <<R as BattleRules>::TR as TeamRules<R>>::Objectives;
 
+/// Type to represent the partial progress of a team towards its objectives.
+///
+/// For instance, it might contain the number of enemies defeated so far.
+pub type ObjectivesProgress<R> = <<R as BattleRules>::TR as TeamRules<R>>::ObjectivesProgress;
+
 /// Describes the different scenarios in which an entity might be added to a team.
 pub enum EntityAddition<'a, R: BattleRules> {
     /// Spawn a new creature.
     CreatureSpawn,
+    /// Spawn a new creature as part of a `CreateCreatures` batch.
+    ///
+    /// The value is the number of creatures of the same batch that join the team before
+    /// this one, and it's always greater than zero. Every creature of a batch is also
+    /// checked with `CreatureSpawn`, against the state of the battle before the batch.
+    CreatureBatchSpawn(usize),
     /// Take a creature from another team.
     CreatureConversion(&'a Creature<R>),
 }
@@ -196,14 +317,17 @@ pub struct CreateTeam<R: BattleRules> {
         ))
     )]
     objectives_seed: Option<ObjectivesSeed<R>>,
+
+    /// Optional conclusion the team starts with.
+    conclusion: Option<Conclusion>,
 }
 
 impl<R: BattleRules> Debug for CreateTeam<R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(
             f,
-            "CreateTeam {{ id: {:?}, relations: {:?}, objectives_seed: {:?} }}",
-            self.id, self.relations, self.objectives_seed
+            "CreateTeam {{ id: {:?}, relations: {:?}, objectives_seed: {:?}, conclusion: {:?} }}",
+            self.id, self.relations, self.objectives_seed, self.conclusion
         )
     }
 }
@@ -214,6 +338,7 @@ impl<R: BattleRules> Clone for CreateTeam<R> {
             id: self.id.clone(),
             relations: self.relations.clone(),
             objectives_seed: self.objectives_seed.clone(),
+            conclusion: self.conclusion,
         }
     }
 }
@@ -229,6 +354,7 @@ impl<R: BattleRules> CreateTeam<R> {
             id,
             relations: None,
             objectives_seed: None,
+            conclusion: None,
         }
     }
 
@@ -246,6 +372,11 @@ impl<R: BattleRules> CreateTeam<R> {
     pub fn objectives_seed(&self) -> &Option<ObjectivesSeed<R>> {
         &self.objectives_seed
     }
+
+    /// Returns the conclusion the team starts with.
+    pub fn conclusion(&self) -> Option<Conclusion> {
+        self.conclusion
+    }
 }
 
 impl<R: BattleRules + 'static> Event<R> for CreateTeam<R> {
@@ -265,12 +396,20 @@ impl<R: BattleRules + 'static> Event<R> for CreateTeam<R> {
                     return Err(WeaselError::KinshipRelation);
                 }
                 // Teams in the relations list must exist.
-                if battle.entities().team(&team_id).is_none() {
+                if battle.entities().team(team_id).is_none() {
                     return Err(WeaselError::TeamNotFound(team_id.clone()));
                 }
             }
         }
-        Ok(())
+        // Prevent implicit kinship.
+        if battle.rules.team_rules().default_relation() == Relation::Kin {
+            return Err(WeaselError::KinshipRelation);
+        }
+        // Objectives seed must be valid.
+        battle
+            .rules
+            .team_rules()
+            .validate_objectives_seed(&battle.state, &self.objectives_seed)
     }
 
     fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
@@ -278,11 +417,12 @@ impl<R: BattleRules + 'static> Event<R> for CreateTeam<R> {
         battle.state.entities.add_team(Team {
             id: self.id.clone(),
             creatures: Vec::new(),
-            conclusion: None,
+            conclusion: self.conclusion,
             objectives: battle
                 .rules
                 .team_rules()
                 .generate_objectives(&self.objectives_seed),
+            objectives_progress: ObjectivesProgress::<R>::default(),
         });
         // Unpack explicit relations into a vector.
         let mut relations = if let Some(relations) = &self.relations {
@@ -293,7 +433,8 @@ impl<R: BattleRules + 'static> Event<R> for CreateTeam<R> {
         } else {
             Vec::new()
         };
-        // Set to `Relation::Enemy` all relations to other teams not explicitly set.
+        // Set to the default relation all relations to other teams not explicitly set.
+        let default_relation = battle.rules.team_rules().default_relation();
         for team_id in battle.entities().teams().map(|e| e.id()).filter(|e| {
             **e != self.id
                 && self
@@ -306,17 +447,19 @@ impl<R: BattleRules + 'static> Event<R> for CreateTeam<R> {
         }) {
             relations.push((
                 RelationshipPair::new(self.id.clone(), team_id.clone()),
-                Relation::Enemy,
+                default_relation,
             ));
         }
         // Insert the new relations.
         battle.state.entities.update_relations(relations);
         // Update metrics.
-        battle
-            .metrics
-            .write_handle()
+        let mut metrics = battle.metrics.write_handle();
+        metrics
             .add_system_u64(TEAMS_CREATED, 1)
             .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
+        metrics
+            .add_team_u64(&self.id, TEAM_CREATURES, 0)
+            .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
     }
 
     fn kind(&self) -> EventKind {
@@ -342,6 +485,7 @@ where
     id: TeamId<R>,
     relations: Option<Vec<(TeamId<R>, Relation)>>,
     objectives_seed: Option<ObjectivesSeed<R>>,
+    conclusion: Option<Conclusion>,
 }
 
 impl<'a, R, P> CreateTeamTrigger<'a, R, P>
@@ -366,6 +510,13 @@ where
         self.objectives_seed = Some(seed);
         self
     }
+
+    /// Sets a conclusion for the team from the start.
+    /// Objectives won't be checked for this team until they are reset.
+    pub fn conclusion(&'a mut self, conclusion: Conclusion) -> &'a mut CreateTeamTrigger<'a, R, P> {
+        self.conclusion = Some(conclusion);
+        self
+    }
 }
 
 impl<'a, R, P> EventTrigger<'a, R, P> for CreateTeamTrigger<'a, R, P>
@@ -383,6 +534,7 @@ where
             id: self.id.clone(),
             relations: self.relations.clone(),
             objectives_seed: self.objectives_seed.clone(),
+            conclusion: self.conclusion,
         })
     }
 }
@@ -396,11 +548,14 @@ pub enum Relation {
     /// Represents enmity.
     Enemy,
     /// Reserved for entities in the same team.
+    ///
+    /// Kinship is implicit: it is never stored and can't be assigned by any event.
+    /// The relation of a team towards itself is always `Kin`, while the relation between
+    /// two different teams is never `Kin`.
     Kin,
 }
 
 /// A pair of two teams that are part of a relationship.
-#[derive(Clone)]
 pub(crate) struct RelationshipPair<R: BattleRules> {
     pub(crate) first: TeamId<R>,
     pub(crate) second: TeamId<R>,
@@ -416,16 +571,16 @@ impl<R: BattleRules> Debug for RelationshipPair<R> {
     }
 }
 
+impl<R: BattleRules> Clone for RelationshipPair<R> {
+    fn clone(&self) -> Self {
+        RelationshipPair::new(self.first.clone(), self.second.clone())
+    }
+}
+
 impl<R: BattleRules> RelationshipPair<R> {
     pub(crate) fn new(first: TeamId<R>, second: TeamId<R>) -> RelationshipPair<R> {
         RelationshipPair { first, second }
     }
-
-    pub(crate) fn values(&self) -> impl Iterator<Item = TeamId<R>> {
-        let first = iter::once(self.first.clone());
-        let second = iter::once(self.second.clone());
-        first.chain(second)
-    }
 }
 
 impl<R: BattleRules> PartialEq for RelationshipPair<R> {
@@ -451,6 +606,9 @@ impl<R: BattleRules> Hash for RelationshipPair<R> {
 
 /// Event to set diplomatic relations between teams.
 /// Relations are symmetric.
+///
+/// Setting a relation between a team and itself, or setting `Relation::Kin`,
+/// is not allowed.
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct SetRelations<R: BattleRules> {
     /// Vector containing tuples of two teams and a relation.
@@ -568,6 +726,287 @@ where
     }
 }
 
+/// Event to replace all diplomatic relations between teams at once.
+/// Relations are symmetric.
+///
+/// Every pair of teams not present in the list takes the relation returned by
+/// `TeamRules::default_relation`. Directed relations set with `SetDirectedRelations`
+/// are removed as well, so that no previous relation survives the overwrite.
+///
+/// Setting a relation between a team and itself, setting `Relation::Kin`
+/// or listing the same pair of teams more than once is not allowed.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct OverwriteRelations<R: BattleRules> {
+    /// Vector containing tuples of two teams and a relation.
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "Vec<(TeamId<R>, TeamId<R>, Relation)>: Serialize",
+            deserialize = "Vec<(TeamId<R>, TeamId<R>, Relation)>: Deserialize<'de>"
+        ))
+    )]
+    relations: Vec<(TeamId<R>, TeamId<R>, Relation)>,
+}
+
+impl<R: BattleRules> Debug for OverwriteRelations<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "OverwriteRelations {{ relations: {:?} }}",
+            self.relations
+        )
+    }
+}
+
+impl<R: BattleRules> Clone for OverwriteRelations<R> {
+    fn clone(&self) -> Self {
+        OverwriteRelations {
+            relations: self.relations.clone(),
+        }
+    }
+}
+
+impl<R: BattleRules> OverwriteRelations<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<'a, P: EventProcessor<R>>(
+        processor: &'a mut P,
+        relations: &[(TeamId<R>, TeamId<R>, Relation)],
+    ) -> OverwriteRelationsTrigger<'a, R, P> {
+        OverwriteRelationsTrigger {
+            processor,
+            relations: relations.into(),
+        }
+    }
+
+    /// Returns the new relations.
+    pub fn relations(&self) -> &Vec<(TeamId<R>, TeamId<R>, Relation)> {
+        &self.relations
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for OverwriteRelations<R> {
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        let mut pairs: HashSet<RelationshipPair<R>> = HashSet::new();
+        for (first, second, relation) in &self.relations {
+            // Prevent self relation assignment.
+            if *first == *second {
+                return Err(WeaselError::SelfRelation);
+            }
+            // Prevent explicit kinship.
+            if *relation == Relation::Kin {
+                return Err(WeaselError::KinshipRelation);
+            }
+            // Teams in the relations list must exist.
+            if battle.entities().team(first).is_none() {
+                return Err(WeaselError::TeamNotFound(first.clone()));
+            }
+            if battle.entities().team(second).is_none() {
+                return Err(WeaselError::TeamNotFound(second.clone()));
+            }
+            // Each pair of teams can appear only once.
+            if !pairs.insert(RelationshipPair::new(first.clone(), second.clone())) {
+                return Err(WeaselError::DuplicatedRelation(
+                    first.clone(),
+                    second.clone(),
+                ));
+            }
+        }
+        // Prevent implicit kinship.
+        if battle.rules.team_rules().default_relation() == Relation::Kin {
+            return Err(WeaselError::KinshipRelation);
+        }
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
+        // Set all relations between existing teams to the default relation.
+        let default_relation = battle.rules.team_rules().default_relation();
+        let teams: Vec<_> = battle.entities().teams().map(|e| e.id().clone()).collect();
+        let mut vec = Vec::new();
+        for (i, first) in teams.iter().enumerate() {
+            for second in &teams[i + 1..] {
+                vec.push((
+                    RelationshipPair::new(first.clone(), second.clone()),
+                    default_relation,
+                ));
+            }
+        }
+        // Then apply the explicit relations.
+        vec.extend(
+            self.relations
+                .iter()
+                .map(|e| (RelationshipPair::new(e.0.clone(), e.1.clone()), e.2)),
+        );
+        // Replace the old relations.
+        battle.state.entities.clear_relations();
+        battle.state.entities.update_relations(vec);
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::OverwriteRelations
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire an `OverwriteRelations` event.
+pub struct OverwriteRelationsTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    relations: Vec<(TeamId<R>, TeamId<R>, Relation)>,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for OverwriteRelationsTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns an `OverwriteRelations` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(OverwriteRelations {
+            relations: self.relations.clone(),
+        })
+    }
+}
+
+/// Event to set one-directional diplomatic relations between teams.
+///
+/// Each tuple `(first, second, relation)` defines how `first` regards `second`,
+/// without altering the opposite direction.\
+/// Directed relations are only visible through `Entities::directed_relation`
+/// and they don't change the symmetric relations set by `SetRelations`.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct SetDirectedRelations<R: BattleRules> {
+    /// Vector containing tuples of two teams and a relation.
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "Vec<(TeamId<R>, TeamId<R>, Relation)>: Serialize",
+            deserialize = "Vec<(TeamId<R>, TeamId<R>, Relation)>: Deserialize<'de>"
+        ))
+    )]
+    relations: Vec<(TeamId<R>, TeamId<R>, Relation)>,
+}
+
+impl<R: BattleRules> Debug for SetDirectedRelations<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "SetDirectedRelations {{ relations: {:?} }}",
+            self.relations
+        )
+    }
+}
+
+impl<R: BattleRules> Clone for SetDirectedRelations<R> {
+    fn clone(&self) -> Self {
+        SetDirectedRelations {
+            relations: self.relations.clone(),
+        }
+    }
+}
+
+impl<R: BattleRules> SetDirectedRelations<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<'a, P: EventProcessor<R>>(
+        processor: &'a mut P,
+        relations: &[(TeamId<R>, TeamId<R>, Relation)],
+    ) -> SetDirectedRelationsTrigger<'a, R, P> {
+        SetDirectedRelationsTrigger {
+            processor,
+            relations: relations.into(),
+        }
+    }
+
+    /// Returns all directed relation changes.
+    pub fn relations(&self) -> &Vec<(TeamId<R>, TeamId<R>, Relation)> {
+        &self.relations
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for SetDirectedRelations<R> {
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        for (first, second, relation) in &self.relations {
+            // Prevent self relation assignment.
+            if *first == *second {
+                return Err(WeaselError::SelfRelation);
+            }
+            // Prevent explicit kinship.
+            if *relation == Relation::Kin {
+                return Err(WeaselError::KinshipRelation);
+            }
+            // Teams in the relations list must exist.
+            if battle.entities().team(first).is_none() {
+                return Err(WeaselError::TeamNotFound(first.clone()));
+            }
+            if battle.entities().team(second).is_none() {
+                return Err(WeaselError::TeamNotFound(second.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
+        // Insert the new directed relations.
+        battle
+            .state
+            .entities
+            .update_directed_relations(self.relations.clone());
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::SetDirectedRelations
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire a `SetDirectedRelations` event.
+pub struct SetDirectedRelationsTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    relations: Vec<(TeamId<R>, TeamId<R>, Relation)>,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for SetDirectedRelationsTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns a `SetDirectedRelations` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(SetDirectedRelations {
+            relations: self.relations.clone(),
+        })
+    }
+}
+
 /// All possible conclusions for a team's objectives.
 /// In other words, this tells if the team reached its objectives or failed.
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -577,6 +1016,8 @@ pub enum Conclusion {
     Victory,
     /// Team failed to achieve its objectives.
     Defeat,
+    /// Team neither achieved nor failed its objectives, but it can't progress any further.
+    Draw,
 }
 
 /// Event to set the `Conclusion` of a team.
@@ -691,6 +1132,9 @@ where
 
 /// Event to reset a team's objectives.
 /// Team's `Conclusion` is resetted as well since the objectives changed.
+///
+/// This is the only event accepted after the battle has ended. In such a case,
+/// the battle is resumed and its outcome is discarded.
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct ResetObjectives<R: BattleRules> {
     #[cfg_attr(
@@ -717,7 +1161,7 @@ impl<R: BattleRules> ResetObjectives<R> {
     pub fn trigger<P: EventProcessor<R>>(
         processor: &mut P,
         id: TeamId<R>,
-    ) -> ResetObjectivesTrigger<R, P> {
+    ) -> ResetObjectivesTrigger<'_, R, P> {
         ResetObjectivesTrigger {
             processor,
             id,
@@ -761,7 +1205,11 @@ impl<R: BattleRules + 'static> Event<R> for ResetObjectives<R> {
         if battle.entities().team(&self.id).is_none() {
             return Err(WeaselError::TeamNotFound(self.id.clone()));
         }
-        Ok(())
+        // Objectives seed must be valid.
+        battle
+            .rules
+            .team_rules()
+            .validate_objectives_seed(&battle.state, &self.seed)
     }
 
     fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
@@ -774,6 +1222,10 @@ impl<R: BattleRules + 'static> Event<R> for ResetObjectives<R> {
         team.objectives = battle.rules.team_rules().generate_objectives(&self.seed);
         // Reset the team's conclusion.
         team.conclusion = None;
+        // Unfreeze the battle.
+        if battle.phase() == BattlePhase::Ended {
+            battle.resume();
+        }
     }
 
     fn kind(&self) -> EventKind {
@@ -832,6 +1284,10 @@ where
 
 /// Event to remove a team from a battle.
 /// Teams can be removed only if they are empty.
+///
+/// If `cascade` is set, a non empty team is accepted as well. In this case the event
+/// fires a `RemoveCreature` event for each creature in the team, followed by
+/// another `RemoveTeam` event to remove the team once it's empty.
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
 pub struct RemoveTeam<R: BattleRules> {
     #[cfg_attr(
@@ -842,6 +1298,9 @@ pub struct RemoveTeam<R: BattleRules> {
         ))
     )]
     id: TeamId<R>,
+
+    #[cfg_attr(feature = "serialization", serde(default))]
+    cascade: bool,
 }
 
 impl<R: BattleRules> RemoveTeam<R> {
@@ -849,19 +1308,32 @@ impl<R: BattleRules> RemoveTeam<R> {
     pub fn trigger<P: EventProcessor<R>>(
         processor: &mut P,
         id: TeamId<R>,
-    ) -> RemoveTeamTrigger<R, P> {
-        RemoveTeamTrigger { processor, id }
+    ) -> RemoveTeamTrigger<'_, R, P> {
+        RemoveTeamTrigger {
+            processor,
+            id,
+            cascade: false,
+        }
     }
 
     /// Returns the id of the team to be removed.
     pub fn id(&self) -> &TeamId<R> {
         &self.id
     }
+
+    /// Returns whether the team's creatures should be removed as well.
+    pub fn cascade(&self) -> bool {
+        self.cascade
+    }
 }
 
 impl<R: BattleRules> Debug for RemoveTeam<R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "RemoveTeam {{ id: {:?} }}", self.id)
+        write!(
+            f,
+            "RemoveTeam {{ id: {:?}, cascade: {:?} }}",
+            self.id, self.cascade
+        )
     }
 }
 
@@ -869,6 +1341,7 @@ impl<R: BattleRules> Clone for RemoveTeam<R> {
     fn clone(&self) -> Self {
         RemoveTeam {
             id: self.id.clone(),
+            cascade: self.cascade,
         }
     }
 }
@@ -877,8 +1350,8 @@ impl<R: BattleRules + 'static> Event<R> for RemoveTeam<R> {
     fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
         // Team must exist.
         if let Some(team) = battle.entities().team(&self.id) {
-            // Team must not have any creature.
-            if team.creatures().peekable().peek().is_some() {
+            // Team must not have any creature, unless they are removed as well.
+            if !self.cascade && team.creatures().peekable().peek().is_some() {
                 return Err(WeaselError::TeamNotEmpty(self.id.clone()));
             }
             Ok(())
@@ -887,7 +1360,22 @@ impl<R: BattleRules + 'static> Event<R> for RemoveTeam<R> {
         }
     }
 
-    fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
+    fn apply(&self, battle: &mut Battle<R>, event_queue: &mut Option<EventQueue<R>>) {
+        let team = battle
+            .state
+            .entities
+            .team(&self.id)
+            .unwrap_or_else(|| panic!("constraint violated: team {:?} not found", self.id));
+        if team.creatures().peekable().peek().is_some() {
+            // Remove all creatures first, then the team itself.
+            for creature_id in team.creatures() {
+                RemoveCreature::trigger(event_queue, creature_id.clone())
+                    .reason(RemovalReason::TeamRemoved)
+                    .fire();
+            }
+            RemoveTeam::trigger(event_queue, self.id.clone()).fire();
+            return;
+        }
         // Remove the team.
         battle
             .state
@@ -896,6 +1384,8 @@ impl<R: BattleRules + 'static> Event<R> for RemoveTeam<R> {
             .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
         // Remove rights of players towards this team.
         battle.rights_mut().remove_team(&self.id);
+        // Remove the team's metrics.
+        battle.metrics.write_handle().remove_team(&self.id);
     }
 
     fn kind(&self) -> EventKind {
@@ -919,6 +1409,20 @@ where
 {
     processor: &'a mut P,
     id: TeamId<R>,
+    cascade: bool,
+}
+
+impl<'a, R, P> RemoveTeamTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    /// Removes all creatures in the team instead of rejecting the event
+    /// if the team is not empty.
+    pub fn cascade(&'a mut self) -> &'a mut RemoveTeamTrigger<'a, R, P> {
+        self.cascade = true;
+        self
+    }
 }
 
 impl<'a, R, P> EventTrigger<'a, R, P> for RemoveTeamTrigger<'a, R, P>
@@ -934,6 +1438,147 @@ where
     fn event(&self) -> Box<dyn Event<R>> {
         Box::new(RemoveTeam {
             id: self.id.clone(),
+            cascade: self.cascade,
+        })
+    }
+}
+
+/// Event to change the id of a team.
+///
+/// All references to the old id, in creatures, relations and players' rights,
+/// are updated to the new one.
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct RenameTeam<R: BattleRules> {
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "TeamId<R>: Serialize",
+            deserialize = "TeamId<R>: Deserialize<'de>"
+        ))
+    )]
+    id: TeamId<R>,
+
+    #[cfg_attr(
+        feature = "serialization",
+        serde(bound(
+            serialize = "TeamId<R>: Serialize",
+            deserialize = "TeamId<R>: Deserialize<'de>"
+        ))
+    )]
+    new_id: TeamId<R>,
+}
+
+impl<R: BattleRules> RenameTeam<R> {
+    /// Returns a trigger for this event.
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &mut P,
+        id: TeamId<R>,
+        new_id: TeamId<R>,
+    ) -> RenameTeamTrigger<'_, R, P> {
+        RenameTeamTrigger {
+            processor,
+            id,
+            new_id,
+        }
+    }
+
+    /// Returns the current id of the team.
+    pub fn id(&self) -> &TeamId<R> {
+        &self.id
+    }
+
+    /// Returns the new id of the team.
+    pub fn new_id(&self) -> &TeamId<R> {
+        &self.new_id
+    }
+}
+
+impl<R: BattleRules> Debug for RenameTeam<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "RenameTeam {{ id: {:?}, new_id: {:?} }}",
+            self.id, self.new_id
+        )
+    }
+}
+
+impl<R: BattleRules> Clone for RenameTeam<R> {
+    fn clone(&self) -> Self {
+        RenameTeam {
+            id: self.id.clone(),
+            new_id: self.new_id.clone(),
+        }
+    }
+}
+
+impl<R: BattleRules + 'static> Event<R> for RenameTeam<R> {
+    fn verify(&self, battle: &Battle<R>) -> WeaselResult<(), R> {
+        // Team must exist.
+        if battle.entities().team(&self.id).is_none() {
+            return Err(WeaselError::TeamNotFound(self.id.clone()));
+        }
+        // New id must not be taken.
+        if battle.entities().team(&self.new_id).is_some() {
+            return Err(WeaselError::DuplicatedTeam(self.new_id.clone()));
+        }
+        Ok(())
+    }
+
+    fn apply(&self, battle: &mut Battle<R>, _: &mut Option<EventQueue<R>>) {
+        // Rename the team.
+        battle
+            .state
+            .entities
+            .rename_team(&self.id, &self.new_id)
+            .unwrap_or_else(|err| panic!("constraint violated: {:?}", err));
+        // Transfer players' rights to the new id.
+        battle.rights_mut().rename_team(&self.id, &self.new_id);
+        // Transfer the team's metrics to the new id.
+        battle
+            .metrics
+            .write_handle()
+            .rename_team(&self.id, &self.new_id);
+    }
+
+    fn kind(&self) -> EventKind {
+        EventKind::RenameTeam
+    }
+
+    fn box_clone(&self) -> Box<dyn Event<R>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Trigger to build and fire a `RenameTeam` event.
+pub struct RenameTeamTrigger<'a, R, P>
+where
+    R: BattleRules,
+    P: EventProcessor<R>,
+{
+    processor: &'a mut P,
+    id: TeamId<R>,
+    new_id: TeamId<R>,
+}
+
+impl<'a, R, P> EventTrigger<'a, R, P> for RenameTeamTrigger<'a, R, P>
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    fn processor(&'a mut self) -> &'a mut P {
+        self.processor
+    }
+
+    /// Returns a `RenameTeam` event.
+    fn event(&self) -> Box<dyn Event<R>> {
+        Box::new(RenameTeam {
+            id: self.id.clone(),
+            new_id: self.new_id.clone(),
         })
     }
 }
@@ -67,12 +67,12 @@ where
 }
 
 #[cfg(test)]
+#[cfg(feature = "serialization")]
 mod tests {
     use super::*;
     use crate::event::{DummyEvent, EventTrigger};
     use crate::{battle_rules, rules::empty::*};
 
-    #[cfg(feature = "serialization")]
     #[test]
     fn empty_user_event_packer() {
         battle_rules! {}
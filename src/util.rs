@@ -34,13 +34,13 @@ pub(crate) mod tests {
     }
 
     /// Creates a team with default arguments.
-    pub(crate) fn team<'a, R: BattleRules + 'static>(server: &'a mut Server<R>, id: TeamId<R>) {
+    pub(crate) fn team<R: BattleRules + 'static>(server: &mut Server<R>, id: TeamId<R>) {
         assert_eq!(CreateTeam::trigger(server, id).fire().err(), None);
     }
 
     /// Creates a creature with default arguments.
-    pub(crate) fn creature<'a, R: BattleRules + 'static>(
-        server: &'a mut Server<R>,
+    pub(crate) fn creature<R: BattleRules + 'static>(
+        server: &mut Server<R>,
         creature_id: CreatureId<R>,
         team_id: TeamId<R>,
         position: Position<R>,
@@ -54,7 +54,7 @@ pub(crate) mod tests {
     }
 
     /// Dummy event.
-    pub(crate) fn dummy<'a, R, P>(processor: &'a mut P)
+    pub(crate) fn dummy<R, P>(processor: &mut P)
     where
         R: BattleRules + 'static,
         P: EventProcessor<R>,
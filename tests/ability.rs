@@ -1,7 +1,6 @@
 use weasel::ability::ActivateAbility;
-use weasel::actor::{Action, ActorRules};
+use weasel::actor::{activable_abilities, Action, ActorRules};
 use weasel::battle::{BattleRules, BattleState};
-use weasel::battle_rules_with_actor;
 use weasel::entity::EntityId;
 use weasel::entropy::Entropy;
 use weasel::event::{DummyEvent, EventKind, EventQueue, EventTrigger};
@@ -9,6 +8,7 @@ use weasel::metric::WriteMetrics;
 use weasel::rules::empty::EmptyAbility;
 use weasel::WeaselError;
 use weasel::{battle_rules, rules::empty::*};
+use weasel::{battle_rules_with, battle_rules_with_actor};
 
 static TEAM_1_ID: u32 = 1;
 static CREATURE_1_ID: u32 = 1;
@@ -75,6 +75,12 @@ fn abilities_generated() {
             .count(),
         1
     );
+    // The ability requires an activation, so it's not activable by default.
+    let actor = server.battle().entities().actor(&ENTITY_1_ID).unwrap();
+    assert_eq!(
+        activable_abilities(actor, server.battle().rules().actor_rules()).count(),
+        0
+    );
 }
 
 #[test]
@@ -132,3 +138,388 @@ fn ability_activation() {
     assert_eq!(events[events.len() - 2].origin(), Some(3));
     assert_eq!(events[events.len() - 1].origin(), Some(3));
 }
+
+#[test]
+fn cooldown() {
+    #[derive(Default)]
+    pub struct CustomActorRules {}
+
+    impl<R: BattleRules> ActorRules<R> for CustomActorRules {
+        type Ability = EmptyAbility;
+        type AbilitiesSeed = ();
+        type Activation = ();
+        type AbilitiesAlteration = ();
+
+        fn generate_abilities(
+            &self,
+            _: &Option<Self::AbilitiesSeed>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Box<dyn Iterator<Item = Self::Ability>> {
+            let v = vec![EmptyAbility { id: ABILITY_ID }];
+            Box::new(v.into_iter())
+        }
+
+        fn ability_cooldown(&self, _ability: &Self::Ability) -> u32 {
+            2
+        }
+    }
+
+    battle_rules_with_actor! { CustomActorRules }
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    // Create a server with a creature.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    let cooldown = |server: &weasel::server::Server<CustomRules>| {
+        server
+            .battle()
+            .entities()
+            .actor(&ENTITY_1_ID)
+            .unwrap()
+            .cooldown(&ABILITY_ID)
+    };
+    let activable = |server: &weasel::server::Server<CustomRules>| {
+        let actor = server.battle().entities().actor(&ENTITY_1_ID).unwrap();
+        activable_abilities(actor, server.battle().rules().actor_rules()).count()
+    };
+    assert_eq!(activable(&server), 1);
+    // Activate the ability.
+    util::start_round(&mut server, &ENTITY_1_ID);
+    assert_eq!(
+        ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(cooldown(&server), 2);
+    assert_eq!(activable(&server), 0);
+    // The ability can't be activated again while on cooldown.
+    assert_eq!(
+        ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::AbilityOnCooldown(ENTITY_1_ID, ABILITY_ID))
+    );
+    util::end_round(&mut server);
+    assert_eq!(cooldown(&server), 1);
+    util::start_round(&mut server, &ENTITY_1_ID);
+    assert_eq!(
+        ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::AbilityOnCooldown(ENTITY_1_ID, ABILITY_ID))
+    );
+    util::end_round(&mut server);
+    assert_eq!(cooldown(&server), 0);
+    assert_eq!(activable(&server), 1);
+    // The cooldown is over.
+    util::start_round(&mut server, &ENTITY_1_ID);
+    assert_eq!(
+        ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_ID)
+            .fire()
+            .err(),
+        None
+    );
+}
+
+#[test]
+fn targets() {
+    use weasel::actor::{validate_targets, TargetSpec};
+    use weasel::team::{Relation, SetRelations};
+
+    #[derive(Default)]
+    pub struct CustomActorRules {}
+
+    impl<R: BattleRules> ActorRules<R> for CustomActorRules {
+        type Ability = EmptyAbility;
+        type AbilitiesSeed = ();
+        type Activation = ();
+        type AbilitiesAlteration = ();
+
+        fn generate_abilities(
+            &self,
+            _: &Option<Self::AbilitiesSeed>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Box<dyn Iterator<Item = Self::Ability>> {
+            let v = vec![EmptyAbility { id: ABILITY_ID }];
+            Box::new(v.into_iter())
+        }
+
+        fn ability_targets(&self, _ability: &Self::Ability) -> TargetSpec {
+            TargetSpec {
+                min: 1,
+                max: Some(1),
+                relations: vec![Relation::Enemy],
+            }
+        }
+    }
+
+    battle_rules_with_actor! { CustomActorRules }
+    static TEAM_2_ID: u32 = 2;
+    static CREATURE_2_ID: u32 = 2;
+    static CREATURE_3_ID: u32 = 3;
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ENTITY_2_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_2_ID);
+    static ENTITY_3_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_3_ID);
+    static ENTITY_ERR_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_ERR_ID);
+    // Create a server with two enemy teams.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    assert_eq!(
+        SetRelations::trigger(&mut server, &[(TEAM_1_ID, TEAM_2_ID, Relation::Enemy)])
+            .fire()
+            .err(),
+        None
+    );
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_2_ID, TEAM_2_ID, ());
+    util::creature(&mut server, CREATURE_3_ID, TEAM_1_ID, ());
+    // Check different sets of targets.
+    let validate = |targets: &[EntityId<CustomRules>]| {
+        let battle = server.battle();
+        let actor = battle.entities().actor(&ENTITY_1_ID).unwrap();
+        let ability = actor.ability(&ABILITY_ID).unwrap();
+        validate_targets(
+            battle.rules().actor_rules(),
+            battle.state(),
+            actor,
+            ability,
+            targets,
+        )
+    };
+    assert_eq!(validate(&[ENTITY_2_ID]).err(), None);
+    assert_eq!(
+        validate(&[]).err(),
+        Some(WeaselError::InvalidTargetsCount(ABILITY_ID, 0))
+    );
+    assert_eq!(
+        validate(&[ENTITY_2_ID, ENTITY_2_ID]).err(),
+        Some(WeaselError::InvalidTargetsCount(ABILITY_ID, 2))
+    );
+    assert_eq!(
+        validate(&[ENTITY_3_ID]).err(),
+        Some(WeaselError::InvalidTarget(ABILITY_ID, ENTITY_3_ID))
+    );
+    assert_eq!(
+        validate(&[ENTITY_ERR_ID]).err(),
+        Some(WeaselError::EntityNotFound(ENTITY_ERR_ID))
+    );
+}
+
+#[test]
+fn activation_result() {
+    use weasel::WeaselResult;
+
+    #[derive(Default)]
+    pub struct CustomActorRules {}
+
+    impl ActorRules<CustomRules> for CustomActorRules {
+        type Ability = EmptyAbility;
+        type AbilitiesSeed = ();
+        type Activation = u32;
+        type AbilitiesAlteration = ();
+
+        fn generate_abilities(
+            &self,
+            _: &Option<Self::AbilitiesSeed>,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) -> Box<dyn Iterator<Item = Self::Ability>> {
+            let v = vec![EmptyAbility { id: ABILITY_ID }];
+            Box::new(v.into_iter())
+        }
+
+        fn activation_result(&self, action: Action<CustomRules>) -> WeaselResult<(), CustomRules> {
+            match action.activation {
+                Some(mana) if *mana >= MANA_COST => Ok(()),
+                _ => Err(WeaselError::UserError("not enough mana".to_string())),
+            }
+        }
+    }
+
+    battle_rules_with_actor! { CustomActorRules }
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static MANA_COST: u32 = 5;
+    // Create a server with a creature.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::start_round(&mut server, &ENTITY_1_ID);
+    // The ability requires an activation, thus it's not activable by default.
+    let actor = server.battle().entities().actor(&ENTITY_1_ID).unwrap();
+    assert_eq!(
+        activable_abilities(actor, server.battle().rules().actor_rules()).count(),
+        0
+    );
+    // The reason of the failure is reported by the event.
+    assert_eq!(
+        ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_ID)
+            .activation(MANA_COST - 1)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::UserError("not enough mana".to_string()))
+    );
+    assert_eq!(
+        ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_ID)
+            .activation(MANA_COST)
+            .fire()
+            .err(),
+        None
+    );
+}
+
+#[test]
+fn auto_end_round() {
+    use weasel::battle::Battle;
+    use weasel::round::RoundState;
+    use weasel::server::Server;
+    // Create a server that ends rounds after two activations.
+    let battle = Battle::builder(CustomRules::new()).build();
+    let mut server = Server::builder(battle).auto_end_round(2).build();
+    assert_eq!(server.auto_end_round(), Some(2));
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    let activate = |server: &mut Server<CustomRules>| {
+        assert_eq!(
+            ActivateAbility::trigger(server, ENTITY_1_ID, ABILITY_ID)
+                .activation(1)
+                .fire()
+                .err(),
+            None
+        );
+    };
+    // The round ends after the second activation.
+    util::start_round(&mut server, &ENTITY_1_ID);
+    activate(&mut server);
+    assert_eq!(
+        *server.battle().rounds().state(),
+        RoundState::Started(ENTITY_1_ID)
+    );
+    activate(&mut server);
+    assert_eq!(*server.battle().rounds().state(), RoundState::Ready);
+    let events = server.battle().history().events();
+    assert_eq!(events[events.len() - 1].kind(), EventKind::EndRound);
+    assert_eq!(events[events.len() - 2].kind(), EventKind::DummyEvent);
+    // Ending a round manually resets the count.
+    util::start_round(&mut server, &ENTITY_1_ID);
+    activate(&mut server);
+    util::end_round(&mut server);
+    util::start_round(&mut server, &ENTITY_1_ID);
+    activate(&mut server);
+    assert_eq!(
+        *server.battle().rounds().state(),
+        RoundState::Started(ENTITY_1_ID)
+    );
+    // Undoing an event preserves the count.
+    assert_eq!(DummyEvent::trigger(&mut server).fire().err(), None);
+    assert_eq!(server.undo_last(CustomRules::new()).err(), None);
+    activate(&mut server);
+    assert_eq!(*server.battle().rounds().state(), RoundState::Ready);
+}
+
+#[test]
+fn ability_cost() {
+    use weasel::ability::AbilityId;
+    use weasel::character::{Character, CharacterRules};
+    use weasel::rules::ability::{activable_with_cost, consume_cost, SimpleAbility};
+    use weasel::rules::statistic::SimpleStatistic;
+
+    static STAT_ID: u32 = 1;
+    static COST: u32 = 30;
+
+    #[derive(Default)]
+    pub struct CustomCharacterRules {}
+
+    impl<R: BattleRules> CharacterRules<R> for CustomCharacterRules {
+        type CreatureId = u32;
+        type ObjectId = u32;
+        type Statistic = SimpleStatistic<u32, u32>;
+        type StatisticsSeed = ();
+        type StatisticsAlteration = ();
+
+        fn generate_statistics(
+            &self,
+            _seed: &Option<Self::StatisticsSeed>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Box<dyn Iterator<Item = Self::Statistic>> {
+            let v = vec![SimpleStatistic::new(STAT_ID, 50)];
+            Box::new(v.into_iter())
+        }
+    }
+
+    #[derive(Default)]
+    pub struct CustomActorRules {}
+
+    impl ActorRules<CustomRules> for CustomActorRules {
+        type Ability = SimpleAbility<u32, u32>;
+        type AbilitiesSeed = ();
+        type Activation = ();
+        type AbilitiesAlteration = ();
+
+        fn generate_abilities(
+            &self,
+            _: &Option<Self::AbilitiesSeed>,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) -> Box<dyn Iterator<Item = Self::Ability>> {
+            let v = vec![SimpleAbility::with_cost(ABILITY_ID, 0, STAT_ID, COST)];
+            Box::new(v.into_iter())
+        }
+
+        fn activable(&self, action: Action<CustomRules>) -> bool {
+            activable_with_cost(action)
+        }
+
+        fn activate(
+            &self,
+            _state: &BattleState<CustomRules>,
+            action: Action<CustomRules>,
+            event_queue: &mut Option<EventQueue<CustomRules>>,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) {
+            consume_cost(action, event_queue);
+        }
+    }
+
+    battle_rules_with! {
+        character: CustomCharacterRules,
+        actor: CustomActorRules
+    }
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ABILITY_1_ID: AbilityId<CustomRules> = ABILITY_ID;
+    // Create a creature.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    let creature = server.battle().entities().creature(&CREATURE_1_ID).unwrap();
+    assert_eq!(
+        creature.ability(&ABILITY_1_ID).unwrap().cost(),
+        Some((&STAT_ID, COST))
+    );
+    // The first activation consumes the statistic.
+    util::start_round(&mut server, &ENTITY_1_ID);
+    assert_eq!(
+        ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_ID)
+            .fire()
+            .err(),
+        None
+    );
+    let creature = server.battle().entities().creature(&CREATURE_1_ID).unwrap();
+    assert_eq!(creature.statistic(&STAT_ID).unwrap().value(), 50 - COST);
+    // The second activation fails because the statistic is too low.
+    assert_eq!(
+        ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::AbilityNotActivable(ENTITY_1_ID, ABILITY_ID))
+    );
+}
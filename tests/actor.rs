@@ -1,11 +1,14 @@
-use weasel::actor::{Actor, ActorRules};
+use weasel::ability::AbilityId;
+use weasel::actor::{Actor, ActorRules, AlterAbilities, ClearAbilities, RegenerateAbilities};
 use weasel::battle::BattleRules;
 use weasel::battle_rules_with_actor;
+use weasel::creature::CreateCreature;
 use weasel::entity::EntityId;
 use weasel::entropy::Entropy;
 use weasel::event::{EventKind, EventQueue, EventTrigger};
 use weasel::metric::WriteMetrics;
 use weasel::rules::empty::EmptyAbility;
+use weasel::rules::empty::EmptyStat;
 use weasel::space::MoveEntity;
 use weasel::{battle_rules, rules::empty::*};
 
@@ -76,3 +79,132 @@ fn round_start_and_end() {
         assert_eq!(events[5].kind(), EventKind::MoveEntity);
     }
 }
+
+#[test]
+fn abilities_changed() {
+    #[derive(Default)]
+    pub struct CustomActorRules {}
+
+    static ADDED_METRIC: u16 = 0;
+    static REMOVED_METRIC: u16 = 1;
+
+    impl ActorRules<CustomRules> for CustomActorRules {
+        type Ability = EmptyAbility;
+        type AbilitiesSeed = Vec<u32>;
+        type Activation = ();
+        // Pair of (ability to add, ability to remove).
+        type AbilitiesAlteration = (u32, u32);
+
+        fn generate_abilities(
+            &self,
+            seed: &Option<Self::AbilitiesSeed>,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) -> Box<dyn Iterator<Item = Self::Ability>> {
+            let v: Vec<_> = seed
+                .iter()
+                .flatten()
+                .map(|id| EmptyStat { id: *id })
+                .collect();
+            Box::new(v.into_iter())
+        }
+
+        fn alter(
+            &self,
+            actor: &mut dyn Actor<CustomRules>,
+            alteration: &Self::AbilitiesAlteration,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) {
+            actor.add_ability(EmptyStat { id: alteration.0 });
+            actor.remove_ability(&alteration.1);
+        }
+
+        fn on_ability_added(
+            &self,
+            _actor: &dyn Actor<CustomRules>,
+            _ability_id: &AbilityId<CustomRules>,
+            _event_queue: &mut Option<EventQueue<CustomRules>>,
+            _entropy: &mut Entropy<CustomRules>,
+            metrics: &mut WriteMetrics<CustomRules>,
+        ) {
+            metrics.add_user_u64(ADDED_METRIC, 1).unwrap();
+        }
+
+        fn on_ability_removed(
+            &self,
+            _actor: &dyn Actor<CustomRules>,
+            _ability_id: &AbilityId<CustomRules>,
+            _event_queue: &mut Option<EventQueue<CustomRules>>,
+            _entropy: &mut Entropy<CustomRules>,
+            metrics: &mut WriteMetrics<CustomRules>,
+        ) {
+            metrics.add_user_u64(REMOVED_METRIC, 1).unwrap();
+        }
+    }
+
+    battle_rules_with_actor! { CustomActorRules }
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    let metric = |server: &weasel::server::Server<CustomRules>, id| {
+        server.battle().metrics().user_u64(id).unwrap_or_default()
+    };
+    // Create a creature with abilities 1 and 2.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    assert_eq!(
+        CreateCreature::trigger(&mut server, CREATURE_1_ID, TEAM_1_ID, ())
+            .abilities_seed(vec![1, 2])
+            .fire()
+            .err(),
+        None
+    );
+    // Regenerate into abilities 2 and 3.
+    assert_eq!(
+        RegenerateAbilities::trigger(&mut server, ENTITY_1_ID)
+            .seed(vec![2, 3])
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(metric(&server, ADDED_METRIC), 1);
+    assert_eq!(metric(&server, REMOVED_METRIC), 1);
+    // Alter by adding ability 4 and removing ability 2.
+    assert_eq!(
+        AlterAbilities::trigger(&mut server, ENTITY_1_ID, (4, 2))
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(metric(&server, ADDED_METRIC), 2);
+    assert_eq!(metric(&server, REMOVED_METRIC), 2);
+    // Clear the remaining abilities, 3 and 4.
+    assert_eq!(
+        ClearAbilities::trigger(&mut server, ENTITY_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(metric(&server, ADDED_METRIC), 2);
+    assert_eq!(metric(&server, REMOVED_METRIC), 4);
+}
+
+#[test]
+fn iterate_actors() {
+    static TEAM_2_ID: u32 = 2;
+    static CREATURE_2_ID: u32 = 2;
+    // Create two creatures in different teams.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_2_ID, TEAM_2_ID, ());
+    // Check that all actors and characters are visited.
+    let entities = server.battle().entities();
+    let mut actors: Vec<_> = entities.actors().map(|actor| *actor.entity_id()).collect();
+    actors.sort_by_key(|id| *id.creature().as_ref().unwrap());
+    assert_eq!(actors, vec![ENTITY_1_ID, EntityId::Creature(CREATURE_2_ID)]);
+    assert_eq!(entities.characters().count(), 2);
+    assert!(entities
+        .characters()
+        .all(|character| character.statistics().count() == 0));
+}
@@ -1,13 +1,15 @@
 use weasel::ability::ActivateAbility;
 use weasel::actor::{Action, ActorRules};
-use weasel::battle::{BattlePhase, BattleRules, BattleState, EndBattle};
+use weasel::battle::{BattleOutcome, BattlePhase, BattleRules, BattleState, EndBattle};
 use weasel::battle_rules_with_actor;
 use weasel::entity::EntityId;
 use weasel::entropy::Entropy;
-use weasel::event::{DummyEvent, EventQueue, EventTrigger};
-use weasel::metric::WriteMetrics;
+use weasel::event::{DummyEvent, EventProcessor, EventQueue, EventTrigger};
+use weasel::metric::{ReadMetrics, WriteMetrics};
 use weasel::round::{EndRound, StartRound};
 use weasel::rules::empty::EmptyAbility;
+use weasel::team::Conclusion;
+use weasel::util::Id;
 use weasel::WeaselError;
 use weasel::{battle_rules, rules::empty::*};
 
@@ -89,3 +91,951 @@ fn end_battle_during_events() {
     );
     assert_eq!(server.battle().phase(), BattlePhase::Ended);
 }
+
+#[test]
+fn resume_battle() {
+    use weasel::team::ResetObjectives;
+    // Create the scenario and end the battle.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    assert_eq!(EndBattle::trigger(&mut server).fire().err(), None);
+    assert!(server.battle().ended());
+    // Resetting the objectives is still allowed and resumes the battle.
+    assert_eq!(
+        ResetObjectives::trigger(&mut server, TEAM_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(server.battle().phase(), BattlePhase::Started);
+    assert!(!server.battle().ended());
+    // Gameplay events are accepted again.
+    util::start_round(&mut server, &ENTITY_1_ID);
+}
+
+#[test]
+fn battle_outcome() {
+    use weasel::team::ConcludeObjectives;
+    static TEAM_2_ID: u32 = 2;
+    static TEAM_3_ID: u32 = 3;
+    // Create a scenario with three teams.
+    let mut server = util::server(CustomRules::new());
+    assert!(!server.battle().ended());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::team(&mut server, TEAM_3_ID);
+    // The battle is not over until every team has a conclusion.
+    for (team, conclusion) in &[
+        (TEAM_1_ID, Conclusion::Victory),
+        (TEAM_2_ID, Conclusion::Defeat),
+    ] {
+        assert_eq!(
+            ConcludeObjectives::trigger(&mut server, *team, *conclusion)
+                .fire()
+                .err(),
+            None
+        );
+    }
+    assert!(!server.battle().ended());
+    assert_eq!(server.battle().outcome(), None);
+    assert_eq!(
+        ConcludeObjectives::trigger(&mut server, TEAM_3_ID, Conclusion::Draw)
+            .fire()
+            .err(),
+        None
+    );
+    assert!(server.battle().ended());
+    assert_eq!(server.battle().phase(), BattlePhase::Started);
+    // Check the outcome.
+    let outcome = server.battle().outcome().unwrap();
+    assert_eq!(outcome.winners().collect::<Vec<_>>(), vec![&TEAM_1_ID]);
+    assert_eq!(outcome.losers().collect::<Vec<_>>(), vec![&TEAM_2_ID]);
+    assert_eq!(outcome.draws().collect::<Vec<_>>(), vec![&TEAM_3_ID]);
+    assert_eq!(outcome.conclusion(&TEAM_2_ID), Some(Conclusion::Defeat));
+    assert_eq!(outcome.conclusions().count(), 3);
+    // An explicitly ended battle is always over.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    assert_eq!(EndBattle::trigger(&mut server).fire().err(), None);
+    assert!(server.battle().ended());
+    assert_eq!(server.battle().outcome().unwrap().conclusions().count(), 0);
+}
+
+/// Battle rules that end the battle as soon as one team wins.
+#[derive(Default)]
+struct EndingRules {
+    team_rules: EmptyTeamRules,
+    character_rules: EmptyCharacterRules,
+    actor_rules: EmptyActorRules,
+    fight_rules: EmptyFightRules,
+    user_rules: EmptyUserRules,
+}
+
+impl BattleRules for EndingRules {
+    type TR = EmptyTeamRules;
+    type CR = EmptyCharacterRules;
+    type AR = EmptyActorRules;
+    type FR = EmptyFightRules;
+    type UR = EmptyUserRules;
+    type SR = EmptySpaceRules;
+    type RR = EmptyRoundsRules;
+    type ER = EmptyEntropyRules;
+    type Version = ();
+
+    fn team_rules(&self) -> &Self::TR {
+        &self.team_rules
+    }
+    fn character_rules(&self) -> &Self::CR {
+        &self.character_rules
+    }
+    fn actor_rules(&self) -> &Self::AR {
+        &self.actor_rules
+    }
+    fn fight_rules(&self) -> &Self::FR {
+        &self.fight_rules
+    }
+    fn user_rules(&self) -> &Self::UR {
+        &self.user_rules
+    }
+    fn space_rules(&mut self) -> Self::SR {
+        EmptySpaceRules::default()
+    }
+    fn rounds_rules(&mut self) -> Self::RR {
+        EmptyRoundsRules::default()
+    }
+    fn entropy_rules(&mut self) -> Self::ER {
+        EmptyEntropyRules::default()
+    }
+    fn version(&self) -> &Self::Version {
+        &()
+    }
+
+    fn check_battle_end(
+        &self,
+        state: &BattleState<Self>,
+        _metrics: &ReadMetrics<Self>,
+    ) -> Option<BattleOutcome<Self>> {
+        let teams = || state.entities().teams();
+        if teams().any(|team| team.conclusion() == Some(Conclusion::Victory)) {
+            Some(BattleOutcome::new(teams().map(|team| {
+                (*team.id(), team.conclusion().unwrap_or(Conclusion::Defeat))
+            })))
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn check_battle_end() {
+    use weasel::team::ConcludeObjectives;
+    static TEAM_2_ID: u32 = 2;
+
+    // Create a scenario with two teams.
+    let mut server = util::server(EndingRules::default());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    assert!(!server.battle().ended());
+    // Let the first team win.
+    assert_eq!(
+        ConcludeObjectives::trigger(&mut server, TEAM_1_ID, Conclusion::Victory)
+            .fire()
+            .err(),
+        None
+    );
+    // Check that the battle ended with the outcome decided by the rules.
+    assert_eq!(server.battle().phase(), BattlePhase::Ended);
+    assert!(server.battle().ended());
+    let outcome = server.battle().outcome().unwrap();
+    assert_eq!(outcome.winners().collect::<Vec<_>>(), vec![&TEAM_1_ID]);
+    assert_eq!(outcome.losers().collect::<Vec<_>>(), vec![&TEAM_2_ID]);
+    // Further events are rejected.
+    assert_eq!(
+        ConcludeObjectives::trigger(&mut server, TEAM_2_ID, Conclusion::Draw)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::BattleEnded)
+    );
+}
+
+#[test]
+fn check_battle_end_with_objectives() {
+    use weasel::creature::CreateCreature;
+    use weasel::event::EventKind;
+    use weasel::metric::MetricsDelta;
+    use weasel::team::{Team, TeamRules};
+
+    static TEAM_2_ID: u32 = 2;
+
+    /// Team rules defeating teams without creatures, as soon as a creature exists.
+    #[derive(Default)]
+    struct CustomTeamRules {}
+
+    impl<R: BattleRules> TeamRules<R> for CustomTeamRules {
+        type Id = u32;
+        type ObjectivesSeed = ();
+        type Objectives = ();
+        type ObjectivesProgress = ();
+
+        fn check_objectives_on_event(
+            &self,
+            state: &BattleState<R>,
+            team: &Team<R>,
+            _metrics: &ReadMetrics<R>,
+            _delta: &MetricsDelta<R>,
+        ) -> Option<Conclusion> {
+            if state.entities().creatures().count() > 0 && team.creatures().count() == 0 {
+                Some(Conclusion::Defeat)
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Battle rules ending the battle as soon as a creature exists.
+    #[derive(Default)]
+    struct CustomRules {
+        team_rules: CustomTeamRules,
+        character_rules: EmptyCharacterRules,
+        actor_rules: EmptyActorRules,
+        fight_rules: EmptyFightRules,
+        user_rules: EmptyUserRules,
+    }
+
+    impl BattleRules for CustomRules {
+        type TR = CustomTeamRules;
+        type CR = EmptyCharacterRules;
+        type AR = EmptyActorRules;
+        type FR = EmptyFightRules;
+        type UR = EmptyUserRules;
+        type SR = EmptySpaceRules;
+        type RR = EmptyRoundsRules;
+        type ER = EmptyEntropyRules;
+        type Version = ();
+
+        fn team_rules(&self) -> &Self::TR {
+            &self.team_rules
+        }
+        fn character_rules(&self) -> &Self::CR {
+            &self.character_rules
+        }
+        fn actor_rules(&self) -> &Self::AR {
+            &self.actor_rules
+        }
+        fn fight_rules(&self) -> &Self::FR {
+            &self.fight_rules
+        }
+        fn user_rules(&self) -> &Self::UR {
+            &self.user_rules
+        }
+        fn space_rules(&mut self) -> Self::SR {
+            EmptySpaceRules::default()
+        }
+        fn rounds_rules(&mut self) -> Self::RR {
+            EmptyRoundsRules::default()
+        }
+        fn entropy_rules(&mut self) -> Self::ER {
+            EmptyEntropyRules::default()
+        }
+        fn version(&self) -> &Self::Version {
+            &()
+        }
+
+        fn check_battle_end(
+            &self,
+            state: &BattleState<Self>,
+            _metrics: &ReadMetrics<Self>,
+        ) -> Option<BattleOutcome<Self>> {
+            if state.entities().creatures().count() > 0 {
+                Some(BattleOutcome::new(vec![(TEAM_1_ID, Conclusion::Victory)]))
+            } else {
+                None
+            }
+        }
+    }
+
+    // Create a scenario with two teams.
+    let mut server = util::server(CustomRules::default());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    // The same event concludes the second team's objectives and ends the battle.
+    assert_eq!(
+        CreateCreature::trigger(&mut server, CREATURE_1_ID, TEAM_1_ID, ())
+            .fire()
+            .err(),
+        None
+    );
+    // The conclusion is applied before the battle ends.
+    let entities = server.battle().entities();
+    assert_eq!(
+        entities.team(&TEAM_2_ID).unwrap().conclusion(),
+        Some(Conclusion::Defeat)
+    );
+    assert_eq!(server.battle().phase(), BattlePhase::Ended);
+    let outcome = server.battle().outcome().unwrap();
+    assert_eq!(outcome.winners().collect::<Vec<_>>(), vec![&TEAM_1_ID]);
+    assert_eq!(outcome.conclusions().count(), 1);
+    let kinds: Vec<_> = server
+        .battle()
+        .history()
+        .events()
+        .iter()
+        .map(|event| event.kind())
+        .skip(2)
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            EventKind::CreateCreature,
+            EventKind::ConcludeObjectives,
+            EventKind::EndBattle
+        ]
+    );
+}
+
+#[test]
+fn check_event() {
+    // Create the scenario.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    let history_len = server.battle().history().len();
+    // Check a valid event.
+    let event = StartRound::trigger(&mut server, ENTITY_1_ID).event();
+    assert_eq!(server.check_event(&*event).err(), None);
+    // Nothing should have been applied.
+    assert_eq!(server.battle().history().len(), history_len);
+    assert_eq!(server.battle().rounds().active_actor(), None);
+    // Check an invalid event, the error must be the same returned by fire.
+    let event = EndRound::trigger(&mut server).event();
+    assert_eq!(
+        server.check_event(&*event).err(),
+        EndRound::trigger(&mut server).fire().err()
+    );
+    assert_eq!(
+        server.check_event(&*event).err().map(|e| e.unfold()),
+        Some(WeaselError::NoRoundInProgress)
+    );
+}
+
+#[test]
+fn fire_all() {
+    use weasel::creature::CreateCreature;
+    use weasel::team::CreateTeam;
+
+    static TEAM_2_ID: u32 = 2;
+    static CREATURE_2_ID: u32 = 2;
+    // Create the scenario.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    let history_len = server.battle().history().len();
+    // A sequence with an invalid event should be rejected as a whole.
+    let events = vec![
+        DummyEvent::trigger(&mut ()).event(),
+        EndRound::trigger(&mut ()).event(),
+    ];
+    assert_eq!(
+        server.fire_all(events).err().map(|e| e.unfold()),
+        Some(WeaselError::NoRoundInProgress)
+    );
+    assert_eq!(server.battle().history().len(), history_len);
+    // A sequence of valid events should be applied in order.
+    let events = vec![
+        DummyEvent::trigger(&mut ()).event(),
+        StartRound::trigger(&mut (), ENTITY_1_ID).event(),
+    ];
+    assert_eq!(server.fire_all(events).err(), None);
+    assert_eq!(server.battle().history().len(), history_len + 2);
+    assert_eq!(server.battle().rounds().active_actor(), Some(&ENTITY_1_ID));
+    // Without a rules factory, events depending on the previous ones are rejected.
+    let events = vec![
+        CreateTeam::trigger(&mut (), TEAM_2_ID).event(),
+        CreateCreature::trigger(&mut (), CREATURE_2_ID, TEAM_2_ID, ()).event(),
+    ];
+    assert_eq!(
+        server.fire_all(events).err().map(|e| e.unfold()),
+        Some(WeaselError::TeamNotFound(TEAM_2_ID))
+    );
+    assert_eq!(server.battle().history().len(), history_len + 2);
+    assert!(server.battle().entities().team(&TEAM_2_ID).is_none());
+    // Events that fail only after the previous ones are applied don't revert them.
+    let events = vec![
+        EndRound::trigger(&mut ()).event(),
+        EndRound::trigger(&mut ()).event(),
+    ];
+    assert_eq!(
+        server.fire_all(events).err().map(|e| e.unfold()),
+        Some(WeaselError::NoRoundInProgress)
+    );
+    assert_eq!(server.battle().history().len(), history_len + 3);
+    assert_eq!(server.battle().rounds().active_actor(), None);
+}
+
+#[test]
+fn fire_all_cumulative() {
+    use std::rc::Rc;
+    use weasel::battle::Battle;
+    use weasel::creature::CreateCreature;
+    use weasel::server::Server;
+    use weasel::team::CreateTeam;
+
+    // Create a server able to copy its battle.
+    let battle = Battle::builder(CustomRules::new()).build();
+    let mut server = Server::builder(battle)
+        .rules_factory(Rc::new(CustomRules::new))
+        .build();
+    // Events can depend on the previous ones in the sequence.
+    let events = vec![
+        CreateTeam::trigger(&mut (), TEAM_1_ID).event(),
+        CreateCreature::trigger(&mut (), CREATURE_1_ID, TEAM_1_ID, ()).event(),
+        StartRound::trigger(&mut (), ENTITY_1_ID).event(),
+    ];
+    assert_eq!(server.fire_all(events).err(), None);
+    assert_eq!(server.battle().rounds().active_actor(), Some(&ENTITY_1_ID));
+    let history_len = server.battle().history().len();
+    // Events valid on their own, but not one after the other, are rejected as a whole.
+    let events = vec![
+        EndRound::trigger(&mut ()).event(),
+        EndRound::trigger(&mut ()).event(),
+    ];
+    assert_eq!(
+        server.fire_all(events).err().map(|e| e.unfold()),
+        Some(WeaselError::NoRoundInProgress)
+    );
+    assert_eq!(server.battle().history().len(), history_len);
+    assert_eq!(server.battle().rounds().active_actor(), Some(&ENTITY_1_ID));
+}
+
+#[test]
+fn rules_on_round_end() {
+    use weasel::event::EventKind;
+
+    // Battle rules that fire an event at the end of each round.
+    #[derive(Default)]
+    struct GlobalRules {
+        team_rules: EmptyTeamRules,
+        character_rules: EmptyCharacterRules,
+        actor_rules: EmptyActorRules,
+        fight_rules: EmptyFightRules,
+        user_rules: EmptyUserRules,
+        version: u32,
+    }
+
+    impl BattleRules for GlobalRules {
+        type TR = EmptyTeamRules;
+        type CR = EmptyCharacterRules;
+        type AR = EmptyActorRules;
+        type FR = EmptyFightRules;
+        type UR = EmptyUserRules;
+        type SR = EmptySpaceRules;
+        type RR = EmptyRoundsRules;
+        type ER = EmptyEntropyRules;
+        type Version = u32;
+
+        fn team_rules(&self) -> &Self::TR {
+            &self.team_rules
+        }
+        fn character_rules(&self) -> &Self::CR {
+            &self.character_rules
+        }
+        fn actor_rules(&self) -> &Self::AR {
+            &self.actor_rules
+        }
+        fn fight_rules(&self) -> &Self::FR {
+            &self.fight_rules
+        }
+        fn user_rules(&self) -> &Self::UR {
+            &self.user_rules
+        }
+        fn space_rules(&mut self) -> Self::SR {
+            EmptySpaceRules::default()
+        }
+        fn rounds_rules(&mut self) -> Self::RR {
+            EmptyRoundsRules::default()
+        }
+        fn entropy_rules(&mut self) -> Self::ER {
+            EmptyEntropyRules::default()
+        }
+        fn version(&self) -> &Self::Version {
+            &self.version
+        }
+
+        fn on_round_end(
+            &self,
+            _: &BattleState<Self>,
+            event_queue: &mut Option<EventQueue<Self>>,
+            _: &mut Entropy<Self>,
+            _: &mut WriteMetrics<Self>,
+        ) {
+            DummyEvent::trigger(event_queue).fire();
+        }
+    }
+
+    static ENTITY_1_ID: EntityId<GlobalRules> = EntityId::Creature(CREATURE_1_ID);
+    let mut server = util::server(GlobalRules::default());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    // The callback should be invoked only when the round ends.
+    util::start_round(&mut server, &ENTITY_1_ID);
+    assert_eq!(
+        server
+            .battle()
+            .history()
+            .count_by_kind(EventKind::DummyEvent),
+        0
+    );
+    util::end_round(&mut server);
+    assert_eq!(
+        server
+            .battle()
+            .history()
+            .count_by_kind(EventKind::DummyEvent),
+        1
+    );
+}
+
+#[test]
+fn migrate_event() {
+    use weasel::event::{Event, EventKind};
+    use weasel::Server;
+
+    static VERSION_OLD: u32 = 1;
+    static VERSION_NEW: u32 = 2;
+
+    // Battle rules able to upgrade dummy events from the previous version.
+    #[derive(Default)]
+    struct MigratingRules {
+        team_rules: EmptyTeamRules,
+        character_rules: EmptyCharacterRules,
+        actor_rules: EmptyActorRules,
+        fight_rules: EmptyFightRules,
+        user_rules: EmptyUserRules,
+        version: u32,
+    }
+
+    impl BattleRules for MigratingRules {
+        type TR = EmptyTeamRules;
+        type CR = EmptyCharacterRules;
+        type AR = EmptyActorRules;
+        type FR = EmptyFightRules;
+        type UR = EmptyUserRules;
+        type SR = EmptySpaceRules;
+        type RR = EmptyRoundsRules;
+        type ER = EmptyEntropyRules;
+        type Version = u32;
+
+        fn team_rules(&self) -> &Self::TR {
+            &self.team_rules
+        }
+        fn character_rules(&self) -> &Self::CR {
+            &self.character_rules
+        }
+        fn actor_rules(&self) -> &Self::AR {
+            &self.actor_rules
+        }
+        fn fight_rules(&self) -> &Self::FR {
+            &self.fight_rules
+        }
+        fn user_rules(&self) -> &Self::UR {
+            &self.user_rules
+        }
+        fn space_rules(&mut self) -> Self::SR {
+            EmptySpaceRules::default()
+        }
+        fn rounds_rules(&mut self) -> Self::RR {
+            EmptyRoundsRules::default()
+        }
+        fn entropy_rules(&mut self) -> Self::ER {
+            EmptyEntropyRules::default()
+        }
+        fn version(&self) -> &Self::Version {
+            &self.version
+        }
+
+        fn migrate_event(
+            &self,
+            from_version: &Self::Version,
+            event: &dyn Event<Self>,
+        ) -> Option<Box<dyn Event<Self>>> {
+            if *from_version == VERSION_OLD && event.kind() == EventKind::DummyEvent {
+                Some(event.box_clone())
+            } else {
+                None
+            }
+        }
+    }
+
+    let rules = |version| MigratingRules {
+        version,
+        ..Default::default()
+    };
+    // Create a history with the old rules.
+    let mut server = util::server(rules(VERSION_OLD));
+    util::dummy(&mut server);
+    util::dummy(&mut server);
+    let len = server.battle().history().len() as usize;
+    // Dummy events are upgraded.
+    let events = server.battle().versioned_events(0..len);
+    let replayed = Server::replay(rules(VERSION_NEW), events);
+    assert!(replayed.is_ok());
+    assert_eq!(replayed.unwrap().battle().history().len(), 2);
+    // Other events are still rejected.
+    util::team(&mut server, TEAM_1_ID);
+    let events = server.battle().versioned_events(0..len + 1);
+    assert_eq!(
+        Server::replay(rules(VERSION_NEW), events).err(),
+        Some(WeaselError::IncompatibleVersions(VERSION_NEW, VERSION_OLD))
+    );
+    // Events from the same version never go through the migration.
+    let events = server.battle().versioned_events(0..len + 1);
+    assert!(Server::replay(rules(VERSION_OLD), events).is_ok());
+}
+
+#[test]
+fn reset_metrics() {
+    use weasel::metric::system::*;
+
+    battle_rules! {}
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    assert_eq!(server.battle().metrics().system_u64(TEAMS_CREATED), Some(1));
+    // Reset metrics. The battle state is not affected.
+    server.reset_metrics();
+    assert_eq!(server.battle().metrics().system_u64(TEAMS_CREATED), None);
+    assert_eq!(server.battle().metrics().system_metrics().count(), 0);
+    assert!(server
+        .battle()
+        .entities()
+        .creature(&CREATURE_1_ID)
+        .is_some());
+    // The number of creatures in each team is preserved.
+    assert_eq!(
+        server
+            .battle()
+            .metrics()
+            .team_u64(&TEAM_1_ID, TEAM_CREATURES),
+        Some(1)
+    );
+    // Counters restart from zero.
+    util::creature(&mut server, 2, TEAM_1_ID, ());
+    assert_eq!(
+        server.battle().metrics().system_u64(CREATURES_CREATED),
+        Some(1)
+    );
+    assert_eq!(
+        server
+            .battle()
+            .metrics()
+            .team_u64(&TEAM_1_ID, TEAM_CREATURES),
+        Some(2)
+    );
+}
+
+#[test]
+fn state_diff() {
+    use weasel::battle::StateDifference;
+    use weasel::battle_rules_with_character;
+    use weasel::character::CharacterRules;
+    use weasel::creature::CreateCreature;
+    use weasel::team::{Relation, SetRelations};
+
+    #[derive(Default)]
+    pub struct CustomCharacterRules {}
+
+    impl<R: BattleRules + 'static> CharacterRules<R> for CustomCharacterRules {
+        type CreatureId = u32;
+        type ObjectId = u32;
+        type Statistic = EmptyStat;
+        type StatisticsSeed = u32;
+        type StatisticsAlteration = ();
+
+        fn generate_statistics(
+            &self,
+            seed: &Option<Self::StatisticsSeed>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Box<dyn Iterator<Item = Self::Statistic>> {
+            let v: Vec<_> = seed.iter().map(|id| EmptyStat { id: *id }).collect();
+            Box::new(v.into_iter())
+        }
+    }
+
+    battle_rules_with_character! { CustomCharacterRules }
+    static TEAM_2_ID: u32 = 2;
+    static CREATURE_2_ID: u32 = 2;
+    static STATISTIC_ID: u32 = 1;
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ENTITY_2_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_2_ID);
+    // Create two identical battles.
+    let mut servers = vec![
+        util::server(CustomRules::new()),
+        util::server(CustomRules::new()),
+    ];
+    for server in &mut servers {
+        util::team(server, TEAM_1_ID);
+        util::team(server, TEAM_2_ID);
+        util::creature(server, CREATURE_1_ID, TEAM_1_ID, ());
+    }
+    let (first, second) = (&servers[0], &servers[1]);
+    assert_eq!(first.battle().state().diff(second.battle().state()), vec![]);
+    // Make the battles diverge.
+    let (first, second) = servers.split_at_mut(1);
+    let (first, second) = (&mut first[0], &mut second[0]);
+    assert_eq!(
+        SetRelations::trigger(first, &[(TEAM_1_ID, TEAM_2_ID, Relation::Ally)])
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(
+        CreateCreature::trigger(first, CREATURE_2_ID, TEAM_2_ID, ())
+            .statistics_seed(STATISTIC_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(
+        CreateCreature::trigger(second, CREATURE_2_ID, TEAM_1_ID, ())
+            .fire()
+            .err(),
+        None
+    );
+    util::start_round(second, &ENTITY_1_ID);
+    assert_eq!(
+        first.battle().state().diff(second.battle().state()),
+        vec![
+            StateDifference::Relation(TEAM_1_ID, TEAM_2_ID),
+            StateDifference::Relation(TEAM_2_ID, TEAM_1_ID),
+            StateDifference::EntityTeam(ENTITY_2_ID),
+            StateDifference::Statistic(ENTITY_2_ID, STATISTIC_ID),
+            StateDifference::Rounds,
+        ]
+    );
+}
+
+#[test]
+fn state_diff_teams_and_actors() {
+    use weasel::battle::StateDifference;
+    use weasel::battle_rules_with;
+    use weasel::creature::{CreateCreature, SetCreatureDisabled};
+    use weasel::team::{ConcludeObjectives, CreateTeam, TeamRules};
+
+    #[derive(Default)]
+    pub struct CustomTeamRules {}
+
+    impl<R: BattleRules> TeamRules<R> for CustomTeamRules {
+        type Id = u32;
+        type ObjectivesSeed = u32;
+        type Objectives = u32;
+        type ObjectivesProgress = ();
+
+        fn generate_objectives(&self, seed: &Option<Self::ObjectivesSeed>) -> Self::Objectives {
+            seed.unwrap_or_default()
+        }
+    }
+
+    #[derive(Default)]
+    pub struct CustomActorRules {}
+
+    impl<R: BattleRules> ActorRules<R> for CustomActorRules {
+        type Ability = EmptyAbility;
+        type AbilitiesSeed = u32;
+        type Activation = ();
+        type AbilitiesAlteration = ();
+
+        fn generate_abilities(
+            &self,
+            seed: &Option<Self::AbilitiesSeed>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Box<dyn Iterator<Item = Self::Ability>> {
+            let v = vec![EmptyAbility {
+                id: seed.unwrap_or(ABILITY_ID),
+            }];
+            Box::new(v.into_iter())
+        }
+
+        fn ability_cooldown(&self, _ability: &Self::Ability) -> u32 {
+            2
+        }
+    }
+
+    battle_rules_with! {
+        team: CustomTeamRules,
+        actor: CustomActorRules
+    }
+    static TEAM_2_ID: u32 = 2;
+    static CREATURE_2_ID: u32 = 2;
+    static ABILITY_2_ID: u32 = 2;
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ENTITY_2_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_2_ID);
+    // Create two battles whose teams and creatures are generated from different seeds.
+    let mut servers = [
+        util::server(CustomRules::new()),
+        util::server(CustomRules::new()),
+    ];
+    for (i, server) in servers.iter_mut().enumerate() {
+        assert_eq!(
+            CreateTeam::trigger(server, TEAM_1_ID)
+                .objectives_seed(i as u32)
+                .fire()
+                .err(),
+            None
+        );
+        util::team(server, TEAM_2_ID);
+        util::creature(server, CREATURE_1_ID, TEAM_1_ID, ());
+    }
+    let (first, second) = servers.split_at_mut(1);
+    let (first, second) = (&mut first[0], &mut second[0]);
+    assert_eq!(
+        CreateCreature::trigger(first, CREATURE_2_ID, TEAM_2_ID, ())
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(
+        CreateCreature::trigger(second, CREATURE_2_ID, TEAM_2_ID, ())
+            .abilities_seed(ABILITY_2_ID)
+            .fire()
+            .err(),
+        None
+    );
+    // Make the battles diverge further.
+    assert_eq!(
+        ConcludeObjectives::trigger(first, TEAM_2_ID, Conclusion::Defeat)
+            .fire()
+            .err(),
+        None
+    );
+    util::start_round(first, &ENTITY_1_ID);
+    assert_eq!(
+        ActivateAbility::trigger(first, ENTITY_1_ID, ABILITY_ID)
+            .fire()
+            .err(),
+        None
+    );
+    util::end_round(first);
+    assert_eq!(
+        SetCreatureDisabled::trigger(second, CREATURE_2_ID, true)
+            .fire()
+            .err(),
+        None
+    );
+    let differences = first.battle().state().diff(second.battle().state());
+    let expected = vec![
+        StateDifference::Objectives(TEAM_1_ID),
+        StateDifference::Conclusion(TEAM_2_ID),
+        StateDifference::Cooldown(ENTITY_1_ID, ABILITY_ID),
+        StateDifference::Disabled(ENTITY_2_ID),
+        StateDifference::Ability(ENTITY_2_ID, ABILITY_ID),
+        StateDifference::Ability(ENTITY_2_ID, ABILITY_2_ID),
+    ];
+    // Entities are not compared in a fixed order.
+    assert_eq!(differences.len(), expected.len());
+    for difference in expected {
+        assert!(differences.contains(&difference));
+    }
+}
+
+#[cfg(feature = "serialization")]
+#[test]
+fn snapshot() {
+    use weasel::battle::Battle;
+    use weasel::metric::system::TEAM_CREATURES;
+    use weasel::player::GrantRights;
+    use weasel::serde::BattleSnapshot;
+    use weasel::server::Server;
+    use weasel::team::{ConcludeObjectives, Relation, SetRelations};
+
+    static TEAM_2_ID: u32 = 2;
+    static OBJECT_1_ID: u32 = 1;
+    static ENTITY_1_ID: EntityId<EndingRules> = EntityId::Creature(CREATURE_1_ID);
+    // Create a battle with some state.
+    let mut server = util::server(EndingRules::default());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::object(&mut server, OBJECT_1_ID, ());
+    assert_eq!(
+        SetRelations::trigger(&mut server, &[(TEAM_1_ID, TEAM_2_ID, Relation::Ally)])
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(
+        GrantRights::trigger(&mut server, 1, TEAM_2_ID).fire().err(),
+        None
+    );
+    util::start_round(&mut server, &ENTITY_1_ID);
+    assert_eq!(
+        ConcludeObjectives::trigger(&mut server, TEAM_1_ID, Conclusion::Victory)
+            .fire()
+            .err(),
+        None
+    );
+    assert!(server.battle().outcome().is_some());
+    // Save a snapshot and restore it.
+    let json = serde_json::to_string(&BattleSnapshot::new(server.battle())).unwrap();
+    let snapshot: BattleSnapshot<EndingRules> = serde_json::from_str(&json).unwrap();
+    let restored = Server::from_snapshot(EndingRules::default(), snapshot);
+    assert_eq!(restored.battle().history().len(), 0);
+    // The restored state must be equal to the one obtained by replaying the history.
+    let events = server
+        .battle()
+        .versioned_events(0..server.battle().history().len() as usize);
+    let replayed = Server::replay(EndingRules::default(), events).unwrap();
+    for battle in &[server.battle(), replayed.battle()] {
+        let teams = |battle: &Battle<EndingRules>| -> Vec<_> {
+            battle
+                .entities()
+                .teams()
+                .map(|team| (*team.id(), team.creatures().cloned().collect::<Vec<_>>()))
+                .collect()
+        };
+        assert_eq!(teams(restored.battle()), teams(battle));
+        let team_of = |battle: &Battle<EndingRules>| {
+            *battle
+                .entities()
+                .creature(&CREATURE_1_ID)
+                .unwrap()
+                .team_id()
+        };
+        assert_eq!(team_of(restored.battle()), team_of(battle));
+        assert_eq!(
+            restored.battle().entities().object(&OBJECT_1_ID).is_some(),
+            battle.entities().object(&OBJECT_1_ID).is_some()
+        );
+        assert_eq!(
+            restored
+                .battle()
+                .entities()
+                .relation(&TEAM_1_ID, &TEAM_2_ID),
+            battle.entities().relation(&TEAM_1_ID, &TEAM_2_ID)
+        );
+        assert_eq!(restored.battle().rounds().state(), battle.rounds().state());
+        assert_eq!(restored.battle().phase(), battle.phase());
+        assert_eq!(restored.battle().outcome(), battle.outcome());
+        assert_eq!(
+            restored.battle().entropy().model(),
+            battle.entropy().model()
+        );
+        let metrics = |battle: &Battle<EndingRules>| {
+            let mut metrics: Vec<_> = battle.metrics().system_metrics().collect();
+            metrics.sort();
+            metrics
+        };
+        assert_eq!(metrics(restored.battle()), metrics(battle));
+        assert_eq!(
+            restored
+                .battle()
+                .metrics()
+                .team_u64(&TEAM_1_ID, TEAM_CREATURES),
+            Some(1)
+        );
+    }
+    assert!(restored.rights().check(1, &TEAM_2_ID));
+}
@@ -67,6 +67,9 @@ struct SinkImpl {
     id: EventSinkId,
     disconnections: u32,
     broken: bool,
+    // Number of events that can be sent before the sink breaks.
+    capacity: Option<u32>,
+    flushes: u32,
 }
 
 impl SinkImpl {
@@ -75,6 +78,8 @@ impl SinkImpl {
             id,
             disconnections: 0,
             broken: false,
+            capacity: None,
+            flushes: 0,
         }
     }
 }
@@ -172,13 +177,26 @@ impl<R: BattleRules> EventSink for TestClientSink<R> {
 
 impl<R: BattleRules> ClientSink<R> for TestClientSink<R> {
     fn send(&mut self, event: &VersionedEventWrapper<R>) -> WeaselResult<(), R> {
-        if self.sink.borrow().broken {
+        let mut sink = self.sink.borrow_mut();
+        if let Some(capacity) = sink.capacity.as_mut() {
+            if *capacity == 0 {
+                sink.broken = true;
+            } else {
+                *capacity -= 1;
+            }
+        }
+        if sink.broken {
             Err(WeaselError::EventSinkError("broken".to_string()))
         } else {
             self.buffer.borrow_mut().push(event.clone());
             Ok(())
         }
     }
+
+    fn flush(&mut self) -> WeaselResult<(), R> {
+        self.sink.borrow_mut().flushes += 1;
+        Ok(())
+    }
 }
 
 #[test]
@@ -218,6 +236,48 @@ fn send_events() {
     );
 }
 
+#[test]
+fn event_notes() {
+    // Create a server.
+    let server = Rc::new(RefCell::new(util::server(CustomRules::new())));
+    let server_sink = TestServerSink::new(SERVER_1_ID, server.clone());
+    // Create a client.
+    let client = Rc::new(RefCell::new(util::client(
+        CustomRules::new(),
+        server_sink.clone(),
+    )));
+    // Connect the client to the server.
+    let mut client_sink = TestClientSink::new(CLIENT_1_ID, client.clone());
+    add_sink!(server, client_sink);
+    // Fire an event without note and one with a note.
+    util::dummy(&mut *server.borrow_mut());
+    assert_eq!(client_sink.receive().err(), None);
+    assert_eq!(
+        DummyEvent::trigger(&mut *client.borrow_mut())
+            .note("AI turn 3")
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(client_sink.receive().err(), None);
+    // Notes must be preserved in both histories.
+    for events in &[events!(server), events!(client)] {
+        assert_eq!(events[0].note(), None);
+        assert_eq!(events[1].note(), Some("AI turn 3"));
+    }
+    // Notes must survive serialization.
+    #[cfg(feature = "serialization")]
+    {
+        let history_json = helper::history_as_json(server.borrow().battle());
+        let mut server = util::server(CustomRules::new());
+        helper::load_json_history(&mut server, history_json);
+        assert_eq!(
+            server.battle().history().events()[1].note(),
+            Some("AI turn 3")
+        );
+    }
+}
+
 #[test]
 fn send_errors() {
     // Create a server.
@@ -260,6 +320,101 @@ fn send_errors() {
     assert_eq!(events!(server).len(), 1);
 }
 
+#[test]
+fn send_range_errors() {
+    // Create a server with four events.
+    let server = Rc::new(RefCell::new(util::server(CustomRules::new())));
+    let server_sink = TestServerSink::new(SERVER_1_ID, server.clone());
+    for _ in 0..4 {
+        util::dummy(&mut *server.borrow_mut());
+    }
+    // Create a client whose sink breaks on the third event.
+    let client = Rc::new(RefCell::new(util::client(
+        CustomRules::new(),
+        server_sink.clone(),
+    )));
+    let mut client_sink = TestClientSink::new(CLIENT_1_ID, client.clone());
+    client_sink.sink.borrow_mut().capacity = Some(2);
+    assert_eq!(
+        server
+            .borrow_mut()
+            .client_sinks_mut()
+            .add_sink_range(Box::new(client_sink.clone()), Range { start: 0, end: 0 })
+            .err(),
+        None
+    );
+    // Send the whole history.
+    assert_eq!(
+        server
+            .borrow_mut()
+            .client_sinks_mut()
+            .send_range(CLIENT_1_ID, Range { start: 0, end: 4 })
+            .err(),
+        Some(WeaselError::EventSinkError("broken".to_string()))
+    );
+    // Only the first two events were delivered, then the sink got disconnected.
+    assert_eq!(client_sink.receive().err(), None);
+    assert_eq!(events!(client).len(), 2);
+    assert_eq!(client_sink.sink.borrow().disconnections, 1);
+    assert_eq!(server.borrow().client_sinks().sinks().count(), 0);
+    // Reconnect the sink and resume from where it stopped.
+    client_sink.sink.borrow_mut().broken = false;
+    client_sink.sink.borrow_mut().capacity = None;
+    let start = client.borrow().battle().history().len();
+    add_sink_from!(server, client_sink, start);
+    assert_eq!(client_sink.receive().err(), None);
+    assert_eq!(events!(client).len(), 4);
+    assert_eq!(
+        server.borrow().client_sinks().status().collect::<Vec<_>>(),
+        vec![(CLIENT_1_ID, 4)]
+    );
+}
+
+#[test]
+fn send_range_chunked() {
+    // Create a server with five events.
+    let server = Rc::new(RefCell::new(util::server(CustomRules::new())));
+    let server_sink = TestServerSink::new(SERVER_1_ID, server.clone());
+    for _ in 0..5 {
+        util::dummy(&mut *server.borrow_mut());
+    }
+    let client = Rc::new(RefCell::new(util::client(
+        CustomRules::new(),
+        server_sink.clone(),
+    )));
+    let mut client_sink = TestClientSink::new(CLIENT_1_ID, client.clone());
+    add_sink!(server, client_sink);
+    let send = |id, range, chunk_size| {
+        server
+            .borrow_mut()
+            .client_sinks_mut()
+            .send_range_chunked(id, range, chunk_size)
+    };
+    // Check errors.
+    assert_eq!(
+        send(CLIENT_ERR_ID, Range { start: 0, end: 5 }, 2).err(),
+        Some(WeaselError::EventSinkNotFound(CLIENT_ERR_ID))
+    );
+    assert_eq!(
+        send(CLIENT_1_ID, Range { start: 0, end: 6 }, 2).err(),
+        Some(WeaselError::InvalidEventRange(
+            Range { start: 0, end: 6 },
+            5
+        ))
+    );
+    // Send all events in three chunks.
+    assert_eq!(send(CLIENT_1_ID, Range { start: 0, end: 5 }, 2), Ok(5));
+    assert_eq!(client_sink.sink.borrow().flushes, 3);
+    assert_eq!(client_sink.receive().err(), None);
+    assert_eq!(events!(client).len(), 5);
+    // The sink breaks in the middle of the second chunk.
+    client_sink.sink.borrow_mut().capacity = Some(3);
+    assert_eq!(send(CLIENT_1_ID, Range { start: 0, end: 5 }, 2), Ok(2));
+    assert_eq!(client_sink.sink.borrow().flushes, 4);
+    assert_eq!(client_sink.sink.borrow().disconnections, 1);
+    assert_eq!(server.borrow().client_sinks().sinks().count(), 0);
+}
+
 #[test]
 fn integrity_checks() {
     // Create a server.
@@ -282,11 +437,23 @@ fn integrity_checks() {
         Some(WeaselError::NonContiguousEventId(1, 0))
     );
     // Reattach client from history start.
-    server
-        .borrow_mut()
-        .client_sinks_mut()
-        .remove_sink(CLIENT_1_ID);
-    add_sink_from!(server, client_sink, 0);
+    let client_sink_err = TestClientSink::new(CLIENT_ERR_ID, client.clone());
+    assert_eq!(
+        server
+            .borrow_mut()
+            .client_sinks_mut()
+            .reconnect_sink(Box::new(client_sink_err), 0)
+            .err(),
+        Some(WeaselError::EventSinkNotFound(CLIENT_ERR_ID))
+    );
+    assert_eq!(
+        server
+            .borrow_mut()
+            .client_sinks_mut()
+            .reconnect_sink(Box::new(client_sink.clone()), 0)
+            .err(),
+        None
+    );
     assert_eq!(client_sink.receive().err(), None);
     assert_eq!(events!(server).len(), 2);
     assert_eq!(events!(client).len(), 2);
@@ -321,6 +488,51 @@ fn integrity_checks() {
     assert_eq!(events!(client).len(), 5);
 }
 
+#[test]
+fn buffered_events() {
+    // Create a server with a few events.
+    let server = Rc::new(RefCell::new(util::server(CustomRules::new())));
+    let server_sink = TestServerSink::new(SERVER_1_ID, server.clone());
+    for _ in 0..3 {
+        util::dummy(&mut *server.borrow_mut());
+    }
+    let events: Vec<_> = server.borrow().battle().versioned_events(0..3).collect();
+    // Create a client that can buffer up to two events.
+    let battle = Battle::builder(CustomRules::new()).build();
+    let mut client = Client::builder(battle, Box::new(server_sink))
+        .pending_events_limit(2)
+        .build();
+    // Events ahead of time should be kept aside.
+    assert_eq!(client.receive_buffered(events[2].clone()).err(), None);
+    assert_eq!(client.receive_buffered(events[1].clone()).err(), None);
+    assert_eq!(client.battle().history().len(), 0);
+    assert_eq!(
+        client
+            .pending_events()
+            .map(|e| e.wrapper().id())
+            .collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    // Buffer is full.
+    util::dummy(&mut *server.borrow_mut());
+    let event = server
+        .borrow()
+        .battle()
+        .versioned_events(3..4)
+        .next()
+        .unwrap();
+    assert_eq!(
+        client.receive_buffered(event.clone()).err(),
+        Some(WeaselError::NonContiguousEventId(3, 0))
+    );
+    // Fill the gap, all pending events should be applied.
+    assert_eq!(client.receive_buffered(events[0].clone()).err(), None);
+    assert_eq!(client.battle().history().len(), 3);
+    assert_eq!(client.pending_events().count(), 0);
+    assert_eq!(client.receive_buffered(event).err(), None);
+    assert_eq!(client.battle().history().len(), 4);
+}
+
 #[test]
 fn check_version() {
     static VERSION_NEW: u32 = 4;
@@ -396,6 +608,10 @@ fn add_client_sink() {
     );
     assert_eq!(client_sink.receive().err(), None);
     assert_eq!(events!(client).len(), 2);
+    let status = |server: &Rc<RefCell<Server<CustomRules>>>| {
+        server.borrow().client_sinks().status().collect::<Vec<_>>()
+    };
+    assert_eq!(status(&server), vec![(CLIENT_1_ID, 2)]);
     // Check id is verified when sending events.
     assert_eq!(
         server
@@ -416,6 +632,103 @@ fn add_client_sink() {
     );
     assert_eq!(client_sink.receive().err(), None);
     assert_eq!(events!(client).len(), 4);
+    assert_eq!(status(&server), vec![(CLIENT_1_ID, 4)]);
+    // New events advance the status.
+    util::dummy(&mut *server.borrow_mut());
+    assert_eq!(status(&server), vec![(CLIENT_1_ID, 5)]);
+}
+
+#[test]
+fn filtered_client_sink() {
+    // Create server.
+    let server = Rc::new(RefCell::new(util::server(CustomRules::new())));
+    let server_sink = TestServerSink::new(SERVER_1_ID, server.clone());
+    // Create two clients, one of them doesn't receive team creation events.
+    let client_1 = Rc::new(RefCell::new(util::client(
+        CustomRules::new(),
+        server_sink.clone(),
+    )));
+    let mut client_sink_1 = TestClientSink::new(CLIENT_1_ID, client_1.clone());
+    add_sink!(server, client_sink_1);
+    let client_2 = Rc::new(RefCell::new(util::client(
+        CustomRules::new(),
+        server_sink.clone(),
+    )));
+    let mut client_sink_2 = TestClientSink::new(CLIENT_2_ID, client_2.clone());
+    assert_eq!(
+        server
+            .borrow_mut()
+            .client_sinks_mut()
+            .add_sink_filtered(
+                Box::new(client_sink_2.clone()),
+                Box::new(|event, id| {
+                    assert_eq!(id, CLIENT_2_ID);
+                    event.kind() != EventKind::CreateTeam
+                })
+            )
+            .err(),
+        None
+    );
+    // Fire some events.
+    util::dummy(&mut *server.borrow_mut());
+    util::team(&mut *server.borrow_mut(), TEAM_1_ID);
+    util::dummy(&mut *server.borrow_mut());
+    assert_eq!(client_sink_1.receive().err(), None);
+    assert_eq!(client_sink_2.receive().err(), None);
+    // Both clients should have a contiguous timeline.
+    assert_eq!(events!(client_1).len(), 3);
+    assert_eq!(events!(client_2).len(), 3);
+    // Only the first client should know about the team.
+    assert!(client_1
+        .borrow()
+        .battle()
+        .entities()
+        .team(&TEAM_1_ID)
+        .is_some());
+    assert!(client_2
+        .borrow()
+        .battle()
+        .entities()
+        .team(&TEAM_1_ID)
+        .is_none());
+    assert_eq!(events!(client_2)[1].kind(), EventKind::DummyEvent);
+    // The filter should also apply to past events.
+    server
+        .borrow_mut()
+        .client_sinks_mut()
+        .remove_sink(CLIENT_2_ID);
+    let client_3 = Rc::new(RefCell::new(util::client(
+        CustomRules::new(),
+        server_sink.clone(),
+    )));
+    let mut client_sink_3 = TestClientSink::new(CLIENT_2_ID, client_3.clone());
+    assert_eq!(
+        server
+            .borrow_mut()
+            .client_sinks_mut()
+            .add_sink_filtered(
+                Box::new(client_sink_3.clone()),
+                Box::new(|event, _| event.kind() != EventKind::CreateTeam)
+            )
+            .err(),
+        None
+    );
+    assert_eq!(
+        server
+            .borrow_mut()
+            .client_sinks_mut()
+            .send_range(CLIENT_2_ID, Range { start: 0, end: 3 })
+            .err(),
+        None
+    );
+    assert_eq!(client_sink_3.receive().err(), None);
+    assert_eq!(events!(client_3).len(), 3);
+    assert!(client_3
+        .borrow()
+        .battle()
+        .entities()
+        .team(&TEAM_1_ID)
+        .is_none());
 }
 
 #[test]
@@ -434,7 +747,7 @@ fn rights() {
     .enable_authentication(PLAYER_1_ID)
     .build();
     let client = Rc::new(RefCell::new(client));
-    assert_eq!(client.borrow().authentication(), true);
+    assert!(client.borrow().authentication());
     // Connect the client to the server.
     let mut client_sink = TestClientSink::new(CLIENT_1_ID, client.clone());
     add_sink!(server, client_sink);
@@ -510,6 +823,11 @@ fn rights() {
             .err(),
         None,
     );
+    assert_eq!(client_sink.receive().err(), None);
+    // Check that the player id is stored in the history.
+    assert_eq!(events!(server)[0].player(), None);
+    assert_eq!(events!(server)[2].player(), Some(PLAYER_2_ID));
+    assert_eq!(events!(client)[2].player(), Some(PLAYER_2_ID));
 }
 
 #[test]
@@ -636,3 +954,193 @@ fn client_server_serde() {
         ]
     );
 }
+
+#[test]
+fn predict() {
+    // Create a server with one creature.
+    let server = Rc::new(RefCell::new(util::server(CustomRules::new())));
+    util::team(&mut *server.borrow_mut(), TEAM_1_ID);
+    util::creature(&mut *server.borrow_mut(), CREATURE_1_ID, TEAM_1_ID, ());
+    let server_sink = TestServerSink::new(SERVER_1_ID, server.clone());
+    // Create a client with prediction enabled.
+    let battle = Battle::builder(CustomRules::new()).build();
+    let client = Rc::new(RefCell::new(
+        Client::builder(battle, Box::new(server_sink.clone()))
+            .enable_prediction(Rc::new(CustomRules::new))
+            .build(),
+    ));
+    let mut client_sink = TestClientSink::new(CLIENT_1_ID, client.clone());
+    add_sink_from!(server, client_sink, 0);
+    assert_eq!(client_sink.receive().err(), None);
+    // Predict the start of a round.
+    let prototype = StartRound::trigger(&mut (), ENTITY_1_ID).prototype();
+    assert_eq!(client.borrow_mut().predict(prototype).err(), None);
+    {
+        let client = client.borrow();
+        assert_eq!(client.predictions().count(), 1);
+        assert_eq!(client.battle().rounds().active_actor(), None);
+        assert_eq!(
+            client.predicted_battle().rounds().active_actor(),
+            Some(&ENTITY_1_ID)
+        );
+    }
+    // The server confirms the prediction.
+    assert_eq!(client_sink.receive().err(), None);
+    {
+        let client = client.borrow();
+        assert_eq!(client.predictions().count(), 0);
+        assert_eq!(client.battle().rounds().active_actor(), Some(&ENTITY_1_ID));
+        assert_eq!(
+            client.predicted_battle().history().len(),
+            client.battle().history().len()
+        );
+    }
+    // Events rejected by the server are not predicted.
+    let prototype = StartRound::trigger(&mut (), ENTITY_1_ID).prototype();
+    assert_eq!(
+        client
+            .borrow_mut()
+            .predict(prototype)
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::RoundInProgress)
+    );
+    assert_eq!(client.borrow().predictions().count(), 0);
+    // Predictions diverging from the server's timeline are applied again on top of it.
+    util::end_round(&mut *server.borrow_mut());
+    let prototype = DummyEvent::trigger(&mut ()).prototype();
+    assert_eq!(client.borrow_mut().predict(prototype).err(), None);
+    assert_eq!(
+        client.borrow().predicted_battle().rounds().active_actor(),
+        Some(&ENTITY_1_ID)
+    );
+    assert_eq!(client_sink.receive().err(), None);
+    {
+        let client = client.borrow();
+        assert_eq!(client.predictions().count(), 0);
+        assert_eq!(client.battle().rounds().active_actor(), None);
+        assert_eq!(
+            client.predicted_battle().history().len(),
+            client.battle().history().len()
+        );
+    }
+    // Predictions not yet confirmed can be discarded.
+    let prototype = DummyEvent::trigger(&mut ()).prototype();
+    assert_eq!(client.borrow_mut().predict(prototype).err(), None);
+    assert_eq!(client.borrow().predictions().count(), 1);
+    client.borrow_mut().clear_predictions();
+    assert_eq!(client.borrow().predictions().count(), 0);
+    assert_eq!(
+        client.borrow().predicted_battle().history().len(),
+        client.borrow().battle().history().len()
+    );
+}
+
+#[test]
+fn predict_rejected() {
+    /// A server sink keeping events aside, to deliver them to the server later.
+    struct QueueServerSink {
+        events: Rc<RefCell<Vec<ClientEventPrototype<CustomRules>>>>,
+    }
+
+    impl EventSink for QueueServerSink {
+        fn id(&self) -> EventSinkId {
+            SERVER_1_ID
+        }
+    }
+
+    impl ServerSink<CustomRules> for QueueServerSink {
+        fn send(
+            &mut self,
+            event: &ClientEventPrototype<CustomRules>,
+        ) -> WeaselResult<(), CustomRules> {
+            self.events.borrow_mut().push(event.clone());
+            Ok(())
+        }
+    }
+
+    static CREATURE_2_ID: u32 = 2;
+    static ENTITY_2_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_2_ID);
+    // Create a server with one creature.
+    let server = Rc::new(RefCell::new(util::server(CustomRules::new())));
+    util::team(&mut *server.borrow_mut(), TEAM_1_ID);
+    util::creature(&mut *server.borrow_mut(), CREATURE_1_ID, TEAM_1_ID, ());
+    // Create a client with prediction enabled, whose events reach the server later.
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let server_sink = QueueServerSink {
+        events: events.clone(),
+    };
+    let battle = Battle::builder(CustomRules::new()).build();
+    let client = Rc::new(RefCell::new(
+        Client::builder(battle, Box::new(server_sink))
+            .enable_prediction(Rc::new(CustomRules::new))
+            .build(),
+    ));
+    let mut client_sink = TestClientSink::new(CLIENT_1_ID, client.clone());
+    add_sink_from!(server, client_sink, 0);
+    assert_eq!(client_sink.receive().err(), None);
+    // Predict two events.
+    let prototype = StartRound::trigger(&mut (), ENTITY_1_ID).prototype();
+    assert_eq!(client.borrow_mut().predict(prototype).err(), None);
+    let prototype = DummyEvent::trigger(&mut ()).prototype();
+    assert_eq!(client.borrow_mut().predict(prototype).err(), None);
+    assert_eq!(client.borrow().predictions().count(), 2);
+    // Meanwhile, the server starts a round with another creature.
+    util::creature(&mut *server.borrow_mut(), CREATURE_2_ID, TEAM_1_ID, ());
+    util::start_round(&mut *server.borrow_mut(), &ENTITY_2_ID);
+    // The server rejects the first prediction and accepts the second.
+    let mut server_events = events.borrow_mut();
+    assert_eq!(
+        server
+            .borrow_mut()
+            .process_client(server_events.remove(0))
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::RoundInProgress)
+    );
+    assert_eq!(
+        server
+            .borrow_mut()
+            .process_client(server_events.remove(0))
+            .err(),
+        None
+    );
+    // Once the second prediction is confirmed, the rejected one is discarded as well.
+    assert_eq!(client_sink.receive().err(), None);
+    let client = client.borrow();
+    assert_eq!(client.predictions().count(), 0);
+    assert_eq!(client.battle().rounds().active_actor(), Some(&ENTITY_2_ID));
+    assert_eq!(
+        client.predicted_battle().history().len(),
+        client.battle().history().len()
+    );
+}
+
+#[test]
+fn has_battle_state() {
+    use weasel::battle::HasBattleState;
+
+    // Counts teams regardless of whether the state belongs to a client or a server.
+    fn teams<S: HasBattleState<CustomRules>>(source: &S) -> usize {
+        source.state().entities().teams().count()
+    }
+
+    // Create a server and a connected client.
+    let server = Rc::new(RefCell::new(util::server(CustomRules::new())));
+    let server_sink = TestServerSink::new(SERVER_1_ID, server.clone());
+    let client = Rc::new(RefCell::new(util::client(
+        CustomRules::new(),
+        server_sink.clone(),
+    )));
+    let mut client_sink = TestClientSink::new(CLIENT_1_ID, client.clone());
+    add_sink!(server, client_sink);
+    // Both should expose the same state.
+    util::team(&mut *server.borrow_mut(), TEAM_1_ID);
+    assert_eq!(client_sink.receive().err(), None);
+    assert_eq!(teams(&*server.borrow()), 1);
+    assert_eq!(teams(&*client.borrow()), 1);
+    assert_eq!(
+        server.borrow().state().diff(client.borrow().state()),
+        vec![]
+    );
+}
@@ -1,15 +1,16 @@
 use weasel::ability::AbilityId;
-use weasel::actor::{ActorRules, RegenerateAbilities};
+use weasel::actor::{Actor, ActorRules, ClearAbilities, RegenerateAbilities};
 use weasel::battle::BattleRules;
 use weasel::character::{
-    AlterStatistics, Character, CharacterRules, RegenerateStatistics, StatisticId,
+    AlterStatistics, AlterStatisticsBatch, Character, CharacterRules, RegenerateStatistics,
+    StatisticId,
 };
-use weasel::creature::{CreateCreature, RemoveCreature};
+use weasel::creature::{ConvertCreature, CreateCreature, CreateCreatures, RemoveCreature};
 use weasel::entity::{EntityId, Transmutation};
 use weasel::entropy::Entropy;
 use weasel::event::EventTrigger;
 use weasel::metric::{system::*, WriteMetrics};
-use weasel::round::RoundState;
+use weasel::round::{RoundState, StartRound};
 use weasel::rules::empty::{EmptyAbility, EmptyStat};
 use weasel::rules::{ability::SimpleAbility, statistic::SimpleStatistic};
 use weasel::user::UserMetricId;
@@ -18,6 +19,7 @@ use weasel::{battle_rules, rules::empty::*};
 use weasel::{battle_rules_with_actor, battle_rules_with_character};
 
 static TEAM_1_ID: u32 = 1;
+static TEAM_2_ID: u32 = 2;
 static TEAM_5_ID: u32 = 5;
 static CREATURE_1_ID: u32 = 1;
 static CREATURE_5_ID: u32 = 5;
@@ -63,6 +65,207 @@ fn new_creature() {
     assert!(server.battle().entities().creature(&0).is_some());
 }
 
+#[test]
+fn create_creatures() {
+    battle_rules! {}
+    static CREATURE_2_ID: u32 = 2;
+    let spec = |id, team_id| CreateCreature::new(id, team_id, (), None, None);
+    // Create a battle with one creature.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    let count =
+        |server: &weasel::Server<CustomRules>| server.battle().entities().creatures().count();
+    // A batch with a non existing team should fail.
+    assert_eq!(
+        CreateCreatures::trigger(
+            &mut server,
+            vec![
+                spec(CREATURE_2_ID, TEAM_1_ID),
+                spec(CREATURE_5_ID, TEAM_2_ID)
+            ]
+        )
+        .fire()
+        .err()
+        .map(|e| e.unfold()),
+        Some(WeaselError::BatchItemFailed(
+            1,
+            Box::new(WeaselError::TeamNotFound(TEAM_2_ID))
+        ))
+    );
+    // A batch with a creature already in the battle should fail.
+    assert_eq!(
+        CreateCreatures::trigger(
+            &mut server,
+            vec![
+                spec(CREATURE_2_ID, TEAM_1_ID),
+                spec(CREATURE_1_ID, TEAM_1_ID)
+            ]
+        )
+        .fire()
+        .err()
+        .map(|e| e.unfold()),
+        Some(WeaselError::BatchItemFailed(
+            1,
+            Box::new(WeaselError::DuplicatedCreature(CREATURE_1_ID))
+        ))
+    );
+    // A batch with the same id twice should fail.
+    assert_eq!(
+        CreateCreatures::trigger(
+            &mut server,
+            vec![
+                spec(CREATURE_2_ID, TEAM_1_ID),
+                spec(CREATURE_2_ID, TEAM_1_ID)
+            ]
+        )
+        .fire()
+        .err()
+        .map(|e| e.unfold()),
+        Some(WeaselError::BatchItemFailed(
+            1,
+            Box::new(WeaselError::DuplicatedCreature(CREATURE_2_ID))
+        ))
+    );
+    // Nothing was created so far.
+    assert_eq!(count(&server), 1);
+    // Create two creatures at once.
+    assert_eq!(
+        CreateCreatures::trigger(
+            &mut server,
+            vec![
+                spec(CREATURE_2_ID, TEAM_1_ID),
+                spec(CREATURE_5_ID, TEAM_1_ID)
+            ]
+        )
+        .fire()
+        .err(),
+        None
+    );
+    assert_eq!(count(&server), 3);
+    assert_eq!(
+        server.battle().metrics().system_u64(CREATURES_CREATED),
+        Some(3)
+    );
+}
+
+#[test]
+fn create_creatures_cumulative() {
+    use weasel::battle::BattleState;
+    use weasel::battle_rules_with;
+    use weasel::entity::Entity;
+    use weasel::space::SpaceRules;
+    use weasel::team::{EntityAddition, Relation, Team, TeamRules};
+
+    static CREATURE_2_ID: u32 = 2;
+    static CREATURE_3_ID: u32 = 3;
+
+    #[derive(Default)]
+    struct CustomTeamRules {}
+
+    impl TeamRules<CustomRules> for CustomTeamRules {
+        type Id = u32;
+        type ObjectivesSeed = ();
+        type Objectives = ();
+        type ObjectivesProgress = ();
+
+        fn allow_new_entity(
+            &self,
+            _state: &BattleState<CustomRules>,
+            team: &Team<CustomRules>,
+            mode: EntityAddition<CustomRules>,
+        ) -> bool {
+            // Teams can have at most two creatures.
+            let pending = match mode {
+                EntityAddition::CreatureBatchSpawn(count) => count,
+                _ => 0,
+            };
+            team.creatures().count() + pending < 2
+        }
+    }
+
+    #[derive(Default)]
+    struct CustomSpaceRules {}
+
+    impl SpaceRules<CustomRules> for CustomSpaceRules {
+        type Position = u32;
+        type SpaceSeed = ();
+        type SpaceModel = ();
+
+        fn generate_model(&self, _: &Option<Self::SpaceSeed>) -> Self::SpaceModel {}
+
+        fn check_overlap(
+            &self,
+            _model: &Self::SpaceModel,
+            _entity: &dyn Entity<CustomRules>,
+            _occupant: &dyn Entity<CustomRules>,
+            _relation: Relation,
+        ) -> bool {
+            // Positions can't be shared.
+            false
+        }
+    }
+
+    battle_rules_with! { team: CustomTeamRules, space: CustomSpaceRules }
+    let spec = |id, team_id, position| CreateCreature::new(id, team_id, position, None, None);
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, 1);
+    // A batch can't exceed the capacity of a team.
+    assert_eq!(
+        CreateCreatures::trigger(
+            &mut server,
+            vec![
+                spec(CREATURE_2_ID, TEAM_1_ID, 2),
+                spec(CREATURE_3_ID, TEAM_1_ID, 3)
+            ]
+        )
+        .fire()
+        .err()
+        .map(|e| e.unfold()),
+        Some(WeaselError::BatchItemFailed(
+            1,
+            Box::new(WeaselError::NewCreatureUnaccepted(TEAM_1_ID))
+        ))
+    );
+    // Two creatures of a batch can't take the same position.
+    assert_eq!(
+        CreateCreatures::trigger(
+            &mut server,
+            vec![
+                spec(CREATURE_2_ID, TEAM_1_ID, 2),
+                spec(CREATURE_3_ID, TEAM_2_ID, 2)
+            ]
+        )
+        .fire()
+        .err()
+        .map(|e| e.unfold()),
+        Some(WeaselError::BatchItemFailed(
+            1,
+            Box::new(WeaselError::PositionOccupied(
+                2,
+                EntityId::Creature(CREATURE_2_ID)
+            ))
+        ))
+    );
+    assert_eq!(server.battle().entities().creatures().count(), 1);
+    // A valid batch is accepted.
+    assert_eq!(
+        CreateCreatures::trigger(
+            &mut server,
+            vec![
+                spec(CREATURE_2_ID, TEAM_1_ID, 2),
+                spec(CREATURE_3_ID, TEAM_2_ID, 3)
+            ]
+        )
+        .fire()
+        .err(),
+        None
+    );
+    assert_eq!(server.battle().entities().creatures().count(), 3);
+}
+
 #[test]
 fn statistics_generated() {
     #[derive(Default)]
@@ -70,6 +273,7 @@ fn statistics_generated() {
 
     impl<R: BattleRules + 'static> CharacterRules<R> for CustomCharacterRules {
         type CreatureId = u32;
+        type ObjectId = u32;
         type Statistic = EmptyStat;
         type StatisticsSeed = u32;
         type StatisticsAlteration = ();
@@ -103,6 +307,100 @@ fn statistics_generated() {
     assert_eq!(stats, vec![&EmptyStat { id: SEED }]);
 }
 
+#[test]
+fn statistics_where() {
+    #[derive(Default)]
+    pub struct CustomCharacterRules {}
+
+    impl<R: BattleRules + 'static> CharacterRules<R> for CustomCharacterRules {
+        type CreatureId = u32;
+        type ObjectId = u32;
+        type Statistic = EmptyStat;
+        type StatisticsSeed = u32;
+        type StatisticsAlteration = ();
+
+        fn generate_statistics(
+            &self,
+            seed: &Option<Self::StatisticsSeed>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Box<dyn Iterator<Item = Self::Statistic>> {
+            Box::new((0..seed.unwrap()).map(|id| EmptyStat { id }))
+        }
+    }
+
+    battle_rules_with_character! { CustomCharacterRules }
+    static SEED: u32 = 6;
+    // Create a creature with a few statistics.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    let mut trigger = CreateCreature::trigger(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    assert_eq!(trigger.statistics_seed(SEED).fire().err(), None);
+    // Query only the statistics with an even id.
+    let creature = server.battle().entities().creature(&CREATURE_1_ID).unwrap();
+    let mut ids: Vec<_> = creature
+        .statistics_where(&|stat| stat.id % 2 == 0)
+        .map(|stat| stat.id)
+        .collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![0, 2, 4]);
+    assert_eq!(creature.statistics_where(&|_| false).count(), 0);
+}
+
+#[test]
+fn convert_creature_keeps_statistics() {
+    #[derive(Default)]
+    pub struct CustomCharacterRules {}
+
+    impl<R: BattleRules + 'static> CharacterRules<R> for CustomCharacterRules {
+        type CreatureId = u32;
+        type ObjectId = u32;
+        type Statistic = EmptyStat;
+        type StatisticsSeed = u32;
+        type StatisticsAlteration = ();
+
+        fn generate_statistics(
+            &self,
+            seed: &Option<Self::StatisticsSeed>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Box<dyn Iterator<Item = Self::Statistic>> {
+            let v = vec![EmptyStat { id: seed.unwrap() }];
+            Box::new(v.into_iter())
+        }
+    }
+
+    battle_rules_with_character! { CustomCharacterRules }
+    static SEED: u32 = 5;
+    // Create two teams and a creature with one statistic.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    assert_eq!(
+        CreateCreature::trigger(&mut server, CREATURE_1_ID, TEAM_1_ID, ())
+            .statistics_seed(SEED)
+            .fire()
+            .err(),
+        None
+    );
+    // Move the creature into the other team.
+    assert_eq!(
+        ConvertCreature::trigger(&mut server, CREATURE_1_ID, TEAM_2_ID)
+            .fire()
+            .err(),
+        None
+    );
+    // Check that the creature is the same as before.
+    let creature = server.battle().entities().creature(&CREATURE_1_ID).unwrap();
+    assert_eq!(*creature.team_id(), TEAM_2_ID);
+    let stats: Vec<_> = creature.statistics().collect();
+    assert_eq!(stats, vec![&EmptyStat { id: SEED }]);
+    // Check that the teams agree with the creature's team.
+    let entities = server.battle().entities();
+    assert_eq!(entities.creatures_of_team(&TEAM_1_ID).count(), 0);
+    assert_eq!(entities.creatures_of_team(&TEAM_2_ID).count(), 1);
+}
+
 #[test]
 fn regenerate_statistics() {
     #[derive(Default)]
@@ -110,6 +408,7 @@ fn regenerate_statistics() {
 
     impl<R: BattleRules + 'static> CharacterRules<R> for CustomCharacterRules {
         type CreatureId = u32;
+        type ObjectId = u32;
         type Statistic = SimpleStatistic<u32, u32>;
         // Vec with pair (id, value).
         type StatisticsSeed = Vec<(u32, u32)>;
@@ -194,6 +493,200 @@ fn regenerate_statistics() {
     );
 }
 
+#[test]
+fn remove_statistic() {
+    use std::cell::Cell;
+    use weasel::character::{RemoveStatistic, Statistic};
+    use weasel::event::EventQueue;
+
+    #[derive(Default)]
+    pub struct CustomCharacterRules {
+        removed: Cell<u32>,
+    }
+
+    impl<R: BattleRules + 'static> CharacterRules<R> for CustomCharacterRules {
+        type CreatureId = u32;
+        type ObjectId = u32;
+        type Statistic = EmptyStat;
+        type StatisticsSeed = Vec<u32>;
+        type StatisticsAlteration = ();
+
+        fn generate_statistics(
+            &self,
+            seed: &Option<Self::StatisticsSeed>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Box<dyn Iterator<Item = Self::Statistic>> {
+            let v: Vec<_> = seed
+                .iter()
+                .flatten()
+                .map(|id| EmptyStat { id: *id })
+                .collect();
+            Box::new(v.into_iter())
+        }
+
+        fn on_statistic_removed(
+            &self,
+            _character: &dyn Character<R>,
+            _statistic: &Statistic<R>,
+            _event_queue: &mut Option<EventQueue<R>>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) {
+            self.removed.set(self.removed.get() + 1);
+        }
+    }
+
+    battle_rules_with_character! { CustomCharacterRules }
+
+    static STAT_1_ID: StatisticId<CustomRules> = 1;
+    static STAT_2_ID: StatisticId<CustomRules> = 2;
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ENTITY_ERR_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_ERR_ID);
+    // Create a new creature with two statistics.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    assert_eq!(
+        CreateCreature::trigger(&mut server, CREATURE_1_ID, TEAM_1_ID, ())
+            .statistics_seed(vec![STAT_1_ID, STAT_2_ID])
+            .fire()
+            .err(),
+        None
+    );
+    // Removal should fail for non existing entities or statistics.
+    assert_eq!(
+        RemoveStatistic::trigger(&mut server, ENTITY_ERR_ID, STAT_1_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::EntityNotFound(ENTITY_ERR_ID))
+    );
+    assert_eq!(
+        RemoveStatistic::trigger(&mut server, ENTITY_1_ID, 99)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::StatisticNotFound(ENTITY_1_ID, 99))
+    );
+    // Remove one statistic.
+    assert_eq!(
+        RemoveStatistic::trigger(&mut server, ENTITY_1_ID, STAT_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    let creature = server.battle().entities().character(&ENTITY_1_ID).unwrap();
+    assert!(creature.statistic(&STAT_1_ID).is_none());
+    assert!(creature.statistic(&STAT_2_ID).is_some());
+    assert_eq!(server.battle().rules().character_rules().removed.get(), 1);
+    // Statistics removed by a regeneration trigger the callback as well.
+    assert_eq!(
+        RegenerateStatistics::trigger(&mut server, ENTITY_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(server.battle().rules().character_rules().removed.get(), 2);
+}
+
+#[test]
+fn add_statistic() {
+    use std::cell::Cell;
+    use weasel::character::{AddStatistic, Statistic};
+    use weasel::event::EventQueue;
+
+    #[derive(Default)]
+    pub struct CustomCharacterRules {
+        added: Cell<u32>,
+        removed: Cell<u32>,
+    }
+
+    impl<R: BattleRules + 'static> CharacterRules<R> for CustomCharacterRules {
+        type CreatureId = u32;
+        type ObjectId = u32;
+        type Statistic = SimpleStatistic<u32, u32>;
+        type StatisticsSeed = ();
+        type StatisticsAlteration = ();
+
+        fn on_statistic_added(
+            &self,
+            _character: &dyn Character<R>,
+            _statistic: &Statistic<R>,
+            _event_queue: &mut Option<EventQueue<R>>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) {
+            self.added.set(self.added.get() + 1);
+        }
+
+        fn on_statistic_removed(
+            &self,
+            _character: &dyn Character<R>,
+            _statistic: &Statistic<R>,
+            _event_queue: &mut Option<EventQueue<R>>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) {
+            self.removed.set(self.removed.get() + 1);
+        }
+    }
+
+    battle_rules_with_character! { CustomCharacterRules }
+
+    static STAT_ID: StatisticId<CustomRules> = 1;
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ENTITY_ERR_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_ERR_ID);
+    // Create a new creature without statistics.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    // Addition should fail for non existing entities.
+    assert_eq!(
+        AddStatistic::trigger(&mut server, ENTITY_ERR_ID, SimpleStatistic::new(STAT_ID, 1))
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::EntityNotFound(ENTITY_ERR_ID))
+    );
+    // Add a statistic.
+    assert_eq!(
+        AddStatistic::trigger(&mut server, ENTITY_1_ID, SimpleStatistic::new(STAT_ID, 1))
+            .fire()
+            .err(),
+        None
+    );
+    let statistic = |server: &weasel::Server<CustomRules>| {
+        server
+            .battle()
+            .entities()
+            .character(&ENTITY_1_ID)
+            .unwrap()
+            .statistic(&STAT_ID)
+            .cloned()
+    };
+    assert_eq!(statistic(&server), Some(SimpleStatistic::new(STAT_ID, 1)));
+    // A statistic with the same id is rejected, unless it's meant to replace the old one.
+    assert_eq!(
+        AddStatistic::trigger(&mut server, ENTITY_1_ID, SimpleStatistic::new(STAT_ID, 2))
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::DuplicatedStatistic(ENTITY_1_ID, STAT_ID))
+    );
+    assert_eq!(statistic(&server), Some(SimpleStatistic::new(STAT_ID, 1)));
+    assert_eq!(
+        AddStatistic::trigger(&mut server, ENTITY_1_ID, SimpleStatistic::new(STAT_ID, 2))
+            .replace()
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(statistic(&server), Some(SimpleStatistic::new(STAT_ID, 2)));
+    // Check that callbacks were invoked.
+    let rules = server.battle().rules().character_rules();
+    assert_eq!(rules.added.get(), 2);
+    assert_eq!(rules.removed.get(), 1);
+}
 #[test]
 fn abilities_generated() {
     #[derive(Default)]
@@ -329,30 +822,119 @@ fn regenerate_abilities() {
 }
 
 #[test]
-fn user_metrics() {
+fn clear_abilities() {
     #[derive(Default)]
-    pub struct CharacterRulesWithMetrics {}
+    pub struct CustomCharacterRules {}
 
-    impl<R: BattleRules + 'static> CharacterRules<R> for CharacterRulesWithMetrics
-    where
-        UserMetricId<R>: Default,
-    {
+    impl<R: BattleRules + 'static> CharacterRules<R> for CustomCharacterRules {
         type CreatureId = u32;
-        type Statistic = SimpleStatistic<u32, u64>;
-        type StatisticsSeed = u64;
+        type ObjectId = u32;
+        type Statistic = EmptyStat;
+        type StatisticsSeed = ();
         type StatisticsAlteration = ();
 
         fn generate_statistics(
             &self,
-            seed: &Option<Self::StatisticsSeed>,
+            _seed: &Option<Self::StatisticsSeed>,
             _entropy: &mut Entropy<R>,
-            metrics: &mut WriteMetrics<R>,
+            _metrics: &mut WriteMetrics<R>,
         ) -> Box<dyn Iterator<Item = Self::Statistic>> {
-            let seed = seed.unwrap();
-            let v = vec![SimpleStatistic::new(0, seed)];
-            metrics
-                .add_user_u64(UserMetricId::<R>::default(), seed)
-                .unwrap();
+            Box::new(std::iter::once(EmptyStat { id: 1 }))
+        }
+    }
+
+    #[derive(Default)]
+    pub struct CustomActorRules {}
+
+    impl<R: BattleRules> ActorRules<R> for CustomActorRules {
+        type Ability = EmptyAbility;
+        type AbilitiesSeed = u32;
+        type Activation = ();
+        type AbilitiesAlteration = ();
+
+        fn generate_abilities(
+            &self,
+            seed: &Option<Self::AbilitiesSeed>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Box<dyn Iterator<Item = Self::Ability>> {
+            Box::new((0..seed.unwrap_or_default()).map(|id| EmptyAbility { id }))
+        }
+    }
+
+    battle_rules! {
+        EmptyTeamRules,
+        CustomCharacterRules,
+        CustomActorRules,
+        EmptyFightRules,
+        EmptyUserRules,
+        EmptySpaceRules,
+        EmptyRoundsRules,
+        EmptyEntropyRules
+    }
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ENTITY_ERR_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_ERR_ID);
+    // Create a new creature with three abilities.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    assert_eq!(
+        CreateCreature::trigger(&mut server, CREATURE_1_ID, TEAM_1_ID, ())
+            .abilities_seed(3)
+            .fire()
+            .err(),
+        None
+    );
+    // Clear should fail for non existing entities.
+    assert_eq!(
+        ClearAbilities::trigger(&mut server, ENTITY_ERR_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::EntityNotFound(ENTITY_ERR_ID))
+    );
+    // Clear abilities.
+    assert_eq!(
+        ClearAbilities::trigger(&mut server, ENTITY_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    let creature = server.battle().entities().actor(&ENTITY_1_ID).unwrap();
+    assert_eq!(creature.abilities().count(), 0);
+    // Statistics are left untouched.
+    assert_eq!(creature.statistics().count(), 1);
+    assert_eq!(
+        server.battle().metrics().system_u64(ABILITIES_CLEARED),
+        Some(3)
+    );
+}
+
+#[test]
+fn user_metrics() {
+    #[derive(Default)]
+    pub struct CharacterRulesWithMetrics {}
+
+    impl<R: BattleRules + 'static> CharacterRules<R> for CharacterRulesWithMetrics
+    where
+        UserMetricId<R>: Default,
+    {
+        type CreatureId = u32;
+        type ObjectId = u32;
+        type Statistic = SimpleStatistic<u32, u64>;
+        type StatisticsSeed = u64;
+        type StatisticsAlteration = ();
+
+        fn generate_statistics(
+            &self,
+            seed: &Option<Self::StatisticsSeed>,
+            _entropy: &mut Entropy<R>,
+            metrics: &mut WriteMetrics<R>,
+        ) -> Box<dyn Iterator<Item = Self::Statistic>> {
+            let seed = seed.unwrap();
+            let v = vec![SimpleStatistic::new(0, seed)];
+            metrics
+                .add_user_u64(UserMetricId::<R>::default(), seed)
+                .unwrap();
             Box::new(v.into_iter())
         }
     }
@@ -434,6 +1016,7 @@ fn remove_creature_on_alter() {
 
     impl<R: BattleRules + 'static> CharacterRules<R> for CustomCharacterRules {
         type CreatureId = u32;
+        type ObjectId = u32;
         type Statistic = EmptyStat;
         type StatisticsSeed = ();
         type StatisticsAlteration = ();
@@ -466,3 +1049,400 @@ fn remove_creature_on_alter() {
     let entities = server.battle().entities();
     assert!(entities.creature(&CREATURE_1_ID).is_none());
 }
+
+#[test]
+fn removal_reason() {
+    use std::cell::RefCell;
+    use weasel::creature::RemovalReason;
+    use weasel::event::EventQueue;
+    use weasel::team::RemoveTeam;
+
+    #[derive(Default)]
+    struct CustomCharacterRules {
+        reasons: RefCell<Vec<RemovalReason>>,
+    }
+
+    impl<R: BattleRules + 'static> CharacterRules<R> for CustomCharacterRules {
+        type CreatureId = u32;
+        type ObjectId = u32;
+        type Statistic = EmptyStat;
+        type StatisticsSeed = ();
+        type StatisticsAlteration = ();
+
+        fn alter(
+            &self,
+            _character: &mut dyn Character<R>,
+            _alteration: &Self::StatisticsAlteration,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Option<Transmutation> {
+            Some(Transmutation::REMOVAL)
+        }
+
+        fn on_remove(
+            &self,
+            _character: &dyn Character<R>,
+            reason: RemovalReason,
+            _event_queue: &mut Option<EventQueue<R>>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) {
+            self.reasons.borrow_mut().push(reason);
+        }
+    }
+
+    battle_rules_with_character! { CustomCharacterRules }
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    // Create a battle with one team and three creatures.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_5_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_ERR_ID, TEAM_1_ID, ());
+    // Remove a creature with a custom reason.
+    assert_eq!(
+        RemoveCreature::trigger(&mut server, CREATURE_5_ID)
+            .reason(RemovalReason::Defeated)
+            .fire()
+            .err(),
+        None
+    );
+    // Remove a creature by altering its statistics.
+    assert_eq!(
+        AlterStatistics::trigger(&mut server, ENTITY_1_ID, ())
+            .fire()
+            .err(),
+        None
+    );
+    // Remove the last creature together with its team.
+    assert_eq!(
+        RemoveTeam::trigger(&mut server, TEAM_1_ID)
+            .cascade()
+            .fire()
+            .err(),
+        None
+    );
+    // Add and remove one more creature, without specifying a reason.
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    assert_eq!(
+        RemoveCreature::trigger(&mut server, CREATURE_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    // Check that the rules received the correct reasons.
+    assert_eq!(
+        *server.battle().rules().character_rules().reasons.borrow(),
+        vec![
+            RemovalReason::Defeated,
+            RemovalReason::Defeated,
+            RemovalReason::TeamRemoved,
+            RemovalReason::Manual
+        ]
+    );
+}
+
+#[test]
+fn disable_creature_on_alter() {
+    #[derive(Default)]
+    struct CustomCharacterRules {}
+
+    impl<R: BattleRules + 'static> CharacterRules<R> for CustomCharacterRules {
+        type CreatureId = u32;
+        type ObjectId = u32;
+        type Statistic = EmptyStat;
+        type StatisticsSeed = ();
+        // True to disable the creature, false to respawn it.
+        type StatisticsAlteration = bool;
+
+        fn alter(
+            &self,
+            _character: &mut dyn Character<R>,
+            alteration: &Self::StatisticsAlteration,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Option<Transmutation> {
+            if *alteration {
+                Some(Transmutation::DISABLE)
+            } else {
+                Some(Transmutation::RESPAWN)
+            }
+        }
+    }
+
+    battle_rules_with_character! { CustomCharacterRules }
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    // Create a battle with one creature and start its round.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::start_round(&mut server, &ENTITY_1_ID);
+    // Disable the creature.
+    assert_eq!(
+        AlterStatistics::trigger(&mut server, ENTITY_1_ID, true)
+            .fire()
+            .err(),
+        None
+    );
+    // The creature is still part of its team, but its round is over.
+    let entities = server.battle().entities();
+    assert!(entities.creature(&CREATURE_1_ID).unwrap().is_disabled());
+    assert!(entities
+        .team(&TEAM_1_ID)
+        .unwrap()
+        .creatures()
+        .any(|e| *e == CREATURE_1_ID));
+    assert_eq!(*server.battle().rounds().state(), RoundState::<_>::Ready);
+    // Disabled creatures can't start a round.
+    assert_eq!(
+        StartRound::trigger(&mut server, ENTITY_1_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::ActorNotEligible(ENTITY_1_ID))
+    );
+    // Respawn the creature.
+    assert_eq!(
+        AlterStatistics::trigger(&mut server, ENTITY_1_ID, false)
+            .fire()
+            .err(),
+        None
+    );
+    assert!(!server
+        .battle()
+        .entities()
+        .creature(&CREATURE_1_ID)
+        .unwrap()
+        .is_disabled());
+    util::start_round(&mut server, &ENTITY_1_ID);
+}
+
+#[test]
+fn alter_statistics_batch() {
+    #[derive(Default)]
+    struct CustomCharacterRules {}
+
+    impl CharacterRules<CustomRules> for CustomCharacterRules {
+        type CreatureId = u32;
+        type ObjectId = u32;
+        type Statistic = SimpleStatistic<u32, u64>;
+        type StatisticsSeed = ();
+        type StatisticsAlteration = u64;
+
+        fn generate_statistics(
+            &self,
+            _seed: &Option<Self::StatisticsSeed>,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) -> Box<dyn Iterator<Item = Self::Statistic>> {
+            Box::new(std::iter::once(SimpleStatistic::new(STAT_ID, 10)))
+        }
+
+        fn alter(
+            &self,
+            character: &mut dyn Character<CustomRules>,
+            alteration: &Self::StatisticsAlteration,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) -> Option<Transmutation> {
+            character
+                .statistic_mut(&STAT_ID)
+                .unwrap()
+                .set_value(*alteration);
+            if *alteration == 0 {
+                Some(Transmutation::REMOVAL)
+            } else {
+                None
+            }
+        }
+    }
+
+    battle_rules_with_character! { CustomCharacterRules }
+    static STAT_ID: u32 = 0;
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ENTITY_5_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_5_ID);
+    static ENTITY_ERR_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_ERR_ID);
+    // Create a battle with two creatures.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_5_ID, TEAM_1_ID, ());
+    let value = |server: &weasel::Server<CustomRules>, id| {
+        server
+            .battle()
+            .entities()
+            .creature(&id)
+            .unwrap()
+            .statistic(&STAT_ID)
+            .unwrap()
+            .value()
+    };
+    // A batch containing a non-existent character should fail without altering anything.
+    assert_eq!(
+        AlterStatisticsBatch::trigger(&mut server, vec![(ENTITY_1_ID, 5), (ENTITY_ERR_ID, 5)])
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::BatchItemFailed(
+            1,
+            Box::new(WeaselError::EntityNotFound(ENTITY_ERR_ID))
+        ))
+    );
+    assert_eq!(value(&server, CREATURE_1_ID), 10);
+    // Alter both creatures, in order.
+    assert_eq!(
+        AlterStatisticsBatch::trigger(
+            &mut server,
+            vec![(ENTITY_1_ID, 5), (ENTITY_5_ID, 3), (ENTITY_1_ID, 7)]
+        )
+        .fire()
+        .err(),
+        None
+    );
+    assert_eq!(value(&server, CREATURE_1_ID), 7);
+    assert_eq!(value(&server, CREATURE_5_ID), 3);
+    // Transmutations should be honored.
+    assert_eq!(
+        AlterStatisticsBatch::trigger(&mut server, vec![(ENTITY_1_ID, 1), (ENTITY_5_ID, 0)])
+            .fire()
+            .err(),
+        None
+    );
+    let entities = server.battle().entities();
+    assert!(entities.creature(&CREATURE_1_ID).is_some());
+    assert!(entities.creature(&CREATURE_5_ID).is_none());
+    // Multiple transmutations should be fired in the same order as the alterations.
+    util::creature(&mut server, CREATURE_5_ID, TEAM_1_ID, ());
+    assert_eq!(
+        AlterStatisticsBatch::trigger(&mut server, vec![(ENTITY_5_ID, 0), (ENTITY_1_ID, 0)])
+            .fire()
+            .err(),
+        None
+    );
+    let events = server.battle().history().events();
+    let removed = |index: usize| {
+        *events[index]
+            .event()
+            .as_any()
+            .downcast_ref::<RemoveCreature<CustomRules>>()
+            .unwrap()
+            .id()
+    };
+    assert_eq!(removed(events.len() - 2), CREATURE_5_ID);
+    assert_eq!(removed(events.len() - 1), CREATURE_1_ID);
+}
+
+#[test]
+fn set_statistic_value() {
+    use weasel::event::EventKind;
+    use weasel::rules::statistic::{SetStatisticValue, SET_STATISTIC_VALUE_ID};
+
+    #[derive(Default)]
+    pub struct CustomCharacterRules {}
+
+    impl<R: BattleRules + 'static> CharacterRules<R> for CustomCharacterRules {
+        type CreatureId = u32;
+        type ObjectId = u32;
+        type Statistic = SimpleStatistic<u32, i32>;
+        type StatisticsSeed = ();
+        type StatisticsAlteration = ();
+
+        fn generate_statistics(
+            &self,
+            _seed: &Option<Self::StatisticsSeed>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Box<dyn Iterator<Item = Self::Statistic>> {
+            let v = vec![SimpleStatistic::with_value(STAT_ID, 0, 100, 50)];
+            Box::new(v.into_iter())
+        }
+    }
+
+    battle_rules_with_character! { CustomCharacterRules }
+    static STAT_ID: u32 = 1;
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ENTITY_ERR_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_ERR_ID);
+    // Create a creature.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    let value = |server: &weasel::Server<CustomRules>| {
+        server
+            .battle()
+            .entities()
+            .character(&ENTITY_1_ID)
+            .unwrap()
+            .statistic(&STAT_ID)
+            .unwrap()
+            .value()
+    };
+    // Entity and statistic must exist.
+    assert_eq!(
+        SetStatisticValue::trigger(&mut server, ENTITY_ERR_ID, STAT_ID, 10)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::EntityNotFound(ENTITY_ERR_ID))
+    );
+    assert_eq!(
+        SetStatisticValue::trigger(&mut server, ENTITY_1_ID, 99, 10)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::StatisticNotFound(ENTITY_1_ID, 99))
+    );
+    // Set the value.
+    assert_eq!(
+        SetStatisticValue::trigger(&mut server, ENTITY_1_ID, STAT_ID, 10)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(value(&server), 10);
+    // Values are clamped within the bounds.
+    assert_eq!(
+        SetStatisticValue::trigger(&mut server, ENTITY_1_ID, STAT_ID, 500)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(value(&server), 100);
+    let events = server.battle().history().events();
+    assert_eq!(
+        events[events.len() - 1].kind(),
+        EventKind::UserEvent(SET_STATISTIC_VALUE_ID)
+    );
+}
+
+#[test]
+fn filter_ids() {
+    battle_rules! {}
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ENTITY_5_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_5_ID);
+    static ENTITY_ERR_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_ERR_ID);
+    assert!(ENTITY_1_ID.is_creature());
+    assert!(ENTITY_1_ID.is_character());
+    assert!(ENTITY_1_ID.is_actor());
+    // Create two creatures.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_5_ID, TEAM_1_ID, ());
+    let entities = server.battle().entities();
+    let ids = [ENTITY_1_ID, ENTITY_ERR_ID, ENTITY_5_ID];
+    // Ids of non existing entities are discarded.
+    assert_eq!(
+        entities
+            .filter_ids(&ids, |entity| entity.entity_id().is_creature())
+            .collect::<Vec<_>>(),
+        vec![&ENTITY_1_ID, &ENTITY_5_ID]
+    );
+    // Only the entities satisfying the predicate are kept.
+    assert_eq!(
+        entities
+            .filter_ids(&ids, |entity| *entity.entity_id() != ENTITY_1_ID)
+            .collect::<Vec<_>>(),
+        vec![&ENTITY_5_ID]
+    );
+}
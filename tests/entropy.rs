@@ -2,7 +2,7 @@ use weasel::actor::ActorRules;
 use weasel::battle::{Battle, BattleRules};
 use weasel::character::{Character, CharacterRules};
 use weasel::entropy::{Entropy, ResetEntropy};
-use weasel::event::EventTrigger;
+use weasel::event::{EventRights, EventTrigger};
 use weasel::metric::WriteMetrics;
 use weasel::rules::ability::SimpleAbility;
 use weasel::rules::entropy::UniformDistribution;
@@ -16,6 +16,7 @@ mod helper;
 static SEED: u64 = 1204678643940597513;
 static TEAM_1_ID: u32 = 1;
 static CREATURE_1_ID: u32 = 1;
+static CREATURE_2_ID: u32 = 2;
 static STAT_ID: u32 = 1;
 static STAT_VALUE_MIN: i32 = 1;
 static STAT_VALUE_MAX: i32 = 1000;
@@ -30,6 +31,7 @@ pub struct CustomCharacterRules {}
 
 impl CharacterRules<CustomRules> for CustomCharacterRules {
     type CreatureId = u32;
+    type ObjectId = u32;
     type Statistic = SimpleStatistic<u32, i32>;
     type StatisticsSeed = ();
     type StatisticsAlteration = ();
@@ -117,6 +119,27 @@ fn use_entropy() {
     stat_abi_randomness_check!(server);
 }
 
+#[test]
+fn reset_entropy_mid_battle() {
+    let mut server = scenario!();
+    // Only the server can reset the entropy.
+    let event = ResetEntropy::trigger(&mut server).seed(SEED).event();
+    assert!(matches!(event.rights(server.battle()), EventRights::Server));
+    // Reseed the entropy with the same seed used at the beginning.
+    assert_eq!(
+        ResetEntropy::trigger(&mut server).seed(SEED).fire().err(),
+        None
+    );
+    // A new creature should be randomized exactly as the first one.
+    util::creature(&mut server, CREATURE_2_ID, TEAM_1_ID, ());
+    let creature = server.battle().entities().creature(&CREATURE_2_ID).unwrap();
+    assert_eq!(creature.statistic(&STAT_ID).unwrap().value(), STAT_VALUE);
+    assert_eq!(
+        creature.ability(&ABILITY_ID).unwrap().power(),
+        ABILITY_POWER
+    );
+}
+
 #[cfg(feature = "serialization")]
 #[test]
 fn entropy_reload() {
@@ -131,3 +154,28 @@ fn entropy_reload() {
     // Verify that randomization is the same.
     stat_abi_randomness_check!(server);
 }
+
+#[cfg(feature = "serialization")]
+#[test]
+fn entropy_state_serde() {
+    use weasel::entropy::RestoreEntropy;
+    // Save the entropy state right after seeding.
+    let battle = Battle::builder(CustomRules::new()).build();
+    let mut server = Server::builder(battle).build();
+    assert_eq!(
+        ResetEntropy::trigger(&mut server).seed(SEED).fire().err(),
+        None
+    );
+    let json = serde_json::to_string(&server.battle().entropy().state()).unwrap();
+    // Restore the state in a new battle.
+    let mut server = util::server(CustomRules::new());
+    let state = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        RestoreEntropy::trigger(&mut server, state).fire().err(),
+        None
+    );
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    // Verify that randomization is the same.
+    stat_abi_randomness_check!(server);
+}
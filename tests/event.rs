@@ -3,24 +3,33 @@ use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::marker::PhantomData;
 use weasel::ability::ActivateAbility;
-use weasel::actor::{Action, Actor, ActorRules, AlterAbilities, RegenerateAbilities};
+use weasel::actor::{
+    Action, Actor, ActorRules, AlterAbilities, ClearAbilities, RegenerateAbilities,
+};
 use weasel::battle::{Battle, BattleRules, BattleState, EndBattle};
-use weasel::character::{AlterStatistics, RegenerateStatistics};
-use weasel::creature::{ConvertCreature, CreateCreature, RemoveCreature};
+use weasel::character::{
+    AddStatistic, AlterStatistics, AlterStatisticsBatch, RegenerateStatistics, RemoveStatistic,
+};
+use weasel::creature::{
+    ConvertCreature, CreateCreature, CreateCreatures, RemoveCreature, SetCreatureDisabled,
+};
 use weasel::entity::EntityId;
-use weasel::entropy::{Entropy, ResetEntropy};
+use weasel::entropy::{Entropy, ResetEntropy, RestoreEntropy};
 use weasel::event::{
-    Conditional, DummyEvent, Event, EventKind, EventProcessor, EventQueue, EventTrigger,
+    Conditional, DummyEvent, Event, EventKind, EventProcessor, EventQueue, EventTrigger, UserEvent,
 };
 use weasel::fight::ApplyImpact;
 use weasel::metric::WriteMetrics;
-use weasel::round::{EndRound, ResetRounds, StartRound};
+use weasel::object::{CreateObject, RemoveObject};
+use weasel::player::{GrantRights, RevokeRights};
+use weasel::round::{EndRound, EndRoundFor, ResetRounds, StartRound, StartTurnCycle};
 use weasel::rules::ability::SimpleAbility;
 #[cfg(feature = "serialization")]
 use weasel::serde::FlatEvent;
 use weasel::space::{MoveEntity, ResetSpace};
 use weasel::team::{
-    ConcludeObjectives, Conclusion, CreateTeam, Relation, RemoveTeam, ResetObjectives, SetRelations,
+    ConcludeObjectives, Conclusion, CreateTeam, OverwriteRelations, Relation, RemoveTeam,
+    RenameTeam, ResetObjectives, SetDirectedRelations, SetRelations,
 };
 #[cfg(feature = "serialization")]
 use weasel::user::UserEventPacker;
@@ -33,6 +42,7 @@ mod helper;
 
 static TEAM_1_ID: u32 = 1;
 static CREATURE_1_ID: u32 = 1;
+static OBJECT_1_ID: u32 = 1;
 
 /// Declare an user event.
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
@@ -44,7 +54,10 @@ pub struct MyEvent<R> {
 
 impl<R: BattleRules> MyEvent<R> {
     /// Returns a trigger for this event.
-    pub fn trigger<P: EventProcessor<R>>(processor: &mut P, data: String) -> MyEventTrigger<R, P> {
+    pub fn trigger<P: EventProcessor<R>>(
+        processor: &mut P,
+        data: String,
+    ) -> MyEventTrigger<'_, R, P> {
         MyEventTrigger {
             processor,
             data,
@@ -205,13 +218,7 @@ fn conditional() {
         ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_ID)
             .activation(POWER)
             .fire()
-            .or_else(|err| err.filter(|err| {
-                if let WeaselError::ConditionUnsatisfied = err {
-                    false
-                } else {
-                    true
-                }
-            }))
+            .or_else(|err| err.filter(|err| !matches!(err, WeaselError::ConditionUnsatisfied)))
             .err(),
         None
     );
@@ -231,6 +238,174 @@ fn conditional() {
     assert_eq!(events[events.len() - 1].kind(), EventKind::DummyEvent);
 }
 
+#[test]
+fn fire_with_output() {
+    #[derive(Default)]
+    pub struct CustomActorRules {}
+
+    impl ActorRules<CustomRules> for CustomActorRules {
+        type Ability = SimpleAbility<u32, u32>;
+        type AbilitiesSeed = ();
+        type Activation = ();
+        type AbilitiesAlteration = ();
+
+        fn generate_abilities(
+            &self,
+            _: &Option<Self::AbilitiesSeed>,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) -> Box<dyn Iterator<Item = Self::Ability>> {
+            let v = vec![SimpleAbility::new(ABILITY_ID, 0)];
+            Box::new(v.into_iter())
+        }
+
+        fn activate(
+            &self,
+            _state: &BattleState<CustomRules>,
+            _action: Action<CustomRules>,
+            mut event_queue: &mut Option<EventQueue<CustomRules>>,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) {
+            DummyEvent::trigger(&mut event_queue).fire();
+            DummyEvent::trigger(&mut event_queue).fire();
+        }
+    }
+
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ABILITY_ID: u32 = 1;
+
+    battle_rules_with_actor! { CustomActorRules }
+
+    // Create a battle with one creature.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::start_round(&mut server, &ENTITY_1_ID);
+    // The output should contain the ability's activation and the events derived from it.
+    let output = ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_ID)
+        .fire_with_output()
+        .unwrap();
+    let kinds: Vec<_> = output.iter().map(|e| e.kind()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            EventKind::ActivateAbility,
+            EventKind::DummyEvent,
+            EventKind::DummyEvent
+        ]
+    );
+    assert_eq!(output[1].origin(), Some(output[0].id()));
+    assert_eq!(output[2].origin(), Some(output[0].id()));
+    // Invalid events produce no output.
+    assert!(StartRound::trigger(&mut server, ENTITY_1_ID)
+        .fire_with_output()
+        .is_err());
+}
+
+#[test]
+fn queue_order() {
+    use weasel::util::Id;
+
+    #[derive(Default)]
+    pub struct CustomActorRules {}
+
+    impl ActorRules<CustomRules> for CustomActorRules {
+        type Ability = SimpleAbility<u32, u32>;
+        type AbilitiesSeed = ();
+        type Activation = ();
+        type AbilitiesAlteration = ();
+
+        fn generate_abilities(
+            &self,
+            _: &Option<Self::AbilitiesSeed>,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) -> Box<dyn Iterator<Item = Self::Ability>> {
+            let v = vec![
+                SimpleAbility::new(ABILITY_1_ID, 0),
+                SimpleAbility::new(ABILITY_2_ID, 0),
+            ];
+            Box::new(v.into_iter())
+        }
+
+        fn activate(
+            &self,
+            _state: &BattleState<CustomRules>,
+            action: Action<CustomRules>,
+            mut event_queue: &mut Option<EventQueue<CustomRules>>,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) {
+            if *action.ability.id() == ABILITY_1_ID {
+                ActivateAbility::trigger(&mut event_queue, ENTITY_1_ID, ABILITY_2_ID).fire();
+                DummyEvent::trigger(&mut event_queue).fire();
+                assert_eq!(event_queue.as_ref().map(|queue| queue.len()), Some(2));
+            } else {
+                ResetEntropy::trigger(&mut event_queue).fire();
+            }
+        }
+    }
+
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ABILITY_1_ID: u32 = 1;
+    static ABILITY_2_ID: u32 = 2;
+
+    battle_rules_with_actor! { CustomActorRules }
+
+    // Create a battle with one creature.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::start_round(&mut server, &ENTITY_1_ID);
+    // Events derived from the second ability must come before the dummy event.
+    let output = ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_1_ID)
+        .fire_with_output()
+        .unwrap();
+    let kinds: Vec<_> = output.iter().map(|e| e.kind()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            EventKind::ActivateAbility,
+            EventKind::ActivateAbility,
+            EventKind::ResetEntropy,
+            EventKind::DummyEvent
+        ]
+    );
+    assert_eq!(output[2].origin(), Some(output[1].id()));
+    assert_eq!(output[3].origin(), Some(output[0].id()));
+}
+
+#[test]
+fn observers() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    battle_rules! {}
+    // Create a server with two observers, recording the events they see.
+    let mut server = util::server(CustomRules::new());
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    for i in 0..2 {
+        let seen = seen.clone();
+        server.add_observer(Box::new(move |event, state| {
+            let teams = state.entities().teams().count();
+            seen.borrow_mut().push((i, event.kind(), teams));
+        }));
+    }
+    // Fire some events.
+    util::dummy(&mut server);
+    util::team(&mut server, TEAM_1_ID);
+    // Observers are invoked in order, with the state after the event.
+    assert_eq!(
+        *seen.borrow(),
+        vec![
+            (0, EventKind::DummyEvent, 0),
+            (1, EventKind::DummyEvent, 0),
+            (0, EventKind::CreateTeam, 1),
+            (1, EventKind::CreateTeam, 1)
+        ]
+    );
+}
+
 macro_rules! user_event_check {
     ($server: expr, $data: expr) => {{
         let event = &$server.battle().history().events()[0];
@@ -266,6 +441,10 @@ fn user_event() {
     );
     // Check that the user event is correct.
     user_event_check!(server, data);
+    // User events can be told apart by their id.
+    let history = server.battle().history();
+    assert_eq!(history.count_by_kind(EventKind::UserEvent(0)), 1);
+    assert_eq!(history.count_by_kind(EventKind::UserEvent(1)), 0);
     // Check that the user metric was increased.
     assert_eq!(
         server
@@ -276,6 +455,63 @@ fn user_event() {
     );
 }
 
+#[test]
+fn user_event_wrapper() {
+    #[derive(Default)]
+    struct CustomUserRules {}
+
+    impl UserRules<CustomRules> for CustomUserRules {
+        type UserMetricId = u32;
+        #[cfg(feature = "serialization")]
+        type UserEventPackage = ();
+    }
+
+    battle_rules_with_user! { CustomUserRules }
+    static METRIC_ID: u32 = 0;
+    static EVENT_ID: u16 = 7;
+    // The event increases a metric by the payload's value.
+    let apply = |value: &u64, battle: &mut Battle<CustomRules>, _: &mut Option<_>| {
+        battle
+            .metrics_mut()
+            .add_user_u64(METRIC_ID, *value)
+            .unwrap();
+    };
+    let verify = |value: &u64, _: &Battle<CustomRules>| {
+        if *value > 0 {
+            Ok(())
+        } else {
+            Err(WeaselError::ConditionUnsatisfied)
+        }
+    };
+    let mut server = util::server(CustomRules::new());
+    // Fire the event.
+    assert_eq!(
+        UserEvent::trigger(&mut server, EVENT_ID, 5, apply)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(server.battle().metrics().user_u64(METRIC_ID), Some(5));
+    // Verification should be honored.
+    assert_eq!(
+        UserEvent::trigger(&mut server, EVENT_ID, 0, apply)
+            .verify(verify)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::ConditionUnsatisfied)
+    );
+    // Check the event in the history.
+    let history = server.battle().history();
+    assert_eq!(history.count_by_kind(EventKind::UserEvent(EVENT_ID)), 1);
+    let event = history.events()[0]
+        .as_any()
+        .downcast_ref::<UserEvent<CustomRules, u64>>()
+        .unwrap();
+    assert_eq!(event.id(), EVENT_ID);
+    assert_eq!(*event.payload(), 5);
+}
+
 #[cfg(feature = "serialization")]
 #[test]
 fn user_event_serde() {
@@ -288,7 +524,7 @@ fn user_event_serde() {
     impl UserEventPacker<CustomRules> for Package {
         fn boxed(self) -> WeaselResult<Box<dyn Event<CustomRules>>, CustomRules> {
             let event = match self {
-                Package::MyEvent(event) => (Box::new(event) as Box<dyn Event<CustomRules>>),
+                Package::MyEvent(event) => Box::new(event) as Box<dyn Event<CustomRules>>,
             };
             Ok(event)
         }
@@ -338,31 +574,57 @@ macro_rules! events_vec {
         static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
         static ABILITY_1_ID: u32 = 1;
         // Collect all events into a vector.
-        let mut events: Vec<Box<dyn Event<CustomRules>>> = Vec::new();
-        events.push(DummyEvent::trigger(&mut ()).event());
-        events.push(StartRound::trigger(&mut (), ENTITY_1_ID).event());
-        events.push(EndRound::trigger(&mut ()).event());
-        events.push(CreateTeam::trigger(&mut (), TEAM_1_ID).event());
-        events.push(CreateCreature::trigger(&mut (), TEAM_1_ID, CREATURE_1_ID, ()).event());
-        events.push(ActivateAbility::trigger(&mut (), ENTITY_1_ID, ABILITY_1_ID).event());
-        events.push(ResetEntropy::trigger(&mut ()).event());
-        events.push(MoveEntity::trigger(&mut (), ENTITY_1_ID, ()).event());
-        events.push(ApplyImpact::trigger(&mut (), ()).event());
-        events.push(AlterStatistics::trigger(&mut (), ENTITY_1_ID, ()).event());
-        events.push(AlterAbilities::trigger(&mut (), ENTITY_1_ID, ()).event());
-        events.push(
+        let events: Vec<Box<dyn Event<CustomRules>>> = vec![
+            DummyEvent::trigger(&mut ()).event(),
+            StartRound::trigger(&mut (), ENTITY_1_ID).event(),
+            EndRound::trigger(&mut ()).event(),
+            CreateTeam::trigger(&mut (), TEAM_1_ID).event(),
+            CreateCreature::trigger(&mut (), TEAM_1_ID, CREATURE_1_ID, ()).event(),
+            ActivateAbility::trigger(&mut (), ENTITY_1_ID, ABILITY_1_ID).event(),
+            ResetEntropy::trigger(&mut ()).event(),
+            MoveEntity::trigger(&mut (), ENTITY_1_ID, ()).event(),
+            ApplyImpact::trigger(&mut (), ()).event(),
+            AlterStatistics::trigger(&mut (), ENTITY_1_ID, ()).event(),
+            AlterAbilities::trigger(&mut (), ENTITY_1_ID, ()).event(),
             SetRelations::trigger(&mut (), &[(TEAM_1_ID, TEAM_1_ID, Relation::Ally)]).event(),
-        );
-        events.push(ConvertCreature::trigger(&mut (), CREATURE_1_ID, TEAM_1_ID).event());
-        events.push(EndBattle::trigger(&mut ()).event());
-        events.push(ConcludeObjectives::trigger(&mut (), TEAM_1_ID, Conclusion::Victory).event());
-        events.push(ResetObjectives::trigger(&mut (), TEAM_1_ID).event());
-        events.push(ResetRounds::trigger(&mut ()).event());
-        events.push(ResetSpace::trigger(&mut ()).event());
-        events.push(RemoveCreature::trigger(&mut (), CREATURE_1_ID).event());
-        events.push(RemoveTeam::trigger(&mut (), TEAM_1_ID).event());
-        events.push(RegenerateStatistics::trigger(&mut (), ENTITY_1_ID.clone()).event());
-        events.push(RegenerateAbilities::trigger(&mut (), ENTITY_1_ID.clone()).event());
+            ConvertCreature::trigger(&mut (), CREATURE_1_ID, TEAM_1_ID).event(),
+            EndBattle::trigger(&mut ()).event(),
+            ConcludeObjectives::trigger(&mut (), TEAM_1_ID, Conclusion::Victory).event(),
+            ResetObjectives::trigger(&mut (), TEAM_1_ID).event(),
+            ResetRounds::trigger(&mut ()).event(),
+            ResetSpace::trigger(&mut ()).event(),
+            RemoveCreature::trigger(&mut (), CREATURE_1_ID).event(),
+            RemoveTeam::trigger(&mut (), TEAM_1_ID).event(),
+            RegenerateStatistics::trigger(&mut (), ENTITY_1_ID.clone()).event(),
+            RegenerateAbilities::trigger(&mut (), ENTITY_1_ID.clone()).event(),
+            SetDirectedRelations::trigger(&mut (), &[(TEAM_1_ID, TEAM_1_ID, Relation::Ally)])
+                .event(),
+            RenameTeam::trigger(&mut (), TEAM_1_ID, TEAM_1_ID).event(),
+            AlterStatisticsBatch::trigger(&mut (), vec![(ENTITY_1_ID, ())]).event(),
+            EndRoundFor::trigger(&mut (), ENTITY_1_ID).event(),
+            StartTurnCycle::trigger(&mut ()).event(),
+            RestoreEntropy::trigger(&mut (), ()).event(),
+            ClearAbilities::trigger(&mut (), ENTITY_1_ID).event(),
+            SetCreatureDisabled::trigger(&mut (), CREATURE_1_ID, true).event(),
+            CreateCreatures::trigger(
+                &mut (),
+                vec![CreateCreature::new(
+                    CREATURE_1_ID,
+                    TEAM_1_ID,
+                    (),
+                    None,
+                    None,
+                )],
+            )
+            .event(),
+            GrantRights::trigger(&mut (), 1, TEAM_1_ID).event(),
+            RevokeRights::trigger(&mut (), 1, TEAM_1_ID).event(),
+            RemoveStatistic::trigger(&mut (), ENTITY_1_ID.clone(), 1).event(),
+            AddStatistic::trigger(&mut (), ENTITY_1_ID.clone(), EmptyStat { id: 1 }).event(),
+            CreateObject::trigger(&mut (), OBJECT_1_ID, ()).event(),
+            RemoveObject::trigger(&mut (), OBJECT_1_ID).event(),
+            OverwriteRelations::trigger(&mut (), &[(TEAM_1_ID, TEAM_1_ID, Relation::Ally)]).event(),
+        ];
         events
     }};
 }
@@ -385,11 +647,7 @@ fn events_debug() {
 fn events_serde() {
     let events = events_vec!();
     // Serialize all events.
-    let flat_events: Vec<_> = events
-        .iter()
-        .cloned()
-        .map(|e| FlatEvent::flattened(e))
-        .collect();
+    let flat_events: Vec<_> = events.iter().cloned().map(FlatEvent::flattened).collect();
     let json = serde_json::to_string(&flat_events).unwrap();
     // Deserialize all events.
     let deserialized_flat_events: Vec<FlatEvent<_>> = serde_json::from_str(&json).unwrap();
@@ -401,3 +659,62 @@ fn events_serde() {
     // Events should 'roughly' be the same as before (Eq for Events checks only the event kind).
     assert_eq!(deserialized_events, events);
 }
+
+#[cfg(feature = "serialization")]
+#[test]
+fn remove_team_without_cascade_serde() {
+    battle_rules! {}
+    // Events serialized before the introduction of `cascade` must still be readable.
+    let json = r#"{"RemoveTeam":{"id":1}}"#;
+    let flat_event: FlatEvent<CustomRules> = serde_json::from_str(json).unwrap();
+    let event = flat_event.boxed();
+    let event: &RemoveTeam<CustomRules> = event.as_any().downcast_ref().unwrap();
+    assert_eq!(*event.id(), 1);
+    assert!(!event.cascade());
+}
+
+#[cfg(feature = "serialization")]
+#[test]
+fn remove_entity_without_reason_serde() {
+    use weasel::creature::RemovalReason;
+
+    battle_rules! {}
+    // Histories recorded before the introduction of `reason` must still be readable.
+    let json = r#"[{"RemoveCreature":{"id":1}},{"RemoveObject":{"id":2}}]"#;
+    let flat_events: Vec<FlatEvent<CustomRules>> = serde_json::from_str(json).unwrap();
+    let events: Vec<_> = flat_events.into_iter().map(FlatEvent::boxed).collect();
+    let event: &RemoveCreature<CustomRules> = events[0].as_any().downcast_ref().unwrap();
+    assert_eq!(*event.id(), 1);
+    assert_eq!(event.reason(), RemovalReason::Manual);
+    let event: &RemoveObject<CustomRules> = events[1].as_any().downcast_ref().unwrap();
+    assert_eq!(*event.id(), 2);
+    assert_eq!(event.reason(), RemovalReason::Manual);
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn events_compression() {
+    use weasel::event::VersionedEventWrapper;
+
+    battle_rules! {}
+    // Create a battle with a few events.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    let battle = server.battle();
+    // Compress and decompress each event.
+    for event in battle.versioned_events(0..battle.history().len() as usize) {
+        let bytes = event.to_compressed_bytes().unwrap();
+        let restored = VersionedEventWrapper::<CustomRules>::from_compressed_bytes(&bytes).unwrap();
+        assert_eq!(restored.id(), event.id());
+        assert_eq!(restored.event(), event.event());
+        assert_eq!(restored.version(), event.version());
+    }
+    // Invalid bytes are rejected.
+    assert!(matches!(
+        VersionedEventWrapper::<CustomRules>::from_compressed_bytes(&[1, 2, 3])
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::SerializationError(_))
+    ));
+}
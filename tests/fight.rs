@@ -4,12 +4,13 @@ use weasel::battle::{BattleRules, BattleState};
 use weasel::character::{AlterStatistics, Character, CharacterRules};
 use weasel::entity::{EntityId, Transmutation};
 use weasel::entropy::Entropy;
-use weasel::event::{EventKind, EventQueue, EventTrigger};
+use weasel::event::{DummyEvent, EventKind, EventQueue, EventTrigger};
 use weasel::fight::{ApplyImpact, FightRules};
 use weasel::metric::WriteMetrics;
 use weasel::rules::ability::SimpleAbility;
 use weasel::rules::statistic::SimpleStatistic;
-use weasel::{battle_rules, rules::empty::*};
+use weasel::{battle_rules, battle_rules_with_fight, rules::empty::*};
+use weasel::{WeaselError, WeaselResult};
 
 static TEAM_1_ID: u32 = 1;
 static CREATURE_1_ID: u32 = 1;
@@ -26,6 +27,7 @@ pub struct CustomCharacterRules {}
 
 impl CharacterRules<CustomRules> for CustomCharacterRules {
     type CreatureId = u32;
+    type ObjectId = u32;
     type Statistic = SimpleStatistic<String, i32>;
     type StatisticsSeed = ();
     type StatisticsAlteration = i32;
@@ -114,8 +116,9 @@ impl FightRules<CustomRules> for CustomFightRules {
         mut event_queue: &mut Option<EventQueue<CustomRules>>,
         _entropy: &mut Entropy<CustomRules>,
         _metrics: &mut WriteMetrics<CustomRules>,
-    ) {
+    ) -> Vec<Self::Impact> {
         AlterStatistics::trigger(&mut event_queue, ENTITY_2_ID, *impact * 2).fire();
+        Vec::new()
     }
 }
 
@@ -167,3 +170,87 @@ fn simple_attack() {
     assert_eq!(events[7].kind(), EventKind::AlterStatistics);
     assert_eq!(events[7].origin(), Some(6));
 }
+
+#[test]
+fn verify_impact() {
+    #[derive(Default)]
+    pub struct CustomFightRules {}
+
+    impl<R: BattleRules> FightRules<R> for CustomFightRules {
+        type Impact = i32;
+
+        fn verify_impact(
+            &self,
+            _state: &BattleState<R>,
+            impact: &Self::Impact,
+        ) -> WeaselResult<(), R> {
+            if *impact >= 0 {
+                Ok(())
+            } else {
+                Err(WeaselError::UserError("negative impact".to_string()))
+            }
+        }
+    }
+
+    battle_rules_with_fight! { CustomFightRules }
+    let mut server = util::server(CustomRules::new());
+    // Check that invalid impacts are rejected.
+    assert_eq!(
+        ApplyImpact::trigger(&mut server, -1)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::UserError("negative impact".to_string()))
+    );
+    assert_eq!(server.battle().history().len(), 0);
+    // Check that valid impacts are accepted.
+    assert_eq!(ApplyImpact::trigger(&mut server, 1).fire().err(), None);
+    assert_eq!(server.battle().history().len(), 1);
+}
+
+#[test]
+fn chained_impacts() {
+    #[derive(Default)]
+    pub struct CustomFightRules {}
+
+    impl<R: BattleRules + 'static> FightRules<R> for CustomFightRules {
+        type Impact = u32;
+
+        fn apply_impact(
+            &self,
+            _state: &BattleState<R>,
+            impact: &Self::Impact,
+            mut event_queue: &mut Option<EventQueue<R>>,
+            _entropy: &mut Entropy<R>,
+            _metrics: &mut WriteMetrics<R>,
+        ) -> Vec<Self::Impact> {
+            // Each impact causes a dummy event and a smaller impact.
+            DummyEvent::trigger(&mut event_queue).fire();
+            if *impact > 0 {
+                vec![*impact - 1]
+            } else {
+                Vec::new()
+            }
+        }
+    }
+
+    battle_rules_with_fight! { CustomFightRules }
+    let mut server = util::server(CustomRules::new());
+    assert_eq!(ApplyImpact::trigger(&mut server, 2).fire().err(), None);
+    // Check that follow-up impacts are applied after the other events.
+    let events = server.battle().history().events();
+    let kinds: Vec<_> = events.iter().map(|event| event.kind()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            EventKind::ApplyImpact,
+            EventKind::DummyEvent,
+            EventKind::ApplyImpact,
+            EventKind::DummyEvent,
+            EventKind::ApplyImpact,
+            EventKind::DummyEvent,
+        ]
+    );
+    assert_eq!(events[2].origin(), Some(0));
+    assert_eq!(events[4].origin(), Some(2));
+}
@@ -2,11 +2,17 @@ use std::convert::TryInto;
 use weasel::battle::BattleRules;
 use weasel::entropy::ResetEntropy;
 use weasel::event::{EventId, EventKind, EventTrigger};
+use weasel::metric::system::CREATURES_CREATED;
+use weasel::player::{GrantRights, PlayerId, RevokeRights};
 use weasel::round::EndRound;
+use weasel::server::Server;
+use weasel::WeaselError;
 use weasel::{battle_rules, rules::empty::*};
 
 static TEAM_1_ID: u32 = 1;
 static CREATURE_1_ID: u32 = 1;
+static PLAYER_1_ID: PlayerId = 1;
+static PLAYER_2_ID: PlayerId = 2;
 
 battle_rules! {}
 
@@ -30,3 +36,268 @@ fn timeline_populated() {
     assert_eq!(events[2].kind(), EventKind::ResetEntropy);
     assert_eq!(events[2].id(), len - 1);
 }
+
+#[test]
+fn replay() {
+    // Create a server with a creature.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::dummy(&mut server);
+    // Rebuild the battle up to the creature creation.
+    let events = server.battle().versioned_events(0..2);
+    let replayed = Server::replay(CustomRules::new(), events).unwrap();
+    assert_eq!(replayed.battle().history().len(), 2);
+    assert!(replayed
+        .battle()
+        .entities()
+        .creature(&CREATURE_1_ID)
+        .is_some());
+    // Replay should stop at the first inconsistent event.
+    let events = server.battle().versioned_events(1..3);
+    assert_eq!(
+        Server::replay(CustomRules::new(), events).err(),
+        Some(WeaselError::NonContiguousEventId(1, 0))
+    );
+}
+
+#[test]
+fn replay_rights() {
+    // Create a server with a team.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    // Rights can't be granted for a non existing team.
+    assert_eq!(
+        GrantRights::trigger(&mut server, PLAYER_1_ID, 2)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::TeamNotFound(2))
+    );
+    // Grant and revoke rights through events.
+    assert_eq!(
+        GrantRights::trigger(&mut server, PLAYER_1_ID, TEAM_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(
+        GrantRights::trigger(&mut server, PLAYER_2_ID, TEAM_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(
+        RevokeRights::trigger(&mut server, PLAYER_2_ID, TEAM_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert!(server.rights().check(PLAYER_1_ID, &TEAM_1_ID));
+    assert!(!server.rights().check(PLAYER_2_ID, &TEAM_1_ID));
+    // Rights should be restored by replaying the history.
+    let events = server.battle().versioned_events(0..4);
+    let replayed = Server::replay(CustomRules::new(), events).unwrap();
+    assert!(replayed.rights().check(PLAYER_1_ID, &TEAM_1_ID));
+    assert!(!replayed.rights().check(PLAYER_2_ID, &TEAM_1_ID));
+}
+
+#[test]
+fn undo_last() {
+    let mut server = util::server(CustomRules::new());
+    // Undo is not possible with an empty history.
+    assert_eq!(
+        server.undo_last(CustomRules::new()).err(),
+        Some(WeaselError::EmptyHistory)
+    );
+    // Create a team and a creature.
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    // Undo the creature creation.
+    assert_eq!(server.undo_last(CustomRules::new()).err(), None);
+    let battle = server.battle();
+    assert_eq!(battle.history().len(), 1);
+    assert!(battle.entities().creature(&CREATURE_1_ID).is_none());
+    assert!(battle.entities().team(&TEAM_1_ID).is_some());
+    // The server can keep processing events.
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    assert_eq!(server.battle().history().len(), 2);
+    assert!(server
+        .battle()
+        .entities()
+        .creature(&CREATURE_1_ID)
+        .is_some());
+    // Rights granted outside of events are kept, those granted by the reverted event are not.
+    assert_eq!(server.rights_mut().add(PLAYER_1_ID, &TEAM_1_ID).err(), None);
+    assert_eq!(
+        GrantRights::trigger(&mut server, PLAYER_2_ID, TEAM_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(server.undo_last(CustomRules::new()).err(), None);
+    assert!(server.rights().check(PLAYER_1_ID, &TEAM_1_ID));
+    assert!(!server.rights().check(PLAYER_2_ID, &TEAM_1_ID));
+    // Metrics changed by the reverted event are restored.
+    assert_eq!(
+        server.battle().metrics().system_u64(CREATURES_CREATED),
+        Some(1)
+    );
+    assert_eq!(server.undo_last(CustomRules::new()).err(), None);
+    assert_eq!(
+        server.battle().metrics().system_u64(CREATURES_CREATED),
+        None
+    );
+}
+
+#[test]
+fn undo_last_twice() {
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    // Grant rights both outside of events and through an event, then create a creature.
+    assert_eq!(server.rights_mut().add(PLAYER_1_ID, &TEAM_1_ID).err(), None);
+    assert_eq!(
+        GrantRights::trigger(&mut server, PLAYER_2_ID, TEAM_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    // Undo the last two events, one after the other.
+    assert_eq!(server.undo_last(CustomRules::new()).err(), None);
+    assert!(server.rights().check(PLAYER_1_ID, &TEAM_1_ID));
+    assert!(server.rights().check(PLAYER_2_ID, &TEAM_1_ID));
+    assert_eq!(server.undo_last(CustomRules::new()).err(), None);
+    assert_eq!(server.battle().history().len(), 1);
+    // Only the rights granted by the reverted event are gone.
+    assert!(server.rights().check(PLAYER_1_ID, &TEAM_1_ID));
+    assert!(!server.rights().check(PLAYER_2_ID, &TEAM_1_ID));
+    assert_eq!(
+        server.battle().metrics().system_u64(CREATURES_CREATED),
+        None
+    );
+}
+
+#[cfg(feature = "serialization")]
+#[test]
+fn undo_last_snapshot() {
+    use weasel::serde::BattleSnapshot;
+
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    let snapshot = BattleSnapshot::new(server.battle());
+    let mut server = Server::from_snapshot(CustomRules::new(), snapshot);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    // The history of a restored battle doesn't contain all events to replay.
+    assert_eq!(
+        server.undo_last(CustomRules::new()).err(),
+        Some(WeaselError::IncompleteHistory)
+    );
+    assert!(server
+        .battle()
+        .entities()
+        .creature(&CREATURE_1_ID)
+        .is_some());
+}
+
+#[test]
+fn fork() {
+    use weasel::battle::Battle;
+
+    let battle = Battle::builder(CustomRules::new()).build();
+    let mut server = Server::builder(battle)
+        .enforce_authentication()
+        .auto_end_round(2)
+        .build();
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    assert_eq!(server.rights_mut().add(PLAYER_1_ID, &TEAM_1_ID).err(), None);
+    // Fork the server.
+    let mut fork = server.fork(CustomRules::new());
+    assert_eq!(
+        fork.battle().history().len(),
+        server.battle().history().len()
+    );
+    assert_eq!(
+        fork.battle().entropy().model(),
+        server.battle().entropy().model()
+    );
+    // Rights and settings are copied.
+    assert!(fork.rights().check(PLAYER_1_ID, &TEAM_1_ID));
+    assert!(fork.authentication());
+    assert_eq!(fork.auto_end_round(), Some(2));
+    // Changes to the fork don't affect the original server.
+    util::creature(&mut fork, CREATURE_1_ID + 1, TEAM_1_ID, ());
+    assert!(fork
+        .battle()
+        .entities()
+        .creature(&(CREATURE_1_ID + 1))
+        .is_some());
+    assert!(server
+        .battle()
+        .entities()
+        .creature(&(CREATURE_1_ID + 1))
+        .is_none());
+    assert_eq!(server.battle().history().len(), 2);
+}
+
+#[cfg(feature = "serialization")]
+#[test]
+fn fork_snapshot() {
+    use weasel::serde::BattleSnapshot;
+
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    let snapshot = BattleSnapshot::new(server.battle());
+    let mut server = Server::from_snapshot(CustomRules::new(), snapshot);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    // Restored battles can be forked, since nothing is replayed.
+    let mut fork = server.fork(CustomRules::new());
+    assert_eq!(fork.battle().state().diff(server.battle().state()), vec![]);
+    assert_eq!(
+        fork.battle().history().len(),
+        server.battle().history().len()
+    );
+    // Like the original, the fork can't undo the events of a restored battle.
+    assert_eq!(
+        fork.undo_last(CustomRules::new()).err(),
+        Some(WeaselError::IncompleteHistory)
+    );
+}
+
+#[cfg(feature = "serialization")]
+#[test]
+fn history_bytes() {
+    use weasel::event::VersionedEventWrapper;
+    use weasel::serde::load_history_bytes;
+
+    // Create a server with a creature.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    // Save and restore the history.
+    let bytes = server.battle().history_to_bytes().unwrap();
+    let mut restored = util::server(CustomRules::new());
+    assert_eq!(load_history_bytes(&mut restored, &bytes).err(), None);
+    assert_eq!(
+        restored.battle().history().len(),
+        server.battle().history().len()
+    );
+    assert!(restored
+        .battle()
+        .entities()
+        .creature(&CREATURE_1_ID)
+        .is_some());
+    // Single events can be converted as well.
+    let event = server.battle().versioned_events(0..1).next().unwrap();
+    let bytes = event.to_bytes().unwrap();
+    let restored_event = VersionedEventWrapper::<CustomRules>::from_bytes(&bytes).unwrap();
+    assert_eq!(restored_event.event(), event.event());
+    // Invalid bytes are rejected.
+    assert!(matches!(
+        load_history_bytes(&mut restored, &[1, 2, 3])
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::SerializationError(_))
+    ));
+}
@@ -0,0 +1,154 @@
+use weasel::battle::BattleRules;
+use weasel::character::{AlterStatistics, Character, CharacterRules};
+use weasel::entity::{EntityId, Transmutation};
+use weasel::entropy::Entropy;
+use weasel::event::EventTrigger;
+use weasel::metric::{system::*, WriteMetrics};
+use weasel::object::{CreateObject, RemoveObject};
+use weasel::round::StartRound;
+use weasel::rules::statistic::SimpleStatistic;
+use weasel::WeaselError;
+use weasel::{battle_rules, battle_rules_with_character, rules::empty::*};
+
+static TEAM_1_ID: u32 = 1;
+static CREATURE_1_ID: u32 = 1;
+static OBJECT_1_ID: u32 = 1;
+static OBJECT_ERR_ID: u32 = 99;
+static ENTITY_O1_ID: EntityId<CustomRules> = EntityId::Object(OBJECT_1_ID);
+
+battle_rules! {}
+
+#[test]
+fn new_object() {
+    // Check object creation.
+    let mut server = util::server(CustomRules::new());
+    util::object(&mut server, OBJECT_1_ID, ());
+    let entities = server.battle().entities();
+    assert!(entities.object(&OBJECT_1_ID).is_some());
+    assert!(entities.entity(&ENTITY_O1_ID).is_some());
+    assert!(entities.character(&ENTITY_O1_ID).is_some());
+    assert!(entities.actor(&ENTITY_O1_ID).is_none());
+    assert_eq!(entities.actors().count(), 0);
+    // Check metrics.
+    assert_eq!(
+        server.battle().metrics().system_u64(OBJECTS_CREATED),
+        Some(1)
+    );
+    // Check object duplication.
+    assert_eq!(
+        CreateObject::trigger(&mut server, OBJECT_1_ID, ())
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::DuplicatedObject(OBJECT_1_ID))
+    );
+    // Objects and creatures live in different id spaces.
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    let entities = server.battle().entities();
+    assert_eq!(entities.entities().count(), 2);
+    assert_eq!(entities.characters().count(), 2);
+    assert_eq!(
+        ENTITY_O1_ID.creature().err(),
+        Some(WeaselError::NotACreature(ENTITY_O1_ID))
+    );
+    assert_eq!(ENTITY_O1_ID.object().ok(), Some(OBJECT_1_ID));
+}
+
+#[test]
+fn objects_cannot_act() {
+    let mut server = util::server(CustomRules::new());
+    util::object(&mut server, OBJECT_1_ID, ());
+    // Objects can't start a round.
+    assert_eq!(
+        StartRound::trigger(&mut server, ENTITY_O1_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::NotAnActor(ENTITY_O1_ID))
+    );
+}
+
+#[test]
+fn remove_object() {
+    let mut server = util::server(CustomRules::new());
+    util::object(&mut server, OBJECT_1_ID, ());
+    // Remove a non existing object.
+    assert_eq!(
+        RemoveObject::trigger(&mut server, OBJECT_ERR_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::ObjectNotFound(OBJECT_ERR_ID))
+    );
+    // Remove the object.
+    assert_eq!(
+        RemoveObject::trigger(&mut server, OBJECT_1_ID).fire().err(),
+        None
+    );
+    assert!(server.battle().entities().object(&OBJECT_1_ID).is_none());
+    assert_eq!(server.battle().entities().entities().count(), 0);
+}
+
+#[test]
+fn alter_object() {
+    static HEALTH: u32 = 1;
+
+    #[derive(Default)]
+    struct CustomCharacterRules {}
+
+    impl CharacterRules<CustomRules> for CustomCharacterRules {
+        type CreatureId = u32;
+        type ObjectId = u32;
+        type Statistic = SimpleStatistic<u32, u32>;
+        type StatisticsSeed = ();
+        type StatisticsAlteration = u32;
+
+        fn generate_statistics(
+            &self,
+            _seed: &Option<Self::StatisticsSeed>,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) -> Box<dyn Iterator<Item = Self::Statistic>> {
+            Box::new(std::iter::once(SimpleStatistic::new(HEALTH, 10)))
+        }
+
+        fn alter(
+            &self,
+            character: &mut dyn Character<CustomRules>,
+            alteration: &Self::StatisticsAlteration,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) -> Option<Transmutation> {
+            let health = character.statistic_mut(&HEALTH).unwrap();
+            health.set_value(health.value().saturating_sub(*alteration));
+            if health.value() == 0 {
+                Some(Transmutation::REMOVAL)
+            } else {
+                None
+            }
+        }
+    }
+
+    battle_rules_with_character! { CustomCharacterRules }
+    let entity_id = EntityId::<CustomRules>::Object(OBJECT_1_ID);
+    let mut server = util::server(CustomRules::new());
+    util::object(&mut server, OBJECT_1_ID, ());
+    // Damage the object.
+    assert_eq!(
+        AlterStatistics::trigger(&mut server, entity_id, 4)
+            .fire()
+            .err(),
+        None
+    );
+    let object = server.battle().entities().object(&OBJECT_1_ID).unwrap();
+    assert_eq!(object.statistic(&HEALTH).unwrap().value(), 6);
+    // Destroy the object.
+    assert_eq!(
+        AlterStatistics::trigger(&mut server, entity_id, 6)
+            .fire()
+            .err(),
+        None
+    );
+    assert!(server.battle().entities().object(&OBJECT_1_ID).is_none());
+}
@@ -1,12 +1,15 @@
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
 use weasel::actor::Actor;
-use weasel::battle::{Battle, BattleRules};
+use weasel::battle::{Battle, BattleRules, BattleState};
+use weasel::creature::{RemoveCreature, SetCreatureDisabled};
 use weasel::entity::EntityId;
 use weasel::entropy::Entropy;
 use weasel::event::EventTrigger;
 use weasel::metric::{system::*, WriteMetrics};
-use weasel::round::{EndRound, ResetRounds, RoundState, RoundsRules, StartRound};
+use weasel::round::{
+    EndRound, EndRoundFor, ResetRounds, RoundState, RoundsRules, StartRound, StartTurnCycle,
+};
 use weasel::server::Server;
 use weasel::WeaselError;
 use weasel::{battle_rules, battle_rules_with_rounds, rules::empty::*};
@@ -29,7 +32,9 @@ struct Model {
 }
 
 #[derive(Default)]
-struct CustomRoundsRules {}
+struct CustomRoundsRules {
+    vetoed: Option<EntityId<CustomRules>>,
+}
 
 impl RoundsRules<CustomRules> for CustomRoundsRules {
     type RoundsSeed = Model;
@@ -51,6 +56,14 @@ impl RoundsRules<CustomRules> for CustomRoundsRules {
         entity_id == *actor.entity_id()
     }
 
+    fn allow_start(
+        &self,
+        _state: &BattleState<CustomRules>,
+        actor: &dyn Actor<CustomRules>,
+    ) -> bool {
+        self.vetoed.as_ref() != Some(actor.entity_id())
+    }
+
     fn on_start(
         &self,
         model: &mut Self::RoundsModel,
@@ -59,7 +72,7 @@ impl RoundsRules<CustomRules> for CustomRoundsRules {
         _metrics: &mut WriteMetrics<CustomRules>,
     ) {
         model.starts += 1;
-        model.last = Some(actor.entity_id().clone());
+        model.last = Some(*actor.entity_id());
     }
 
     fn on_end(
@@ -81,15 +94,28 @@ impl RoundsRules<CustomRules> for CustomRoundsRules {
     ) {
         model.adds += 1;
     }
+
+    fn turn_order(
+        &self,
+        _model: &Self::RoundsModel,
+        actors: &mut dyn Iterator<Item = &dyn Actor<CustomRules>>,
+    ) -> Vec<EntityId<CustomRules>> {
+        let mut order: Vec<_> = actors.map(|actor| *actor.entity_id()).collect();
+        order.sort_by_key(|id| id.creature().unwrap());
+        order
+    }
 }
 
 battle_rules_with_rounds! { CustomRoundsRules }
 
 macro_rules! server {
     () => {{
+        server!(CustomRules::new())
+    }};
+    ($rules: expr) => {{
         let mut model = Model::default();
         model.last = Some(ENTITY_2_ID);
-        let battle = Battle::builder(CustomRules::new()).build();
+        let battle = Battle::builder($rules).build();
         let mut server = Server::builder(battle).build();
         assert_eq!(
             ResetRounds::trigger(&mut server).seed(model).fire().err(),
@@ -127,6 +153,7 @@ fn start_round() {
     assert_eq!(*server.battle().rounds().state(), RoundState::<_>::Ready);
     assert_eq!(server.battle().rounds().model().starts, 0);
     assert_eq!(server.battle().metrics().system_u64(ROUNDS_STARTED), None);
+    assert_eq!(server.battle().rounds().active_actor(), None);
     // Check start works.
     util::start_round(&mut server, &ENTITY_1_ID);
     // Post-start checks.
@@ -134,6 +161,7 @@ fn start_round() {
         *server.battle().rounds().state(),
         RoundState::<_>::Started(ENTITY_1_ID)
     );
+    assert_eq!(server.battle().rounds().active_actor(), Some(&ENTITY_1_ID));
     assert_eq!(server.battle().rounds().model().starts, 1);
     assert_eq!(
         server.battle().metrics().system_u64(ROUNDS_STARTED),
@@ -182,10 +210,44 @@ fn end_round() {
     // Post-end checks.
     assert_eq!(server.battle().rounds().model().ends, 1);
     assert_eq!(*server.battle().rounds().state(), RoundState::<_>::Ready);
+    assert_eq!(server.battle().rounds().active_actor(), None);
     // Check start round.
     util::start_round(&mut server, &ENTITY_2_ID);
 }
 
+#[test]
+fn end_round_for() {
+    // Initialize the battle.
+    let mut server = server!();
+    // Check end is prevented when no round is in progress.
+    assert_eq!(
+        EndRoundFor::trigger(&mut server, ENTITY_1_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::NoRoundInProgress)
+    );
+    // Start a round.
+    util::start_round(&mut server, &ENTITY_1_ID);
+    // Check that another actor can't end the round.
+    assert_eq!(
+        EndRoundFor::trigger(&mut server, ENTITY_2_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::ActorNotReady(ENTITY_2_ID))
+    );
+    assert_eq!(server.battle().rounds().active_actor(), Some(&ENTITY_1_ID));
+    assert_eq!(server.battle().rounds().model().ends, 0);
+    // End the round of the acting actor.
+    assert_eq!(
+        EndRoundFor::trigger(&mut server, ENTITY_1_ID).fire().err(),
+        None
+    );
+    assert_eq!(server.battle().rounds().model().ends, 1);
+    assert_eq!(*server.battle().rounds().state(), RoundState::<_>::Ready);
+}
+
 #[test]
 fn reset_rounds() {
     // Initialize the battle.
@@ -204,3 +266,82 @@ fn reset_rounds() {
     util::end_round(&mut server);
     assert_eq!(ResetRounds::trigger(&mut server).fire().err(), None);
 }
+
+#[test]
+fn turn_cycle() {
+    // Initialize the battle.
+    let mut server = server!();
+    assert!(server.battle().rounds().order().is_empty());
+    // Start a turn cycle.
+    assert_eq!(StartTurnCycle::trigger(&mut server).fire().err(), None);
+    assert_eq!(server.battle().rounds().active_actor(), Some(&ENTITY_1_ID));
+    assert_eq!(server.battle().rounds().order(), &[ENTITY_2_ID]);
+    // A new cycle can't start while a round is in progress.
+    assert_eq!(
+        StartTurnCycle::trigger(&mut server)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::RoundInProgress)
+    );
+    // Ending a round should start the next one.
+    util::end_round(&mut server);
+    assert_eq!(server.battle().rounds().active_actor(), Some(&ENTITY_2_ID));
+    assert!(server.battle().rounds().order().is_empty());
+    // The cycle is over after the last actor's round.
+    util::end_round(&mut server);
+    assert_eq!(*server.battle().rounds().state(), RoundState::<_>::Ready);
+    assert_eq!(server.battle().rounds().model().starts, 2);
+    assert_eq!(server.battle().rounds().model().ends, 2);
+}
+
+#[test]
+fn turn_cycle_actor_removed() {
+    // Initialize the battle.
+    let mut server = server!();
+    assert_eq!(StartTurnCycle::trigger(&mut server).fire().err(), None);
+    assert_eq!(server.battle().rounds().active_actor(), Some(&ENTITY_1_ID));
+    // Removing the active actor should start the next round in the cycle.
+    assert_eq!(
+        RemoveCreature::trigger(&mut server, CREATURE_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(server.battle().rounds().active_actor(), Some(&ENTITY_2_ID));
+    assert_eq!(server.battle().rounds().model().ends, 1);
+    // Same for disabling the active actor.
+    assert_eq!(
+        SetCreatureDisabled::trigger(&mut server, CREATURE_2_ID, true)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(*server.battle().rounds().state(), RoundState::<_>::Ready);
+    assert_eq!(server.battle().rounds().model().starts, 2);
+    assert_eq!(server.battle().rounds().model().ends, 2);
+}
+
+#[test]
+fn allow_start() {
+    // Initialize the battle, vetoing the first creature.
+    let mut rules = CustomRules::new();
+    rules.rounds_rules = Some(CustomRoundsRules {
+        vetoed: Some(ENTITY_1_ID),
+    });
+    let mut server = server!(rules);
+    // Check that the rules prevent the round from starting.
+    assert_eq!(
+        StartRound::trigger(&mut server, ENTITY_1_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::RoundCannotStart(ENTITY_1_ID))
+    );
+    assert_eq!(*server.battle().rounds().state(), RoundState::<_>::Ready);
+    // Turn cycles should skip vetoed actors.
+    assert_eq!(StartTurnCycle::trigger(&mut server).fire().err(), None);
+    assert_eq!(*server.battle().rounds().state(), RoundState::<_>::Ready);
+    assert!(server.battle().rounds().order().is_empty());
+    assert_eq!(server.battle().rounds().model().starts, 0);
+}
@@ -3,19 +3,24 @@ use weasel::battle::BattleRules;
 use weasel::battle_rules_with_space;
 use weasel::creature::CreateCreature;
 use weasel::entity::{Entity, EntityId};
-use weasel::event::{EventQueue, EventTrigger};
-use weasel::metric::WriteMetrics;
+use weasel::event::{EventQueue, EventServer, EventTrigger};
+use weasel::metric::{system::ENTITIES_MOVED, WriteMetrics};
 use weasel::server::Server;
 use weasel::space::{MoveEntity, ResetSpace, SpaceRules};
+use weasel::team::Relation;
 use weasel::WeaselError;
 use weasel::{battle_rules, rules::empty::*};
 
 static TEAM_1_ID: u32 = 1;
+static TEAM_2_ID: u32 = 2;
 static CREATURE_1_ID: u32 = 1;
 static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
 static CREATURE_2_ID: u32 = 2;
+static ENTITY_2_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_2_ID);
+static CREATURE_3_ID: u32 = 3;
 static POSITION_1: u32 = 1;
 static POSITION_2: u32 = 2;
+static POSITION_3: u32 = 3;
 static POSITION_T: u32 = 99;
 
 #[derive(Default)]
@@ -156,3 +161,115 @@ fn reset_space() {
     );
     assert_eq!(server.battle().space().model().len(), 1);
 }
+
+#[test]
+fn move_only_active_actor() {
+    let mut server = init_custom_game();
+    util::creature(&mut server, CREATURE_2_ID, TEAM_1_ID, POSITION_2);
+    util::start_round(&mut server, &ENTITY_1_ID);
+    let client_move = |server: &mut Server<CustomRules>, id, position| {
+        let version = *server.battle().rules().version();
+        let prototype = MoveEntity::trigger(&mut (), id, position)
+            .prototype()
+            .client_prototype(version, None);
+        server.process_client(prototype)
+    };
+    // Players can't move entities that are not acting.
+    assert_eq!(
+        client_move(&mut server, ENTITY_2_ID, POSITION_3)
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::ActorNotReady(ENTITY_2_ID))
+    );
+    assert_eq!(server.battle().metrics().system_u64(ENTITIES_MOVED), None);
+    // Players can move the active actor.
+    assert_eq!(
+        client_move(&mut server, ENTITY_1_ID, POSITION_3).err(),
+        None
+    );
+    assert_eq!(
+        server.battle().metrics().system_u64(ENTITIES_MOVED),
+        Some(1)
+    );
+    // The server can move any entity, for instance to apply a knockback.
+    assert_eq!(
+        MoveEntity::trigger(&mut server, ENTITY_2_ID, POSITION_1)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(
+        server.battle().metrics().system_u64(ENTITIES_MOVED),
+        Some(2)
+    );
+    // Outside of rounds players can move everyone.
+    util::end_round(&mut server);
+    assert_eq!(
+        client_move(&mut server, ENTITY_2_ID, POSITION_2).err(),
+        None
+    );
+    assert_eq!(
+        server.battle().metrics().system_u64(ENTITIES_MOVED),
+        Some(3)
+    );
+}
+
+#[test]
+fn move_overlap() {
+    #[derive(Default)]
+    struct OverlapSpaceRules {}
+
+    impl SpaceRules<CustomRules> for OverlapSpaceRules {
+        type Position = u32;
+        type SpaceSeed = ();
+        type SpaceModel = ();
+
+        fn generate_model(&self, _: &Option<Self::SpaceSeed>) -> Self::SpaceModel {}
+
+        fn check_overlap(
+            &self,
+            _model: &Self::SpaceModel,
+            _entity: &dyn Entity<CustomRules>,
+            _occupant: &dyn Entity<CustomRules>,
+            relation: Relation,
+        ) -> bool {
+            // Entities can only share a position with their kin.
+            relation == Relation::Kin
+        }
+    }
+
+    battle_rules_with_space! { OverlapSpaceRules }
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ENTITY_3_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_3_ID);
+    // Create two teams. Creature 3 is an enemy of the other two.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, POSITION_1);
+    util::creature(&mut server, CREATURE_2_ID, TEAM_1_ID, POSITION_2);
+    util::creature(&mut server, CREATURE_3_ID, TEAM_2_ID, POSITION_3);
+    // Moving into a position occupied by an enemy fails.
+    assert_eq!(
+        MoveEntity::trigger(&mut server, ENTITY_1_ID, POSITION_3)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::PositionOccupied(POSITION_3, ENTITY_3_ID))
+    );
+    // Moving into a position occupied by a kin succeeds.
+    assert_eq!(
+        MoveEntity::trigger(&mut server, ENTITY_1_ID, POSITION_2)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(
+        *server
+            .battle()
+            .entities()
+            .entity(&ENTITY_1_ID)
+            .unwrap()
+            .position(),
+        POSITION_2
+    );
+}
@@ -1,18 +1,19 @@
 use std::cell::RefCell;
 use weasel::ability::ActivateAbility;
 use weasel::actor::{Action, Actor, ActorRules};
-use weasel::battle::{BattleRules, BattleState};
+use weasel::battle::{BattlePhase, BattleRules, BattleState};
 use weasel::battle_rules_with_team;
 use weasel::creature::{ConvertCreature, CreateCreature, RemoveCreature};
 use weasel::entity::EntityId;
 use weasel::entropy::Entropy;
 use weasel::event::{DummyEvent, EventKind, EventQueue, EventTrigger};
-use weasel::metric::{system::*, ReadMetrics, WriteMetrics};
+use weasel::metric::{system::*, MetricsDelta, ReadMetrics, WriteMetrics};
 use weasel::player::PlayerId;
 use weasel::team::{
-    ConcludeObjectives, Conclusion, CreateTeam, EntityAddition, Relation, RemoveTeam,
-    ResetObjectives, SetRelations, Team, TeamRules,
+    ConcludeObjectives, Conclusion, CreateTeam, EntityAddition, Relation, RemoveTeam, RenameTeam,
+    ResetObjectives, SetDirectedRelations, SetRelations, Team, TeamRules,
 };
+use weasel::util::Id;
 use weasel::WeaselError;
 use weasel::{battle_rules, rules::empty::*};
 
@@ -26,10 +27,13 @@ impl<R: BattleRules> TeamRules<R> for CustomTeamRules {
     type Id = u32;
     type ObjectivesSeed = ();
     type Objectives = ();
+    type ObjectivesProgress = ();
 
     fn allow_new_entity(&self, _: &BattleState<R>, _: &Team<R>, mode: EntityAddition<R>) -> bool {
         match mode {
-            EntityAddition::CreatureSpawn => *self.allow_new_entities.borrow(),
+            EntityAddition::CreatureSpawn | EntityAddition::CreatureBatchSpawn(_) => {
+                *self.allow_new_entities.borrow()
+            }
             EntityAddition::CreatureConversion(_) => *self.allow_converted_entities.borrow(),
         }
     }
@@ -40,6 +44,7 @@ static TEAM_2_ID: u32 = 2;
 static TEAM_3_ID: u32 = 3;
 static TEAM_ERR_ID: u32 = 99;
 static CREATURE_1_ID: u32 = 1;
+static CREATURE_2_ID: u32 = 2;
 static CREATURE_ERR_ID: u32 = 99;
 
 #[test]
@@ -134,6 +139,7 @@ fn diplomacy() {
     // Check that diplomacy is created correctly.
     let entities = server.battle().entities();
     assert_eq!(entities.relation(&TEAM_1_ID, &TEAM_ERR_ID), None);
+    assert_eq!(entities.relation(&TEAM_ERR_ID, &TEAM_ERR_ID), None);
     assert_eq!(
         entities.relation(&TEAM_1_ID, &TEAM_1_ID),
         Some(Relation::Kin)
@@ -185,6 +191,26 @@ fn diplomacy() {
     let mut vec = entities.enemies_id(&TEAM_3_ID).collect::<Vec<_>>();
     vec.sort_unstable();
     assert_eq!(vec, vec![TEAM_1_ID, TEAM_2_ID]);
+    // Check that every pair of teams is listed once.
+    let mut vec = entities
+        .relations()
+        .map(|(first, second, relation)| {
+            (
+                *std::cmp::min(first, second),
+                *std::cmp::max(first, second),
+                relation,
+            )
+        })
+        .collect::<Vec<_>>();
+    vec.sort_unstable_by_key(|e| (e.0, e.1));
+    assert_eq!(
+        vec,
+        vec![
+            (TEAM_1_ID, TEAM_2_ID, Relation::Ally),
+            (TEAM_1_ID, TEAM_3_ID, Relation::Enemy),
+            (TEAM_2_ID, TEAM_3_ID, Relation::Enemy)
+        ]
+    );
     // Set team three ally with team two and team one enemy with team two.
     // Check that pre-conditions are checked.
     assert_eq!(
@@ -271,6 +297,75 @@ fn diplomacy() {
     );
 }
 
+#[test]
+fn directed_relations() {
+    battle_rules! {}
+    let mut server = util::server(CustomRules::new());
+    // Create two allied teams.
+    util::team(&mut server, TEAM_1_ID);
+    assert_eq!(
+        CreateTeam::trigger(&mut server, TEAM_2_ID)
+            .relations(&[(TEAM_1_ID, Relation::Ally)])
+            .fire()
+            .err(),
+        None
+    );
+    // Check that pre-conditions are checked.
+    assert_eq!(
+        SetDirectedRelations::trigger(&mut server, &[(TEAM_1_ID, TEAM_ERR_ID, Relation::Enemy)])
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::TeamNotFound(TEAM_ERR_ID))
+    );
+    assert_eq!(
+        SetDirectedRelations::trigger(&mut server, &[(TEAM_1_ID, TEAM_1_ID, Relation::Enemy)])
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::SelfRelation)
+    );
+    assert_eq!(
+        SetDirectedRelations::trigger(&mut server, &[(TEAM_1_ID, TEAM_2_ID, Relation::Kin)])
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::KinshipRelation)
+    );
+    // Without directed relations, the symmetric ones are returned.
+    let entities = server.battle().entities();
+    assert_eq!(
+        entities.directed_relation(&TEAM_1_ID, &TEAM_2_ID),
+        Some(Relation::Ally)
+    );
+    assert_eq!(
+        entities.directed_relation(&TEAM_1_ID, &TEAM_1_ID),
+        Some(Relation::Kin)
+    );
+    assert_eq!(entities.directed_relation(&TEAM_1_ID, &TEAM_ERR_ID), None);
+    // Make team one regard team two as an enemy.
+    assert_eq!(
+        SetDirectedRelations::trigger(&mut server, &[(TEAM_1_ID, TEAM_2_ID, Relation::Enemy)])
+            .fire()
+            .err(),
+        None
+    );
+    let entities = server.battle().entities();
+    assert_eq!(
+        entities.directed_relation(&TEAM_1_ID, &TEAM_2_ID),
+        Some(Relation::Enemy)
+    );
+    assert_eq!(
+        entities.directed_relation(&TEAM_2_ID, &TEAM_1_ID),
+        Some(Relation::Ally)
+    );
+    // Symmetric relations are left untouched.
+    assert_eq!(
+        entities.relation(&TEAM_1_ID, &TEAM_2_ID),
+        Some(Relation::Ally)
+    );
+}
+
 #[test]
 fn convert_creature() {
     // Create a server with creature conversion disabled.
@@ -424,6 +519,7 @@ fn conclusion() {
     );
     assert_eq!(server.battle().entities().victorious().count(), 0);
     assert_eq!(server.battle().entities().defeated().count(), 0);
+    assert_eq!(server.battle().entities().drawn().count(), 0);
     // Make one team win and the other lose.
     // Check team existence.
     assert_eq!(
@@ -466,6 +562,59 @@ fn conclusion() {
     );
     assert_eq!(server.battle().entities().victorious().count(), 1);
     assert_eq!(server.battle().entities().defeated().count(), 1);
+    // Create a third team and end its objectives with a draw.
+    util::team(&mut server, TEAM_3_ID);
+    assert_eq!(
+        ConcludeObjectives::trigger(&mut server, TEAM_3_ID, Conclusion::Draw)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(
+        server
+            .battle()
+            .entities()
+            .team(&TEAM_3_ID)
+            .unwrap()
+            .conclusion(),
+        Some(Conclusion::Draw)
+    );
+    assert_eq!(
+        server.battle().entities().drawn_id().collect::<Vec<_>>(),
+        vec![TEAM_3_ID]
+    );
+}
+
+#[test]
+fn initial_conclusion() {
+    battle_rules! {}
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    // Invalid relations are still rejected.
+    assert_eq!(
+        CreateTeam::trigger(&mut server, TEAM_2_ID)
+            .conclusion(Conclusion::Draw)
+            .relations(&[(TEAM_2_ID, Relation::Ally)])
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::SelfRelation)
+    );
+    // Create a team with a conclusion.
+    assert_eq!(
+        CreateTeam::trigger(&mut server, TEAM_2_ID)
+            .conclusion(Conclusion::Draw)
+            .fire()
+            .err(),
+        None
+    );
+    let entities = server.battle().entities();
+    assert_eq!(entities.team(&TEAM_1_ID).unwrap().conclusion(), None);
+    assert_eq!(
+        entities.team(&TEAM_2_ID).unwrap().conclusion(),
+        Some(Conclusion::Draw)
+    );
+    assert_eq!(entities.drawn_id().collect::<Vec<_>>(), vec![TEAM_2_ID]);
 }
 
 #[test]
@@ -477,6 +626,7 @@ fn reset_objectives() {
         type Id = u32;
         type ObjectivesSeed = u32;
         type Objectives = u32;
+        type ObjectivesProgress = ();
 
         fn generate_objectives(&self, seed: &Option<Self::ObjectivesSeed>) -> Self::Objectives {
             seed.unwrap_or_default()
@@ -554,6 +704,71 @@ fn reset_objectives() {
     );
 }
 
+#[test]
+fn validate_objectives_seed() {
+    use weasel::error::WeaselResult;
+
+    #[derive(Default)]
+    struct CustomTeamRules {}
+
+    impl<R: BattleRules<TR = Self>> TeamRules<R> for CustomTeamRules {
+        type Id = u32;
+        // The seed is the id of the enemy team to defeat.
+        type ObjectivesSeed = u32;
+        type Objectives = ();
+        type ObjectivesProgress = ();
+
+        fn validate_objectives_seed(
+            &self,
+            state: &BattleState<R>,
+            seed: &Option<Self::ObjectivesSeed>,
+        ) -> WeaselResult<(), R> {
+            match seed {
+                Some(id) if state.entities().team(id).is_none() => {
+                    Err(WeaselError::TeamNotFound(*id))
+                }
+                _ => Ok(()),
+            }
+        }
+    }
+
+    battle_rules_with_team! { CustomTeamRules }
+    let mut server = util::server(CustomRules::new());
+    // Seeds referencing a non existing team are rejected.
+    assert_eq!(
+        CreateTeam::trigger(&mut server, TEAM_1_ID)
+            .objectives_seed(TEAM_2_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::TeamNotFound(TEAM_2_ID))
+    );
+    util::team(&mut server, TEAM_2_ID);
+    assert_eq!(
+        CreateTeam::trigger(&mut server, TEAM_1_ID)
+            .objectives_seed(TEAM_2_ID)
+            .fire()
+            .err(),
+        None
+    );
+    // The same validation applies when objectives are reset.
+    assert_eq!(
+        ResetObjectives::trigger(&mut server, TEAM_2_ID)
+            .seed(TEAM_ERR_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::TeamNotFound(TEAM_ERR_ID))
+    );
+    assert_eq!(
+        ResetObjectives::trigger(&mut server, TEAM_2_ID)
+            .seed(TEAM_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+}
+
 #[test]
 fn check_objectives() {
     #[derive(Default)]
@@ -597,12 +812,14 @@ fn check_objectives() {
         type Id = u32;
         type ObjectivesSeed = ();
         type Objectives = ();
+        type ObjectivesProgress = ();
 
         fn check_objectives_on_event(
             &self,
             _state: &BattleState<CustomRules>,
             _team: &Team<CustomRules>,
             metrics: &ReadMetrics<CustomRules>,
+            _delta: &MetricsDelta<CustomRules>,
         ) -> Option<Conclusion> {
             if !self.check_round {
                 if let Some(v) = metrics.user_u64(0) {
@@ -632,14 +849,8 @@ fn check_objectives() {
     }
 
     battle_rules! {
-        CustomTeamRules,
-        EmptyCharacterRules,
-        CustomActorRules,
-        EmptyFightRules,
-        EmptyUserRules,
-        EmptySpaceRules,
-        EmptyRoundsRules,
-        EmptyEntropyRules
+        team: CustomTeamRules,
+        actor: CustomActorRules
     }
 
     static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
@@ -718,8 +929,124 @@ fn check_objectives() {
             .count(),
         1
     );
+    // Replaying the history reproduces the same conclusion, without new events.
+    let len = server.battle().history().len() as usize;
+    let mut rules = CustomRules::new();
+    rules.team_rules = CustomTeamRules { check_round: false };
+    let replayed = weasel::Server::replay(rules, server.battle().versioned_events(0..len)).unwrap();
+    assert_eq!(replayed.battle().history().len() as usize, len);
+    assert_eq!(
+        replayed
+            .battle()
+            .entities()
+            .team(&TEAM_1_ID)
+            .unwrap()
+            .conclusion(),
+        Some(Conclusion::Victory)
+    );
 }
 
+#[test]
+fn objectives_delta() {
+    #[derive(Default)]
+    pub struct CustomActorRules {}
+
+    impl ActorRules<CustomRules> for CustomActorRules {
+        type Ability = EmptyAbility;
+        type AbilitiesSeed = ();
+        type Activation = u64;
+        type AbilitiesAlteration = ();
+
+        fn generate_abilities(
+            &self,
+            _: &Option<Self::AbilitiesSeed>,
+            _entropy: &mut Entropy<CustomRules>,
+            _metrics: &mut WriteMetrics<CustomRules>,
+        ) -> Box<dyn Iterator<Item = Self::Ability>> {
+            let v = vec![EmptyAbility { id: ABILITY_ID }];
+            Box::new(v.into_iter())
+        }
+
+        fn activate(
+            &self,
+            _state: &BattleState<CustomRules>,
+            action: Action<CustomRules>,
+            _event_queue: &mut Option<EventQueue<CustomRules>>,
+            _entropy: &mut Entropy<CustomRules>,
+            metrics: &mut WriteMetrics<CustomRules>,
+        ) {
+            metrics.add_user_u64(0, action.activation.unwrap()).unwrap();
+        }
+    }
+
+    #[derive(Default)]
+    struct CustomTeamRules {}
+
+    impl TeamRules<CustomRules> for CustomTeamRules {
+        type Id = u32;
+        type ObjectivesSeed = ();
+        type Objectives = ();
+        type ObjectivesProgress = ();
+
+        fn check_objectives_on_event(
+            &self,
+            _state: &BattleState<CustomRules>,
+            _team: &Team<CustomRules>,
+            _metrics: &ReadMetrics<CustomRules>,
+            delta: &MetricsDelta<CustomRules>,
+        ) -> Option<Conclusion> {
+            // Victory if the metric increased by at least two in a single event.
+            if delta.user_u64(0).unwrap_or_default() >= 2 {
+                Some(Conclusion::Victory)
+            } else {
+                None
+            }
+        }
+    }
+
+    battle_rules! {
+        team: CustomTeamRules,
+        actor: CustomActorRules
+    }
+
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    static ABILITY_ID: u32 = 1;
+
+    // Create a battle with one creature.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::start_round(&mut server, &ENTITY_1_ID);
+    let conclusion = |server: &weasel::Server<CustomRules>| {
+        server
+            .battle()
+            .entities()
+            .team(&TEAM_1_ID)
+            .unwrap()
+            .conclusion()
+    };
+    // Increase the metric by one, twice.
+    for _ in 0..2 {
+        assert_eq!(
+            ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_ID)
+                .activation(1)
+                .fire()
+                .err(),
+            None
+        );
+    }
+    assert_eq!(server.battle().metrics().user_u64(0), Some(2));
+    assert_eq!(conclusion(&server), None);
+    // Increase the metric by two in one go.
+    assert_eq!(
+        ActivateAbility::trigger(&mut server, ENTITY_1_ID, ABILITY_ID)
+            .activation(2)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(conclusion(&server), Some(Conclusion::Victory));
+}
 #[test]
 fn remove_team() {
     static PLAYER_1_ID: PlayerId = 1;
@@ -772,3 +1099,679 @@ fn remove_team() {
     assert!(!server.rights().check(PLAYER_1_ID, &TEAM_1_ID));
     assert!(server.battle().entities().team(&TEAM_1_ID).is_none());
 }
+
+#[test]
+fn remove_team_cascade() {
+    battle_rules! {}
+    // Create a battle with one team and two creatures.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_2_ID, TEAM_1_ID, ());
+    // Remove the team together with its creatures.
+    assert_eq!(
+        RemoveTeam::trigger(&mut server, TEAM_1_ID)
+            .cascade()
+            .fire()
+            .err(),
+        None
+    );
+    assert!(server.battle().entities().team(&TEAM_1_ID).is_none());
+    assert_eq!(server.battle().entities().creatures().count(), 0);
+    // Check that creatures were removed through separate events.
+    let kinds: Vec<_> = server
+        .battle()
+        .history()
+        .events()
+        .iter()
+        .skip(3)
+        .map(|event| event.kind())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            EventKind::RemoveTeam,
+            EventKind::RemoveCreature,
+            EventKind::RemoveCreature,
+            EventKind::RemoveTeam
+        ]
+    );
+    // Cascade works on empty teams as well.
+    util::team(&mut server, TEAM_2_ID);
+    assert_eq!(
+        RemoveTeam::trigger(&mut server, TEAM_2_ID)
+            .cascade()
+            .fire()
+            .err(),
+        None
+    );
+    assert!(server.battle().entities().team(&TEAM_2_ID).is_none());
+}
+
+#[test]
+fn rename_team() {
+    static PLAYER_1_ID: PlayerId = 1;
+    battle_rules! {}
+    // Create a battle with two enemy teams.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    assert_eq!(server.rights_mut().add(PLAYER_1_ID, &TEAM_1_ID).err(), None);
+    // Renaming should fail if the team doesn't exist or the new id is taken.
+    assert_eq!(
+        RenameTeam::trigger(&mut server, TEAM_ERR_ID, TEAM_3_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::TeamNotFound(TEAM_ERR_ID))
+    );
+    assert_eq!(
+        RenameTeam::trigger(&mut server, TEAM_1_ID, TEAM_2_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::DuplicatedTeam(TEAM_2_ID))
+    );
+    // Rename the first team.
+    assert_eq!(
+        RenameTeam::trigger(&mut server, TEAM_1_ID, TEAM_3_ID)
+            .fire()
+            .err(),
+        None
+    );
+    // Check that all references to the team are updated.
+    let entities = server.battle().entities();
+    assert!(entities.team(&TEAM_1_ID).is_none());
+    assert_eq!(
+        entities
+            .team(&TEAM_3_ID)
+            .unwrap()
+            .creatures()
+            .collect::<Vec<_>>(),
+        vec![&CREATURE_1_ID]
+    );
+    assert_eq!(
+        *entities.creature(&CREATURE_1_ID).unwrap().team_id(),
+        TEAM_3_ID
+    );
+    assert_eq!(
+        entities.relation(&TEAM_3_ID, &TEAM_2_ID),
+        Some(Relation::Enemy)
+    );
+    assert_eq!(entities.relations().count(), 1);
+    assert!(!server.rights().check(PLAYER_1_ID, &TEAM_1_ID));
+    assert!(server.rights().check(PLAYER_1_ID, &TEAM_3_ID));
+}
+
+#[test]
+fn team_creatures_metric() {
+    battle_rules_with_team! { CustomTeamRules }
+    let mut rules = CustomRules::new();
+    rules.team_rules = CustomTeamRules {
+        allow_new_entities: RefCell::new(true),
+        allow_converted_entities: RefCell::new(true),
+    };
+    let mut server = util::server(rules);
+    // Create two teams and two creatures.
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    let team_creatures = |server: &weasel::server::Server<CustomRules>, team| {
+        server.battle().metrics().team_u64(team, TEAM_CREATURES)
+    };
+    assert_eq!(team_creatures(&server, &TEAM_1_ID), Some(0));
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_2_ID, TEAM_1_ID, ());
+    assert_eq!(team_creatures(&server, &TEAM_1_ID), Some(2));
+    assert_eq!(team_creatures(&server, &TEAM_2_ID), Some(0));
+    // Convert a creature.
+    assert_eq!(
+        ConvertCreature::trigger(&mut server, CREATURE_1_ID, TEAM_2_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(team_creatures(&server, &TEAM_1_ID), Some(1));
+    assert_eq!(team_creatures(&server, &TEAM_2_ID), Some(1));
+    // Remove a creature.
+    assert_eq!(
+        RemoveCreature::trigger(&mut server, CREATURE_2_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(team_creatures(&server, &TEAM_1_ID), Some(0));
+    // Removing the team also removes its metrics.
+    assert_eq!(
+        RemoveTeam::trigger(&mut server, TEAM_1_ID).fire().err(),
+        None
+    );
+    assert_eq!(team_creatures(&server, &TEAM_1_ID), None);
+}
+
+#[test]
+fn objectives_progress() {
+    #[derive(Default)]
+    struct CustomTeamRules {}
+
+    impl<R: BattleRules> TeamRules<R> for CustomTeamRules {
+        type Id = u32;
+        type ObjectivesSeed = ();
+        type Objectives = ();
+        // Progress is the number of creatures in the team.
+        type ObjectivesProgress = usize;
+
+        fn objectives_progress(
+            &self,
+            _: &BattleState<R>,
+            team: &Team<R>,
+            _: &ReadMetrics<R>,
+        ) -> Self::ObjectivesProgress {
+            team.creatures().count()
+        }
+    }
+
+    battle_rules_with_team! { CustomTeamRules }
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    let progress = |server: &weasel::server::Server<CustomRules>| {
+        *server
+            .battle()
+            .entities()
+            .team(&TEAM_1_ID)
+            .unwrap()
+            .objectives_progress()
+    };
+    assert_eq!(progress(&server), 0);
+    // Progress is updated after each event.
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    assert_eq!(progress(&server), 1);
+    util::creature(&mut server, CREATURE_2_ID, TEAM_1_ID, ());
+    assert_eq!(progress(&server), 2);
+}
+
+#[test]
+fn round_hooks() {
+    #[derive(Default)]
+    struct CustomTeamRules {}
+
+    impl<R: BattleRules + 'static> TeamRules<R> for CustomTeamRules {
+        type Id = u32;
+        type ObjectivesSeed = ();
+        type Objectives = ();
+        type ObjectivesProgress = ();
+
+        fn on_round_start(
+            &self,
+            _: &BattleState<R>,
+            _: &Team<R>,
+            _: &dyn Actor<R>,
+            event_queue: &mut Option<EventQueue<R>>,
+            _: &mut Entropy<R>,
+            _: &mut WriteMetrics<R>,
+        ) {
+            DummyEvent::trigger(event_queue).fire();
+        }
+
+        fn on_round_end(
+            &self,
+            _: &BattleState<R>,
+            _: &Team<R>,
+            _: &dyn Actor<R>,
+            event_queue: &mut Option<EventQueue<R>>,
+            _: &mut Entropy<R>,
+            _: &mut WriteMetrics<R>,
+        ) {
+            DummyEvent::trigger(event_queue).fire();
+        }
+    }
+
+    battle_rules_with_team! { CustomTeamRules }
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    // Create a battle with two teams.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    let dummies = |server: &weasel::server::Server<CustomRules>| {
+        server
+            .battle()
+            .history()
+            .count_by_kind(EventKind::DummyEvent)
+    };
+    // Each team should react to the start of the round.
+    util::start_round(&mut server, &ENTITY_1_ID);
+    assert_eq!(dummies(&server), 2);
+    // Each team should react to the end of the round.
+    util::end_round(&mut server);
+    assert_eq!(dummies(&server), 4);
+}
+
+#[test]
+fn teams_order() {
+    battle_rules! {}
+    let mut server = util::server(CustomRules::new());
+    let ids = |server: &weasel::server::Server<CustomRules>| {
+        server
+            .battle()
+            .entities()
+            .teams()
+            .map(|team| *team.id())
+            .collect::<Vec<_>>()
+    };
+    // Teams are iterated in order of creation.
+    for id in &[5, 3, 9, 1] {
+        util::team(&mut server, *id);
+    }
+    assert_eq!(ids(&server), vec![5, 3, 9, 1]);
+    // Removing a team preserves the order of the others.
+    assert_eq!(RemoveTeam::trigger(&mut server, 3).fire().err(), None);
+    assert_eq!(ids(&server), vec![5, 9, 1]);
+    // A renamed team keeps its position.
+    assert_eq!(RenameTeam::trigger(&mut server, 9, 7).fire().err(), None);
+    assert_eq!(ids(&server), vec![5, 7, 1]);
+    // Enemies follow the same order.
+    assert_eq!(
+        server
+            .battle()
+            .entities()
+            .enemies_id(&1)
+            .collect::<Vec<_>>(),
+        vec![5, 7]
+    );
+}
+
+#[test]
+fn creatures_of_team() {
+    battle_rules! {}
+    static CREATURE_3_ID: u32 = 3;
+    // Create two teams with some creatures.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_2_ID, TEAM_2_ID, ());
+    util::creature(&mut server, CREATURE_3_ID, TEAM_1_ID, ());
+    // Check the creatures of each team.
+    let entities = server.battle().entities();
+    let creatures = |team| {
+        entities
+            .creatures_of_team(team)
+            .map(|creature| *creature.id())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(creatures(&TEAM_1_ID), vec![CREATURE_1_ID, CREATURE_3_ID]);
+    assert_eq!(creatures(&TEAM_2_ID), vec![CREATURE_2_ID]);
+    assert!(creatures(&TEAM_ERR_ID).is_empty());
+}
+
+#[test]
+fn last_team_standing() {
+    use weasel::rules::team::LastTeamStandingRules;
+
+    battle_rules_with_team! { LastTeamStandingRules }
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    // Create a battle with three teams, two of which have creatures.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::team(&mut server, TEAM_3_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_2_ID, TEAM_2_ID, ());
+    let conclusion = |server: &weasel::Server<CustomRules>, id| {
+        server.battle().entities().team(&id).unwrap().conclusion()
+    };
+    // Teams without creatures are defeated at the end of a round.
+    util::start_round(&mut server, &ENTITY_1_ID);
+    util::end_round(&mut server);
+    assert_eq!(conclusion(&server, TEAM_1_ID), None);
+    assert_eq!(conclusion(&server, TEAM_2_ID), None);
+    assert_eq!(conclusion(&server, TEAM_3_ID), Some(Conclusion::Defeat));
+    // The last team with creatures wins, as soon as the others lose their creatures.
+    assert_eq!(
+        RemoveCreature::trigger(&mut server, CREATURE_2_ID)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(conclusion(&server, TEAM_1_ID), Some(Conclusion::Victory));
+    assert_eq!(conclusion(&server, TEAM_2_ID), Some(Conclusion::Defeat));
+    // The battle ends once all teams have a conclusion.
+    assert_eq!(server.battle().phase(), BattlePhase::Ended);
+}
+
+#[test]
+fn last_team_standing_remove_team() {
+    use weasel::rules::team::LastTeamStandingRules;
+
+    battle_rules_with_team! { LastTeamStandingRules }
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    // Create a battle with two teams.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_2_ID, TEAM_2_ID, ());
+    util::start_round(&mut server, &ENTITY_1_ID);
+    // Removing a team together with its creatures concludes the battle
+    // only after the team itself is gone.
+    assert_eq!(
+        RemoveTeam::trigger(&mut server, TEAM_2_ID)
+            .cascade()
+            .fire()
+            .err(),
+        None
+    );
+    let entities = server.battle().entities();
+    assert!(entities.team(&TEAM_2_ID).is_none());
+    assert_eq!(
+        entities.team(&TEAM_1_ID).unwrap().conclusion(),
+        Some(Conclusion::Victory)
+    );
+    assert_eq!(server.battle().phase(), BattlePhase::Ended);
+}
+
+#[test]
+fn last_team_standing_disabled() {
+    use weasel::creature::SetCreatureDisabled;
+    use weasel::rules::team::LastTeamStandingRules;
+
+    battle_rules_with_team! { LastTeamStandingRules }
+    // Create a battle with two teams, without starting any round.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_2_ID, TEAM_2_ID, ());
+    let standing = |server: &weasel::Server<CustomRules>, id| {
+        *server
+            .battle()
+            .entities()
+            .team(&id)
+            .unwrap()
+            .objectives_progress()
+    };
+    assert_eq!(standing(&server, TEAM_1_ID).creatures(), 1);
+    assert!(standing(&server, TEAM_2_ID).deployed());
+    // Metrics have no influence on the objectives.
+    server.reset_metrics();
+    // Disabled creatures can't fight.
+    assert_eq!(
+        SetCreatureDisabled::trigger(&mut server, CREATURE_2_ID, true)
+            .fire()
+            .err(),
+        None
+    );
+    assert_eq!(standing(&server, TEAM_2_ID).creatures(), 0);
+    // Each team is concluded only once.
+    let conclusions = server
+        .battle()
+        .history()
+        .events()
+        .iter()
+        .filter(|event| event.kind() == EventKind::ConcludeObjectives)
+        .count();
+    assert_eq!(conclusions, 2);
+    let entities = server.battle().entities();
+    assert_eq!(
+        entities.team(&TEAM_1_ID).unwrap().conclusion(),
+        Some(Conclusion::Victory)
+    );
+    assert_eq!(
+        entities.team(&TEAM_2_ID).unwrap().conclusion(),
+        Some(Conclusion::Defeat)
+    );
+    assert_eq!(server.battle().phase(), BattlePhase::Ended);
+}
+
+#[cfg(feature = "serialization")]
+#[test]
+fn last_team_standing_snapshot() {
+    use weasel::rules::team::LastTeamStandingRules;
+    use weasel::serde::BattleSnapshot;
+    use weasel::Server;
+
+    battle_rules_with_team! { LastTeamStandingRules }
+    static ENTITY_1_ID: EntityId<CustomRules> = EntityId::Creature(CREATURE_1_ID);
+    // Create a battle with two teams and start it.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::creature(&mut server, CREATURE_1_ID, TEAM_1_ID, ());
+    util::creature(&mut server, CREATURE_2_ID, TEAM_2_ID, ());
+    util::start_round(&mut server, &ENTITY_1_ID);
+    // Restore a copy of the battle from a snapshot.
+    let snapshot = BattleSnapshot::new(server.battle());
+    let mut restored = Server::from_snapshot(CustomRules::new(), snapshot);
+    // Both battles must evolve in the same way.
+    for server in &mut [&mut server, &mut restored] {
+        assert_eq!(
+            RemoveCreature::trigger(*server, CREATURE_2_ID).fire().err(),
+            None
+        );
+        let entities = server.battle().entities();
+        assert_eq!(
+            entities.team(&TEAM_1_ID).unwrap().conclusion(),
+            Some(Conclusion::Victory)
+        );
+        assert_eq!(
+            entities.team(&TEAM_2_ID).unwrap().conclusion(),
+            Some(Conclusion::Defeat)
+        );
+        assert_eq!(server.battle().phase(), BattlePhase::Ended);
+    }
+}
+
+#[test]
+fn last_team_standing_generic_id() {
+    use weasel::rules::team::LastTeamStandingRules;
+
+    battle_rules_with_team! { LastTeamStandingRules<String> }
+    // Teams can have any kind of id.
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, "red".to_string());
+    util::team(&mut server, "blue".to_string());
+    util::creature(&mut server, CREATURE_1_ID, "red".to_string(), ());
+    util::start_round(&mut server, &EntityId::Creature(CREATURE_1_ID));
+    util::end_round(&mut server);
+    let entities = server.battle().entities();
+    assert_eq!(
+        entities.team(&"blue".to_string()).unwrap().conclusion(),
+        Some(Conclusion::Defeat)
+    );
+    assert_eq!(
+        entities.team(&"red".to_string()).unwrap().conclusion(),
+        Some(Conclusion::Victory)
+    );
+}
+
+#[test]
+fn default_relation() {
+    #[derive(Default)]
+    struct CustomTeamRules {
+        default: RefCell<Option<Relation>>,
+    }
+
+    impl<R: BattleRules> TeamRules<R> for CustomTeamRules {
+        type Id = u32;
+        type ObjectivesSeed = ();
+        type Objectives = ();
+        type ObjectivesProgress = ();
+
+        fn default_relation(&self) -> Relation {
+            self.default.borrow().unwrap_or(Relation::Ally)
+        }
+    }
+
+    battle_rules_with_team! { CustomTeamRules }
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    // Teams not explicitly related take the default relation.
+    assert_eq!(
+        CreateTeam::trigger(&mut server, TEAM_2_ID).fire().err(),
+        None
+    );
+    assert_eq!(
+        CreateTeam::trigger(&mut server, TEAM_3_ID)
+            .relations(&[(TEAM_1_ID, Relation::Enemy)])
+            .fire()
+            .err(),
+        None
+    );
+    let entities = server.battle().entities();
+    assert_eq!(
+        entities.relation(&TEAM_1_ID, &TEAM_2_ID),
+        Some(Relation::Ally)
+    );
+    assert_eq!(
+        entities.relation(&TEAM_1_ID, &TEAM_3_ID),
+        Some(Relation::Enemy)
+    );
+    assert_eq!(
+        entities.relation(&TEAM_2_ID, &TEAM_3_ID),
+        Some(Relation::Ally)
+    );
+    // Kinship can't be the default relation.
+    *server.battle().rules().team_rules().default.borrow_mut() = Some(Relation::Kin);
+    assert_eq!(
+        CreateTeam::trigger(&mut server, TEAM_ERR_ID)
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::KinshipRelation)
+    );
+}
+
+#[test]
+fn kinship_invariant() {
+    battle_rules! {}
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    assert_eq!(
+        SetDirectedRelations::trigger(&mut server, &[(TEAM_2_ID, TEAM_1_ID, Relation::Ally)])
+            .fire()
+            .err(),
+        None
+    );
+    // Relations of a removed team are forgotten.
+    assert_eq!(
+        RemoveTeam::trigger(&mut server, TEAM_1_ID).fire().err(),
+        None
+    );
+    assert_eq!(server.battle().entities().relations().count(), 0);
+    // A team taking the id of the removed one doesn't end up related to itself.
+    assert_eq!(
+        RenameTeam::trigger(&mut server, TEAM_2_ID, TEAM_1_ID)
+            .fire()
+            .err(),
+        None
+    );
+    let entities = server.battle().entities();
+    assert_eq!(entities.relations().count(), 0);
+    assert_eq!(
+        entities.relation(&TEAM_1_ID, &TEAM_1_ID),
+        Some(Relation::Kin)
+    );
+    assert_eq!(
+        entities.directed_relation(&TEAM_1_ID, &TEAM_1_ID),
+        Some(Relation::Kin)
+    );
+    // A new team with a previously used id starts with fresh relations.
+    util::team(&mut server, TEAM_2_ID);
+    let entities = server.battle().entities();
+    assert_eq!(
+        entities.directed_relation(&TEAM_2_ID, &TEAM_1_ID),
+        Some(Relation::Enemy)
+    );
+}
+
+#[test]
+fn overwrite_relations() {
+    use weasel::team::OverwriteRelations;
+
+    battle_rules! {}
+    let mut server = util::server(CustomRules::new());
+    util::team(&mut server, TEAM_1_ID);
+    util::team(&mut server, TEAM_2_ID);
+    util::team(&mut server, TEAM_3_ID);
+    assert_eq!(
+        SetRelations::trigger(
+            &mut server,
+            &[
+                (TEAM_1_ID, TEAM_2_ID, Relation::Ally),
+                (TEAM_2_ID, TEAM_3_ID, Relation::Ally)
+            ]
+        )
+        .fire()
+        .err(),
+        None
+    );
+    // Invalid relations are rejected.
+    assert_eq!(
+        OverwriteRelations::trigger(&mut server, &[(TEAM_1_ID, TEAM_ERR_ID, Relation::Ally)])
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::TeamNotFound(TEAM_ERR_ID))
+    );
+    assert_eq!(
+        OverwriteRelations::trigger(&mut server, &[(TEAM_1_ID, TEAM_1_ID, Relation::Ally)])
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::SelfRelation)
+    );
+    assert_eq!(
+        OverwriteRelations::trigger(&mut server, &[(TEAM_1_ID, TEAM_2_ID, Relation::Kin)])
+            .fire()
+            .err()
+            .map(|e| e.unfold()),
+        Some(WeaselError::KinshipRelation)
+    );
+    assert_eq!(
+        OverwriteRelations::trigger(
+            &mut server,
+            &[
+                (TEAM_1_ID, TEAM_2_ID, Relation::Ally),
+                (TEAM_2_ID, TEAM_1_ID, Relation::Enemy)
+            ]
+        )
+        .fire()
+        .err()
+        .map(|e| e.unfold()),
+        Some(WeaselError::DuplicatedRelation(TEAM_2_ID, TEAM_1_ID))
+    );
+    // Set a directed relation.
+    assert_eq!(
+        SetDirectedRelations::trigger(&mut server, &[(TEAM_1_ID, TEAM_3_ID, Relation::Enemy)])
+            .fire()
+            .err(),
+        None
+    );
+    // Replace all relations. Pairs not listed become enemies and directed relations are removed.
+    assert_eq!(
+        OverwriteRelations::trigger(&mut server, &[(TEAM_3_ID, TEAM_1_ID, Relation::Ally)])
+            .fire()
+            .err(),
+        None
+    );
+    let entities = server.battle().entities();
+    assert_eq!(entities.relations().count(), 3);
+    assert_eq!(
+        entities.relation(&TEAM_1_ID, &TEAM_3_ID),
+        Some(Relation::Ally)
+    );
+    assert_eq!(
+        entities.relation(&TEAM_1_ID, &TEAM_2_ID),
+        Some(Relation::Enemy)
+    );
+    assert_eq!(
+        entities.relation(&TEAM_2_ID, &TEAM_3_ID),
+        Some(Relation::Enemy)
+    );
+    assert_eq!(
+        entities.directed_relation(&TEAM_1_ID, &TEAM_3_ID),
+        Some(Relation::Ally)
+    );
+}
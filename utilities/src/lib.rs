@@ -3,6 +3,7 @@ use weasel::client::Client;
 use weasel::creature::{CreateCreature, CreatureId};
 use weasel::entity::EntityId;
 use weasel::event::{DefaultOutput, DummyEvent, EventProcessor, EventTrigger, ServerSink};
+use weasel::object::{CreateObject, ObjectId};
 use weasel::round::{EndRound, StartRound};
 use weasel::server::Server;
 use weasel::space::Position;
@@ -51,6 +52,20 @@ pub fn creature<'a, R, P>(
     );
 }
 
+/// Creates an object with default arguments.
+pub fn object<'a, R, P>(processor: &'a mut P, object_id: ObjectId<R>, position: Position<R>)
+where
+    R: BattleRules + 'static,
+    P: EventProcessor<R>,
+{
+    assert_eq!(
+        CreateObject::trigger(processor, object_id, position)
+            .fire()
+            .err(),
+        None
+    );
+}
+
 /// Starts a round with the given entity.
 pub fn start_round<'a, R, P>(processor: &'a mut P, id: &EntityId<R>)
 where